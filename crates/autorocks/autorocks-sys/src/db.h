@@ -7,6 +7,8 @@
 #include <memory>
 #include "rocksdb/utilities/transaction_db.h"
 #include "rocksdb/utilities/options_util.h"
+#include "rocksdb/utilities/checkpoint.h"
+#include "rocksdb/utilities/backup_engine.h"
 
 using namespace std;
 using namespace rocksdb;
@@ -150,6 +152,8 @@ struct TransactionDBWrapper
 {
     unique_ptr<TransactionDB> db;
     std::vector<ColumnFamilyHandle *> cf_handles;
+    // Opened as a secondary instance, which can't be written.
+    bool secondary = false;
 
     Status open(
         const DbOptionsWrapper &options,
@@ -170,6 +174,64 @@ struct TransactionDBWrapper
         return status;
     }
 
+    // Open as a secondary instance of the primary db at `options.path`, and
+    // wrap it as a TransactionDB so that it can be read the same way.
+    Status open_as_secondary(
+        const DbOptionsWrapper &options,
+        const TransactionDBOptions &transaction_db_options,
+        Slice secondary_path)
+    {
+        DBOptions db_options = options.db_options;
+        // Required by secondary instances.
+        db_options.max_open_files = -1;
+        DB *db_ptr;
+        Status status = DB::OpenAsSecondary(
+            db_options,
+            options.path,
+            secondary_path.ToString(),
+            options.cf_descriptors,
+            &cf_handles,
+            &db_ptr);
+        if (!status.ok())
+        {
+            return status;
+        }
+        TransactionDB *ptr;
+        status = TransactionDB::WrapDB(
+            db_ptr,
+            transaction_db_options,
+            vector<size_t>(),
+            cf_handles,
+            &ptr);
+        if (!status.ok())
+        {
+            for (auto cf : cf_handles)
+            {
+                db_ptr->DestroyColumnFamilyHandle(cf);
+            }
+            cf_handles.clear();
+            delete db_ptr;
+            return status;
+        }
+        db.reset(ptr);
+        secondary = true;
+        return status;
+    }
+
+    bool is_secondary() const
+    {
+        return secondary;
+    }
+
+    Status try_catch_up_with_primary() const
+    {
+        if (!secondary)
+        {
+            return Status::NotSupported("not a secondary instance");
+        }
+        return db->GetRootDB()->TryCatchUpWithPrimary();
+    }
+
     ~TransactionDBWrapper()
     {
         for (auto cf : cf_handles)
@@ -221,6 +283,10 @@ struct TransactionDBWrapper
 
     Status clear_cf(size_t col)
     {
+        if (secondary)
+        {
+            return read_only_error();
+        }
         auto cf = get_cf(col);
         if (!cf)
         {
@@ -245,6 +311,10 @@ struct TransactionDBWrapper
 
     Status drop_cf(size_t col)
     {
+        if (secondary)
+        {
+            return read_only_error();
+        }
         auto cf = get_cf(col);
         if (!cf)
         {
@@ -272,11 +342,19 @@ struct TransactionDBWrapper
 
     Status put(const WriteOptions &options, ColumnFamilyHandle *cf, const Slice &key, const Slice &value) const
     {
+        if (secondary)
+        {
+            return read_only_error();
+        }
         return db->Put(options, cf, key, value);
     }
 
     Status del(const WriteOptions &options, ColumnFamilyHandle *cf, const Slice &key) const
     {
+        if (secondary)
+        {
+            return read_only_error();
+        }
         return db->Delete(options, cf, key);
     }
 
@@ -294,6 +372,10 @@ struct TransactionDBWrapper
 
     Status write(const WriteOptions &wopts, const TransactionDBWriteOptimizations &opts, WriteBatch *updates) const
     {
+        if (secondary)
+        {
+            return read_only_error();
+        }
         return db->Write(wopts, opts, updates);
     }
 
@@ -306,6 +388,78 @@ struct TransactionDBWrapper
     {
         db->ReleaseSnapshot(snapshot);
     }
+
+    Status create_checkpoint(Slice dir) const
+    {
+        Checkpoint *ptr;
+        Status status = Checkpoint::Create(db.get(), &ptr);
+        if (!status.ok())
+        {
+            return status;
+        }
+        unique_ptr<Checkpoint> checkpoint(ptr);
+        return checkpoint->CreateCheckpoint(dir.ToString());
+    }
+
+    static Status read_only_error()
+    {
+        return Status::NotSupported("secondary instance is read only");
+    }
+};
+
+// Note: make sure BackupEngineWrapper is Unpin.
+struct BackupEngineWrapper
+{
+    unique_ptr<BackupEngine> engine;
+
+    Status open(Slice backup_dir)
+    {
+        BackupEngine *ptr;
+        Status status = BackupEngine::Open(
+            BackupEngineOptions(backup_dir.ToString()),
+            Env::Default(),
+            &ptr);
+        if (status.ok())
+        {
+            engine.reset(ptr);
+        }
+        return status;
+    }
+
+    Status create_new_backup(const TransactionDBWrapper &db, bool flush_before_backup)
+    {
+        return engine->CreateNewBackup(db.db.get(), flush_before_backup);
+    }
+
+    Status purge_old_backups(uint32_t num_backups_to_keep)
+    {
+        return engine->PurgeOldBackups(num_backups_to_keep);
+    }
+
+    Status verify_backup(uint32_t backup_id) const
+    {
+        return engine->VerifyBackup(backup_id);
+    }
+
+    Status restore_from_latest_backup(Slice db_dir, Slice wal_dir) const
+    {
+        return engine->RestoreDBFromLatestBackup(db_dir.ToString(), wal_dir.ToString());
+    }
+
+    size_t backup_count() const
+    {
+        vector<BackupInfo> backups;
+        engine->GetBackupInfo(&backups);
+        return backups.size();
+    }
+
+    // Backup ids start from 1, 0 means there are no backups.
+    uint32_t latest_backup_id() const
+    {
+        vector<BackupInfo> backups;
+        engine->GetBackupInfo(&backups);
+        return backups.empty() ? 0 : backups.back().backup_id;
+    }
 };
 
 // Note: make sure ReadOnlyDbWrapper is Unpin.
@@ -368,6 +522,8 @@ struct ReadOnlyDbWrapper
 struct TransactionWrapper
 {
     unique_ptr<Transaction> tx;
+    // Began on a secondary instance.
+    bool secondary;
 
     Status get(const ReadOptions &options, ColumnFamilyHandle *cf, const Slice &key, PinnableSlice *slice) const
     {
@@ -402,6 +558,15 @@ struct TransactionWrapper
 
     Status commit()
     {
+        if (secondary)
+        {
+            // Committing an empty transaction is allowed, nothing is written.
+            if (tx->GetWriteBatch()->GetWriteBatch()->Count() == 0)
+            {
+                return tx->Rollback();
+            }
+            return TransactionDBWrapper::read_only_error();
+        }
         return tx->Commit();
     }
 
@@ -413,5 +578,5 @@ struct TransactionWrapper
 
 inline TransactionWrapper TransactionDBWrapper::begin(const WriteOptions &write_options, const TransactionOptions &transaction_options) const
 {
-    return {unique_ptr<Transaction>(db->BeginTransaction(write_options, transaction_options))};
+    return {unique_ptr<Transaction>(db->BeginTransaction(write_options, transaction_options)), secondary};
 }
@@ -34,6 +34,7 @@ autocxx::include_cpp! {
     generate!("TransactionDBWrapper")
     generate!("ReadOnlyDbWrapper")
     generate!("TransactionWrapper")
+    generate!("BackupEngineWrapper")
 }
 
 pub use ffi::*;
@@ -41,6 +42,7 @@ pub use ffi::*;
 impl Unpin for TransactionDBWrapper {}
 impl Unpin for ReadOnlyDbWrapper {}
 impl Unpin for TransactionWrapper {}
+impl Unpin for BackupEngineWrapper {}
 
 unsafe impl Send for TransactionDBWrapper {}
 unsafe impl Sync for TransactionDBWrapper {}
@@ -48,6 +50,10 @@ unsafe impl Sync for TransactionDBWrapper {}
 unsafe impl Send for ReadOnlyDbWrapper {}
 unsafe impl Sync for ReadOnlyDbWrapper {}
 
+unsafe impl Send for BackupEngineWrapper {}
+// Sync because mutable methods take Pin<&mut Self>.
+unsafe impl Sync for BackupEngineWrapper {}
+
 unsafe impl Send for TransactionWrapper {}
 // Sync because mutable methods take Pin<&mut Self>.
 unsafe impl Sync for TransactionWrapper {}
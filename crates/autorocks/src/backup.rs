@@ -0,0 +1,75 @@
+use std::{os::unix::prelude::OsStrExt, path::Path, pin::Pin};
+
+use autorocks_sys::BackupEngineWrapper;
+use moveit::{moveit, Emplace};
+
+use crate::{into_result, Result, TransactionDb};
+
+/// Incremental backups of a db. SST files shared by backups are only copied
+/// once.
+pub struct BackupEngine {
+    inner: Pin<Box<BackupEngineWrapper>>,
+}
+
+impl BackupEngine {
+    /// Open or create the backup directory.
+    pub fn open(backup_dir: &Path) -> Result<Self> {
+        let mut inner = Box::emplace(BackupEngineWrapper::new());
+        moveit! {
+            let status = inner.as_mut().open(backup_dir.as_os_str().as_bytes().into());
+        }
+        into_result(&status)?;
+        Ok(Self { inner })
+    }
+
+    /// Back up the db, it can be done while the db is being written.
+    pub fn create_new_backup(
+        &mut self,
+        db: &TransactionDb,
+        flush_before_backup: bool,
+    ) -> Result<()> {
+        moveit! {
+            let status = self.inner.as_mut().create_new_backup(db.as_inner(), flush_before_backup);
+        }
+        into_result(&status)
+    }
+
+    /// Delete all but the latest `num_backups_to_keep` backups.
+    pub fn purge_old_backups(&mut self, num_backups_to_keep: u32) -> Result<()> {
+        moveit! {
+            let status = self.inner.as_mut().purge_old_backups(num_backups_to_keep);
+        }
+        into_result(&status)
+    }
+
+    /// Check that the files of the backup exist and have the expected sizes.
+    pub fn verify_backup(&self, backup_id: u32) -> Result<()> {
+        moveit! {
+            let status = self.inner.verify_backup(backup_id);
+        }
+        into_result(&status)
+    }
+
+    /// Restore the latest backup to `db_dir`, WAL files go to `wal_dir`, which
+    /// is usually the same as `db_dir`.
+    pub fn restore_from_latest_backup(&self, db_dir: &Path, wal_dir: &Path) -> Result<()> {
+        moveit! {
+            let status = self.inner.restore_from_latest_backup(
+                db_dir.as_os_str().as_bytes().into(),
+                wal_dir.as_os_str().as_bytes().into(),
+            );
+        }
+        into_result(&status)
+    }
+
+    pub fn backup_count(&self) -> usize {
+        self.inner.backup_count()
+    }
+
+    pub fn latest_backup_id(&self) -> Option<u32> {
+        match self.inner.latest_backup_id() {
+            0 => None,
+            id => Some(id),
+        }
+    }
+}
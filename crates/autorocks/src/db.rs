@@ -76,6 +76,17 @@ impl DbOptions {
         }
         TransactionDb::open(&self.inner, &txn_db_options)
     }
+
+    /// Open as a secondary instance of the db, which is read only and follows
+    /// the primary instance with [`TransactionDb::try_catch_up_with_primary`].
+    ///
+    /// `secondary_path` stores the info logs of the secondary instance.
+    pub fn open_as_secondary(&self, secondary_path: &Path) -> Result<TransactionDb> {
+        moveit! {
+            let txn_db_options = new_transaction_db_options();
+        }
+        TransactionDb::open_as_secondary(&self.inner, &txn_db_options, secondary_path)
+    }
 }
 
 #[derive(Clone)]
@@ -98,6 +109,37 @@ impl TransactionDb {
         Ok(TransactionDb { inner: db })
     }
 
+    fn open_as_secondary(
+        options: &DbOptionsWrapper,
+        txn_db_options: &TransactionDBOptions,
+        secondary_path: &Path,
+    ) -> Result<TransactionDb> {
+        let db = Arc::emplace(TransactionDBWrapper::new());
+        let mut db = Pin::into_inner(db);
+        let db_mut = Arc::get_mut(&mut db).unwrap();
+        moveit! {
+            let status = Pin::new(db_mut).open_as_secondary(
+                options,
+                txn_db_options,
+                secondary_path.as_os_str().as_bytes().into(),
+            );
+        }
+        into_result(&status)?;
+        Ok(TransactionDb { inner: db })
+    }
+
+    pub fn is_secondary(&self) -> bool {
+        self.inner.is_secondary()
+    }
+
+    /// Catch up with the primary instance. Only for secondary instances.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        moveit! {
+            let status = self.inner.try_catch_up_with_primary();
+        }
+        into_result(&status)
+    }
+
     pub fn put(&self, col: usize, key: &[u8], value: &[u8]) -> Result<()> {
         moveit! {
             let options = WriteOptions::new();
@@ -331,6 +373,18 @@ impl TransactionDb {
         into_result(&status)
     }
 
+    /// Create a checkpoint, an openable copy of the db, in `dir`, which must
+    /// not exist yet.
+    ///
+    /// SST files are hard linked if `dir` is on the same filesystem as the
+    /// db, so this is cheap and can be done while the db is being written.
+    pub fn create_checkpoint(&self, dir: &Path) -> Result<()> {
+        moveit! {
+            let status = self.inner.create_checkpoint(dir.as_os_str().as_bytes().into());
+        }
+        into_result(&status)
+    }
+
     pub fn as_inner(&self) -> &TransactionDBWrapper {
         &self.inner
     }
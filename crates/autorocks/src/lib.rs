@@ -1,6 +1,7 @@
 pub extern crate autorocks_sys;
 pub extern crate moveit;
 
+mod backup;
 mod db;
 mod error;
 mod iter;
@@ -9,6 +10,7 @@ mod snapshot;
 mod transaction;
 mod write_batch;
 
+pub use backup::*;
 pub use db::*;
 pub use error::*;
 pub use iter::*;
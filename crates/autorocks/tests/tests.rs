@@ -193,3 +193,89 @@ fn test_clear_cf() {
     db.put(0, b"key", b"value").unwrap();
     assert_eq!(db.iter(0, Direction::Forward).count(), 1);
 }
+
+#[test]
+fn test_checkpoint() {
+    let (db, dir) = open_temp(1);
+    db.put(0, b"key", b"value").unwrap();
+    let checkpoint_dir = dir.path().join("checkpoint");
+    db.create_checkpoint(&checkpoint_dir).unwrap();
+    // Not in the checkpoint.
+    db.put(0, b"key1", b"value1").unwrap();
+
+    let checkpoint = DbOptions::new(&checkpoint_dir, 1).open().unwrap();
+    slot!(slice);
+    let v = checkpoint.get(0, b"key", slice).unwrap();
+    assert_eq!(v.unwrap().as_ref(), b"value");
+    slot!(slice);
+    assert!(checkpoint.get(0, b"key1", slice).unwrap().is_none());
+
+    // The checkpoint dir must not exist.
+    assert!(db.create_checkpoint(&checkpoint_dir).is_err());
+}
+
+#[test]
+fn test_backup_and_restore() {
+    let (db, _dir) = open_temp(1);
+    let backup_dir = tempdir().unwrap();
+    let mut engine = BackupEngine::open(backup_dir.path()).unwrap();
+    assert_eq!(engine.latest_backup_id(), None);
+
+    db.put(0, b"key", b"value").unwrap();
+    engine.create_new_backup(&db, true).unwrap();
+    db.put(0, b"key1", b"value1").unwrap();
+    engine.create_new_backup(&db, true).unwrap();
+    assert_eq!(engine.backup_count(), 2);
+    let latest = engine.latest_backup_id().unwrap();
+    engine.verify_backup(latest).unwrap();
+
+    engine.purge_old_backups(1).unwrap();
+    assert_eq!(engine.backup_count(), 1);
+    assert_eq!(engine.latest_backup_id(), Some(latest));
+
+    let restore_dir = tempdir().unwrap();
+    engine
+        .restore_from_latest_backup(restore_dir.path(), restore_dir.path())
+        .unwrap();
+    let restored = DbOptions::new(restore_dir.path(), 1).open().unwrap();
+    slot!(slice);
+    let v = restored.get(0, b"key1", slice).unwrap();
+    assert_eq!(v.unwrap().as_ref(), b"value1");
+}
+
+#[test]
+fn test_secondary() {
+    let (db, dir) = open_temp(1);
+    db.put(0, b"key", b"value").unwrap();
+
+    let secondary_dir = tempdir().unwrap();
+    let secondary = DbOptions::new(dir.path(), 1)
+        .open_as_secondary(secondary_dir.path())
+        .unwrap();
+    assert!(secondary.is_secondary());
+    assert!(!db.is_secondary());
+    slot!(slice);
+    let v = secondary.get(0, b"key", slice).unwrap();
+    assert_eq!(v.unwrap().as_ref(), b"value");
+
+    db.put(0, b"key1", b"value1").unwrap();
+    slot!(slice);
+    assert!(secondary.get(0, b"key1", slice).unwrap().is_none());
+    secondary.try_catch_up_with_primary().unwrap();
+    slot!(slice);
+    let v = secondary.get(0, b"key1", slice).unwrap();
+    assert_eq!(v.unwrap().as_ref(), b"value1");
+
+    // Read only.
+    assert!(secondary.put(0, b"key2", b"value2").is_err());
+    let mut tx = secondary.begin_transaction();
+    slot!(slice);
+    let v = tx.get(0, b"key", slice).unwrap();
+    assert_eq!(v.unwrap().as_ref(), b"value");
+    tx.commit().unwrap();
+    let mut tx = secondary.begin_transaction();
+    tx.put(0, b"key2", b"value2").unwrap();
+    assert!(tx.commit().is_err());
+
+    assert!(db.try_catch_up_with_primary().is_err());
+}
@@ -165,6 +165,7 @@ fn setup_genesis(store: &Store) {
         timestamp: 0,
         meta_contract_validator_type_hash: [0u8; 32].into(),
         eth_registry_validator_type_hash: [1u8; 32].into(),
+        ckb_registry_validator_type_hash: None,
         rollup_config: rollup_config.into(),
         rollup_type_hash: rollup_type_hash.into(),
         secp_data_dep: Default::default(),
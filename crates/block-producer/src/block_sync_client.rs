@@ -1,28 +1,40 @@
 //! L1 and P2P block sync.
 
-use std::{collections::VecDeque, sync::Arc, time::Duration};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{ensure, Context, Result};
+use anyhow::{bail, ensure, Context, Result};
 use bytes::Bytes;
 use futures::TryStreamExt;
 use gw_chain::chain::Chain;
 use gw_generator::generator::CyclesPool;
 use gw_mem_pool::pool::MemPool;
-use gw_p2p_network::{FnSpawn, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME};
-use gw_rpc_client::rpc_client::RPCClient;
+use gw_p2p_network::{
+    block_catch_up::{BlockCatchUpStream, CatchUpMessage, MAX_CATCH_UP_BLOCKS},
+    FnSpawn, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_rpc_client::l1_client::L1Client;
 use gw_store::{autorocks::RocksDBStatusError, traits::chain_store::ChainStore, Store};
 use gw_telemetry::{
     trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState},
     traits::{TelemetryContextNewSpan, TraceContextExt},
 };
 use gw_types::{
+    offchain::ExportedBlock,
     packed::{
         BlockSync, BlockSyncReader, BlockSyncUnion, NumberHash, P2PSyncRequest,
         P2PSyncResponseReader, P2PSyncResponseUnionReader, Script,
     },
     prelude::*,
 };
-use gw_utils::{compression::StreamDecoder, liveness::Liveness};
+use gw_utils::{
+    compression::StreamDecoder,
+    export_block::{check_block_post_state, insert_bad_block_hashes},
+    liveness::Liveness,
+};
 
 use tentacle::{
     builder::MetaBuilder,
@@ -34,17 +46,19 @@ use tracing::{info_span, Instrument};
 
 use crate::{
     chain_updater::ChainUpdater,
+    stop_signal::StopReceiver,
     sync_l1::{revert, sync_l1, SyncL1Context},
 };
 
 pub struct BlockSyncClient {
     pub store: Store,
-    pub rpc_client: RPCClient,
+    pub l1_client: Arc<dyn L1Client>,
     pub chain: Arc<Mutex<Chain>>,
     pub mem_pool: Option<Arc<Mutex<MemPool>>>,
     pub chain_updater: ChainUpdater,
     pub rollup_type_script: Script,
     pub p2p_stream_inbox: Arc<std::sync::Mutex<Option<P2PStream>>>,
+    pub p2p_catch_up_inbox: Arc<std::sync::Mutex<Option<BlockCatchUpStream>>>,
     pub completed_initial_syncing: bool,
     pub liveness: Arc<Liveness>,
 }
@@ -53,8 +67,8 @@ impl SyncL1Context for BlockSyncClient {
     fn store(&self) -> &Store {
         &self.store
     }
-    fn rpc_client(&self) -> &RPCClient {
-        &self.rpc_client
+    fn l1_client(&self) -> &dyn L1Client {
+        &*self.l1_client
     }
     fn chain(&self) -> &Mutex<Chain> {
         &self.chain
@@ -71,11 +85,34 @@ impl SyncL1Context for BlockSyncClient {
 }
 
 impl BlockSyncClient {
-    pub async fn run(mut self) {
+    /// Run until `stop` is signaled. The block or message being applied is
+    /// finished before returning.
+    pub async fn run(&mut self, stop: &StopReceiver) {
         let mut p2p_stream = None;
+        let mut catch_up_stream = None;
         loop {
+            if stop.is_stopped() {
+                return;
+            }
+            if catch_up_stream.is_none() {
+                catch_up_stream = self.p2p_catch_up_inbox.lock().unwrap().take();
+            }
+            // Fill gaps from the trusted full node before syncing with the
+            // block sync stream or L1.
+            if let Some(ref mut s) = catch_up_stream {
+                if let Err(err) = catch_up(self, s, stop).await {
+                    if err.is::<RocksDBStatusError>() {
+                        // Cannot recover from db commit error.
+                        log::error!("db error, exiting: {:#}", err);
+                        return;
+                    }
+                    log::warn!("block catch-up: {:#}, fall back to normal sync", err);
+                    let _ = s.disconnect().await;
+                    catch_up_stream = None;
+                }
+            }
             if let Some(ref mut s) = p2p_stream {
-                if let Err(err) = run_with_p2p_stream(&mut self, s).await {
+                if let Err(err) = run_with_p2p_stream(self, s, stop).await {
                     if err.is::<RocksDBStatusError>() {
                         // Cannot recover from db commit error.
                         log::error!("db error, exiting: {:#}", err);
@@ -88,13 +125,13 @@ impl BlockSyncClient {
                     log::warn!("{:#}", err);
                 }
                 // TODO: backoff.
-                tokio::time::sleep(Duration::from_secs(3)).await;
+                sleep_unless_stopped(Duration::from_secs(3), stop).await;
             } else {
                 p2p_stream = self.p2p_stream_inbox.lock().unwrap().take();
                 if p2p_stream.is_some() {
                     continue;
                 }
-                if let Err(err) = run_once_without_p2p_stream(&mut self).await {
+                if let Err(err) = run_once_without_p2p_stream(self).await {
                     if err.is::<RocksDBStatusError>() {
                         // Cannot recover from db error.
                         log::error!("db error, exiting: {:#}", err);
@@ -103,12 +140,19 @@ impl BlockSyncClient {
                     log::warn!("{:#}", err);
                 }
 
-                tokio::time::sleep(Duration::from_secs(3)).await;
+                sleep_unless_stopped(Duration::from_secs(3), stop).await;
             }
         }
     }
 }
 
+async fn sleep_unless_stopped(duration: Duration, stop: &StopReceiver) {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => {}
+        _ = stop.stopped() => {}
+    }
+}
+
 #[derive(Debug)]
 struct RecoverableCtx;
 
@@ -124,8 +168,104 @@ async fn run_once_without_p2p_stream(client: &mut BlockSyncClient) -> Result<()>
     Ok(())
 }
 
-async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStream) -> Result<()> {
+/// Request historical blocks after the local tip from the peer and apply them,
+/// until we reach the peer's tip or a block that doesn't connect to ours (e.g.
+/// the local chain needs to be reverted first, which is left to `sync_l1` and
+/// the block sync stream).
+async fn catch_up(
+    client: &mut BlockSyncClient,
+    stream: &mut BlockCatchUpStream,
+    stop: &StopReceiver,
+) -> Result<()> {
+    loop {
+        if stop.is_stopped() {
+            return Ok(());
+        }
+        let local_tip = client.store.get_last_valid_tip_block()?;
+        let start = local_tip.raw().number().unpack() + 1;
+        stream.request(start, MAX_CATCH_UP_BLOCKS).await?;
+
+        let mut applied = 0;
+        let mut disconnected = false;
+        let remote_tip = loop {
+            match stream.recv().await? {
+                CatchUpMessage::Block(exported) => {
+                    if disconnected {
+                        continue;
+                    }
+                    if apply_exported_block(client, *exported).await? {
+                        applied += 1;
+                    } else {
+                        disconnected = true;
+                    }
+                }
+                CatchUpMessage::End { tip_number } => break tip_number,
+            }
+        };
+        if applied > 0 {
+            log::info!("caught up {} blocks from {}", applied, start);
+            notify_new_tip(client, false).await?;
+            client.liveness.tick();
+        }
+        if applied == 0 || disconnected || start + applied > remote_tip {
+            return Ok(());
+        }
+    }
+}
+
+/// Returns false if the block doesn't connect to the local tip.
+async fn apply_exported_block(
+    client: &mut BlockSyncClient,
+    exported: ExportedBlock,
+) -> Result<bool> {
+    let block_number = exported.block_number();
+    let mut chain = client.chain.lock().await;
+    let local_tip = chain.local_state().tip();
+    if exported.parent_block_hash() != local_tip.hash()
+        || block_number != local_tip.raw().number().unpack() + 1
+    {
+        log::info!(
+            "catch-up block {} doesn't connect to local tip",
+            block_number
+        );
+        return Ok(false);
+    }
+
+    block_in_place(|| {
+        let mut store_tx = client.store.begin_transaction();
+        if let Some(_challenge_target) = chain.process_block(
+            &mut store_tx,
+            exported.block,
+            exported.post_global_state.clone(),
+            exported.deposit_info_vec,
+            HashSet::from_iter(exported.deposit_asset_scripts),
+            exported.withdrawals,
+        )? {
+            bail!("bad block {}", block_number);
+        }
+        if let Some(bad_block_hashes_vec) = exported.bad_block_hashes {
+            insert_bad_block_hashes(&mut store_tx, bad_block_hashes_vec)?;
+        }
+        check_block_post_state(&store_tx, block_number, &exported.post_global_state)?;
+        if let Some(hash) = exported.submit_tx_hash {
+            store_tx.set_block_submit_tx_hash(block_number, &hash)?;
+        }
+        chain.calculate_and_store_finalized_custodians(&mut store_tx, block_number)?;
+        store_tx.commit()?;
+        anyhow::Ok(())
+    })?;
+    Ok(true)
+}
+
+async fn run_with_p2p_stream(
+    client: &mut BlockSyncClient,
+    stream: &mut P2PStream,
+    stop: &StopReceiver,
+) -> Result<()> {
     loop {
+        if stop.is_stopped() {
+            return Ok(());
+        }
         sync_l1(client).await.context(RecoverableCtx)?;
         notify_new_tip(client, false)
             .await
@@ -147,7 +287,7 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
             P2PSyncResponseUnionReader::TryAgain(_) => {}
         }
         log::info!("will try again");
-        tokio::time::sleep(Duration::from_secs(3)).await;
+        sleep_unless_stopped(Duration::from_secs(3), stop).await;
     }
     log::info!("receiving block sync messages from peer");
     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
@@ -203,8 +343,18 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
         }
         anyhow::Ok(())
     });
-    while let Some(msg) = rx.recv().await {
-        apply_msg(client, msg).await?;
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => msg,
+            _ = stop.stopped() => {
+                recv_handle.abort();
+                return Ok(());
+            }
+        };
+        match msg {
+            Some(msg) => apply_msg(client, msg).await?,
+            None => break,
+        }
     }
     recv_handle.await??;
     Ok(())
@@ -8,7 +8,7 @@ use std::{
 use anyhow::{anyhow, Context, Result};
 use ckb_fixed_hash::H256;
 use gw_chain::chain::{Chain, ChallengeCell, L1Action, L1ActionContext, SyncParam};
-use gw_rpc_client::rpc_client::RPCClient;
+use gw_rpc_client::l1_client::L1Client;
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
@@ -43,7 +43,7 @@ impl QueryL1TxError {
 #[derive(Clone)]
 pub struct ChainUpdater {
     chain: Arc<Mutex<Chain>>,
-    rpc_client: RPCClient,
+    l1_client: Arc<dyn L1Client>,
     rollup_context: RollupContext,
     rollup_type_script: Script,
 }
@@ -51,13 +51,13 @@ pub struct ChainUpdater {
 impl ChainUpdater {
     pub fn new(
         chain: Arc<Mutex<Chain>>,
-        rpc_client: RPCClient,
+        l1_client: Arc<dyn L1Client>,
         rollup_context: RollupContext,
         rollup_type_script: Script,
     ) -> ChainUpdater {
         ChainUpdater {
             chain,
-            rpc_client,
+            l1_client,
             rollup_context,
             rollup_type_script,
         }
@@ -66,8 +66,7 @@ impl ChainUpdater {
     #[instrument(skip_all)]
     pub async fn update_single(&self, tx_hash: &H256) -> anyhow::Result<()> {
         let tx = self
-            .rpc_client
-            .ckb
+            .l1_client
             .get_packed_transaction(tx_hash.0)
             .await?
             .context("get transaction")?;
@@ -211,8 +210,7 @@ impl ChainUpdater {
             let tx_hash: H256 = input.previous_output().tx_hash().unpack();
             let index = input.previous_output().index().unpack();
             let tx = self
-                .rpc_client
-                .ckb
+                .l1_client
                 .get_packed_transaction(tx_hash.0)
                 .await?
                 .ok_or_else(|| QueryL1TxError::new(&tx_hash, anyhow!("cannot locate tx")))?;
@@ -26,7 +26,9 @@ use gw_challenge::{
 use gw_config::{BlockProducerConfig, DebugConfig};
 use gw_generator::types::vm::ChallengeContext;
 use gw_jsonrpc_types::{test_mode::TestModePayload, JsonCalcHash};
-use gw_rpc_client::{contract::ContractsCellDepManager, rpc_client::RPCClient};
+use gw_rpc_client::{
+    contract::ContractsCellDepManager, l1_client::L1Client, rpc_client::RPCClient,
+};
 use gw_types::{
     bytes::Bytes,
     core::{ChallengeTargetType, Status},
@@ -58,6 +60,9 @@ const TRANSACTION_FAILED_TO_RESOLVE_ERROR: &str = "TransactionFailedToResolve";
 
 pub struct Challenger {
     rollup_context: RollupContext,
+    l1_client: Arc<dyn L1Client>,
+    /// Only for collecting fee, stake and owner cells from the indexer, and
+    /// dumping transactions.
     rpc_client: RPCClient,
     wallet: Wallet,
     config: BlockProducerConfig,
@@ -74,6 +79,7 @@ pub struct Challenger {
 
 pub struct ChallengerNewArgs {
     pub rollup_context: RollupContext,
+    pub l1_client: Arc<dyn L1Client>,
     pub rpc_client: RPCClient,
     pub wallet: Wallet,
     pub config: BlockProducerConfig,
@@ -91,6 +97,7 @@ impl Challenger {
     pub fn new(args: ChallengerNewArgs) -> Self {
         let ChallengerNewArgs {
             rollup_context,
+            l1_client,
             rpc_client,
             wallet,
             config,
@@ -106,6 +113,7 @@ impl Challenger {
 
         Self {
             rollup_context,
+            l1_client,
             rpc_client,
             wallet,
             config,
@@ -134,8 +142,10 @@ impl Challenger {
         }
 
         if let Some(last_submit_tx) = self.last_submit_tx {
-            let ckb_client = &self.rpc_client.ckb;
-            let tx_status = ckb_client.get_transaction_status(last_submit_tx).await?;
+            let tx_status = self
+                .l1_client
+                .get_transaction_status(last_submit_tx)
+                .await?;
             use gw_jsonrpc_types::ckb_jsonrpc_types::Status;
             match tx_status {
                 Some(Status::Pending) | Some(Status::Proposed) => return Ok(()),
@@ -146,7 +156,7 @@ impl Challenger {
             }
         }
 
-        let rollup = RollupState::query(&self.rpc_client).await?;
+        let rollup = RollupState::query(&*self.l1_client).await?;
         if let Some(ref tests_control) = self.tests_control {
             if let Some(TestModePayload::Challenge { .. }) = tests_control.payload().await {
                 match rollup.status()? {
@@ -282,7 +292,7 @@ impl Challenger {
             bail!(err);
         }
 
-        let tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        let tx_hash = self.l1_client.send_transaction(&tx).await?;
         log::info!("Challenge block {} in tx {}", block_numer, to_hex(&tx_hash));
         self.last_submit_tx = Some(tx_hash);
 
@@ -339,12 +349,12 @@ impl Challenger {
                 .await?
         };
         let verifier_spent_inputs = extract_inputs(&tx);
-        let verifier_tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        let verifier_tx_hash = self.l1_client.send_transaction(&tx).await?;
         log::info!("Create verifier in tx {}", to_hex(&verifier_tx_hash));
 
         tokio::time::timeout(
             Duration::from_secs(30),
-            self.rpc_client.ckb.wait_tx_proposed(verifier_tx_hash),
+            self.l1_client.wait_tx_proposed(verifier_tx_hash),
         )
         .await
         .with_context(|| format!("waiting for tx proposed 0x{}", to_hex(&verifier_tx_hash)))??;
@@ -397,7 +407,7 @@ impl Challenger {
             verifier_context.input,
             verifier_context.witness,
         );
-        match self.rpc_client.send_transaction(&tx).await {
+        match self.l1_client.send_transaction(&tx).await {
             Ok(tx_hash) => {
                 self.cleaner.watch_verifier(verifier, Some(tx_hash)).await;
                 log::info!("Cancel challenge in tx {}", to_hex(&tx_hash));
@@ -432,7 +442,7 @@ impl Challenger {
         let challenge_cell = to_cell_info(challenge_cell);
         let challenge_tx_block_number = {
             let tx_hash: H256 = challenge_cell.out_point.tx_hash().unpack();
-            let tx_status = self.rpc_client.ckb.get_transaction_status(tx_hash).await?;
+            let tx_status = self.l1_client.get_transaction_status(tx_hash).await?;
             if !matches!(
                 tx_status,
                 Some(gw_jsonrpc_types::ckb_jsonrpc_types::Status::Committed)
@@ -441,11 +451,7 @@ impl Challenger {
                 return Ok(());
             }
 
-            let query = self
-                .rpc_client
-                .ckb
-                .get_transaction_block_number(tx_hash)
-                .await;
+            let query = self.l1_client.get_transaction_block_number(tx_hash).await;
             query?.ok_or_else(|| anyhow!("challenge tx block number not found"))?
         };
 
@@ -559,7 +565,7 @@ impl Challenger {
             bail!(err);
         }
 
-        let tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        let tx_hash = self.l1_client.send_transaction(&tx).await?;
         log::info!("Revert block in tx {}", to_hex(&tx_hash));
         self.last_submit_tx = Some(tx_hash);
 
@@ -728,7 +734,7 @@ impl Challenger {
         log::debug!("can't find a owner cell for verifier, try wait verifier tx committed");
         tokio::time::timeout(
             Duration::from_secs(30),
-            self.rpc_client.ckb.wait_tx_committed(verifier_tx_hash),
+            self.l1_client.wait_tx_committed(verifier_tx_hash),
         )
         .await
         .with_context(|| format!("wait for tx committed 0x{}", to_hex(&verifier_tx_hash)))??;
@@ -743,7 +749,7 @@ impl Challenger {
     }
 
     async fn dry_run_transaction(&self, tx: &Transaction, action: &str) -> Result<()> {
-        match self.rpc_client.dry_run_transaction(tx).await {
+        match self.l1_client.dry_run_transaction(tx).await {
             Ok(cycles) => {
                 log::info!("tx({}) {} cycles: {}", action, tx.calc_tx_hash(), cycles);
                 Ok(())
@@ -771,8 +777,8 @@ struct RollupState {
 }
 
 impl RollupState {
-    async fn query(rpc_client: &RPCClient) -> Result<Self> {
-        let query_cell = rpc_client.query_rollup_cell().await?;
+    async fn query(l1_client: &dyn L1Client) -> Result<Self> {
+        let query_cell = l1_client.query_rollup_cell().await?;
 
         let rollup_cell = query_cell.ok_or_else(|| anyhow!("rollup cell not found"))?;
         let global_state = global_state_from_slice(&rollup_cell.data)?;
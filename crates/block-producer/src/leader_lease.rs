@@ -0,0 +1,336 @@
+//! Leader lease of block producers.
+//!
+//! Only the lease holder produces and submits blocks. Standby producers follow
+//! the holder with P2P block sync, and take over once the lease expires.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use gw_config::{LeaderLeaseConfig, LeaseBackendConfig};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+/// Coordination backend of the leader lease.
+#[async_trait]
+pub trait LeaseBackend: Send + Sync {
+    /// Acquire the lease, or renew it if `holder` already holds it. Returns
+    /// false if another holder holds an unexpired lease.
+    async fn try_acquire(&self, holder: &str, lease: Duration) -> Result<bool>;
+    /// Release the lease if `holder` holds it.
+    async fn release(&self, holder: &str) -> Result<()>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct LeaseRecord {
+    holder: String,
+    /// Unix timestamp in milliseconds.
+    expires_at: u64,
+}
+
+/// Lease file guarded by `flock`.
+pub struct FileLeaseBackend {
+    path: PathBuf,
+}
+
+impl FileLeaseBackend {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Open and lock the lease file, the lock is released when the file is
+    /// closed.
+    fn open_locked(path: &Path) -> Result<File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)
+            .with_context(|| format!("open lease file {}", path.display()))?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(std::io::Error::last_os_error()).context("lock lease file");
+        }
+        Ok(file)
+    }
+
+    fn read_record(file: &mut File) -> Result<Option<LeaseRecord>> {
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        if content.is_empty() {
+            return Ok(None);
+        }
+        let record = serde_json::from_str(&content).context("parse lease file")?;
+        Ok(Some(record))
+    }
+
+    fn write_record(file: &mut File, record: Option<&LeaseRecord>) -> Result<()> {
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        if let Some(record) = record {
+            file.write_all(&serde_json::to_vec(record)?)?;
+        }
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("unix time")
+        .as_millis() as u64
+}
+
+#[async_trait]
+impl LeaseBackend for FileLeaseBackend {
+    async fn try_acquire(&self, holder: &str, lease: Duration) -> Result<bool> {
+        let path = self.path.clone();
+        let holder = holder.to_string();
+        tokio::task::spawn_blocking(move || {
+            let mut file = Self::open_locked(&path)?;
+            let now = unix_millis();
+            if let Some(record) = Self::read_record(&mut file)? {
+                if record.holder != holder && record.expires_at > now {
+                    return Ok(false);
+                }
+            }
+            let record = LeaseRecord {
+                holder,
+                expires_at: now + lease.as_millis() as u64,
+            };
+            Self::write_record(&mut file, Some(&record))?;
+            Ok(true)
+        })
+        .await?
+    }
+
+    async fn release(&self, holder: &str) -> Result<()> {
+        let path = self.path.clone();
+        let holder = holder.to_string();
+        tokio::task::spawn_blocking(move || {
+            let mut file = Self::open_locked(&path)?;
+            match Self::read_record(&mut file)? {
+                Some(record) if record.holder == holder => Self::write_record(&mut file, None),
+                _ => Ok(()),
+            }
+        })
+        .await?
+    }
+}
+
+/// Leader lease of this producer.
+pub struct LeaderLease {
+    backend: Arc<dyn LeaseBackend>,
+    holder: String,
+    lease: Duration,
+    is_leader: AtomicBool,
+}
+
+impl LeaderLease {
+    pub fn new(backend: Arc<dyn LeaseBackend>, holder: String, lease: Duration) -> Self {
+        Self {
+            backend,
+            holder,
+            lease,
+            is_leader: AtomicBool::new(false),
+        }
+    }
+
+    pub fn from_config(config: &LeaderLeaseConfig) -> Self {
+        let backend = match config.backend {
+            LeaseBackendConfig::File { ref path } => FileLeaseBackend::new(path.clone()),
+        };
+        Self::new(
+            Arc::new(backend),
+            config.holder.clone(),
+            Duration::from_secs(config.lease_secs),
+        )
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::SeqCst)
+    }
+
+    fn renew_interval(&self) -> Duration {
+        self.lease / 3
+    }
+
+    /// The backend may block, e.g. `flock` on a stale NFS mount. Give up
+    /// after a renew interval so that the lease is never kept past expiry.
+    async fn try_acquire(&self) -> Result<bool> {
+        let try_acquire = self.backend.try_acquire(&self.holder, self.lease);
+        tokio::time::timeout(self.renew_interval(), try_acquire)
+            .await
+            .context("lease backend timeout")?
+    }
+
+    /// Wait until the lease is acquired.
+    pub async fn acquire(&self) -> Result<()> {
+        loop {
+            match self.try_acquire().await {
+                Ok(true) => {
+                    log::info!("[leader lease] {} acquired the lease", self.holder);
+                    self.is_leader.store(true, Ordering::SeqCst);
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(err) => log::warn!("[leader lease] failed to acquire the lease: {:#}", err),
+            }
+            tokio::time::sleep(self.renew_interval()).await;
+        }
+    }
+
+    /// Renew the lease until it's lost. The lease is considered lost if it
+    /// can't be renewed within 2/3 of the lease, so that we stop producing
+    /// before it expires and a standby producer takes over.
+    pub async fn keep(&self) -> Result<()> {
+        let mut renewed_at = Instant::now();
+        loop {
+            tokio::time::sleep(self.renew_interval()).await;
+            match self.try_acquire().await {
+                Ok(true) => renewed_at = Instant::now(),
+                Ok(false) => {
+                    self.is_leader.store(false, Ordering::SeqCst);
+                    bail!("[leader lease] the lease is taken by another producer");
+                }
+                Err(err) => {
+                    log::warn!("[leader lease] failed to renew the lease: {:#}", err);
+                    if renewed_at.elapsed() >= self.lease * 2 / 3 {
+                        self.is_leader.store(false, Ordering::SeqCst);
+                        bail!("[leader lease] the lease is about to expire");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Release the lease so that a standby producer can take over without
+    /// waiting for it to expire.
+    pub async fn release(&self) -> Result<()> {
+        self.is_leader.store(false, Ordering::SeqCst);
+        self.backend.release(&self.holder).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    use super::{FileLeaseBackend, LeaderLease, LeaseBackend};
+
+    /// Hangs once stalled, like `flock` on an unresponsive file system.
+    struct StallingBackend {
+        inner: FileLeaseBackend,
+        stalled: AtomicBool,
+    }
+
+    #[async_trait]
+    impl LeaseBackend for StallingBackend {
+        async fn try_acquire(&self, holder: &str, lease: Duration) -> Result<bool> {
+            if self.stalled.load(Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            self.inner.try_acquire(holder, lease).await
+        }
+
+        async fn release(&self, holder: &str) -> Result<()> {
+            self.inner.release(holder).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_lease_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileLeaseBackend::new(dir.path().join("leader.lease"));
+        let lease = Duration::from_millis(200);
+
+        assert!(backend.try_acquire("a", lease).await.unwrap());
+        assert!(!backend.try_acquire("b", lease).await.unwrap());
+        // Renew.
+        assert!(backend.try_acquire("a", lease).await.unwrap());
+
+        // Expired.
+        tokio::time::sleep(lease).await;
+        assert!(backend.try_acquire("b", lease).await.unwrap());
+        assert!(!backend.try_acquire("a", lease).await.unwrap());
+
+        // Released.
+        backend.release("a").await.unwrap();
+        assert!(!backend.try_acquire("a", lease).await.unwrap());
+        backend.release("b").await.unwrap();
+        assert!(backend.try_acquire("a", lease).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_leader_lease_failover() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("leader.lease");
+        let lease = Duration::from_millis(300);
+        let backend_a = Arc::new(StallingBackend {
+            inner: FileLeaseBackend::new(path.clone()),
+            stalled: AtomicBool::new(false),
+        });
+        let a = LeaderLease::new(backend_a.clone(), "a".to_string(), lease);
+        let b = LeaderLease::new(
+            Arc::new(FileLeaseBackend::new(path)),
+            "b".to_string(),
+            lease,
+        );
+
+        a.acquire().await.unwrap();
+        assert!(a.is_leader());
+
+        // b stands by while a keeps the lease.
+        tokio::select! {
+            _ = a.keep() => panic!("lease lost"),
+            result = tokio::time::timeout(lease * 3, b.acquire()) => assert!(result.is_err()),
+        }
+        assert!(!b.is_leader());
+
+        // a steps down before the lease expires once its backend hangs, and b
+        // takes over.
+        backend_a.stalled.store(true, Ordering::SeqCst);
+        tokio::time::timeout(lease * 2, a.keep())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(!a.is_leader());
+        tokio::time::timeout(lease * 3, b.acquire())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(b.is_leader());
+
+        // a is back to standby, and takes over once b releases the lease.
+        backend_a.stalled.store(false, Ordering::SeqCst);
+        tokio::select! {
+            _ = b.keep() => panic!("lease lost"),
+            result = tokio::time::timeout(lease * 3, a.acquire()) => assert!(result.is_err()),
+        }
+        b.release().await.unwrap();
+        tokio::time::timeout(lease, a.acquire())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(a.is_leader());
+    }
+}
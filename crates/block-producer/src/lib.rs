@@ -6,14 +6,19 @@ pub mod cleaner;
 pub mod custodian;
 pub mod debugger;
 pub mod deposit;
+pub mod leader_lease;
 pub mod produce_block;
 pub(crate) mod psc;
 pub mod replay_block;
 pub mod runner;
+pub mod secondary_catch_up;
 pub mod stake;
+pub mod stop_signal;
+pub mod state_history_pruner;
 pub mod sync_l1;
 pub mod test_mode_control;
 pub mod types;
 pub mod utils;
 pub mod withdrawal;
+pub mod withdrawal_unlock_sync;
 pub mod withdrawal_unlocker;
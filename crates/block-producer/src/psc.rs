@@ -2,19 +2,19 @@
 
 use std::{collections::HashSet, fmt::Display, sync::Arc, time::Duration};
 
-use anyhow::{bail, ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use gw_chain::chain::Chain;
 use gw_config::PscConfig;
 use gw_mem_pool::{block_sync_server::BlockSyncServerState, pool::MemPool};
 use gw_rpc_client::{
     error::{get_jsonrpc_error_code, CkbRpcError},
-    rpc_client::RPCClient,
+    l1_client::L1Client,
 };
 use gw_store::{snapshot::StoreSnapshot, traits::chain_store::ChainStore, Store};
 use gw_telemetry::traits::{OpenTelemetrySpanExt, TraceContextExt};
 use gw_types::{
     h256::*,
-    offchain::{CellStatus, DepositInfo},
+    offchain::{global_state_from_slice, CellStatus, DepositInfo},
     packed::{
         self, Confirmed, GlobalState, LocalBlock, NumberHash, OutPoint, Revert, Script, ScriptVec,
         Submitted, Transaction, WithdrawalKey,
@@ -35,7 +35,9 @@ use tracing::instrument;
 use crate::{
     block_producer::{check_block_size, BlockProducer, ComposeSubmitTxArgs, TransactionSizeError},
     chain_updater::ChainUpdater,
+    leader_lease::LeaderLease,
     produce_block::ProduceBlockResult,
+    stop_signal::StopReceiver,
     sync_l1::{revert, sync_l1, SyncL1Context},
 };
 
@@ -71,7 +73,7 @@ impl ProduceSubmitConfirm {
 
 pub struct PSCContext {
     pub store: Store,
-    pub rpc_client: RPCClient,
+    pub l1_client: Arc<dyn L1Client>,
     pub chain: Arc<Mutex<Chain>>,
     pub mem_pool: Arc<Mutex<MemPool>>,
     pub block_producer: BlockProducer,
@@ -81,20 +83,45 @@ pub struct PSCContext {
     pub psc_config: PscConfig,
     pub block_sync_server_state: Option<Arc<std::sync::Mutex<BlockSyncServerState>>>,
     pub liveness: Arc<Liveness>,
+    pub leader_lease: Option<Arc<LeaderLease>>,
 }
 
 impl PSCContext {
     fn rollup_context(&self) -> &RollupContext {
         self.block_producer.generator().rollup_context()
     }
+
+    /// A producer that just took over may not have seen the last submission
+    /// of the previous leader yet. Check that the rollup cell is at the last
+    /// confirmed block, so that we don't submit a conflicting block.
+    async fn check_rollup_cell(&self, last_confirmed: &NumberHash) -> Result<()> {
+        let rollup_cell = self
+            .l1_client
+            .query_rollup_cell()
+            .await?
+            .context("rollup cell not found")?;
+        let global_state = global_state_from_slice(&rollup_cell.data)
+            .map_err(|_| anyhow!("parse rollup cell global state"))?;
+        let tip_block_hash: H256 = global_state.tip_block_hash().unpack();
+        let last_confirmed_hash: H256 = last_confirmed.block_hash().unpack();
+        if tip_block_hash != last_confirmed_hash {
+            return Err(anyhow!(
+                "rollup cell tip block {} is not the last confirmed block {}",
+                tip_block_hash.pack(),
+                last_confirmed_hash.pack()
+            )
+            .context(ShouldResyncError));
+        }
+        Ok(())
+    }
 }
 
 impl SyncL1Context for PSCContext {
     fn store(&self) -> &Store {
         &self.store
     }
-    fn rpc_client(&self) -> &RPCClient {
-        &self.rpc_client
+    fn l1_client(&self) -> &dyn L1Client {
+        &*self.l1_client
     }
     fn chain(&self) -> &Mutex<Chain> {
         &self.chain
@@ -163,10 +190,12 @@ impl ProduceSubmitConfirm {
         Ok(psc)
     }
 
-    /// Run the producing, submitting and confirming loop.
-    pub async fn run(mut self) -> Result<()> {
+    /// Run the producing, submitting and confirming loop until `stop` is
+    /// signaled. After that no new block is produced, and the block being
+    /// submitted or confirmed is finished before returning.
+    pub async fn run(mut self, stop: StopReceiver) -> Result<()> {
         loop {
-            match run(&mut self).await {
+            match run(&mut self, &stop).await {
                 Ok(()) => return Ok(()),
                 Err(e) => {
                     log::warn!("{:#}", e);
@@ -239,11 +268,14 @@ impl ProduceSubmitConfirm {
     }
 }
 
-async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
+async fn run(state: &mut ProduceSubmitConfirm, stop: &StopReceiver) -> Result<()> {
+    let mut stopping = stop.is_stopped();
+    // The submitting and confirming tasks return None if they are stopped
+    // before succeeding.
     let mut submitting = false;
-    let mut submit_handle = spawn_abort_on_drop(async { anyhow::Ok(NumberHash::default()) });
+    let mut submit_handle = spawn_abort_on_drop(async { anyhow::Ok(None::<NumberHash>) });
     let mut confirming = false;
-    let mut confirm_handle = spawn_abort_on_drop(async { anyhow::Ok(NumberHash::default()) });
+    let mut confirm_handle = spawn_abort_on_drop(async { anyhow::Ok(None::<NumberHash>) });
     let ctx = state.context.clone();
     let config = &ctx.psc_config;
     let mut interval = tokio::time::interval(Duration::from_secs(config.block_interval_secs));
@@ -253,9 +285,17 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
     let mut revert_submitted_signal = signal(SignalKind::user_defined2())?;
 
     loop {
-        if !submitting && state.local_count > 0 && state.submitted_count < config.submitted_limit {
+        if stopping && !submitting && !confirming {
+            return Ok(());
+        }
+        if !stopping
+            && !submitting
+            && state.local_count > 0
+            && state.submitted_count < config.submitted_limit
+        {
             submitting = true;
             let context = state.context.clone();
+            let stop = stop.clone();
             submit_handle.replace_with(tokio::spawn(async move {
                 loop {
                     submit_pending_l1_upgrade(&context)
@@ -263,22 +303,26 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                         .with_context(|| "failed to submit pending l1 upgrade")?;
 
                     match submit_next_block(&context).await {
-                        Ok(nh) => return Ok(nh),
+                        Ok(nh) => return Ok(Some(nh)),
                         Err(err) => {
                             if err.is::<ShouldResyncError>() || err.is::<ShouldRevertError>() {
                                 bail!(err);
                             }
                             log::warn!("failed to submit next block: {:#}", err);
                             // TOOO: backoff.
-                            tokio::time::sleep(Duration::from_secs(20)).await;
+                            tokio::select! {
+                                _ = tokio::time::sleep(Duration::from_secs(20)) => {}
+                                _ = stop.stopped() => return Ok(None),
+                            }
                         }
                     }
                 }
             }));
         }
-        if !confirming && state.submitted_count > 0 {
+        if !stopping && !confirming && state.submitted_count > 0 {
             confirming = true;
             let context = state.context.clone();
+            let stop = stop.clone();
             confirm_handle.replace_with(tokio::spawn(async move {
                 loop {
                     confirm_pending_l1_upgrade(&context)
@@ -286,14 +330,17 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                         .with_context(|| "failed to confirm pending l1 upgrade")?;
 
                     match confirm_next_block(&context).await {
-                        Ok(nh) => break Ok(nh),
+                        Ok(nh) => break Ok(Some(nh)),
                         Err(err) => {
                             if err.is::<ShouldResyncError>() || err.is::<ShouldRevertError>() {
                                 bail!(err);
                             }
                             log::warn!("failed to confirm next block: {:#}", err);
                             // TOOO: backoff.
-                            tokio::time::sleep(Duration::from_secs(3)).await;
+                            tokio::select! {
+                                _ = tokio::time::sleep(Duration::from_secs(3)) => {}
+                                _ = stop.stopped() => return Ok(None),
+                            }
                         }
                     }
                 }
@@ -301,9 +348,15 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
         }
         // One of the producing, submitting or confirming branch is always
         // enabled. Otherwise we'd be stuck waiting for one of the signals.
-        assert!(state.local_count < config.local_limit || confirming || submitting);
+        assert!((!stopping && state.local_count < config.local_limit) || confirming || submitting);
         tokio::select! {
             biased;
+            _ = stop.stopped(), if !stopping => {
+                log::info!("stop producing, finishing the blocks being submitted or confirmed");
+                stopping = true;
+                // Don't tick liveness.
+                continue;
+            }
             _ = revert_local_signal.recv() => {
                 log::info!("revert not submitted blocks due to signal");
                 let last_submitted = state
@@ -331,8 +384,7 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                 confirming = false;
                 match result {
                     Err(err) if err.is_panic() => bail!("sync task panic: {:?}", err.into_panic()),
-                    Ok(nh) => {
-                        let nh = nh?;
+                    Ok(nh) => if let Some(nh) = nh? {
                         let mut store_tx = state.context.store.begin_transaction();
                         store_tx.set_last_confirmed_block_number_hash(&nh.as_reader())?;
                         store_tx.commit()?;
@@ -350,8 +402,7 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                 submitting = false;
                 match result {
                     Err(err) if err.is_panic() => bail!("submit task panic: {:?}", err.into_panic()),
-                    Ok(nh) => {
-                        let nh = nh?;
+                    Ok(nh) => if let Some(nh) = nh? {
                         let mut store_tx = state.context.store.begin_transaction();
                         store_tx.set_last_submitted_block_number_hash(&nh.as_reader())?;
                         store_tx.commit()?;
@@ -367,7 +418,7 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
             }
             // Produce a new local block if the produce timer has expired and
             // there are not too many local blocks.
-            _ = interval.tick(), if state.local_count < config.local_limit => {
+            _ = interval.tick(), if !stopping && state.local_count < config.local_limit => {
                 log::info!("producing next block");
                 if let Err(e) = produce_local_block(&state.context).await {
                     log::warn!("failed to produce local block: {:#}", e);
@@ -499,15 +550,17 @@ async fn submit_next_block(ctx: &PSCContext) -> Result<NumberHash> {
         .number()
         .unpack()
         + 1;
-    let last_confirmed = snap
+    let last_confirmed_nh = snap
         .get_last_confirmed_block_number_hash()
-        .expect("get last confirmed block number and hash")
-        .number()
-        .unpack();
+        .expect("get last confirmed block number and hash");
+    let last_confirmed: u64 = last_confirmed_nh.number().unpack();
     // The first submission should not have any unknown cell error. If
     // it does, it means that previous block is probably not confirmed
     // anymore, and we should sync with L1 again.
     let is_first = block_number == last_confirmed + 1;
+    if is_first && ctx.leader_lease.is_some() {
+        ctx.check_rollup_cell(&last_confirmed_nh).await?;
+    }
     submit_block(ctx, snap, is_first, block_number).await
 }
 
@@ -599,7 +652,7 @@ async fn submit_block(
 
     // Wait until median >= since, or CKB will reject the transaction.
     loop {
-        match median_gte(&ctx.rpc_client, since_millis).await {
+        match median_gte(&*ctx.l1_client, since_millis).await {
             Ok(_) => break,
             Err(err) => {
                 log::info!("wait for median >= {}: {:#}", since_millis, err);
@@ -617,7 +670,7 @@ async fn submit_block(
         for d in deposits {
             let out_point = d.cell().out_point();
             if !matches!(
-                ctx.rpc_client
+                ctx.l1_client
                     .get_cell(out_point.clone())
                     .await?
                     .map(|c| c.status),
@@ -633,7 +686,7 @@ async fn submit_block(
         "sending transaction 0x{}",
         hex::encode(tx.calc_tx_hash().as_slice())
     );
-    if let Err(e) = send_transaction_or_check_inputs(&ctx.rpc_client, &tx).await {
+    if let Err(e) = send_transaction_or_check_inputs(&*ctx.l1_client, &tx).await {
         if e.is::<UnknownCellError>() {
             if is_first {
                 bail!(e.context(ShouldResyncError));
@@ -652,11 +705,11 @@ async fn submit_block(
         .build())
 }
 
-async fn poll_tx_confirmed(rpc_client: &RPCClient, tx: &Transaction) -> Result<()> {
+async fn poll_tx_confirmed(l1_client: &dyn L1Client, tx: &Transaction) -> Result<()> {
     log::info!("waiting for tx 0x{}", hex::encode(tx.hash()));
     let mut last_sent = Instant::now();
     loop {
-        let status = rpc_client.ckb.get_transaction_status(tx.hash()).await?;
+        let status = l1_client.get_transaction_status(tx.hash()).await?;
         use gw_jsonrpc_types::ckb_jsonrpc_types::Status;
         let should_resend = match status {
             Some(Status::Committed) => break,
@@ -675,20 +728,19 @@ async fn poll_tx_confirmed(rpc_client: &RPCClient, tx: &Transaction) -> Result<(
         };
         if should_resend {
             log::info!("resend transaction 0x{}", hex::encode(tx.hash()));
-            send_transaction_or_check_inputs(rpc_client, tx).await?;
+            send_transaction_or_check_inputs(l1_client, tx).await?;
             last_sent = Instant::now();
             gw_metrics::block_producer().resend.inc();
         }
         tokio::time::sleep(Duration::from_secs(1)).await;
     }
     // Wait for indexer syncing the L1 block.
-    let block_number = rpc_client
-        .ckb
+    let block_number = l1_client
         .get_transaction_block_number(tx.hash())
         .await?
         .context("get tx block hash")?;
     loop {
-        let tip = rpc_client.get_tip().await?;
+        let tip = l1_client.get_tip().await?;
         if tip.number().unpack() >= block_number {
             break;
         }
@@ -721,7 +773,7 @@ async fn confirm_block(
         .get_block_submit_tx(block_number)
         .expect("get submit tx");
     drop(snap);
-    poll_tx_confirmed(&context.rpc_client, &tx)
+    poll_tx_confirmed(&*context.l1_client, &tx)
         .await
         .map_err(|e| {
             if e.is::<UnknownCellError>() {
@@ -741,9 +793,9 @@ async fn confirm_block(
 }
 
 /// Check that current CKB tip block median time >= timestamp.
-async fn median_gte(rpc_client: &RPCClient, timestamp_millis: u64) -> Result<()> {
-    let tip = rpc_client.get_tip().await?;
-    let median = rpc_client
+async fn median_gte(l1_client: &dyn L1Client, timestamp_millis: u64) -> Result<()> {
+    let tip = l1_client.get_tip().await?;
+    let median = l1_client
         .get_block_median_time(tip.block_hash().unpack())
         .await?;
     ensure!(median >= Some(Duration::from_millis(timestamp_millis)));
@@ -776,7 +828,7 @@ async fn submit_pending_l1_upgrade(ctx: &PSCContext) -> Result<()> {
         );
 
         // We can't recover from errors when sending l1 upgrade tx, so we just throw it
-        send_transaction_or_check_inputs(&ctx.rpc_client, &tx).await?;
+        send_transaction_or_check_inputs(&*ctx.l1_client, &tx).await?;
         log::info!("tx sent");
 
         // Refresh block producer's contracts deps
@@ -810,7 +862,7 @@ async fn confirm_pending_l1_upgrade(ctx: &PSCContext) -> Result<()> {
         let tx: Transaction = l1_upgrade.signed_transaction.clone().into();
         // We can't recover from errors when confirming l1 upgrade tx,
         // both deadcell error and unknwown cell is unacceptable, so we just throw it
-        poll_tx_confirmed(&ctx.rpc_client, &tx).await?;
+        poll_tx_confirmed(&*ctx.l1_client, &tx).await?;
         log::info!("l1 upgrade tx confirmed");
     }
     Ok(())
@@ -835,13 +887,12 @@ fn test_greater_since() {
     }
 }
 
-async fn check_cell(rpc_client: &RPCClient, out_point: &OutPoint) -> Result<()> {
-    let block_number = rpc_client
-        .ckb
+async fn check_cell(l1_client: &dyn L1Client, out_point: &OutPoint) -> Result<()> {
+    let block_number = l1_client
         .get_transaction_block_number(out_point.tx_hash().unpack())
         .await?
         .context(UnknownCellError)?;
-    let mut opt_block = rpc_client.get_block_by_number(block_number).await?;
+    let mut opt_block = l1_client.get_block_by_number(block_number).await?;
     // Search later blocks to see who consumed this cell.
     for _ in 0..100 {
         if let Some(block) = opt_block {
@@ -857,7 +908,7 @@ async fn check_cell(rpc_client: &RPCClient, out_point: &OutPoint) -> Result<()>
                     });
                 }
             }
-            opt_block = rpc_client
+            opt_block = l1_client
                 .get_block_by_number(block.header().raw().number().unpack() + 1)
                 .await?;
         } else {
@@ -875,13 +926,13 @@ async fn check_cell(rpc_client: &RPCClient, out_point: &OutPoint) -> Result<()>
 /// Will check input cells if sending fails with `TransactionFailedToResolve`.
 /// If any input cell is dead, the error returned will be a `DeadCellError`.
 async fn send_transaction_or_check_inputs(
-    rpc_client: &RPCClient,
+    l1_client: &dyn L1Client,
     tx: &Transaction,
 ) -> anyhow::Result<()> {
-    if let Err(mut err) = rpc_client.send_transaction(tx).await {
+    if let Err(mut err) = l1_client.send_transaction(tx).await {
         let code = get_jsonrpc_error_code(&err);
         if code == Some(CkbRpcError::TransactionFailedToResolve as i64) {
-            if let Err(e) = check_tx_input(rpc_client, tx).await {
+            if let Err(e) = check_tx_input(l1_client, tx).await {
                 // If the input is consumed by tx, tx is actually confirmed.
                 // This can happen if the tx is confirmed right before it is
                 // resent and its inputs is checked.
@@ -936,18 +987,18 @@ impl Display for DeadCellError {
 #[error("previous transaction not confirmed")]
 struct UnknownCellError;
 
-async fn check_tx_input(rpc_client: &RPCClient, tx: &Transaction) -> Result<()> {
+async fn check_tx_input(l1_client: &dyn L1Client, tx: &Transaction) -> Result<()> {
     // Check inputs.
     for input in tx.raw().inputs() {
         let out_point = input.previous_output();
-        let status = rpc_client
+        let status = l1_client
             .get_cell(out_point.clone())
             .await?
             .map(|c| c.status);
         match status {
             Some(CellStatus::Live) => {}
             _ => {
-                check_cell(rpc_client, &out_point)
+                check_cell(l1_client, &out_point)
                     .await
                     .with_context(|| format!("checking out point {}", &out_point))?;
             }
@@ -78,7 +78,7 @@ impl ReplayBlock {
 
         // apply deposition to state
         for req in deposits {
-            state.apply_deposit_request(generator.rollup_context(), req)?;
+            state.apply_deposit_request(generator.rollup_context(), block_number, req)?;
         }
         if generator
             .fork_config()
@@ -98,7 +98,7 @@ impl ReplayBlock {
         let db = &store.begin_transaction();
         let chain_view = ChainView::new(&db, parent_block_hash);
         for (tx_index, tx) in block.transactions().into_iter().enumerate() {
-            generator.check_transaction_signature(&state, &tx)?;
+            generator.check_transaction_signature(&state, &tx, block_number)?;
 
             // check nonce
             let raw_tx = tx.raw();
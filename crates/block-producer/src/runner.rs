@@ -10,11 +10,17 @@ use futures::future::OptionFuture;
 use gw_chain::chain::Chain;
 use gw_challenge::offchain::{OffChainMockContext, OffChainMockContextBuildArgs};
 use gw_common::{
-    blake2b::new_blake2b, builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress,
+    blake2b::new_blake2b, builtins::ETH_REGISTRY_ACCOUNT_ID, registry::Registries,
+    registry_address::RegistryAddress,
+};
+use gw_config::{
+    BlockProducerConfig, Config, ForkConfig, NodeMode, P2PNetworkConfig, RegistryType,
 };
-use gw_config::{BlockProducerConfig, Config, ForkConfig, NodeMode, RegistryType};
 use gw_generator::{
-    account_lock_manage::{secp256k1::Secp256k1Eth, AccountLockManage},
+    account_lock_manage::{
+        secp256k1::Secp256k1Eth, secp256k1_blake160::Secp256k1Blake160,
+        secp256r1_webauthn::Secp256r1WebAuthn, AccountLockManage,
+    },
     backend_manage::BackendManage,
     genesis::init_genesis,
     Generator,
@@ -25,18 +31,25 @@ use gw_mem_pool::{
     default_provider::DefaultMemPoolProvider,
     pool::{MemPool, MemPoolCreateArgs},
 };
-use gw_p2p_network::P2PNetwork;
+use gw_p2p_network::{
+    block_catch_up::{
+        block_catch_up_client_protocol, block_catch_up_server_protocol, BlockCatchUpStream,
+    },
+    P2PNetwork,
+};
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
 use gw_rpc_client::{
     ckb_client::CkbClient, contract::ContractsCellDepManager, error::get_jsonrpc_error_code,
-    indexer_client::CkbIndexerClient, rpc_client::RPCClient,
+    indexer_client::CkbIndexerClient, l1_client::L1Client, rpc_client::RPCClient,
 };
 use gw_rpc_server::{
     registry::{BoxedTestModeRpc, Registry, RegistryArgs},
     server::start_jsonrpc_server,
 };
 use gw_store::{
-    migrate::{init_migration_factory, open_or_create_db},
+    migrate::{init_migration_factory, open_or_create_db, open_secondary_db},
+    state::MemStateDB,
+    traits::chain_store::ChainStore,
     Store,
 };
 use gw_types::{
@@ -51,10 +64,11 @@ use gw_utils::{
     local_cells::LocalCellsManager, wallet::Wallet, RollupContext,
 };
 use semver::Version;
-use tentacle::service::ProtocolMeta;
+use tentacle::service::{ProtocolMeta, ServiceAsyncControl};
 use tokio::{
     spawn,
     sync::{broadcast, mpsc, Mutex},
+    task::JoinHandle,
 };
 use tracing::{info_span, instrument};
 
@@ -64,9 +78,14 @@ use crate::{
     chain_updater::ChainUpdater,
     challenger::{Challenger, ChallengerNewArgs},
     cleaner::Cleaner,
+    leader_lease::LeaderLease,
     psc::{PSCContext, ProduceSubmitConfirm},
+    secondary_catch_up::SecondaryCatchUp,
+    state_history_pruner::StateHistoryPruner,
+    stop_signal::{stop_signal, StopReceiver},
     test_mode_control::TestModeControl,
     types::ChainEvent,
+    withdrawal_unlock_sync::WithdrawalUnlockSync,
     withdrawal_unlocker::FinalizedWithdrawalUnlocker,
 };
 
@@ -76,7 +95,11 @@ const EVENT_TIMEOUT_SECONDS: u64 = 30;
 struct ChainTaskContext {
     challenger: Option<Challenger>,
     withdrawal_unlocker: Option<FinalizedWithdrawalUnlocker>,
+    /// Unlocked withdrawals are tracked by every node.
+    withdrawal_unlock_sync: WithdrawalUnlockSync,
     cleaner: Option<Arc<Cleaner>>,
+    /// L1 events are only handled by the leader.
+    leader_lease: Option<Arc<LeaderLease>>,
 }
 
 struct ChainTaskRunStatus {
@@ -168,6 +191,24 @@ impl ChainTask {
             let ctx = self.ctx.clone();
             let mut ctx = ctx.lock().await;
 
+            if let Err(err) = ctx.withdrawal_unlock_sync.handle_event(&event).await {
+                if is_l1_query_error(&err) {
+                    log::error!(
+                        "[polling] withdrawal unlock sync event: {} error: {}",
+                        event,
+                        err
+                    );
+                    return Ok(None);
+                }
+                log::error!("[withdrawal unlock sync] {:#}", err);
+            }
+
+            if let Some(ref lease) = ctx.leader_lease {
+                if !lease.is_leader() {
+                    return Ok(Some((new_block_number, new_block_hash)));
+                }
+            }
+
             if let Some(ref mut withdrawal_unlocker) = ctx.withdrawal_unlocker {
                 if let Err(err) = withdrawal_unlocker.handle_event(&event).await {
                     log::error!("[unlock withdrawal] {:#}", err);
@@ -320,7 +361,7 @@ impl BaseInitComponents {
         if !skip_config_check {
             check_ckb_version(&rpc_client).await?;
             // TODO: check ckb indexer version
-            if NodeMode::ReadOnly != config.node_mode {
+            if matches!(config.node_mode, NodeMode::FullNode | NodeMode::Test) {
                 let block_producer_config =
                     opt_block_producer_config.ok_or_else(|| anyhow!("not set block producer"))?;
                 check_rollup_config_cell(consensus, &rollup_config, &rpc_client).await?;
@@ -330,7 +371,34 @@ impl BaseInitComponents {
 
         // Open store
         let timer = Instant::now();
-        let store = Store::new(open_or_create_db(&config.store, init_migration_factory())?);
+        let db = match config.node_mode {
+            NodeMode::Secondary => {
+                let secondary = config.store.secondary.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "store.secondary must be set in mode: {:?}",
+                        config.node_mode
+                    )
+                })?;
+                open_secondary_db(&config.store, &secondary.path, init_migration_factory())?
+            }
+            _ => open_or_create_db(&config.store, init_migration_factory())?,
+        };
+        let mut store = Store::new(db);
+        store.set_index_account_history(config.store.index_account_history);
+        let state_history = &config.store.state_history;
+        if let Some(keep_last_blocks) = state_history.keep_last_blocks {
+            let finality_blocks: u64 = rollup_config.finality_blocks().unpack();
+            if keep_last_blocks < finality_blocks {
+                bail!(
+                    "store.state_history.keep_last_blocks {} is less than finality blocks {}",
+                    keep_last_blocks,
+                    finality_blocks
+                );
+            }
+        }
+        if state_history.checkpoint_interval == Some(0) {
+            bail!("store.state_history.checkpoint_interval must be greater than 0");
+        }
         let elapsed_ms = timer.elapsed().as_millis();
         log::debug!("Open rocksdb costs: {}ms.", elapsed_ms);
 
@@ -348,6 +416,12 @@ impl BaseInitComponents {
                 .raw_data()
         };
 
+        if store.is_secondary() && !store.has_genesis()? {
+            bail!("the primary store is not initialized");
+        }
+        if store.is_state_restoring() {
+            bail!("the store is being restored, or its import-state is interrupted");
+        }
         let genesis_tx_hash = consensus
             .chain
             .genesis_committed_info
@@ -361,6 +435,11 @@ impl BaseInitComponents {
             secp_data.clone(),
         )
         .with_context(|| "init genesis")?;
+        if config.node_mode != NodeMode::Secondary {
+            store
+                .backfill_account_history()
+                .context("backfill account history")?;
+        }
 
         let rollup_config_hash: H256 = rollup_config.hash();
         let generator = {
@@ -376,6 +455,26 @@ impl BaseInitComponents {
                 eth_lock_script_type_hash.hash().unpack(),
                 Arc::new(Secp256k1Eth::default()),
             );
+            // secp256k1-blake160 EoA is optional
+            if let Some(ckb_lock_script_type_hash) = allowed_eoa_type_hashes
+                .iter()
+                .find(|th| th.type_().to_entity() == AllowedEoaType::Secp256k1Blake160.into())
+            {
+                account_lock_manage.register_lock_algorithm(
+                    ckb_lock_script_type_hash.hash().unpack(),
+                    Arc::new(Secp256k1Blake160::default()),
+                );
+            }
+            // WebAuthn EoA is optional
+            if let Some(webauthn_lock_script_type_hash) = allowed_eoa_type_hashes
+                .iter()
+                .find(|th| th.type_().to_entity() == AllowedEoaType::WebAuthn.into())
+            {
+                account_lock_manage.register_lock_algorithm(
+                    webauthn_lock_script_type_hash.hash().unpack(),
+                    Arc::new(Secp256r1WebAuthn::default()),
+                );
+            }
             Arc::new(Generator::new(
                 backend_manage,
                 account_lock_manage,
@@ -469,6 +568,18 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     }
     let base = BaseInitComponents::init(&config, skip_config_check).await?;
 
+    // A full node with leader lease starts as a standby producer.
+    let leader_lease = match config
+        .block_producer
+        .as_ref()
+        .and_then(|c| c.leader_lease.as_ref())
+    {
+        Some(c) if config.node_mode == NodeMode::FullNode => {
+            Some(Arc::new(LeaderLease::from_config(c)))
+        }
+        _ => None,
+    };
+
     let has_block_producer_and_p2p =
         config.block_producer.is_some() && config.p2p_network_config.is_some();
     let block_sync_server_state = if has_block_producer_and_p2p {
@@ -497,6 +608,17 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             let mem_pool = {
                 let registry_id = match block_producer_config.block_producer.address_type {
                     RegistryType::Eth => ETH_REGISTRY_ACCOUNT_ID,
+                    RegistryType::Ckb => {
+                        let state = MemStateDB::from_store(base.store.get_snapshot())?;
+                        let rollup_context = base.generator.rollup_context();
+                        Registries::resolve(
+                            &state,
+                            &rollup_context.rollup_script_hash,
+                            &rollup_context.rollup_config,
+                        )?
+                        .ckb_registry_id()
+                        .context("the rollup has no CKB registry")?
+                    }
                 };
                 let block_producer = RegistryAddress::new(
                     registry_id,
@@ -578,19 +700,24 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         .with_context(|| "create chain")?,
     ));
 
+    let l1_client: Arc<dyn L1Client> = Arc::new(rpc_client.clone());
+
     // create chain updater
     let chain_updater = ChainUpdater::new(
         Arc::clone(&chain),
-        rpc_client.clone(),
+        l1_client.clone(),
         rollup_context.clone(),
         rollup_type_script.clone(),
     );
 
+    let withdrawal_unlock_sync =
+        WithdrawalUnlockSync::new(store.clone(), l1_client.clone(), rollup_context.clone());
+
     let local_cells_manager = Arc::new(Mutex::new(LocalCellsManager::default()));
     let (block_producer, challenger, test_mode_control, withdrawal_unlocker, cleaner) = match config
         .node_mode
     {
-        NodeMode::ReadOnly => (None, None, None, None, None),
+        NodeMode::ReadOnly | NodeMode::Secondary => (None, None, None, None, None),
         mode => {
             let block_producer_config = config
                 .block_producer
@@ -648,6 +775,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             // Challenger
             let args = ChallengerNewArgs {
                 rollup_context,
+                l1_client: l1_client.clone(),
                 rpc_client: rpc_client.clone(),
                 wallet: challenger_wallet,
                 config: block_producer_config.clone(),
@@ -694,34 +822,34 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
 
     let block_sync_client_p2p_stream_inbox: Arc<std::sync::Mutex<Option<P2PStream>>> =
         Arc::new(std::sync::Mutex::new(None));
+    let block_sync_client_catch_up_inbox: Arc<std::sync::Mutex<Option<BlockCatchUpStream>>> =
+        Arc::new(std::sync::Mutex::new(None));
 
     // P2P network.
     let p2p_control_and_handle = if let Some(ref p2p_network_config) = config.p2p_network_config {
-        let mut protocols: Vec<ProtocolMeta> = Vec::new();
-        match config.node_mode {
-            NodeMode::ReadOnly => {
-                log::info!("will enable p2p block sync client");
-                protocols.push(block_sync_client_protocol(
-                    block_sync_client_p2p_stream_inbox.clone(),
-                ));
-            }
-            NodeMode::FullNode | NodeMode::Test => {
-                if let Some(ref state) = block_sync_server_state {
-                    log::info!("will enable p2p block sync server");
-                    protocols.push(block_sync_server_protocol(state.clone()));
-                }
-            }
+        if config.node_mode == NodeMode::Secondary {
+            log::warn!("p2p network is disabled in secondary mode");
+            None
+        } else if config.node_mode == NodeMode::ReadOnly || leader_lease.is_some() {
+            log::info!("will enable p2p block sync client");
+            let protocols = block_sync_client_protocols(
+                &block_sync_client_p2p_stream_inbox,
+                &block_sync_client_catch_up_inbox,
+            );
+            let p2p_network_config = if leader_lease.is_some() {
+                standby_p2p_network_config(p2p_network_config)
+            } else {
+                p2p_network_config.clone()
+            };
+            Some(start_p2p_network(&p2p_network_config, protocols).await?)
+        } else {
+            let protocols = block_sync_server_protocols(&block_sync_server_state, &store);
+            Some(start_p2p_network(p2p_network_config, protocols).await?)
         }
-        let mut network = P2PNetwork::init(p2p_network_config, protocols).await?;
-        let control = network.control().clone();
-        let handle = tokio::spawn(async move {
-            log::info!("running the p2p network");
-            network.run().await;
-        });
-        Some((control, handle))
     } else {
         None
     };
+    let p2p_control_and_handle = Arc::new(Mutex::new(p2p_control_and_handle));
 
     // RPC registry
     let polyjuice_sender_recover = {
@@ -786,34 +914,121 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
 
     log::info!("{:?} mode", config.node_mode);
 
-    let bm = (block_producer, mem_pool.clone()); // To keep the next line short.
+    // Read-only nodes and standby producers follow the active producer.
+    let mut block_sync_client = if config.node_mode == NodeMode::ReadOnly || leader_lease.is_some()
+    {
+        Some(BlockSyncClient {
+            store: store.clone(),
+            l1_client: l1_client.clone(),
+            chain: chain.clone(),
+            mem_pool: mem_pool.clone(),
+            chain_updater: chain_updater.clone(),
+            rollup_type_script: rollup_type_script.clone(),
+            p2p_stream_inbox: block_sync_client_p2p_stream_inbox,
+            p2p_catch_up_inbox: block_sync_client_catch_up_inbox,
+            completed_initial_syncing: false,
+            liveness: liveness.clone(),
+        })
+    } else {
+        None
+    };
+
+    let bm = (block_producer, mem_pool); // To keep the next line short.
     let psc_task = if let (Some(block_producer), Some(mem_pool)) = bm {
-        let psc_state = ProduceSubmitConfirm::init(Arc::new(PSCContext {
+        let psc_context = Arc::new(PSCContext {
             store: store.clone(),
             block_producer,
-            rpc_client: rpc_client.clone(),
+            l1_client: l1_client.clone(),
             chain: chain.clone(),
             mem_pool,
             local_cells_manager,
-            chain_updater: chain_updater.clone(),
+            chain_updater,
             rollup_type_script: rollup_type_script.clone(),
             psc_config: config.block_producer.as_ref().unwrap().psc_config.clone(),
             block_sync_server_state: block_sync_server_state.clone(),
             liveness: liveness.clone(),
-        }))
-        .await
-        .context("create ProduceSubmitConfirm")?;
+            leader_lease: leader_lease.clone(),
+        });
+        // Standby producers init ProduceSubmitConfirm after taking over.
+        let (standby, psc_state) = match leader_lease {
+            Some(ref lease) => {
+                let standby = StandbyProducer {
+                    lease: lease.clone(),
+                    block_sync_client: block_sync_client.take().expect("block sync client"),
+                    p2p_network_config: config.p2p_network_config.clone(),
+                    p2p_control_and_handle: p2p_control_and_handle.clone(),
+                    block_sync_server_state: block_sync_server_state.clone(),
+                    store: store.clone(),
+                };
+                (Some(standby), None)
+            }
+            None => {
+                let psc_state = ProduceSubmitConfirm::init(psc_context.clone())
+                    .await
+                    .context("create ProduceSubmitConfirm")?;
+                (None, Some(psc_state))
+            }
+        };
 
+        let leader_lease = leader_lease.clone();
         let shutdown_completed_send = shutdown_completed_send.clone();
         let mut shutdown_event_recv = shutdown_event.subscribe();
         Some(tokio::spawn(async move {
+            // Stop on shutdown after finishing the current step.
+            let (stop, stop_recv) = stop_signal();
+            let run = async {
+                let mut standby = match (standby, psc_state) {
+                    (_, Some(psc_state)) => return psc_state.run(stop_recv.clone()).await,
+                    (standby, None) => standby.expect("standby producer"),
+                };
+                loop {
+                    if !standby.take_over(&stop_recv).await? {
+                        return Ok(());
+                    }
+                    let psc_state = ProduceSubmitConfirm::init(psc_context.clone())
+                        .await
+                        .context("create ProduceSubmitConfirm")?;
+                    // Stop producing once the lease is lost, and follow the
+                    // new active producer.
+                    let (psc_stop, psc_stop_recv) = stop_signal();
+                    let psc = psc_state.run(psc_stop_recv);
+                    tokio::pin!(psc);
+                    tokio::select! {
+                        result = &mut psc => return result,
+                        result = standby.lease.keep() => {
+                            if let Err(err) = result {
+                                log::warn!("{:#}", err);
+                            }
+                        }
+                        _ = stop_recv.stopped() => {}
+                    }
+                    // Finish the blocks being submitted or confirmed.
+                    psc_stop.stop();
+                    psc.await?;
+                    if stop_recv.is_stopped() {
+                        return Ok(());
+                    }
+                    standby.step_down().await?;
+                }
+            };
+            tokio::pin!(run);
             let result = tokio::select! {
-                _ = shutdown_event_recv.recv() => return,
-                result = psc_state.run() => result,
+                _ = shutdown_event_recv.recv() => {
+                    stop.stop();
+                    run.await
+                }
+                result = &mut run => result,
             };
             if let Err(e) = result {
                 log::error!("ProduceSubmitConfirm error: {:#}", e);
             }
+            if let Some(lease) = leader_lease.filter(|lease| lease.is_leader()) {
+                // So that a standby producer takes over without waiting for
+                // the lease to expire.
+                if let Err(err) = lease.release().await {
+                    log::warn!("[leader lease] failed to release the lease: {:#}", err);
+                }
+            }
             drop(shutdown_completed_send);
         }))
     } else {
@@ -822,24 +1037,20 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     let has_psc_task = psc_task.is_some();
     let psc_task = OptionFuture::from(psc_task);
 
-    let block_sync_task = if config.node_mode == NodeMode::ReadOnly {
-        let client = BlockSyncClient {
-            store: store.clone(),
-            rpc_client: rpc_client.clone(),
-            chain: chain.clone(),
-            mem_pool,
-            chain_updater,
-            rollup_type_script: rollup_type_script.clone(),
-            p2p_stream_inbox: block_sync_client_p2p_stream_inbox,
-            completed_initial_syncing: false,
-            liveness: liveness.clone(),
-        };
+    let block_sync_task = if let Some(client) = block_sync_client {
         let shutdown_completed_send = shutdown_completed_send.clone();
         let mut shutdown_event_recv = shutdown_event.subscribe();
         Some(tokio::spawn(async move {
+            let mut client = client;
+            let (stop, stop_recv) = stop_signal();
+            let run = client.run(&stop_recv);
+            tokio::pin!(run);
             tokio::select! {
-                _ = shutdown_event_recv.recv() => {},
-                _ = client.run() => {},
+                _ = shutdown_event_recv.recv() => {
+                    stop.stop();
+                    run.await;
+                }
+                _ = &mut run => {},
             }
             drop(shutdown_completed_send);
         }))
@@ -849,79 +1060,128 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     let has_block_sync_task = block_sync_task.is_some();
     let block_sync_task = OptionFuture::from(block_sync_task);
 
-    let (chain_task_ended_tx, chain_task) = tokio::sync::oneshot::channel::<()>();
-    let rt_handle = tokio::runtime::Handle::current();
-    tokio::task::spawn_blocking({
-        let shutdown_send = shutdown_completed_send.clone();
-        move || {
-            rt_handle.block_on(async move {
-                use tracing::Instrument;
-
-                let _tx = chain_task_ended_tx;
-                let ctx = ChainTaskContext {
-                    // chain_updater,
-                    challenger,
-                    withdrawal_unlocker,
-                    cleaner,
-                };
-                let mut backoff = ExponentialBackoff::new(Duration::from_secs(1));
-                let mut chain_task = ChainTask::create(
-                    rpc_client,
-                    Duration::from_secs(3),
-                    ctx,
-                    shutdown_send,
-                    shutdown_event_recv,
-                );
+    // Secondary nodes follow the primary store.
+    let secondary = config
+        .store
+        .secondary
+        .as_ref()
+        .filter(|_| config.node_mode == NodeMode::Secondary);
+    if let Some(secondary) = secondary {
+        let catch_up = SecondaryCatchUp::new(
+            store.clone(),
+            mem_pool.clone(),
+            Duration::from_millis(secondary.catch_up_interval_ms),
+            liveness.clone(),
+        );
+        let shutdown_completed_send = shutdown_completed_send.clone();
+        let mut shutdown_event_recv = shutdown_event.subscribe();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = shutdown_event_recv.recv() => {},
+                _ = catch_up.run() => {},
+            }
+            drop(shutdown_completed_send);
+        });
+    }
 
-                let mut run_status = ChainTaskRunStatus::default();
-                loop {
-                    // Exit if shutdown event is received.
-                    if chain_task.shutdown_event.try_recv().is_ok() {
-                        log::info!("ChainTask existed successfully");
-                        return;
-                    }
+    let pruner = StateHistoryPruner::new(store.clone(), &config.store.state_history)
+        .filter(|_| !store.is_secondary());
+    if let Some(pruner) = pruner {
+        let shutdown_completed_send = shutdown_completed_send.clone();
+        let mut shutdown_event_recv = shutdown_event.subscribe();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = shutdown_event_recv.recv() => {},
+                _ = pruner.run() => {},
+            }
+            drop(shutdown_completed_send);
+        });
+    }
 
-                    let run_span = info_span!("chain_task_run");
-                    match chain_task
-                        .run(&run_status)
-                        .instrument(run_span.clone())
-                        .await
-                    {
-                        Ok(updated_status) => {
-                            run_status = updated_status;
-                            backoff.reset();
-
-                            let sleep_span =
-                                info_span!(parent: &run_span, "chain_task interval sleep");
-                            tokio::time::sleep(chain_task.poll_interval)
-                                .instrument(sleep_span)
-                                .await;
-                        }
-                        Err(err) if get_jsonrpc_error_code(&err).is_some() => {
-                            // Reset status and refresh tip number hash
-                            run_status = ChainTaskRunStatus::default();
-                            let backoff_sleep = backoff.next_sleep();
-                            log::error!(
-                                "chain polling loop request error, will retry in {}s: {}",
-                                backoff_sleep.as_secs(),
-                                err
-                            );
-
-                            let sleep_span =
-                                info_span!(parent: &run_span, "chain_task backoff sleep");
-                            tokio::time::sleep(backoff_sleep)
-                                .instrument(sleep_span)
-                                .await;
+    let chain_task_leader_lease = leader_lease;
+    // Secondary nodes don't sync from L1.
+    let chain_task = if config.node_mode != NodeMode::Secondary {
+        let (chain_task_ended_tx, chain_task) = tokio::sync::oneshot::channel::<()>();
+        let rt_handle = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking({
+            let shutdown_send = shutdown_completed_send.clone();
+            move || {
+                rt_handle.block_on(async move {
+                    use tracing::Instrument;
+
+                    let _tx = chain_task_ended_tx;
+                    let ctx = ChainTaskContext {
+                        // chain_updater,
+                        challenger,
+                        withdrawal_unlocker,
+                        withdrawal_unlock_sync,
+                        cleaner,
+                        leader_lease: chain_task_leader_lease,
+                    };
+                    let mut backoff = ExponentialBackoff::new(Duration::from_secs(1));
+                    let mut chain_task = ChainTask::create(
+                        rpc_client,
+                        Duration::from_secs(3),
+                        ctx,
+                        shutdown_send,
+                        shutdown_event_recv,
+                    );
+
+                    let mut run_status = ChainTaskRunStatus::default();
+                    loop {
+                        // Exit if shutdown event is received.
+                        if chain_task.shutdown_event.try_recv().is_ok() {
+                            log::info!("ChainTask existed successfully");
+                            return;
                         }
-                        Err(err) => {
-                            log::error!("chain polling loop exit unexpected, error: {}", err);
-                            break;
+
+                        let run_span = info_span!("chain_task_run");
+                        match chain_task
+                            .run(&run_status)
+                            .instrument(run_span.clone())
+                            .await
+                        {
+                            Ok(updated_status) => {
+                                run_status = updated_status;
+                                backoff.reset();
+
+                                let sleep_span =
+                                    info_span!(parent: &run_span, "chain_task interval sleep");
+                                tokio::time::sleep(chain_task.poll_interval)
+                                    .instrument(sleep_span)
+                                    .await;
+                            }
+                            Err(err) if get_jsonrpc_error_code(&err).is_some() => {
+                                // Reset status and refresh tip number hash
+                                run_status = ChainTaskRunStatus::default();
+                                let backoff_sleep = backoff.next_sleep();
+                                log::error!(
+                                    "chain polling loop request error, will retry in {}s: {}",
+                                    backoff_sleep.as_secs(),
+                                    err
+                                );
+
+                                let sleep_span =
+                                    info_span!(parent: &run_span, "chain_task backoff sleep");
+                                tokio::time::sleep(backoff_sleep)
+                                    .instrument(sleep_span)
+                                    .await;
+                            }
+                            Err(err) => {
+                                log::error!("chain polling loop exit unexpected, error: {}", err);
+                                break;
+                            }
                         }
                     }
-                }
-            });
-        }
-    });
+                });
+            }
+        });
+        Some(chain_task)
+    } else {
+        None
+    };
+    let has_chain_task = chain_task.is_some();
+    let chain_task = OptionFuture::from(chain_task);
 
     let sub_shutdown = shutdown_event.subscribe();
     let rpc_shutdown_send = shutdown_completed_send.clone();
@@ -941,7 +1201,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
 
     tokio::select! {
         _ = sigint_or_sigterm() => {},
-        _ = chain_task => {},
+        _ = chain_task, if has_chain_task => {},
         _ = rpc_task => {},
         _ = psc_task, if has_psc_task => {},
         _ = block_sync_task, if has_block_sync_task => {},
@@ -953,7 +1213,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         log::error!("Failed to brodcast error message: {:?}", err);
     }
     // Shutdown p2p network.
-    if let Some((control, handle)) = p2p_control_and_handle {
+    if let Some((control, handle)) = p2p_control_and_handle.lock().await.take() {
         log::info!("closing p2p network");
         let _ = control.close().await;
         let _ = handle.await;
@@ -972,6 +1232,126 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     Ok(())
 }
 
+fn block_sync_server_protocols(
+    block_sync_server_state: &Option<Arc<std::sync::Mutex<BlockSyncServerState>>>,
+    store: &Store,
+) -> Vec<ProtocolMeta> {
+    let mut protocols = Vec::new();
+    if let Some(ref state) = block_sync_server_state {
+        log::info!("will enable p2p block sync server");
+        protocols.push(block_sync_server_protocol(state.clone()));
+        log::info!("will enable p2p block catch-up server");
+        protocols.push(block_catch_up_server_protocol(store.clone()));
+    }
+    protocols
+}
+
+async fn start_p2p_network(
+    p2p_network_config: &P2PNetworkConfig,
+    protocols: Vec<ProtocolMeta>,
+) -> Result<(ServiceAsyncControl, JoinHandle<()>)> {
+    let mut network = P2PNetwork::init(p2p_network_config, protocols).await?;
+    let control = network.control().clone();
+    let handle = tokio::spawn(async move {
+        log::info!("running the p2p network");
+        network.run().await;
+    });
+    Ok((control, handle))
+}
+
+fn block_sync_client_protocols(
+    p2p_stream_inbox: &Arc<std::sync::Mutex<Option<P2PStream>>>,
+    catch_up_inbox: &Arc<std::sync::Mutex<Option<BlockCatchUpStream>>>,
+) -> Vec<ProtocolMeta> {
+    vec![
+        block_sync_client_protocol(p2p_stream_inbox.clone()),
+        block_catch_up_client_protocol(catch_up_inbox.clone()),
+    ]
+}
+
+/// Standby producers follow the active producer, and don't listen until
+/// taking over.
+fn standby_p2p_network_config(p2p_network_config: &P2PNetworkConfig) -> P2PNetworkConfig {
+    P2PNetworkConfig {
+        listen: None,
+        ..p2p_network_config.clone()
+    }
+}
+
+/// Standby producer, see `LeaderLeaseConfig`.
+struct StandbyProducer {
+    lease: Arc<LeaderLease>,
+    block_sync_client: BlockSyncClient,
+    p2p_network_config: Option<P2PNetworkConfig>,
+    p2p_control_and_handle: Arc<Mutex<Option<(ServiceAsyncControl, JoinHandle<()>)>>>,
+    block_sync_server_state: Option<Arc<std::sync::Mutex<BlockSyncServerState>>>,
+    store: Store,
+}
+
+impl StandbyProducer {
+    /// Follow the active producer until acquiring the leader lease, then
+    /// serve P2P block sync in place of it. Returns false if stopped before
+    /// acquiring the lease.
+    async fn take_over(&mut self, stop: &StopReceiver) -> Result<bool> {
+        log::info!("standby producer, following the active producer");
+        let acquired = {
+            let (sync_stop, sync_stop_recv) = stop_signal();
+            let sync = self.block_sync_client.run(&sync_stop_recv);
+            tokio::pin!(sync);
+            let acquired = tokio::select! {
+                _ = &mut sync => bail!("block sync client exited"),
+                result = self.lease.acquire() => Some(result),
+                _ = stop.stopped() => None,
+            };
+            // Finish the block or message being synced.
+            sync_stop.stop();
+            sync.await;
+            acquired
+        };
+        match acquired {
+            Some(result) => result?,
+            None => return Ok(false),
+        }
+
+        log::info!("taking over block producing");
+        if let Some(ref p2p_network_config) = self.p2p_network_config {
+            let protocols = block_sync_server_protocols(&self.block_sync_server_state, &self.store);
+            self.restart_p2p_network(p2p_network_config, protocols)
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Back to standby after losing the lease.
+    async fn step_down(&mut self) -> Result<()> {
+        log::info!("lost the leader lease, back to standby");
+        if let Some(ref p2p_network_config) = self.p2p_network_config {
+            let protocols = block_sync_client_protocols(
+                &self.block_sync_client.p2p_stream_inbox,
+                &self.block_sync_client.p2p_catch_up_inbox,
+            );
+            let p2p_network_config = standby_p2p_network_config(p2p_network_config);
+            self.restart_p2p_network(&p2p_network_config, protocols)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn restart_p2p_network(
+        &self,
+        p2p_network_config: &P2PNetworkConfig,
+        protocols: Vec<ProtocolMeta>,
+    ) -> Result<()> {
+        let mut p2p = self.p2p_control_and_handle.lock().await;
+        if let Some((control, handle)) = p2p.take() {
+            let _ = control.close().await;
+            let _ = handle.await;
+        }
+        *p2p = Some(start_p2p_network(p2p_network_config, protocols).await?);
+        Ok(())
+    }
+}
+
 async fn check_ckb_version(rpc_client: &RPCClient) -> Result<()> {
     let ckb_version = rpc_client.get_ckb_version().await?;
     let ckb_version = ckb_version.split('(').collect::<Vec<&str>>()[0].trim_end();
@@ -0,0 +1,72 @@
+//! Secondary nodes serve RPC from a secondary instance of the primary node's
+//! store, configured by `store.secondary`. Instead of syncing from L1 or P2P,
+//! the store catches up with the primary periodically.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use gw_mem_pool::pool::MemPool;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::h256::*;
+use gw_utils::liveness::Liveness;
+use tokio::{sync::Mutex, time::MissedTickBehavior};
+
+pub struct SecondaryCatchUp {
+    store: Store,
+    mem_pool: Option<Arc<Mutex<MemPool>>>,
+    interval: Duration,
+    liveness: Arc<Liveness>,
+    tip: Option<H256>,
+}
+
+impl SecondaryCatchUp {
+    pub fn new(
+        store: Store,
+        mem_pool: Option<Arc<Mutex<MemPool>>>,
+        interval: Duration,
+        liveness: Arc<Liveness>,
+    ) -> Self {
+        Self {
+            store,
+            mem_pool,
+            interval,
+            liveness,
+            tip: None,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.catch_up().await {
+                log::error!("[secondary] catch up with primary: {:#}", err);
+            }
+        }
+    }
+
+    /// Catch up with the primary once, and reset the mem pool to the new tip.
+    pub async fn catch_up(&mut self) -> Result<()> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || store.try_catch_up_with_primary()).await??;
+        self.liveness.tick();
+
+        let new_tip = self.store.get_last_valid_tip_block_hash()?;
+        if self.tip == Some(new_tip) {
+            return Ok(());
+        }
+        if let Some(ref mem_pool) = self.mem_pool {
+            let mut mem_pool = mem_pool.lock().await;
+            mem_pool.reset_read_only(Some(new_tip), true)?;
+            if self.tip.is_none() {
+                mem_pool.mem_pool_state().set_completed_initial_syncing();
+            }
+        }
+        if self.tip.is_none() {
+            log::info!("[secondary] caught up with primary");
+        }
+        self.tip = Some(new_tip);
+        Ok(())
+    }
+}
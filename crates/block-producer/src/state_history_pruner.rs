@@ -0,0 +1,65 @@
+//! Background pruning of the per-block state history, configured by
+//! `store.state_history`.
+
+use std::{cmp::min, time::Duration};
+
+use anyhow::Result;
+use gw_config::StateHistoryConfig;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::prelude::Unpack;
+use tokio::time::MissedTickBehavior;
+
+#[derive(Clone)]
+pub struct StateHistoryPruner {
+    store: Store,
+    keep_last_blocks: u64,
+    checkpoint_interval: Option<u64>,
+}
+
+impl StateHistoryPruner {
+    const INTERVAL: Duration = Duration::from_secs(10);
+    /// Max number of blocks pruned in one db transaction.
+    const BATCH_BLOCKS: u64 = 100;
+
+    /// Returns `None` if all state history is kept.
+    pub fn new(store: Store, config: &StateHistoryConfig) -> Option<Self> {
+        Some(Self {
+            store,
+            keep_last_blocks: config.keep_last_blocks?,
+            checkpoint_interval: config.checkpoint_interval,
+        })
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Self::INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let pruner = self.clone();
+            match tokio::task::spawn_blocking(move || pruner.prune()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => log::error!("prune state history: {:#}", err),
+                Err(err) => log::error!("prune state history task: {}", err),
+            }
+        }
+    }
+
+    fn prune(&self) -> Result<()> {
+        let tip_number: u64 = {
+            let snap = self.store.get_snapshot();
+            snap.get_last_valid_tip_block()?.raw().number().unpack()
+        };
+        let target = tip_number.saturating_sub(self.keep_last_blocks);
+        loop {
+            let mut db = self.store.begin_transaction();
+            let pruned_number = db.get_state_history_pruned_number();
+            if pruned_number >= target {
+                return Ok(());
+            }
+            let block_number = min(target, pruned_number + Self::BATCH_BLOCKS);
+            db.prune_state_history(block_number, self.checkpoint_interval)?;
+            db.commit()?;
+            log::debug!("pruned state history before block {}", block_number);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Ask a long running task to stop after its current step, instead of dropping
+//! its future in the middle of an await.
+
+use tokio::sync::watch;
+
+/// Create a stop signal and its receiver.
+pub fn stop_signal() -> (StopSignal, StopReceiver) {
+    let (tx, rx) = watch::channel(false);
+    (StopSignal(tx), StopReceiver(rx))
+}
+
+pub struct StopSignal(watch::Sender<bool>);
+
+impl StopSignal {
+    pub fn stop(&self) {
+        let _ = self.0.send(true);
+    }
+}
+
+#[derive(Clone)]
+pub struct StopReceiver(watch::Receiver<bool>);
+
+impl StopReceiver {
+    /// A receiver that is never stopped.
+    pub fn never() -> Self {
+        stop_signal().1
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until stopped. Never returns if the signal is dropped without
+    /// stopping.
+    pub async fn stopped(&self) {
+        let mut rx = self.0.clone();
+        while !*rx.borrow_and_update() {
+            if rx.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+}
@@ -5,7 +5,7 @@ use gw_chain::chain::{Chain, RevertedL1Action};
 use gw_jsonrpc_types::ckb_jsonrpc_types::BlockNumber;
 use gw_rpc_client::{
     indexer_types::{Order, SearchKey, SearchKeyFilter},
-    rpc_client::RPCClient,
+    l1_client::L1Client,
 };
 use gw_store::{
     autorocks::RocksDBStatusError, traits::chain_store::ChainStore, transaction::StoreTransaction,
@@ -22,7 +22,7 @@ use crate::chain_updater::ChainUpdater;
 
 pub trait SyncL1Context {
     fn store(&self) -> &Store;
-    fn rpc_client(&self) -> &RPCClient;
+    fn l1_client(&self) -> &dyn L1Client;
     fn chain(&self) -> &Mutex<Chain>;
     fn chain_updater(&self) -> &ChainUpdater;
     fn rollup_type_script(&self) -> &Script;
@@ -66,7 +66,7 @@ async fn sync_l1_impl(ctx: &(dyn SyncL1Context + Sync + Send)) -> Result<()> {
             .get_block_submit_tx_hash(last_confirmed_l1)
             .context("get submit tx")?;
         if let Some(gw_jsonrpc_types::ckb_jsonrpc_types::Status::Committed) =
-            ctx.rpc_client().ckb.get_transaction_status(tx_hash).await?
+            ctx.l1_client().get_transaction_status(tx_hash).await?
         {
             log::info!("L2 block {last_confirmed_l1} is on L1");
             break;
@@ -98,8 +98,7 @@ async fn sync_l1_unknown(
         .get_block_submit_tx_hash(last_confirmed)
         .context("get submit tx")?;
     let start_l1_block = ctx
-        .rpc_client()
-        .ckb
+        .l1_client()
         .get_transaction_block_number(tx_hash)
         .await?
         .context("get transaction block number")?;
@@ -119,8 +118,7 @@ async fn sync_l1_unknown(
     let mut reverted = false;
     loop {
         let mut txs = ctx
-            .rpc_client()
-            .indexer
+            .l1_client()
             .get_transactions(&search_key, &Order::Asc, 500.into(), &last_cursor)
             .await?;
         txs.objects.dedup_by_key(|obj| obj.tx_hash.clone());
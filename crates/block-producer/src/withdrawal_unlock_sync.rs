@@ -0,0 +1,193 @@
+//! Track the withdrawal cells unlocked to their owners on L1, so that
+//! `gw_get_withdrawal` reports the unlock tx on every node, no matter who sent
+//! it.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use gw_rpc_client::l1_client::L1Client;
+use gw_store::{traits::chain_store::ChainStore, transaction::StoreTransaction, Store};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    h256::*,
+    packed::{
+        Block, CellOutput, OutPoint, Transaction, UnlockWithdrawalWitness,
+        UnlockWithdrawalWitnessReader, UnlockWithdrawalWitnessUnion, WitnessArgs,
+        WitnessArgsReader,
+    },
+    prelude::*,
+};
+use gw_utils::{withdrawal::parse_lock_args, RollupContext};
+use tracing::instrument;
+
+use crate::types::ChainEvent;
+
+pub struct WithdrawalUnlockSync {
+    store: Store,
+    l1_client: Arc<dyn L1Client>,
+    rollup_context: RollupContext,
+}
+
+impl WithdrawalUnlockSync {
+    pub fn new(store: Store, l1_client: Arc<dyn L1Client>, rollup_context: RollupContext) -> Self {
+        WithdrawalUnlockSync {
+            store,
+            l1_client,
+            rollup_context,
+        }
+    }
+
+    #[instrument(skip_all, name = "withdrawal unlock sync handle_event")]
+    pub async fn handle_event(&self, event: &ChainEvent) -> Result<()> {
+        match event {
+            ChainEvent::NewBlock { block } => self.sync_block(block).await,
+            ChainEvent::Reverted { new_block, .. } => {
+                self.revert_from(new_block.header().raw().number().unpack())?;
+                self.sync_block(new_block).await
+            }
+        }
+    }
+
+    /// Forget the withdrawal cells unlocked by the txs in the L1 blocks at or
+    /// above `l1_block_number`, which are no longer on the L1 chain.
+    pub fn revert_from(&self, l1_block_number: u64) -> Result<()> {
+        let mut db = self.store.begin_transaction();
+        db.remove_withdrawal_unlocks_from(l1_block_number)?;
+        db.commit()?;
+        Ok(())
+    }
+
+    /// Record the withdrawal cells unlocked to owners by the txs in the L1
+    /// block.
+    pub async fn sync_block(&self, block: &Block) -> Result<()> {
+        let unlocked: Vec<(OutPoint, H256)> = block
+            .transactions()
+            .into_iter()
+            .flat_map(|tx| {
+                let tx_hash = tx.hash();
+                let inputs = tx.raw().inputs().into_iter();
+                // The witness of a withdrawal cell is at the same index as the input.
+                let witnesses = tx.witnesses().into_iter();
+                inputs
+                    .zip(witnesses)
+                    .filter(|(_input, witness)| is_unlock_via_finalize(&witness.unpack()))
+                    .map(move |(input, _witness)| (input.previous_output(), tx_hash))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if unlocked.is_empty() {
+            return Ok(());
+        }
+
+        let l1_block_number: u64 = block.header().raw().number().unpack();
+        let mut db = self.store.begin_transaction();
+        for (out_point, unlock_tx_hash) in unlocked {
+            let submit_tx_hash: H256 = out_point.tx_hash().unpack();
+            let submit_tx = match self
+                .l1_client
+                .get_packed_transaction(submit_tx_hash)
+                .await?
+            {
+                Some(tx) => tx,
+                None => continue,
+            };
+            let index: u32 = out_point.index().unpack();
+            if let Some(withdrawal_hash) =
+                find_withdrawal_hash(&db, &self.rollup_context, &submit_tx, index)?
+            {
+                log::info!(
+                    "[withdrawal unlock sync] withdrawal {} unlocked in tx {}",
+                    withdrawal_hash.pack(),
+                    unlock_tx_hash.pack()
+                );
+                db.set_withdrawal_unlock_tx_hash(
+                    &withdrawal_hash,
+                    &unlock_tx_hash,
+                    l1_block_number,
+                )?;
+            }
+        }
+        db.commit()?;
+        Ok(())
+    }
+}
+
+fn is_unlock_via_finalize(witness: &Bytes) -> bool {
+    if WitnessArgsReader::verify(witness, false).is_err() {
+        return false;
+    }
+    let lock: Bytes = match WitnessArgs::new_unchecked(witness.clone()).lock().to_opt() {
+        Some(lock) => lock.unpack(),
+        None => return false,
+    };
+    if UnlockWithdrawalWitnessReader::verify(&lock, false).is_err() {
+        return false;
+    }
+    matches!(
+        UnlockWithdrawalWitness::new_unchecked(lock).to_enum(),
+        UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(_)
+    )
+}
+
+fn is_withdrawal_cell(output: &CellOutput, rollup_context: &RollupContext) -> bool {
+    let lock = output.lock();
+    let args = lock.args().raw_data();
+    lock.code_hash() == rollup_context.rollup_config.withdrawal_script_type_hash()
+        && lock.hash_type() == ScriptHashType::Type.into()
+        && args.len() >= 32
+        && args[..32] == rollup_context.rollup_script_hash
+}
+
+/// Find the withdrawal of the `index`th output of `submit_tx`. The submit tx
+/// of a block creates a withdrawal cell for each withdrawal of the block, in
+/// the same order.
+///
+/// Returns `None` if the output isn't a withdrawal cell of a block on the
+/// local chain.
+pub fn find_withdrawal_hash(
+    db: &StoreTransaction,
+    rollup_context: &RollupContext,
+    submit_tx: &Transaction,
+    index: u32,
+) -> Result<Option<H256>> {
+    let outputs = submit_tx.raw().outputs();
+    let output = match outputs.get(index as usize) {
+        Some(output) if is_withdrawal_cell(&output, rollup_context) => output,
+        _ => return Ok(None),
+    };
+    let args: Bytes = output.lock().args().unpack();
+    let lock_args = parse_lock_args(&args)?.lock_args;
+    let block_hash: H256 = lock_args.withdrawal_block_hash().unpack();
+    let block = match db.get_block(&block_hash)? {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    // Cells of reverted blocks are created by other submit txs.
+    if db.get_block_submit_tx_hash(block.raw().number().unpack()) != Some(submit_tx.hash()) {
+        return Ok(None);
+    }
+
+    let position = outputs
+        .into_iter()
+        .take(index as usize)
+        .filter(|output| is_withdrawal_cell(output, rollup_context))
+        .count();
+    let withdrawal = match block.withdrawals().get(position) {
+        Some(withdrawal) => withdrawal,
+        None => return Ok(None),
+    };
+    let raw = withdrawal.raw();
+    if raw.account_script_hash().as_slice() != lock_args.account_script_hash().as_slice()
+        || raw.owner_lock_hash().as_slice() != lock_args.owner_lock_hash().as_slice()
+        || raw.capacity().as_slice() != output.capacity().as_slice()
+    {
+        log::warn!(
+            "[withdrawal unlock sync] withdrawal cell {} mismatches the withdrawal of block {}",
+            index,
+            block_hash.pack()
+        );
+        return Ok(None);
+    }
+    Ok(Some(withdrawal.hash()))
+}
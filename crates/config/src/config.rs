@@ -59,6 +59,7 @@ pub enum RPCMethods {
     PProf,
     Test,
     Debug,
+    Admin,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -68,6 +69,9 @@ pub struct RPCServerConfig {
     #[serde(default)]
     pub enable_methods: HashSet<RPCMethods>,
     pub send_tx_rate_limit: Option<RPCRateLimit>,
+    /// Directory of the checkpoints created by `gw_create_checkpoint`.
+    #[serde(default)]
+    pub checkpoint_root: Option<PathBuf>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -96,7 +100,34 @@ pub struct RPCRateLimit {
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WalletConfig {
+    // secp256k1 private key file, ignored if `signer` is set
+    #[serde(default)]
     pub privkey_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<SignerConfig>,
+}
+
+/// Sign with a key that is not stored in plain text on the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// A signer service speaking line delimited JSON-RPC.
+    Remote {
+        // `unix:<path>` or `tcp:<host>:<port>`
+        address: String,
+        #[serde(default = "default_remote_signer_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// A private key file encrypted with a passphrase.
+    Keystore {
+        path: PathBuf,
+        // name of the environment variable that holds the passphrase
+        passphrase_env: String,
+    },
+}
+
+fn default_remote_signer_timeout_ms() -> u64 {
+    5000
 }
 
 // NOTE: Rewards receiver lock must be different than lock in WalletConfig,
@@ -129,6 +160,9 @@ pub struct ContractsCellDep {
 pub enum RegistryType {
     #[default]
     Eth,
+    /// CKB secp256k1-blake160 address, requires the CKB registry, see
+    /// `GenesisConfig::ckb_registry_validator_type_hash`.
+    Ckb,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -147,6 +181,8 @@ pub struct BlockProducerConfig {
     pub challenger_config: ChallengerConfig,
     pub wallet_config: Option<WalletConfig>,
     pub withdrawal_unlocker_wallet_config: Option<WalletConfig>,
+    /// Run a standby producer in full node mode, see `LeaderLeaseConfig`.
+    pub leader_lease: Option<LeaderLeaseConfig>,
 }
 
 impl Default for BlockProducerConfig {
@@ -159,6 +195,7 @@ impl Default for BlockProducerConfig {
             challenger_config: ChallengerConfig::default(),
             wallet_config: None,
             withdrawal_unlocker_wallet_config: None,
+            leader_lease: None,
         }
     }
 }
@@ -192,6 +229,55 @@ impl Default for PscConfig {
     }
 }
 
+/// Only the holder of the leader lease produces blocks. Other producers are
+/// standby: they follow the holder with P2P block sync, and take over producing
+/// once the lease expires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeaderLeaseConfig {
+    /// Name of this producer, must be unique among the producers.
+    pub holder: String,
+    pub backend: LeaseBackendConfig,
+    /// The lease expires if the holder doesn't renew it in time. Default is 30
+    /// seconds.
+    #[serde(default = "default_lease_secs")]
+    pub lease_secs: u64,
+}
+
+fn default_lease_secs() -> u64 {
+    30
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LeaseBackendConfig {
+    /// Lease file guarded by a file lock, on a file system shared by the
+    /// producers. Clocks of the producers should be in sync.
+    File { path: PathBuf },
+}
+
+#[test]
+fn test_leader_lease_config() {
+    let config: BlockProducerConfig = toml::from_str(
+        r#"
+        [leader_lease]
+        holder = "producer-1"
+        backend = { type = "file", path = "/mnt/shared/leader.lease" }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.leader_lease,
+        Some(LeaderLeaseConfig {
+            holder: "producer-1".into(),
+            backend: LeaseBackendConfig::File {
+                path: "/mnt/shared/leader.lease".into(),
+            },
+            lease_secs: 30,
+        })
+    );
+}
+
 #[test]
 fn test_psc_config_optional() {
     #[derive(Deserialize)]
@@ -407,6 +493,9 @@ pub enum NodeMode {
     FullNode,
     Test,
     ReadOnly,
+    /// Read only node serving RPC from a secondary instance of a primary
+    /// node's store on the same host, see `store.secondary`.
+    Secondary,
 }
 
 impl Default for NodeMode {
@@ -444,12 +533,52 @@ pub struct StoreConfig {
     pub cache_size: Option<usize>,
     #[serde(default)]
     pub options_file: Option<PathBuf>,
+    // index transactions and withdrawals by account, for
+    // gw_get_transactions_by_account and gw_get_withdrawals_by_address.
+    // Existing blocks are indexed at startup.
+    #[serde(default)]
+    pub index_account_history: bool,
+    #[serde(default)]
+    pub state_history: StateHistoryConfig,
+    // required by secondary nodes, `path` is the store path of the primary node
+    #[serde(default)]
+    pub secondary: Option<SecondaryStoreConfig>,
 }
 
 fn default_store_path() -> PathBuf {
     "./gw-db".into()
 }
 
+/// Secondary instance of the store, which follows the primary instance
+/// opened by another node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecondaryStoreConfig {
+    // info logs of the secondary instance
+    pub path: PathBuf,
+    #[serde(default = "default_catch_up_interval_ms")]
+    pub catch_up_interval_ms: u64,
+}
+
+fn default_catch_up_interval_ms() -> u64 {
+    500
+}
+
+/// Retention of the per-block state history, which is used by queries at a
+/// block number, e.g. `gw_get_balance` with `block_number`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateHistoryConfig {
+    // keep the state history of the last N blocks, keep all if not set. Must
+    // not be less than the rollup finality blocks
+    #[serde(default)]
+    pub keep_last_blocks: Option<u64>,
+    // also keep the state history of every N-th block before the window. Can't
+    // be changed once blocks are pruned
+    #[serde(default)]
+    pub checkpoint_interval: Option<u64>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FeeConfig {
     // fee_rate: fee / cycles limit
@@ -460,6 +589,29 @@ pub struct FeeConfig {
     pub eth_addr_reg_cycles_limit: u64,
     // fee_rate: fee / cycles limit
     pub withdraw_cycles_limit: u64,
+    // a queued tx or withdrawal can be replaced by one with the same sender and
+    // nonce only if the fee rate is higher by this percentage
+    #[serde(default = "default_replace_by_fee_bump_percentage")]
+    pub replace_by_fee_bump_percentage: u64,
+    // max queued txs and withdrawals of a sender
+    #[serde(default = "default_max_queued_entries_per_sender")]
+    pub max_queued_entries_per_sender: usize,
+    // max queued txs and withdrawals of a sender whose nonces are greater than
+    // the sender's current nonce
+    #[serde(default = "default_max_future_entries_per_sender")]
+    pub max_future_entries_per_sender: usize,
+}
+
+fn default_replace_by_fee_bump_percentage() -> u64 {
+    10
+}
+
+fn default_max_queued_entries_per_sender() -> usize {
+    256
+}
+
+fn default_max_future_entries_per_sender() -> usize {
+    64
 }
 
 impl FeeConfig {
@@ -480,6 +632,9 @@ impl Default for FeeConfig {
             sudt_cycles_limit: 20000,
             withdraw_cycles_limit: 20000,
             eth_addr_reg_cycles_limit: 20000, // 1176198 cycles used
+            replace_by_fee_bump_percentage: default_replace_by_fee_bump_percentage(),
+            max_queued_entries_per_sender: default_max_queued_entries_per_sender(),
+            max_future_entries_per_sender: default_max_future_entries_per_sender(),
         }
     }
 }
@@ -18,6 +18,7 @@ pub enum BackendType {
     Sudt,
     Polyjuice,
     EthAddrReg,
+    CkbAddrReg,
     Unknown,
 }
 
@@ -75,6 +76,11 @@ pub struct GenesisConfig {
     pub rollup_type_hash: H256,
     pub meta_contract_validator_type_hash: H256,
     pub eth_registry_validator_type_hash: H256,
+    /// Create the CKB registry account in the genesis block. The registry is
+    /// enabled once the rollup config allows the contract as `CkbAddrReg`, see
+    /// `ForkConfig::enable_ckb_registry`.
+    #[serde(default)]
+    pub ckb_registry_validator_type_hash: Option<H256>,
     // For load secp data and use in challenge transaction
     pub secp_data_dep: CellDep,
     pub rollup_config: RollupConfig,
@@ -116,6 +122,13 @@ pub struct ForkConfig {
     ///   - Remove `state_checkpoints` from RawL2Block
     pub upgrade_global_state_version_to_v2: Option<u64>,
 
+    /// Enable the CKB registry, i.e. the account of the `CkbAddrReg` contract
+    /// allowed by the rollup config. The state-validator enables it once the
+    /// rollup config allows the contract, so the contract must only be added
+    /// to the rollup config after this height.
+    #[serde(default)]
+    pub enable_ckb_registry: Option<u64>,
+
     /// Backend fork configs
     pub backend_forks: Vec<BackendForkConfig>,
 
@@ -153,6 +166,14 @@ impl ForkConfig {
         self.global_state_version(block_number) <= 1
     }
 
+    /// Returns if the CKB registry is enabled
+    pub fn ckb_registry_enabled(&self, block_number: u64) -> bool {
+        match self.enable_ckb_registry {
+            None => false,
+            Some(fork_number) => block_number >= fork_number,
+        }
+    }
+
     /// Return l2 tx cycles limit by block height
     pub fn max_l2_tx_cycles(&self, block_number: u64) -> u64 {
         match self.increase_max_l2_tx_cycles_to_500m {
@@ -192,6 +213,7 @@ mod tests {
         assert_eq!(fork.max_l2_tx_cycles(0), L2TX_MAX_CYCLES_150M);
         assert_eq!(fork.max_l2_tx_cycles(100), L2TX_MAX_CYCLES_150M);
         assert_eq!(fork.max_l2_tx_cycles(u64::MAX), L2TX_MAX_CYCLES_150M);
+        assert!(!fork.ckb_registry_enabled(u64::MAX));
     }
 
     #[test]
@@ -205,5 +227,12 @@ mod tests {
         assert_eq!(fork.max_l2_tx_cycles(42), L2TX_MAX_CYCLES_500M);
         assert_eq!(fork.max_l2_tx_cycles(100), L2TX_MAX_CYCLES_500M);
         assert_eq!(fork.max_l2_tx_cycles(u64::MAX), L2TX_MAX_CYCLES_500M);
+
+        let fork = ForkConfig {
+            enable_ckb_registry: Some(42),
+            ..Default::default()
+        };
+        assert!(!fork.ckb_registry_enabled(41));
+        assert!(fork.ckb_registry_enabled(42));
     }
 }
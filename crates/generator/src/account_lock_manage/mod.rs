@@ -1,6 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
-use gw_common::registry_address::RegistryAddress;
+use gw_common::{registry::Registries, registry_address::RegistryAddress};
 use gw_types::h256::*;
 use gw_types::{
     bytes::Bytes,
@@ -12,6 +12,8 @@ use gw_utils::RollupContext;
 pub mod always_success;
 pub mod eip712;
 pub mod secp256k1;
+pub mod secp256k1_blake160;
+pub mod secp256r1_webauthn;
 
 use crate::error::LockAlgorithmError;
 
@@ -34,6 +36,16 @@ pub trait LockAlgorithm {
         withdrawal: &WithdrawalRequestExtra,
         withdrawal_address: RegistryAddress,
     ) -> Result<(), LockAlgorithmError>;
+
+    /// Check that the lock signs for `address`, `registries` are the
+    /// registries of the rollup.
+    fn check_address(
+        &self,
+        _registries: &Registries,
+        _address: &RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        Ok(())
+    }
 }
 
 #[derive(Default, Clone)]
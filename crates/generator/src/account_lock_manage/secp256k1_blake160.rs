@@ -0,0 +1,279 @@
+use std::convert::TryInto;
+
+use super::secp256k1::SECP256K1;
+use super::LockAlgorithm;
+use crate::error::LockAlgorithmError;
+use gw_common::blake2b::new_blake2b;
+use gw_common::registry::ckb_registry::{calc_tx_signing_message, calc_withdrawal_signing_message};
+use gw_common::registry::Registries;
+use gw_common::registry_address::RegistryAddress;
+use gw_types::packed::WithdrawalRequestExtra;
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    packed::{L2Transaction, Script},
+};
+use gw_utils::RollupContext;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+
+/// CKB secp256k1-blake160 signatures, the pubkey hash is the first 20 bytes
+/// of the blake2b hash of the compressed pubkey, the same as the
+/// secp256k1-blake160-sighash-all lock on CKB.
+///
+/// EOA script args: rollup_script_hash(32 bytes) | pubkey_hash(20 bytes)
+#[derive(Debug, Default)]
+pub struct Secp256k1Blake160;
+
+impl Secp256k1Blake160 {
+    fn verify_alone(
+        &self,
+        lock_args: Bytes,
+        signature: Bytes,
+        message: H256,
+    ) -> Result<(), LockAlgorithmError> {
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs);
+        }
+
+        let pubkey_hash = self.recover(message, signature.as_ref())?;
+        if pubkey_hash.as_ref() != &lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Secp256k1Blake160: Mismatch pubkey hash".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl LockAlgorithm for Secp256k1Blake160 {
+    fn recover(&self, message: H256, signature: &[u8]) -> Result<Bytes, LockAlgorithmError> {
+        let signature: [u8; 65] = signature.try_into().map_err(|_| {
+            LockAlgorithmError::InvalidSignature(format!(
+                "Signature length is {}, expect 65",
+                signature.len()
+            ))
+        })?;
+        let signature = {
+            let recid = RecoveryId::from_i32(signature[64].into())
+                .map_err(|err| LockAlgorithmError::InvalidSignature(err.to_string()))?;
+            RecoverableSignature::from_compact(&signature[..64], recid)
+                .map_err(|err| LockAlgorithmError::InvalidSignature(err.to_string()))?
+        };
+        let msg = secp256k1::Message::from_slice(message.as_slice())
+            .map_err(|err| LockAlgorithmError::InvalidSignature(err.to_string()))?;
+        let pubkey = SECP256K1
+            .recover_ecdsa(&msg, &signature)
+            .map_err(|err| LockAlgorithmError::InvalidSignature(err.to_string()))?;
+
+        let mut hasher = new_blake2b();
+        hasher.update(&pubkey.serialize());
+        let mut buf = [0u8; 32];
+        hasher.finalize(&mut buf);
+        Ok(Bytes::copy_from_slice(&buf[..20]))
+    }
+
+    fn verify_tx(
+        &self,
+        ctx: &RollupContext,
+        sender_address: RegistryAddress,
+        sender_script: Script,
+        receiver_script: Script,
+        tx: L2Transaction,
+    ) -> Result<(), LockAlgorithmError> {
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = tx.raw().chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidTransactionArgs);
+        }
+        let message = calc_tx_signing_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash(),
+            &receiver_script.hash(),
+            &tx.raw(),
+        );
+        self.verify_alone(
+            sender_script.args().unpack(),
+            tx.signature().unpack(),
+            message,
+        )
+    }
+
+    fn verify_withdrawal(
+        &self,
+        ctx: &RollupContext,
+        sender_script: Script,
+        withdrawal: &WithdrawalRequestExtra,
+        address: RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = withdrawal.raw().chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Invalid chain id {} expected {}",
+                chain_id, expected_chain_id
+            )));
+        }
+        let message = calc_withdrawal_signing_message(&ctx.rollup_script_hash, &withdrawal.raw());
+        self.verify_alone(
+            sender_script.args().unpack(),
+            withdrawal.request().signature().unpack(),
+            message,
+        )
+    }
+
+    /// Only CKB registry addresses are signed by the lock.
+    fn check_address(
+        &self,
+        registries: &Registries,
+        address: &RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        if Some(address.registry_id) != registries.ckb_registry_id() {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Secp256k1Blake160: Invalid registry id {}",
+                address.registry_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+    use gw_types::packed::{
+        RawL2Transaction, RawWithdrawalRequest, RollupConfig, WithdrawalRequest,
+    };
+    use secp256k1::{PublicKey, SecretKey};
+
+    const CHAIN_ID: u64 = 42;
+    const CKB_REGISTRY_ACCOUNT_ID: u32 = 3;
+
+    fn sign(secret_key: &SecretKey, message: H256) -> Bytes {
+        let msg = secp256k1::Message::from_slice(&message).unwrap();
+        let (recid, data) = SECP256K1
+            .sign_ecdsa_recoverable(&msg, secret_key)
+            .serialize_compact();
+        let mut signature = data.to_vec();
+        signature.push(recid.to_i32() as u8);
+        signature.into()
+    }
+
+    fn setup() -> (SecretKey, RollupContext, RegistryAddress, Script) {
+        let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&*SECP256K1, &secret_key);
+        let mut hasher = new_blake2b();
+        hasher.update(&pubkey.serialize());
+        let mut pubkey_hash = [0u8; 32];
+        hasher.finalize(&mut pubkey_hash);
+
+        let ctx = RollupContext {
+            rollup_script_hash: [7u8; 32],
+            rollup_config: RollupConfig::new_builder()
+                .chain_id(CHAIN_ID.pack())
+                .build(),
+            ..Default::default()
+        };
+        let address = RegistryAddress::new(CKB_REGISTRY_ACCOUNT_ID, pubkey_hash[..20].to_vec());
+        let args = [&ctx.rollup_script_hash[..], &pubkey_hash[..20]].concat();
+        let sender_script = Script::new_builder().args(args.pack()).build();
+        (secret_key, ctx, address, sender_script)
+    }
+
+    #[test]
+    fn test_secp256k1_blake160_verify_tx() {
+        let (secret_key, ctx, sender_address, sender_script) = setup();
+        let receiver_script = Script::new_builder().args(vec![3u8; 32].pack()).build();
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(CHAIN_ID.pack())
+            .nonce(1u32.pack())
+            .to_id(4u32.pack())
+            .args(vec![5u8; 8].pack())
+            .build();
+        let message = calc_tx_signing_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash(),
+            &receiver_script.hash(),
+            &raw_tx,
+        );
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx.clone())
+            .signature(sign(&secret_key, message).pack())
+            .build();
+        let lock = Secp256k1Blake160::default();
+        lock.verify_tx(
+            &ctx,
+            sender_address.clone(),
+            sender_script.clone(),
+            receiver_script.clone(),
+            tx.clone(),
+        )
+        .expect("verify signature");
+
+        // Signed by another key
+        let other_key = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let tx = tx
+            .as_builder()
+            .signature(sign(&other_key, message).pack())
+            .build();
+        lock.verify_tx(
+            &ctx,
+            sender_address.clone(),
+            sender_script.clone(),
+            receiver_script.clone(),
+            tx,
+        )
+        .unwrap_err();
+
+        // Signed for another receiver
+        let other_receiver = Script::new_builder().args(vec![6u8; 32].pack()).build();
+        let message = calc_tx_signing_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash(),
+            &other_receiver.hash(),
+            &raw_tx,
+        );
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx)
+            .signature(sign(&secret_key, message).pack())
+            .build();
+        lock.verify_tx(&ctx, sender_address, sender_script, receiver_script, tx)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_secp256k1_blake160_verify_withdrawal() {
+        let (secret_key, ctx, address, sender_script) = setup();
+        let raw = RawWithdrawalRequest::new_builder()
+            .chain_id(CHAIN_ID.pack())
+            .nonce(1u32.pack())
+            .capacity(1000u64.pack())
+            .account_script_hash(sender_script.hash().pack())
+            .registry_id(CKB_REGISTRY_ACCOUNT_ID.pack())
+            .build();
+        let message = calc_withdrawal_signing_message(&ctx.rollup_script_hash, &raw);
+        let withdrawal = WithdrawalRequestExtra::new_builder()
+            .request(
+                WithdrawalRequest::new_builder()
+                    .raw(raw)
+                    .signature(sign(&secret_key, message).pack())
+                    .build(),
+            )
+            .build();
+        let lock = Secp256k1Blake160::default();
+        lock.verify_withdrawal(&ctx, sender_script, &withdrawal, address)
+            .expect("verify signature");
+    }
+
+    #[test]
+    fn test_secp256k1_blake160_check_address_without_ckb_registry() {
+        let (_, _, address, _) = setup();
+        let lock = Secp256k1Blake160::default();
+        lock.check_address(&Registries::eth_only(), &address)
+            .unwrap_err();
+        let eth_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, address.address);
+        lock.check_address(&Registries::eth_only(), &eth_address)
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,484 @@
+use super::LockAlgorithm;
+use crate::error::LockAlgorithmError;
+use gw_common::blake2b::new_blake2b;
+use gw_common::registry::ckb_registry::{calc_tx_signing_message, calc_withdrawal_signing_message};
+use gw_common::registry::Registries;
+use gw_common::registry_address::RegistryAddress;
+use gw_types::packed::WithdrawalRequestExtra;
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    packed::{L2Transaction, Script},
+};
+use gw_utils::RollupContext;
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+const PUBKEY_LEN: usize = 33;
+/// rp id hash(32 bytes) | flags(1 byte) | sign count(4 bytes)
+const MIN_AUTHENTICATOR_DATA_LEN: usize = 37;
+/// User present
+const FLAG_UP: u8 = 0x01;
+const CLIENT_DATA_TYPE_GET: &str = "webauthn.get";
+
+/// WebAuthn assertions signed by secp256r1 (P-256) passkeys. The challenge of
+/// the assertion is the signing message, and the pubkey hash is the first 20
+/// bytes of the blake2b hash of the compressed pubkey.
+///
+/// EOA script args: rollup_script_hash(32 bytes) | pubkey_hash(20 bytes)
+///
+/// Signature:
+/// pubkey(33 bytes, compressed) | signature_len(1 byte) | signature(DER)
+/// | authenticator_data_len(2 bytes, little endian) | authenticator_data
+/// | client_data_json
+///
+/// The addresses of WebAuthn EOAs are registered in the CKB registry. The lock
+/// algorithm is registered for the `web_authn` EOA type hash of the rollup
+/// config, which must be an L1 lock verifying the same assertions, so that the
+/// signatures can be checked in challenges.
+#[derive(Debug, Default)]
+pub struct Secp256r1WebAuthn;
+
+struct Assertion<'a> {
+    pubkey: &'a [u8],
+    signature: &'a [u8],
+    authenticator_data: &'a [u8],
+    client_data_json: &'a [u8],
+}
+
+impl<'a> Assertion<'a> {
+    fn from_slice(data: &'a [u8]) -> Result<Self, LockAlgorithmError> {
+        let invalid = |reason: &str| {
+            LockAlgorithmError::InvalidSignature(format!("Secp256r1WebAuthn: {}", reason))
+        };
+        let (pubkey, rest) = split_at(data, PUBKEY_LEN).ok_or_else(|| invalid("Invalid pubkey"))?;
+        let (signature_len, rest) =
+            split_at(rest, 1).ok_or_else(|| invalid("Invalid signature"))?;
+        let (signature, rest) =
+            split_at(rest, signature_len[0].into()).ok_or_else(|| invalid("Invalid signature"))?;
+        let (authenticator_data_len, rest) =
+            split_at(rest, 2).ok_or_else(|| invalid("Invalid authenticator data"))?;
+        let authenticator_data_len =
+            u16::from_le_bytes([authenticator_data_len[0], authenticator_data_len[1]]);
+        let (authenticator_data, client_data_json) = split_at(rest, authenticator_data_len.into())
+            .ok_or_else(|| invalid("Invalid authenticator data"))?;
+        Ok(Assertion {
+            pubkey,
+            signature,
+            authenticator_data,
+            client_data_json,
+        })
+    }
+
+    /// Verify the assertion signed the challenge, the pubkey is checked by
+    /// the caller.
+    fn verify(&self, challenge: &H256) -> Result<(), LockAlgorithmError> {
+        let invalid = |reason: String| {
+            LockAlgorithmError::InvalidSignature(format!("Secp256r1WebAuthn: {}", reason))
+        };
+
+        if self.authenticator_data.len() < MIN_AUTHENTICATOR_DATA_LEN {
+            return Err(invalid(format!(
+                "Authenticator data length is {}, expect at least {}",
+                self.authenticator_data.len(),
+                MIN_AUTHENTICATOR_DATA_LEN
+            )));
+        }
+        if self.authenticator_data[32] & FLAG_UP == 0 {
+            return Err(invalid("User not present".to_string()));
+        }
+
+        let client_data: serde_json::Value = serde_json::from_slice(self.client_data_json)
+            .map_err(|err| invalid(format!("Invalid client data: {}", err)))?;
+        let type_ = client_data.get("type").and_then(|t| t.as_str());
+        if type_ != Some(CLIENT_DATA_TYPE_GET) {
+            return Err(invalid(format!("Invalid client data type {:?}", type_)));
+        }
+        let expected_challenge = base64::encode_config(challenge, base64::URL_SAFE_NO_PAD);
+        if client_data.get("challenge").and_then(|c| c.as_str())
+            != Some(expected_challenge.as_str())
+        {
+            return Err(invalid("Mismatch challenge".to_string()));
+        }
+
+        let pubkey = VerifyingKey::from_sec1_bytes(self.pubkey)
+            .map_err(|err| invalid(format!("Invalid pubkey: {}", err)))?;
+        let signature = Signature::from_der(self.signature)
+            .map_err(|err| invalid(format!("Invalid signature: {}", err)))?;
+        let signed_data = [
+            self.authenticator_data,
+            Sha256::digest(self.client_data_json).as_slice(),
+        ]
+        .concat();
+        pubkey
+            .verify(&signed_data, &signature)
+            .map_err(|err| invalid(err.to_string()))
+    }
+}
+
+fn split_at(data: &[u8], mid: usize) -> Option<(&[u8], &[u8])> {
+    if data.len() < mid {
+        return None;
+    }
+    Some(data.split_at(mid))
+}
+
+fn pubkey_hash(pubkey: &[u8]) -> [u8; 20] {
+    let mut hasher = new_blake2b();
+    hasher.update(pubkey);
+    let mut buf = [0u8; 32];
+    hasher.finalize(&mut buf);
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&buf[..20]);
+    hash
+}
+
+impl Secp256r1WebAuthn {
+    fn verify_alone(
+        &self,
+        lock_args: Bytes,
+        signature: Bytes,
+        message: H256,
+    ) -> Result<(), LockAlgorithmError> {
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs);
+        }
+
+        let assertion = Assertion::from_slice(&signature)?;
+        if pubkey_hash(assertion.pubkey) != lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Secp256r1WebAuthn: Mismatch pubkey hash".to_string(),
+            ));
+        }
+        assertion.verify(&message)
+    }
+
+    /// The sender address must be the pubkey hash in the EOA script args.
+    fn check_pubkey_hash(
+        address: &RegistryAddress,
+        sender_script: &Script,
+    ) -> Result<(), LockAlgorithmError> {
+        let args: Bytes = sender_script.args().unpack();
+        if args.len() != 52 || address.address != args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Secp256r1WebAuthn: Mismatch address".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl LockAlgorithm for Secp256r1WebAuthn {
+    /// The pubkey is carried in the signature rather than recovered, returns
+    /// the pubkey hash once the assertion is verified.
+    fn recover(&self, message: H256, signature: &[u8]) -> Result<Bytes, LockAlgorithmError> {
+        let assertion = Assertion::from_slice(signature)?;
+        assertion.verify(&message)?;
+        Ok(Bytes::copy_from_slice(&pubkey_hash(assertion.pubkey)))
+    }
+
+    fn verify_tx(
+        &self,
+        ctx: &RollupContext,
+        sender_address: RegistryAddress,
+        sender_script: Script,
+        receiver_script: Script,
+        tx: L2Transaction,
+    ) -> Result<(), LockAlgorithmError> {
+        Self::check_pubkey_hash(&sender_address, &sender_script)?;
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = tx.raw().chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidTransactionArgs);
+        }
+        let message = calc_tx_signing_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash(),
+            &receiver_script.hash(),
+            &tx.raw(),
+        );
+        self.verify_alone(
+            sender_script.args().unpack(),
+            tx.signature().unpack(),
+            message,
+        )
+    }
+
+    fn verify_withdrawal(
+        &self,
+        ctx: &RollupContext,
+        sender_script: Script,
+        withdrawal: &WithdrawalRequestExtra,
+        address: RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        Self::check_pubkey_hash(&address, &sender_script)?;
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = withdrawal.raw().chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Invalid chain id {} expected {}",
+                chain_id, expected_chain_id
+            )));
+        }
+        let message = calc_withdrawal_signing_message(&ctx.rollup_script_hash, &withdrawal.raw());
+        self.verify_alone(
+            sender_script.args().unpack(),
+            withdrawal.request().signature().unpack(),
+            message,
+        )
+    }
+
+    /// Only CKB registry addresses are signed by the lock.
+    fn check_address(
+        &self,
+        registries: &Registries,
+        address: &RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        if Some(address.registry_id) != registries.ckb_registry_id() {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Secp256r1WebAuthn: Invalid registry id {}",
+                address.registry_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_types::packed::{
+        RawL2Transaction, RawWithdrawalRequest, RollupConfig, WithdrawalRequest,
+    };
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    const CHAIN_ID: u64 = 42;
+    const REGISTRY_ID: u32 = 4;
+
+    // An assertion of a P-256 passkey, whose challenge is `FIXTURE_MESSAGE`.
+    const FIXTURE_PUBKEY: &str =
+        "02232c7d1bdb1ecb5dc841d6811e3059d6377bf6a590db0d46180ba4496a6e8c44";
+    const FIXTURE_MESSAGE: &str =
+        "3f6fc30c80a4a2a81ea01a66b20810a030a69984d24646e3201eca7d9df3c94b";
+    const FIXTURE_CLIENT_DATA_JSON: &str = r#"{"type":"webauthn.get","challenge":"P2_DDICkoqgeoBpmsggQoDCmmYTSRkbjIB7KfZ3zyUs","origin":"http://localhost:8080","crossOrigin":false}"#;
+    const FIXTURE_AUTHENTICATOR_DATA: &str =
+        "49960de5880e8c687434170f6476605b8fe4aeb9a28632c7995cf3ba831d97630500000001";
+    const FIXTURE_SIGNATURE: &str = "304502202420dbebe7f9ed8bcbd4126c2200ceffe66ada743f2e9be7071d4e2317df5d04022100fc9a7317aaa167c6551371d2bef4b2f43c17c1e7c8773a62421263fa487788c5";
+
+    fn encode(
+        pubkey: &[u8],
+        signature: &[u8],
+        authenticator_data: &[u8],
+        client_data_json: &[u8],
+    ) -> Bytes {
+        let mut buf = pubkey.to_vec();
+        buf.push(signature.len() as u8);
+        buf.extend_from_slice(signature);
+        buf.extend_from_slice(&(authenticator_data.len() as u16).to_le_bytes());
+        buf.extend_from_slice(authenticator_data);
+        buf.extend_from_slice(client_data_json);
+        buf.into()
+    }
+
+    fn fixture_message() -> H256 {
+        hex::decode(FIXTURE_MESSAGE).unwrap().try_into().unwrap()
+    }
+
+    fn fixture(authenticator_data: &[u8], client_data_json: &[u8]) -> Bytes {
+        encode(
+            &hex::decode(FIXTURE_PUBKEY).unwrap(),
+            &hex::decode(FIXTURE_SIGNATURE).unwrap(),
+            authenticator_data,
+            client_data_json,
+        )
+    }
+
+    fn sign(signing_key: &SigningKey, message: H256) -> Bytes {
+        let challenge = base64::encode_config(message, base64::URL_SAFE_NO_PAD);
+        let client_data_json = format!(
+            r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://godwoken.test"}}"#,
+            challenge
+        );
+        let authenticator_data = [&[9u8; 32][..], &[FLAG_UP], &[0, 0, 0, 1]].concat();
+        let signed_data = [
+            &authenticator_data[..],
+            Sha256::digest(client_data_json.as_bytes()).as_slice(),
+        ]
+        .concat();
+        let signature: Signature = signing_key.sign(&signed_data);
+        let pubkey = signing_key.verifying_key().to_encoded_point(true);
+        encode(
+            pubkey.as_bytes(),
+            signature.to_der().as_bytes(),
+            &authenticator_data,
+            client_data_json.as_bytes(),
+        )
+    }
+
+    fn setup() -> (SigningKey, RollupContext, RegistryAddress, Script) {
+        let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let pubkey = signing_key.verifying_key().to_encoded_point(true);
+        let pubkey_hash = pubkey_hash(pubkey.as_bytes());
+
+        let ctx = RollupContext {
+            rollup_script_hash: [7u8; 32],
+            rollup_config: RollupConfig::new_builder()
+                .chain_id(CHAIN_ID.pack())
+                .build(),
+            ..Default::default()
+        };
+        let address = RegistryAddress::new(REGISTRY_ID, pubkey_hash.to_vec());
+        let args = [&ctx.rollup_script_hash[..], &pubkey_hash[..]].concat();
+        let sender_script = Script::new_builder().args(args.pack()).build();
+        (signing_key, ctx, address, sender_script)
+    }
+
+    #[test]
+    fn test_webauthn_assertion_fixture() {
+        let message = fixture_message();
+        let authenticator_data = hex::decode(FIXTURE_AUTHENTICATOR_DATA).unwrap();
+        let client_data_json = FIXTURE_CLIENT_DATA_JSON.as_bytes();
+
+        let lock = Secp256r1WebAuthn::default();
+        let signature = fixture(&authenticator_data, client_data_json);
+        let pubkey_hash = lock.recover(message, &signature).expect("verify assertion");
+        assert_eq!(
+            pubkey_hash.as_ref(),
+            &super::pubkey_hash(&hex::decode(FIXTURE_PUBKEY).unwrap())
+        );
+
+        // Another challenge
+        let mut other_message = message;
+        other_message[0] ^= 1;
+        lock.recover(other_message, &signature).unwrap_err();
+
+        // User not present
+        let mut tampered_authenticator_data = authenticator_data.clone();
+        tampered_authenticator_data[32] &= !FLAG_UP;
+        let signature = fixture(&tampered_authenticator_data, client_data_json);
+        lock.recover(message, &signature).unwrap_err();
+
+        // Not an assertion
+        let client_data_json = FIXTURE_CLIENT_DATA_JSON.replace("webauthn.get", "webauthn.create");
+        let signature = fixture(&authenticator_data, client_data_json.as_bytes());
+        lock.recover(message, &signature).unwrap_err();
+
+        // Truncated
+        let signature = fixture(&authenticator_data, FIXTURE_CLIENT_DATA_JSON.as_bytes());
+        lock.recover(message, &signature[..PUBKEY_LEN + 10])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_webauthn_fixture_mismatch_pubkey_hash() {
+        let message = fixture_message();
+        let signature = fixture(
+            &hex::decode(FIXTURE_AUTHENTICATOR_DATA).unwrap(),
+            FIXTURE_CLIENT_DATA_JSON.as_bytes(),
+        );
+        let pubkey_hash = super::pubkey_hash(&hex::decode(FIXTURE_PUBKEY).unwrap());
+        let lock = Secp256r1WebAuthn::default();
+
+        let lock_args = Bytes::from([&[0u8; 32][..], &pubkey_hash[..]].concat());
+        lock.verify_alone(lock_args, signature.clone(), message)
+            .expect("verify signature");
+
+        let lock_args = Bytes::from([&[0u8; 32][..], &[1u8; 20][..]].concat());
+        lock.verify_alone(lock_args, signature, message)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_secp256r1_webauthn_verify_tx() {
+        let (signing_key, ctx, sender_address, sender_script) = setup();
+        let receiver_script = Script::new_builder().args(vec![3u8; 32].pack()).build();
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(CHAIN_ID.pack())
+            .nonce(1u32.pack())
+            .to_id(4u32.pack())
+            .args(vec![5u8; 8].pack())
+            .build();
+        let message = calc_tx_signing_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash(),
+            &receiver_script.hash(),
+            &raw_tx,
+        );
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx.clone())
+            .signature(sign(&signing_key, message).pack())
+            .build();
+        let lock = Secp256r1WebAuthn::default();
+        lock.verify_tx(
+            &ctx,
+            sender_address.clone(),
+            sender_script.clone(),
+            receiver_script.clone(),
+            tx.clone(),
+        )
+        .expect("verify signature");
+
+        // Signed by another passkey
+        let other_key = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+        let tx = tx
+            .as_builder()
+            .signature(sign(&other_key, message).pack())
+            .build();
+        lock.verify_tx(
+            &ctx,
+            sender_address.clone(),
+            sender_script.clone(),
+            receiver_script.clone(),
+            tx,
+        )
+        .unwrap_err();
+
+        // Address of another EOA
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx)
+            .signature(sign(&signing_key, message).pack())
+            .build();
+        let other_address = RegistryAddress::new(sender_address.registry_id, vec![1u8; 20]);
+        lock.verify_tx(&ctx, other_address, sender_script, receiver_script, tx)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_secp256r1_webauthn_verify_withdrawal() {
+        let (signing_key, ctx, address, sender_script) = setup();
+        let raw = RawWithdrawalRequest::new_builder()
+            .chain_id(CHAIN_ID.pack())
+            .nonce(1u32.pack())
+            .capacity(1000u64.pack())
+            .account_script_hash(sender_script.hash().pack())
+            .registry_id(REGISTRY_ID.pack())
+            .build();
+        let message = calc_withdrawal_signing_message(&ctx.rollup_script_hash, &raw);
+        let withdrawal = WithdrawalRequestExtra::new_builder()
+            .request(
+                WithdrawalRequest::new_builder()
+                    .raw(raw.clone())
+                    .signature(sign(&signing_key, message).pack())
+                    .build(),
+            )
+            .build();
+        let lock = Secp256r1WebAuthn::default();
+        lock.verify_withdrawal(&ctx, sender_script.clone(), &withdrawal, address.clone())
+            .expect("verify signature");
+
+        // Wrong chain id
+        let raw = raw.as_builder().chain_id((CHAIN_ID + 1).pack()).build();
+        let message = calc_withdrawal_signing_message(&ctx.rollup_script_hash, &raw);
+        let withdrawal = WithdrawalRequestExtra::new_builder()
+            .request(
+                WithdrawalRequest::new_builder()
+                    .raw(raw)
+                    .signature(sign(&signing_key, message).pack())
+                    .build(),
+            )
+            .build();
+        lock.verify_withdrawal(&ctx, sender_script, &withdrawal, address)
+            .unwrap_err();
+    }
+}
@@ -19,8 +19,9 @@ use crate::{error::AccountError, syscalls::L2Syscalls};
 use crate::{error::LockAlgorithmError, traits::StateExt};
 use arc_swap::ArcSwapOption;
 use gw_common::{
-    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    builtins::CKB_SUDT_ACCOUNT_ID,
     error::Error as StateError,
+    registry::Registries,
     registry_address::RegistryAddress,
     state::{build_account_key, State, SUDT_TOTAL_SUPPLY_KEY},
 };
@@ -143,6 +144,7 @@ pub struct MachineRunArgs<'a, C, S> {
     max_cycles: u64,
     backend: &'a Backend,
     cycles_pool: Option<&'a mut CyclesPool>,
+    trace_syscalls: bool,
 }
 
 pub struct Generator {
@@ -207,10 +209,12 @@ impl Generator {
             max_cycles,
             backend,
             mut cycles_pool,
+            trace_syscalls,
         } = args;
 
         let mut context = RunContext::default();
         context.debug_log_buf.reserve(1024);
+        context.syscall_trace = trace_syscalls.then(Vec::new);
         let used_cycles;
         let exit_code;
         let org_cycles_pool = cycles_pool.as_mut().map(|p| p.clone());
@@ -228,6 +232,7 @@ impl Generator {
                     account_lock_manage: &self.account_lock_manage,
                     cycles_pool: &mut cycles_pool,
                     context: &mut context,
+                    trace_data: None,
                 }))
                 .instruction_cycle_func(&instruction_cycles);
             let default_machine = machine_builder.build();
@@ -333,6 +338,12 @@ impl Generator {
         let address = state
             .get_registry_address_by_script_hash(raw.registry_id().unpack(), &account_script_hash)?
             .ok_or(AccountError::RegistryAddressNotFound)?;
+        let registries = Registries::resolve(
+            state,
+            &self.rollup_context.rollup_script_hash,
+            &self.rollup_context.rollup_config,
+        )?;
+        lock_algo.check_address(&registries, &address)?;
 
         lock_algo.verify_withdrawal(self.rollup_context(), account_script, withdrawal, address)?;
 
@@ -345,6 +356,7 @@ impl Generator {
         &self,
         state: &S,
         tx: &L2Transaction,
+        block_number: u64,
     ) -> Result<(), TransactionValidateError> {
         let raw_tx = tx.raw();
         let sender_id: u32 = raw_tx.from_id().unpack();
@@ -377,9 +389,11 @@ impl Generator {
             .get_lock_algorithm(&lock_code_hash)
             .ok_or(LockAlgorithmError::UnknownAccountLock)?;
 
+        let registries = self.rollup_context.registries(state, block_number)?;
         let sender_address = state
-            .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &script_hash)?
+            .find_registry_address_by_script_hash(&registries, &script_hash)?
             .ok_or(AccountError::RegistryAddressNotFound)?;
+        lock_algo.check_address(&registries, &sender_address)?;
 
         lock_algo.verify_tx(
             &self.rollup_context,
@@ -509,7 +523,8 @@ impl Generator {
         }
 
         for req in args.deposit_info_vec.into_iter().map(|i| i.request()) {
-            if let Err(err) = state.apply_deposit_request(&self.rollup_context, &req) {
+            if let Err(err) = state.apply_deposit_request(&self.rollup_context, block_number, &req)
+            {
                 return ApplyBlockResult::Error(err.into());
             }
         }
@@ -546,7 +561,7 @@ impl Generator {
                 hex::encode(tx.hash())
             );
             let now = Instant::now();
-            if let Err(err) = self.check_transaction_signature(&state, &tx) {
+            if let Err(err) = self.check_transaction_signature(&state, &tx, block_number) {
                 let target = build_challenge_target(
                     block_hash,
                     ChallengeTargetType::TxSignature,
@@ -748,7 +763,54 @@ impl Generator {
     }
 
     /// execute a layer2 tx
+    pub fn execute_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        override_max_cycles: Option<u64>,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult> {
+        self.execute_transaction_inner(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            override_max_cycles,
+            cycles_pool,
+            false,
+        )
+    }
+
+    /// execute a layer2 tx and record the dispatched syscalls in
+    /// `RunResult::syscall_trace`
+    pub fn execute_transaction_with_syscall_trace<
+        S: State + CodeStore + JournalDB,
+        C: ChainView,
+    >(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        override_max_cycles: Option<u64>,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult> {
+        self.execute_transaction_inner(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            override_max_cycles,
+            cycles_pool,
+            true,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
     #[instrument(
+        name = "execute_transaction",
         skip_all,
         err(Debug),
         fields(
@@ -764,7 +826,7 @@ impl Generator {
             write_data_count = field::Empty,
         )
     )]
-    pub fn execute_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+    fn execute_transaction_inner<S: State + CodeStore + JournalDB, C: ChainView>(
         &self,
         chain: &C,
         state: &mut S,
@@ -772,6 +834,7 @@ impl Generator {
         raw_tx: &RawL2Transaction,
         override_max_cycles: Option<u64>,
         cycles_pool: Option<&mut CyclesPool>,
+        trace_syscalls: bool,
     ) -> Result<RunResult> {
         let account_id = raw_tx.to_id().unpack();
         let script_hash = state.get_script_hash(account_id)?;
@@ -812,6 +875,7 @@ impl Generator {
             max_cycles,
             backend,
             cycles_pool,
+            trace_syscalls,
         };
 
         let run_context = self.machine_run(args).map_err(|err| {
@@ -903,6 +967,7 @@ impl Generator {
                 .cloned()
                 .collect(),
             debug_log_buf: run_context.debug_log_buf,
+            syscall_trace: run_context.syscall_trace,
         };
 
         // Record run result
@@ -972,8 +1037,11 @@ impl Generator {
         // sender address
         let payer = {
             let script_hash = state.get_script_hash(sender_id)?;
+            let registries = self
+                .rollup_context
+                .registries(state, block_info.number().unpack())?;
             state
-                .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &script_hash)?
+                .find_registry_address_by_script_hash(&registries, &script_hash)?
                 .ok_or_else(|| anyhow::Error::from(TransactionError::ScriptHashNotFound))
                 .context("failed to find sender's account")?
         };
@@ -107,6 +107,18 @@ pub fn build_genesis_from_store(
         ETH_REGISTRY_ACCOUNT_ID
     );
 
+    // setup CKB registry contract, the account is found by its script, see
+    // `Registries::resolve`
+    if let Some(ref ckb_registry_validator_type_hash) = config.ckb_registry_validator_type_hash {
+        tree.create_account_from_script(
+            Script::new_builder()
+                .code_hash(ckb_registry_validator_type_hash.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .args(rollup_context.rollup_script_hash.as_slice().pack())
+                .build(),
+        )?;
+    }
+
     // insert secp256k1 data
     let secp_data_hash = {
         let mut hasher = new_blake2b();
@@ -22,7 +22,7 @@ use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
     h256::*,
-    offchain::CycleMeter,
+    offchain::{CycleMeter, SyscallTraceData, SyscallTraceStep},
     packed::{BlockInfo, LogItem, RawL2Transaction, Script},
     prelude::*,
 };
@@ -82,6 +82,8 @@ pub struct RunContext {
     pub return_data: Bytes,
     pub exit_code: i8,
     pub debug_log_buf: Vec<u8>,
+    /// Syscalls are recorded if this is `Some`.
+    pub syscall_trace: Option<Vec<SyscallTraceStep>>,
 }
 
 impl RunContext {
@@ -100,6 +102,8 @@ pub(crate) struct L2Syscalls<'a, 'b, S, C> {
     pub(crate) raw_tx: &'a RawL2Transaction,
     pub(crate) context: &'b mut RunContext,
     pub(crate) cycles_pool: &'b mut Option<&'a mut CyclesPool>,
+    /// Set by the syscall handler, recorded with the step.
+    pub(crate) trace_data: Option<SyscallTraceData>,
 }
 
 #[allow(dead_code)]
@@ -193,6 +197,35 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
             }
         }
 
+        let trace_cycles = self
+            .context
+            .syscall_trace
+            .is_some()
+            .then(|| machine.cycles());
+        let handled = self.dispatch(code, machine)?;
+        if let (Some(cycles), true) = (trace_cycles, handled) {
+            let step = SyscallTraceStep {
+                syscall: code,
+                name: syscall_name(code),
+                cycles,
+                virtual_cycles: self.context.cycle_meter.r#virtual,
+                return_code: machine.registers()[A0].to_i8(),
+                data: self.trace_data.take(),
+            };
+            if let Some(trace) = self.context.syscall_trace.as_mut() {
+                trace.push(step);
+            }
+        }
+        Ok(handled)
+    }
+}
+
+impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView> L2Syscalls<'a, 'b, S, C> {
+    fn dispatch<Mac: SupportMachine>(
+        &mut self,
+        code: u64,
+        machine: &mut Mac,
+    ) -> Result<bool, VMError> {
         match code {
             SYS_STORE => {
                 let key_addr = machine.registers()[A0].to_u64();
@@ -202,6 +235,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 self.state
                     .update_raw(key, value)
                     .map_err(|err| VMError::Unexpected(format!("store kv error: {}", err)))?;
+                self.trace(|| SyscallTraceData::Store { key, value });
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
             }
@@ -213,6 +247,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                     .state
                     .get_raw(&key)
                     .map_err(|err| VMError::Unexpected(format!("get raw: {}", err)))?;
+                self.trace(|| SyscallTraceData::Load { key, value });
                 machine
                     .memory_mut()
                     .store_bytes(value_addr, value.as_slice())?;
@@ -246,6 +281,10 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                     }
                 };
                 let script_hash = script.hash();
+                self.trace(|| SyscallTraceData::Create {
+                    script_hash,
+                    account_id: None,
+                });
 
                 // Return error if script_hash is exists
                 if self.get_account_id_by_script_hash(&script_hash)?.is_some() {
@@ -349,6 +388,10 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 self.state
                     .set_account_count(id + 1)
                     .map_err(|err| VMError::Unexpected(format!("set acccount: {}", err)))?;
+                self.trace(|| SyscallTraceData::Create {
+                    script_hash,
+                    account_id: Some(id),
+                });
                 machine
                     .memory_mut()
                     .store32(&account_id_addr, &Mac::REG::from_u32(id))?;
@@ -500,6 +543,11 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 let data_addr = machine.registers()[A3].to_u64();
 
                 let data = load_bytes(machine, data_addr, data_len as usize)?;
+                self.trace(|| SyscallTraceData::Log {
+                    account_id,
+                    service_flag,
+                    data: Bytes::from(data.clone()),
+                });
                 self.state.append_log(
                     LogItem::new_builder()
                         .account_id(account_id.pack())
@@ -549,6 +597,12 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                     load_data_h256(machine, amount_addr)?.to_u256()
                 };
 
+                self.trace(|| SyscallTraceData::PayFee {
+                    payer: payer_addr.clone(),
+                    sudt_id: sudt_id.into(),
+                    amount,
+                });
+
                 // TODO record fee payment in the generator context
                 log::debug!(
                     "[contract syscall: SYS_PAY_FEE] payer: {}, registry_id: {}, sudt_id: {}, amount: {}",
@@ -633,6 +687,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 let snapshot_addr = machine.registers()[A0].clone();
                 // create snapshot
                 let snapshot_id = self.state.snapshot() as u32;
+                self.trace(|| SyscallTraceData::Snapshot { snapshot_id });
                 machine
                     .memory_mut()
                     .store32(&snapshot_addr, &Mac::REG::from_u32(snapshot_id))?;
@@ -641,6 +696,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
             }
             SYS_REVERT => {
                 let snapshot_id = machine.registers()[A0].to_u32();
+                self.trace(|| SyscallTraceData::Revert { snapshot_id });
                 self.state
                     .revert(snapshot_id as usize)
                     .map_err(|err| VMError::Unexpected(format!("revert: {}", err)))?;
@@ -679,6 +735,12 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
 }
 
 impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
+    fn trace(&mut self, data: impl FnOnce() -> SyscallTraceData) {
+        if self.context.syscall_trace.is_some() {
+            self.trace_data = Some(data());
+        }
+    }
+
     fn get_script_hash(&mut self, id: u32) -> Result<H256, VMError> {
         let value = self
             .state
@@ -749,3 +811,30 @@ impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
         }
     }
 }
+
+fn syscall_name(syscall: u64) -> &'static str {
+    match syscall {
+        SYS_CREATE => "SYS_CREATE",
+        SYS_STORE => "SYS_STORE",
+        SYS_LOAD => "SYS_LOAD",
+        SYS_LOAD_ACCOUNT_SCRIPT => "SYS_LOAD_ACCOUNT_SCRIPT",
+        SYS_SET_RETURN_DATA => "SYS_SET_RETURN_DATA",
+        SYS_STORE_DATA => "SYS_STORE_DATA",
+        SYS_LOAD_DATA => "SYS_LOAD_DATA",
+        SYS_LOAD_ROLLUP_CONFIG => "SYS_LOAD_ROLLUP_CONFIG",
+        SYS_LOAD_TRANSACTION => "SYS_LOAD_TRANSACTION",
+        SYS_LOAD_BLOCKINFO => "SYS_LOAD_BLOCKINFO",
+        SYS_GET_BLOCK_HASH => "SYS_GET_BLOCK_HASH",
+        SYS_PAY_FEE => "SYS_PAY_FEE",
+        SYS_LOG => "SYS_LOG",
+        SYS_RECOVER_ACCOUNT => "SYS_RECOVER_ACCOUNT",
+        SYS_BN_ADD => "SYS_BN_ADD",
+        SYS_BN_MUL => "SYS_BN_MUL",
+        SYS_BN_PAIRING => "SYS_BN_PAIRING",
+        SYS_SNAPSHOT => "SYS_SNAPSHOT",
+        SYS_REVERT => "SYS_REVERT",
+        SYS_CHECK_SUDT_ADDRESS => "SYS_CHECK_SUDT_ADDRESS",
+        DEBUG_PRINT_SYSCALL_NUMBER => "DEBUG_PRINT",
+        _ => "UNKNOWN",
+    }
+}
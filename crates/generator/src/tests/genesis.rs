@@ -1,13 +1,30 @@
-use crate::genesis::{build_genesis, init_genesis};
-use gw_common::state::State;
-use gw_config::GenesisConfig;
+use crate::{
+    account_lock_manage::{
+        secp256k1_blake160::Secp256k1Blake160, secp256r1_webauthn::Secp256r1WebAuthn, LockAlgorithm,
+    },
+    genesis::{build_genesis, init_genesis},
+    traits::StateExt,
+    Error,
+};
+use gw_common::{
+    builtins::ETH_REGISTRY_ACCOUNT_ID, registry::Registries, registry_address::RegistryAddress,
+    state::State,
+};
+use gw_config::{ForkConfig, GenesisConfig};
 use gw_store::{
     state::{history::history_state::RWConfig, BlockStateDB},
     traits::chain_store::ChainStore,
     Store,
 };
 use gw_traits::CodeStore;
-use gw_types::{bytes::Bytes, core::ScriptHashType, h256::*, packed::RollupConfig, prelude::*};
+use gw_types::{
+    bytes::Bytes,
+    core::{AllowedContractType, AllowedEoaType, ScriptHashType},
+    h256::*,
+    packed::{AllowedTypeHash, DepositRequest, RollupConfig, Script},
+    prelude::*,
+};
+use gw_utils::RollupContext;
 use std::convert::TryInto;
 
 const GENESIS_BLOCK_HASH: [u8; 32] = [
@@ -24,6 +41,7 @@ fn test_init_genesis() {
         timestamp: 42,
         meta_contract_validator_type_hash: meta_contract_code_hash.into(),
         eth_registry_validator_type_hash: eth_registry_contract_code_hash.into(),
+        ckb_registry_validator_type_hash: None,
         rollup_config: RollupConfig::default().into(),
         rollup_type_hash: rollup_script_hash.into(),
         secp_data_dep: Default::default(),
@@ -58,3 +76,153 @@ fn test_init_genesis() {
     let code_hash: [u8; 32] = script.code_hash().unpack();
     assert_eq!(code_hash, meta_contract_code_hash);
 }
+
+#[test]
+fn test_ckb_registry() {
+    let ckb_registry_contract_code_hash = [3u8; 32];
+    let ckb_eoa_code_hash = [4u8; 32];
+    let webauthn_eoa_code_hash = [5u8; 32];
+    let rollup_script_hash: [u8; 32] = [42u8; 32];
+    let rollup_config = RollupConfig::new_builder()
+        .allowed_eoa_type_hashes(
+            vec![
+                AllowedTypeHash::new(AllowedEoaType::Secp256k1Blake160, ckb_eoa_code_hash),
+                AllowedTypeHash::new(AllowedEoaType::WebAuthn, webauthn_eoa_code_hash),
+            ]
+            .pack(),
+        )
+        .allowed_contract_type_hashes(
+            vec![AllowedTypeHash::new(
+                AllowedContractType::CkbAddrReg,
+                ckb_registry_contract_code_hash,
+            )]
+            .pack(),
+        )
+        .build();
+    let config = GenesisConfig {
+        timestamp: 42,
+        meta_contract_validator_type_hash: [1u8; 32].into(),
+        eth_registry_validator_type_hash: [2u8; 32].into(),
+        ckb_registry_validator_type_hash: Some(ckb_registry_contract_code_hash.into()),
+        rollup_config: rollup_config.clone().into(),
+        rollup_type_hash: rollup_script_hash.into(),
+        secp_data_dep: Default::default(),
+    };
+    let store: Store = Store::open_tmp().unwrap();
+    init_genesis(&store, &config, &[0u8; 32], Bytes::default()).unwrap();
+    let mut db = store.begin_transaction();
+    let mut tree = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+    // CKB registry account is created in the genesis block, and enabled at the
+    // fork height
+    const FORK_HEIGHT: u64 = 2;
+    let ctx = RollupContext {
+        rollup_script_hash,
+        rollup_config: rollup_config.clone(),
+        fork_config: ForkConfig {
+            enable_ckb_registry: Some(FORK_HEIGHT),
+            ..Default::default()
+        },
+    };
+    let registries = ctx.registries(&tree, FORK_HEIGHT - 1).unwrap();
+    assert_eq!(registries, Registries::eth_only());
+    let registries = ctx.registries(&tree, FORK_HEIGHT).unwrap();
+    let ckb_registry_id = registries.ckb_registry_id().expect("ckb registry");
+    let ckb_registry_script_hash = tree.get_script_hash(ckb_registry_id).unwrap();
+    let script = tree.get_script(&ckb_registry_script_hash).expect("script");
+    let code_hash: [u8; 32] = script.code_hash().unpack();
+    assert_eq!(code_hash, ckb_registry_contract_code_hash);
+
+    // The rollup config doesn't allow the CKB registry
+    let eth_only_ctx = RollupContext {
+        rollup_config: RollupConfig::default(),
+        ..ctx.clone()
+    };
+    assert_eq!(
+        eth_only_ctx.registries(&tree, FORK_HEIGHT).unwrap(),
+        Registries::eth_only()
+    );
+
+    // Deposit to a secp256k1-blake160 EOA
+    let eoa_script = |blake160: [u8; 20]| {
+        Script::new_builder()
+            .code_hash(ckb_eoa_code_hash.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args([&rollup_script_hash[..], &blake160[..]].concat().pack())
+            .build()
+    };
+    let deposit = |script: Script, registry_id: u32| {
+        DepositRequest::new_builder()
+            .capacity(1000u64.pack())
+            .script(script)
+            .registry_id(registry_id.pack())
+            .build()
+    };
+    let script = eoa_script([5u8; 20]);
+    let err = tree
+        .apply_deposit_request(
+            &ctx,
+            FORK_HEIGHT - 1,
+            &deposit(script.clone(), ckb_registry_id),
+        )
+        .unwrap_err();
+    assert_eq!(err, Error::State(gw_common::error::Error::InvalidArgs));
+
+    let mut db = store.begin_transaction();
+    let mut tree = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+    tree.apply_deposit_request(&ctx, FORK_HEIGHT, &deposit(script.clone(), ckb_registry_id))
+        .unwrap();
+    let address = RegistryAddress::new(ckb_registry_id, vec![5u8; 20]);
+    assert_eq!(
+        tree.find_registry_address_by_script_hash(&registries, &script.hash())
+            .unwrap(),
+        Some(address.clone())
+    );
+    assert_eq!(
+        tree.find_registry_address_by_script_hash(&Registries::eth_only(), &script.hash())
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        tree.get_script_hash_by_registry_address(&address).unwrap(),
+        Some(script.hash())
+    );
+
+    // The EOA type doesn't match the ETH registry
+    let script = eoa_script([6u8; 20]);
+    let err = tree
+        .apply_deposit_request(&ctx, FORK_HEIGHT, &deposit(script, ETH_REGISTRY_ACCOUNT_ID))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Error::State(gw_common::error::Error::UnknownEoaCodeHash)
+    );
+
+    // Deposit to a WebAuthn EOA
+    let mut db = store.begin_transaction();
+    let mut tree = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+    let script = Script::new_builder()
+        .code_hash(webauthn_eoa_code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args([&rollup_script_hash[..], &[7u8; 20][..]].concat().pack())
+        .build();
+    tree.apply_deposit_request(&ctx, FORK_HEIGHT, &deposit(script.clone(), ckb_registry_id))
+        .unwrap();
+    let webauthn_address = RegistryAddress::new(ckb_registry_id, vec![7u8; 20]);
+    assert_eq!(
+        tree.find_registry_address_by_script_hash(&registries, &script.hash())
+            .unwrap(),
+        Some(webauthn_address.clone())
+    );
+
+    // The secp256k1-blake160 and WebAuthn locks only sign for CKB registry
+    // addresses
+    let locks: [&dyn LockAlgorithm; 2] = [&Secp256k1Blake160, &Secp256r1WebAuthn];
+    for (lock, address) in locks.into_iter().zip([address, webauthn_address]) {
+        lock.check_address(&registries, &address).unwrap();
+        for registry_id in [ETH_REGISTRY_ACCOUNT_ID, ckb_registry_id + 1] {
+            let other_address = RegistryAddress::new(registry_id, address.address.clone());
+            lock.check_address(&registries, &other_address).unwrap_err();
+        }
+    }
+}
@@ -22,6 +22,7 @@ pub trait StateExt {
     fn apply_deposit_request(
         &mut self,
         ctx: &RollupContext,
+        block_number: u64,
         deposit_request: &DepositRequest,
     ) -> Result<(), Error>;
 
@@ -87,6 +88,7 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
     fn apply_deposit_request(
         &mut self,
         ctx: &RollupContext,
+        block_number: u64,
         request: &DepositRequest,
     ) -> Result<(), Error> {
         // find or create user account
@@ -95,7 +97,6 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
         let capacity: u64 = request.capacity().unpack();
         log::debug!("[generator] deposit capacity {}", capacity);
 
-        // NOTE: the address length `20` is a hard-coded value, we may re-visit here to extend more address format
         let address = match self.get_account_id_by_script_hash(&account_script_hash)? {
             Some(_id) => {
                 // account is exist, query registry address
@@ -107,6 +108,7 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
             }
             None => {
                 // account isn't exist
+                let registries = ctx.registries(&*self, block_number)?;
                 self.insert_script(account_script_hash, request.script());
                 let new_id = self.create_account(account_script_hash)?;
                 log::debug!(
@@ -115,6 +117,7 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
                     new_id
                 );
                 let registry_ctx = RegistryContext::new(
+                    registries,
                     ctx.rollup_config
                         .allowed_eoa_type_hashes()
                         .into_iter()
@@ -126,7 +129,11 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
                     &request.script().args().raw_data(),
                 )?;
                 // mapping addr to script hash
-                self.mapping_registry_address_to_script_hash(addr.clone(), account_script_hash)?;
+                self.mapping_registry_address_to_script_hash_in(
+                    &registries,
+                    addr.clone(),
+                    account_script_hash,
+                )?;
                 addr
             }
         };
@@ -21,7 +21,10 @@ pub enum TypedRawTransaction {
 impl TypedRawTransaction {
     pub fn from_tx(raw_tx: RawL2Transaction, type_: AllowedContractType) -> Option<Self> {
         let tx = match type_ {
-            AllowedContractType::EthAddrReg => Self::EthAddrReg(EthAddrRegTx(raw_tx)),
+            // The CKB registry shares the args of the ETH registry
+            AllowedContractType::EthAddrReg | AllowedContractType::CkbAddrReg => {
+                Self::EthAddrReg(EthAddrRegTx(raw_tx))
+            }
             AllowedContractType::Meta => Self::Meta(MetaTx(raw_tx)),
             AllowedContractType::Sudt => Self::SimpleUDT(SimpleUDTTx(raw_tx)),
             AllowedContractType::Polyjuice => Self::Polyjuice(PolyjuiceTx(raw_tx)),
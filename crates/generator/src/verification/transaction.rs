@@ -1,7 +1,4 @@
-use gw_common::{
-    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
-    state::State,
-};
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
 use gw_config::ForkConfig;
 use gw_traits::CodeStore;
 use gw_types::{packed::L2Transaction, prelude::*};
@@ -70,9 +67,10 @@ impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
 
         // verify balance
         let sender_script_hash = self.state.get_script_hash(sender_id)?;
+        let registries = self.rollup_context.registries(self.state, block_number)?;
         let sender_address = self
             .state
-            .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+            .find_registry_address_by_script_hash(&registries, &sender_script_hash)?
             .ok_or(AccountError::RegistryAddressNotFound)?;
         // get balance
         let balance = self
@@ -4,9 +4,12 @@ static GLOBAL_ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 use anyhow::{Context, Result};
 use clap::{Arg, Command, CommandFactory, Parser};
+use godwoken_bin::subcommand::backup::{BackupCommand, COMMAND_BACKUP};
 use godwoken_bin::subcommand::db_block_validator;
 use godwoken_bin::subcommand::export_block::{ExportArgs, ExportBlock};
+use godwoken_bin::subcommand::export_state::{ExportStateCommand, COMMAND_EXPORT_STATE};
 use godwoken_bin::subcommand::import_block::{ImportArgs, ImportBlock};
+use godwoken_bin::subcommand::import_state::{ImportStateCommand, COMMAND_IMPORT_STATE};
 use godwoken_bin::subcommand::migrate::{MigrateCommand, COMMAND_MIGRATE};
 use godwoken_bin::subcommand::peer_id::{PeerIdCommand, COMMAND_PEER_ID};
 use godwoken_bin::subcommand::rewind_to_last_valid_block::{
@@ -210,7 +213,10 @@ async fn run_cli() -> Result<()> {
         )
         .subcommand(PeerIdCommand::command())
         .subcommand(RewindToLastValidBlockCommand::command())
-        .subcommand(MigrateCommand::command());
+        .subcommand(MigrateCommand::command())
+        .subcommand(ExportStateCommand::command())
+        .subcommand(ImportStateCommand::command())
+        .subcommand(BackupCommand::command());
 
     // handle subcommands
     let matches = app.clone().get_matches();
@@ -283,6 +289,17 @@ async fn run_cli() -> Result<()> {
         Some((COMMAND_MIGRATE, m)) => {
             MigrateCommand::from_clap(m).run()?;
         }
+        Some((COMMAND_EXPORT_STATE, m)) => {
+            let _guard = trace::init()?;
+            ExportStateCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_IMPORT_STATE, m)) => {
+            let _guard = trace::init()?;
+            ImportStateCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_BACKUP, m)) => {
+            BackupCommand::from_clap(m).run().await?;
+        }
         _ => {
             // default command: start a Godwoken node
             let config_path = "./config.toml";
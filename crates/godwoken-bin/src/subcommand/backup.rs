@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_rpc_client::gw_client::GWClient;
+use gw_store::{
+    autorocks::{BackupEngine, DbOptions},
+    schema::COLUMNS,
+};
+
+pub const COMMAND_BACKUP: &str = "backup";
+/// Name of the checkpoint created under the checkpoint root of the node.
+pub const CHECKPOINT_NAME: &str = "backup";
+
+/// Incremental backups of the node database.
+#[derive(Parser)]
+#[clap(name = COMMAND_BACKUP)]
+pub enum BackupCommand {
+    /// Back up the database of a running node. The `admin` RPC methods and
+    /// `rpc_server.checkpoint_root` must be enabled on the node.
+    Create {
+        /// Backup directory.
+        #[clap(long)]
+        backup_dir: PathBuf,
+        /// `rpc_server.checkpoint_root` of the node, it must be accessible
+        /// from here.
+        #[clap(long)]
+        checkpoint_root: PathBuf,
+        /// Godwoken RPC url.
+        #[clap(long, default_value = "http://127.0.0.1:8119")]
+        rpc_url: String,
+        /// Purge old backups, only keep the latest N backups.
+        #[clap(long)]
+        keep: Option<u32>,
+    },
+    /// Restore the latest backup. The node must be stopped.
+    Restore {
+        /// Backup directory.
+        #[clap(long)]
+        backup_dir: PathBuf,
+        /// Database path, `store.path` in config.toml.
+        #[clap(long)]
+        store_path: PathBuf,
+    },
+}
+
+impl BackupCommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            BackupCommand::Create {
+                backup_dir,
+                checkpoint_root,
+                rpc_url,
+                keep,
+            } => {
+                let checkpoint_dir = checkpoint_root.join(CHECKPOINT_NAME);
+                // Left over by an interrupted backup.
+                remove_checkpoint(&checkpoint_dir)?;
+
+                let client = GWClient::with_url(&rpc_url)?;
+                client
+                    .gw_create_checkpoint(CHECKPOINT_NAME.into())
+                    .await
+                    .context("create checkpoint")?;
+
+                let backup_id = backup_checkpoint(&checkpoint_dir, &backup_dir, keep)?;
+                remove_checkpoint(&checkpoint_dir)?;
+
+                println!("backup {} created in {}", backup_id, backup_dir.display());
+            }
+            BackupCommand::Restore {
+                backup_dir,
+                store_path,
+            } => {
+                if store_path
+                    .read_dir()
+                    .map_or(false, |mut d| d.next().is_some())
+                {
+                    bail!("store path {} is not empty", store_path.display());
+                }
+                let engine = BackupEngine::open(&backup_dir)?;
+                let backup_id = match engine.latest_backup_id() {
+                    Some(id) => id,
+                    None => bail!("no backup found in {}", backup_dir.display()),
+                };
+                engine.restore_from_latest_backup(&store_path, &store_path)?;
+
+                println!("backup {} restored to {}", backup_id, store_path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Back up the checkpoint to `backup_dir`, only files changed since the
+/// previous backup are copied. Returns the id of the new backup.
+pub fn backup_checkpoint(
+    checkpoint_dir: &Path,
+    backup_dir: &Path,
+    keep: Option<u32>,
+) -> Result<u32> {
+    std::fs::create_dir_all(backup_dir).context("create backup dir")?;
+    let db = DbOptions::new(checkpoint_dir, COLUMNS)
+        .create_missing_column_families(true)
+        .open()
+        .context("open checkpoint")?;
+    let mut engine = BackupEngine::open(backup_dir)?;
+    engine.create_new_backup(&db, true)?;
+    let backup_id = engine.latest_backup_id().context("no backup created")?;
+    engine.verify_backup(backup_id)?;
+    if let Some(keep) = keep {
+        engine.purge_old_backups(keep)?;
+    }
+    Ok(backup_id)
+}
+
+/// Remove a checkpoint created by a previous backup. Refuse to remove
+/// anything else that happens to be there.
+pub fn remove_checkpoint(checkpoint_dir: &Path) -> Result<()> {
+    let metadata = match std::fs::symlink_metadata(checkpoint_dir) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).context("stat checkpoint"),
+    };
+    // A checkpoint is a flat directory of RocksDB files.
+    let mut is_checkpoint = metadata.is_dir() && checkpoint_dir.join("CURRENT").is_file();
+    if is_checkpoint {
+        for entry in checkpoint_dir.read_dir()? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Opening the checkpoint adds the LOCK and info LOG files.
+            let is_db_file = name == "CURRENT"
+                || name == "IDENTITY"
+                || name == "LOCK"
+                || name.starts_with("LOG")
+                || name.starts_with("MANIFEST-")
+                || name.starts_with("OPTIONS-")
+                || name.ends_with(".sst")
+                || name.ends_with(".log")
+                || name.ends_with(".blob");
+            if !entry.file_type()?.is_file() || !is_db_file {
+                is_checkpoint = false;
+                break;
+            }
+        }
+    }
+    if !is_checkpoint {
+        bail!(
+            "{} is not a database checkpoint, remove it manually",
+            checkpoint_dir.display()
+        );
+    }
+    std::fs::remove_dir_all(checkpoint_dir).context("remove checkpoint")
+}
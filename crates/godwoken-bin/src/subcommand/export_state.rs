@@ -0,0 +1,74 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_store::{
+    migrate::{init_migration_factory, open_or_create_db},
+    Store,
+};
+use gw_types::packed::RollupConfig;
+use gw_utils::export_state::{export_state, last_finalized_block_number, StateSnapshotWriter};
+
+pub const COMMAND_EXPORT_STATE: &str = "export-state";
+
+/// Export the state at a finalized block, to bootstrap new nodes with
+/// `import-state`. The node should be stopped.
+#[derive(Parser)]
+#[clap(name = COMMAND_EXPORT_STATE)]
+pub struct ExportStateCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The output file of the state snapshot
+    #[clap(short, long)]
+    output_path: PathBuf,
+    /// The block number, defaults to the last finalized block
+    #[clap(short, long)]
+    block_number: Option<u64>,
+}
+
+impl ExportStateCommand {
+    pub fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let store = Store::new(open_or_create_db(&config.store, init_migration_factory())?);
+        let fork_config = config.consensus.get_config();
+        let rollup_config: RollupConfig = fork_config.genesis.rollup_config.clone().into();
+
+        let snap = store.get_snapshot();
+        let block_number = match self.block_number {
+            Some(block_number) => block_number,
+            None => last_finalized_block_number(&rollup_config, fork_config, &snap)?
+                .context("no finalized block")?,
+        };
+
+        if let Some(parent) = self.output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let f = fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&self.output_path)
+            .with_context(|| format!("create {}", self.output_path.to_string_lossy()))?;
+        let mut writer = StateSnapshotWriter::new(io::BufWriter::new(f))?;
+        export_state(
+            &snap,
+            &rollup_config,
+            fork_config,
+            block_number,
+            &mut writer,
+        )?;
+        writer.finish()?;
+        println!("exported state of block {}", block_number);
+
+        Ok(())
+    }
+}
+
+pub(crate) fn read_config(config_path: &Path) -> Result<Config> {
+    let content = fs::read(config_path)
+        .with_context(|| format!("read config file from {}", config_path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
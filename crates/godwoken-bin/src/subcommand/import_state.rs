@@ -0,0 +1,63 @@
+use std::fs;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_store::{
+    migrate::{init_migration_factory, open_or_create_db},
+    Store,
+};
+use gw_types::h256::*;
+use gw_utils::export_state::{import_state, verify_state_snapshot, StateSnapshotReader};
+
+use super::export_state::read_config;
+
+pub const COMMAND_IMPORT_STATE: &str = "import-state";
+
+/// Restore an empty store from a state snapshot created by `export-state`.
+///
+/// The restored store starts at the snapshot block, the node syncs the
+/// following blocks from L1 when it starts.
+#[derive(Parser)]
+#[clap(name = COMMAND_IMPORT_STATE)]
+pub struct ImportStateCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The state snapshot file
+    #[clap(short, long)]
+    source_path: PathBuf,
+}
+
+impl ImportStateCommand {
+    pub fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let rollup_type_hash: H256 = config
+            .consensus
+            .get_config()
+            .genesis
+            .rollup_type_hash
+            .clone()
+            .into();
+
+        let open_source = || {
+            fs::File::open(&self.source_path)
+                .map(BufReader::new)
+                .with_context(|| format!("open {}", self.source_path.to_string_lossy()))
+        };
+        let (snapshot_rollup_type_hash, block_number) = verify_state_snapshot(open_source()?)?;
+        if snapshot_rollup_type_hash != rollup_type_hash {
+            bail!("the state snapshot is not of the rollup in config");
+        }
+        println!("verified state snapshot of block {}", block_number);
+
+        let mut store = Store::new(open_or_create_db(&config.store, init_migration_factory())?);
+        store.set_index_account_history(config.store.index_account_history);
+        let mut reader = StateSnapshotReader::new(open_source()?)?;
+        import_state(&store, &mut reader, &rollup_type_hash)?;
+        println!("imported state of block {}", block_number);
+
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
+pub mod backup;
 pub mod db_block_validator;
 pub mod export_block;
+pub mod export_state;
 pub mod import_block;
+pub mod import_state;
 pub mod migrate;
 pub mod peer_id;
 pub mod rewind_to_last_valid_block;
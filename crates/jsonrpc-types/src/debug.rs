@@ -1,11 +1,14 @@
 use std::convert::TryFrom;
 
 use ckb_fixed_hash::H256 as JsonH256;
-use ckb_jsonrpc_types::{JsonBytes, Uint64};
-use gw_types::offchain::{self};
+use ckb_jsonrpc_types::{JsonBytes, Uint32, Uint64};
+use gw_types::{
+    offchain::{self},
+    U256,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::godwoken::LogItem;
+use crate::godwoken::{LogItem, RegistryAddress};
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
@@ -39,6 +42,131 @@ pub struct DebugRunResult {
     pub debug_log: Vec<String>,
     pub execution_time_ms: u32,
     pub write_mem_smt_time_ms: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syscall_trace: Option<Vec<SyscallTraceStep>>,
+}
+
+/// Tracers of `debug_replay_transaction`.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugTracer {
+    /// Record every syscall dispatched by the generator.
+    Syscall,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SyscallTraceStep {
+    pub syscall: Uint64,
+    pub name: String,
+    // execution cycles before the syscall
+    pub cycles: Uint64,
+    // virtual cycles after charging the syscall
+    pub virtual_cycles: Uint64,
+    pub return_code: i8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<SyscallTraceData>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyscallTraceData {
+    Load {
+        key: JsonH256,
+        value: JsonH256,
+    },
+    Store {
+        key: JsonH256,
+        value: JsonH256,
+    },
+    Create {
+        script_hash: JsonH256,
+        account_id: Option<Uint32>,
+    },
+    PayFee {
+        payer: RegistryAddress,
+        sudt_id: Uint32,
+        amount: U256,
+    },
+    Log {
+        account_id: Uint32,
+        service_flag: Uint32,
+        data: JsonBytes,
+    },
+    Snapshot {
+        snapshot_id: Uint32,
+    },
+    Revert {
+        snapshot_id: Uint32,
+    },
+}
+
+impl From<offchain::SyscallTraceStep> for SyscallTraceStep {
+    fn from(step: offchain::SyscallTraceStep) -> Self {
+        let offchain::SyscallTraceStep {
+            syscall,
+            name,
+            cycles,
+            virtual_cycles,
+            return_code,
+            data,
+        } = step;
+        SyscallTraceStep {
+            syscall: syscall.into(),
+            name: name.to_string(),
+            cycles: cycles.into(),
+            virtual_cycles: virtual_cycles.into(),
+            return_code,
+            data: data.map(Into::into),
+        }
+    }
+}
+
+impl From<offchain::SyscallTraceData> for SyscallTraceData {
+    fn from(data: offchain::SyscallTraceData) -> Self {
+        use offchain::SyscallTraceData as Data;
+        match data {
+            Data::Load { key, value } => SyscallTraceData::Load {
+                key: key.into(),
+                value: value.into(),
+            },
+            Data::Store { key, value } => SyscallTraceData::Store {
+                key: key.into(),
+                value: value.into(),
+            },
+            Data::Create {
+                script_hash,
+                account_id,
+            } => SyscallTraceData::Create {
+                script_hash: script_hash.into(),
+                account_id: account_id.map(Into::into),
+            },
+            Data::PayFee {
+                payer,
+                sudt_id,
+                amount,
+            } => SyscallTraceData::PayFee {
+                payer: payer.into(),
+                sudt_id: sudt_id.into(),
+                amount,
+            },
+            Data::Log {
+                account_id,
+                service_flag,
+                data,
+            } => SyscallTraceData::Log {
+                account_id: account_id.into(),
+                service_flag: u32::from(service_flag).into(),
+                data: JsonBytes::from_bytes(data),
+            },
+            Data::Snapshot { snapshot_id } => SyscallTraceData::Snapshot {
+                snapshot_id: snapshot_id.into(),
+            },
+            Data::Revert { snapshot_id } => SyscallTraceData::Revert {
+                snapshot_id: snapshot_id.into(),
+            },
+        }
+    }
 }
 
 impl TryFrom<offchain::RunResult> for DebugRunResult {
@@ -52,7 +180,7 @@ impl TryFrom<offchain::RunResult> for DebugRunResult {
             read_data_hashes,
             write_data_hashes,
             debug_log_buf,
-            ..
+            syscall_trace,
         } = data;
         Ok(DebugRunResult {
             return_data: JsonBytes::from_bytes(return_data),
@@ -73,6 +201,7 @@ impl TryFrom<offchain::RunResult> for DebugRunResult {
                 .collect(),
             execution_time_ms: 0,
             write_mem_smt_time_ms: 0,
+            syscall_trace: syscall_trace.map(|trace| trace.into_iter().map(Into::into).collect()),
         })
     }
 }
@@ -2,9 +2,10 @@ use anyhow::{anyhow, Context, Error as JsonError};
 use ckb_fixed_hash::{H160, H256};
 use ckb_jsonrpc_types::{JsonBytes, Script, Uint128, Uint32, Uint64};
 use gw_types::core::Timepoint;
-use gw_types::{bytes::Bytes, offchain, packed, prelude::*};
+use gw_types::{bytes::Bytes, offchain, packed, prelude::*, U256};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
@@ -524,11 +525,62 @@ pub struct L2TransactionWithStatus {
     pub status: L2TransactionStatus,
 }
 
+/// Lifecycle status of a transaction, see `gw_get_transaction_status`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum TransactionStatus {
+    /// In the fee queue.
+    Queued,
+    /// Pushed to the mem block, not packaged in a block yet.
+    InMemBlock,
+    /// Packaged in a local block.
+    Packaged {
+        block_number: Uint64,
+        block_hash: H256,
+    },
+    /// Dropped recently. `replaced_by` is the replacement tx hash if it's
+    /// replaced by fee.
+    Dropped {
+        reason: TransactionDropReason,
+        message: String,
+        replaced_by: Option<H256>,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDropReason {
+    /// Replaced by a tx with the same sender and nonce and a higher fee rate.
+    ReplacedByFee,
+    /// Fee rate isn't high enough to replace the queued tx.
+    Underpriced,
+    SenderQuotaExceeded,
+    FutureQuotaExceeded,
+    /// Evicted from the full fee queue.
+    QueueFull,
+    /// Nonce is lower than the sender's nonce.
+    StaleNonce,
+    /// Nonce is higher than the sender's nonce, and the txs in between never
+    /// arrived.
+    FutureNonce,
+    /// Cycles limit exceeds the mem block max cycles limit.
+    ExceededCyclesLimit,
+    /// Failed to parse the fee or to find the sender of the tx.
+    Invalid,
+    /// Failed to push the tx to the mem pool.
+    PushFailed,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum WithdrawalStatus {
     Pending,
+    /// Packaged in a block, not finalized yet.
     Committed,
+    /// Finalized, the withdrawal cell can be unlocked to the owner on L1.
+    Finalized,
+    /// The withdrawal cell has been unlocked to the owner on L1.
+    Unlocked,
 }
 
 impl Default for WithdrawalStatus {
@@ -544,6 +596,10 @@ pub struct WithdrawalWithStatus {
     pub status: WithdrawalStatus,
     pub l1_committed_info: Option<L2BlockCommittedInfo>,
     pub l2_committed_info: Option<L2WithdrawalCommittedInfo>,
+    /// Estimated finalization time, unix timestamp in milliseconds.
+    pub estimated_finalized_at: Option<Uint64>,
+    /// L1 tx which unlocked the withdrawal cell to the owner.
+    pub unlock_tx_hash: Option<H256>,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -554,6 +610,84 @@ pub struct L2WithdrawalCommittedInfo {
     pub withdrawal_index: Uint32,
 }
 
+/// A page of account history, newest first. Pass `last_cursor` to the next
+/// request to continue; it is empty when there are no more items.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountHistoryPage<T> {
+    pub objects: Vec<T>,
+    pub last_cursor: JsonBytes,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountTransaction {
+    pub transaction_hash: H256,
+    pub block_number: Uint64,
+    pub transaction_index: Uint32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountWithdrawal {
+    pub withdrawal_hash: H256,
+    pub block_number: Uint64,
+    pub withdrawal_index: Uint32,
+}
+
+/// Temporary state changes of `gw_execute_raw_l2transaction`, keyed by
+/// account script hash.
+pub type StateOverride = HashMap<H256, AccountOverride>;
+
+/// Overrides of an account, applied in the order of the fields.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct AccountOverride {
+    /// Create the account with this script if it doesn't exist.
+    #[serde(default)]
+    pub script: Option<Script>,
+    /// Map the account to this ETH registry address if it isn't mapped yet.
+    #[serde(default)]
+    pub registry_address: Option<RegistryAddressJsonBytes>,
+    #[serde(default)]
+    pub nonce: Option<Uint32>,
+    /// CKB balance of the account's ETH registry address.
+    #[serde(default)]
+    pub balance: Option<U256>,
+    /// Polyjuice contract code.
+    #[serde(default)]
+    pub code: Option<JsonBytes>,
+    /// Account storage, same keys as `gw_get_storage_at`.
+    #[serde(default)]
+    pub storage: Option<HashMap<H256, H256>>,
+}
+
+/// Account values with a compiled merkle proof against
+/// `account_merkle_state.merkle_root` of the block.
+///
+/// Leaves of the proof are the account nonce, the account script hash and
+/// the storage values, keyed by the raw SMT keys. Values of absent keys are
+/// zero.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountProof {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub account_merkle_state: AccountMerkleState,
+    pub account_id: Uint32,
+    pub script_hash: H256,
+    pub nonce: Uint32,
+    pub storage: Vec<StorageValue>,
+    pub merkle_proof: JsonBytes,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct StorageValue {
+    pub key: H256,
+    pub value: H256,
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub struct SubmitTransactions {
@@ -953,6 +1087,8 @@ pub struct L2BlockCommittedInfo {
 pub enum AllowedEoaType {
     Unknown,
     Eth,
+    Secp256k1Blake160,
+    WebAuthn,
 }
 
 impl From<AllowedEoaType> for packed::Byte {
@@ -960,6 +1096,8 @@ impl From<AllowedEoaType> for packed::Byte {
         match json {
             AllowedEoaType::Unknown => packed::Byte::new(0),
             AllowedEoaType::Eth => packed::Byte::new(1),
+            AllowedEoaType::Secp256k1Blake160 => packed::Byte::new(2),
+            AllowedEoaType::WebAuthn => packed::Byte::new(3),
         }
     }
 }
@@ -971,6 +1109,8 @@ impl TryFrom<packed::Byte> for AllowedEoaType {
         match u8::from(v) {
             0 => Ok(AllowedEoaType::Unknown),
             1 => Ok(AllowedEoaType::Eth),
+            2 => Ok(AllowedEoaType::Secp256k1Blake160),
+            3 => Ok(AllowedEoaType::WebAuthn),
             _ => Err(anyhow!("invalid allowed eoa type {}", v)),
         }
     }
@@ -984,6 +1124,7 @@ pub enum AllowedContractType {
     Sudt,
     Polyjuice,
     EthAddrReg,
+    CkbAddrReg,
 }
 
 impl From<AllowedContractType> for packed::Byte {
@@ -994,6 +1135,7 @@ impl From<AllowedContractType> for packed::Byte {
             AllowedContractType::Sudt => packed::Byte::new(2),
             AllowedContractType::Polyjuice => packed::Byte::new(3),
             AllowedContractType::EthAddrReg => packed::Byte::new(4),
+            AllowedContractType::CkbAddrReg => packed::Byte::new(5),
         }
     }
 }
@@ -1008,6 +1150,7 @@ impl TryFrom<packed::Byte> for AllowedContractType {
             2 => Ok(AllowedContractType::Sudt),
             3 => Ok(AllowedContractType::Polyjuice),
             4 => Ok(AllowedContractType::EthAddrReg),
+            5 => Ok(AllowedContractType::CkbAddrReg),
             _ => Err(anyhow!("invalid allowed contract type {}", v)),
         }
     }
@@ -1239,6 +1382,7 @@ pub enum BackendType {
     Sudt,
     Polyjuice,
     EthAddrReg,
+    CkbAddrReg,
 }
 
 impl Default for BackendType {
@@ -1359,6 +1503,41 @@ pub struct FeeConfig {
     pub withdraw_cycles_limit: Uint64,
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct CyclesEstimate {
+    /// Minimal `max_cycles` to execute the transaction.
+    pub max_cycles: Uint64,
+    /// Syscall cycles, charged to the block cycles limit besides the
+    /// execution cycles.
+    pub virtual_cycles: Uint64,
+}
+
+/// Fee rates are fee / cycles limit, the cycles limit is the gas limit of
+/// Polyjuice txs, or the cycles limit of the backend in the fee config.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeSuggestion {
+    pub fee_rate: Uint128,
+    /// Fee rate to get into the next mem block, `None` if the fee queue has
+    /// fewer entries than a block.
+    pub queue_fee_rate: Option<Uint128>,
+    pub queue_len: Uint32,
+    /// Median of the lowest fee rates of the recent blocks, `None` if they
+    /// are empty.
+    pub recent_blocks_fee_rate: Option<Uint128>,
+}
+
+/// Result of a transaction in `gw_submit_l2transactions`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum SubmitL2TransactionResult {
+    /// `tx_hash` is `None` for a tx from zero, like `gw_submit_l2transaction`.
+    Submitted { tx_hash: Option<H256> },
+    /// Error code and message of `gw_submit_l2transaction`.
+    Rejected { code: i64, message: String },
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct WithdrawalLockArgs {
@@ -1525,3 +1704,75 @@ impl GetVerbose {
         self == GetVerbose::WithStatus
     }
 }
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+    NewHeads,
+    PendingTransactions,
+    Logs,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct L2BlockHeaderView {
+    pub raw: RawL2Block,
+    pub hash: H256,
+}
+
+impl From<packed::L2Block> for L2BlockHeaderView {
+    fn from(l2_block: packed::L2Block) -> L2BlockHeaderView {
+        Self {
+            hash: H256::from(l2_block.raw().hash()),
+            raw: l2_block.raw().into(),
+        }
+    }
+}
+
+/// Notifications other than blocks, transactions and logs.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionEvent {
+    /// Notified blocks since `from_number` are no longer on the main chain.
+    /// Blocks of the new main chain are notified after this.
+    Reorg { from_number: Uint64 },
+    /// Blocks `from_number..=to_number` are not notified.
+    Gap {
+        from_number: Uint64,
+        to_number: Uint64,
+    },
+    /// The subscriber is too slow and `skipped` notifications are dropped.
+    Lagged { skipped: Uint64 },
+}
+
+/// Filter of the `logs` subscription. Empty or missing fields match all logs.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct LogFilter {
+    pub account_ids: Option<Vec<Uint32>>,
+    pub service_flags: Option<Vec<Uint32>>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, log: &LogItem) -> bool {
+        fn contains(field: &Option<Vec<Uint32>>, value: &Uint32) -> bool {
+            match field {
+                Some(values) if !values.is_empty() => values.contains(value),
+                _ => true,
+            }
+        }
+        contains(&self.account_ids, &log.account_id)
+            && contains(&self.service_flags, &log.service_flag)
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct LogView {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+    pub transaction_index: Uint32,
+    #[serde(flatten)]
+    pub log: LogItem,
+}
@@ -1,5 +1,8 @@
 use anyhow::{anyhow, Result};
-use gw_common::{registry::context::RegistryContext, state::State};
+use gw_common::{
+    registry::{context::RegistryContext, Registries},
+    state::State,
+};
 use gw_config::DepositTimeoutConfig;
 use gw_store::state::MemStateDB;
 use gw_types::core::Timepoint;
@@ -18,13 +21,14 @@ pub fn sanitize_deposit_cells(
     config: &DepositTimeoutConfig,
     unsanitize_deposits: Vec<DepositInfo>,
     state: &MemStateDB,
+    registries: Registries,
 ) -> Vec<DepositInfo> {
     log::debug!(target: "collect-deposit-cells", "sanitize {} deposits", unsanitize_deposits.len());
     let mut deposit_cells = Vec::with_capacity(unsanitize_deposits.len());
     for cell in unsanitize_deposits {
         // check deposit lock
         // the lock should be correct unless the upstream ckb-indexer has bugs
-        if let Err(err) = check_deposit_cell(ctx, config, &cell, state) {
+        if let Err(err) = check_deposit_cell(ctx, config, &cell, state, registries) {
             log::debug!(target: "collect-deposit-cells", "invalid deposit cell: {}", err);
             continue;
         }
@@ -95,6 +99,7 @@ fn check_deposit_cell(
     config: &DepositTimeoutConfig,
     cell: &DepositInfo,
     state: &MemStateDB,
+    registries: Registries,
 ) -> Result<()> {
     let hash_type = ScriptHashType::Type.into();
 
@@ -188,6 +193,7 @@ fn check_deposit_cell(
 
         // try extract address from deposit
         let registry_ctx = RegistryContext::new(
+            registries,
             ctx.rollup_config
                 .allowed_eoa_type_hashes()
                 .into_iter()
@@ -1,9 +1,12 @@
 use anyhow::{anyhow, Result};
 use gw_common::state::State;
+use gw_config::FeeConfig;
+use gw_metrics::rpc::{FeeQueueEvictReason, FeeQueueRejectReason};
 use gw_telemetry::traits::{
     TelemetryContext, TelemetryContextNewSpan, TelemetrySpanExt, TraceContextExt,
 };
-use std::collections::{BTreeMap, HashMap};
+use gw_types::h256::*;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
 use tracing::{field, instrument};
 
 /// Max queue size
@@ -13,17 +16,66 @@ const DROP_SIZE: usize = 100;
 
 use super::types::{FeeEntry, FeeItemSender};
 
+/// Result of adding an entry to the queue
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddResult {
+    Added,
+    /// Replaced the queued entry(hash) which has the same sender and nonce
+    Replaced(H256),
+    /// Fee rate isn't high enough to replace the queued entry(hash), the new
+    /// entry is dropped
+    Underpriced(H256),
+    /// Sender has too many queued entries, the new entry is dropped
+    SenderQuotaExceeded,
+    /// Sender has too many future nonce entries, the new entry is dropped
+    FutureQuotaExceeded,
+}
+
+/// Why a queued entry is dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropReason {
+    /// Replaced by the entry(hash) which has the same sender and nonce
+    Replaced(H256),
+    /// Evicted because the queue is full
+    QueueFull,
+    /// Nonce is lower than the sender's nonce when fetched
+    StaleNonce,
+    /// Nonce is higher than the sender's nonce when fetched, and no other
+    /// entry of the sender is fetched
+    FutureNonce,
+}
+
 /// Txs & withdrawals queue sorted by fee rate
 pub struct FeeQueue<T: TelemetryContext> {
     // priority queue to store tx and withdrawal
     queue: BTreeMap<FeeEntry, T>,
+    // queued entries by sender and nonce, for replace-by-fee
+    nonce_entries: HashMap<(FeeItemSender, u32), FeeEntry>,
+    // queued nonces of each sender, for quotas and eviction
+    sender_nonces: HashMap<FeeItemSender, BTreeSet<u32>>,
+    // required fee rate bump in percentage to replace a queued entry
+    replace_by_fee_bump_percentage: u64,
+    max_queued_entries_per_sender: usize,
+    max_future_entries_per_sender: usize,
+    // entries dropped since the last `take_dropped`
+    dropped: Vec<(H256, DropReason)>,
 }
 
 impl<T: TelemetryContext> FeeQueue<T> {
     #[inline]
     pub fn new() -> Self {
+        Self::with_config(&FeeConfig::default())
+    }
+
+    pub fn with_config(config: &FeeConfig) -> Self {
         Self {
             queue: BTreeMap::new(),
+            nonce_entries: HashMap::new(),
+            sender_nonces: HashMap::new(),
+            replace_by_fee_bump_percentage: config.replace_by_fee_bump_percentage,
+            max_queued_entries_per_sender: config.max_queued_entries_per_sender,
+            max_future_entries_per_sender: config.max_future_entries_per_sender,
+            dropped: Vec::new(),
         }
     }
 
@@ -37,8 +89,41 @@ impl<T: TelemetryContext> FeeQueue<T> {
         self.queue.is_empty()
     }
 
+    /// Add a new request, reject it if its sender already has too many future
+    /// nonce entries.
+    pub fn add_checked(
+        &mut self,
+        state: &impl State,
+        entry: FeeEntry,
+        handle: T,
+    ) -> Result<AddResult> {
+        let key = (entry.sender, entry.item.nonce());
+        if !self.nonce_entries.contains_key(&key) {
+            let nonce = match entry.sender {
+                FeeItemSender::PendingCreate(_) => 0,
+                FeeItemSender::AccountId(account_id) => state.get_nonce(account_id)?,
+            };
+            if entry.item.nonce() > nonce
+                && self.count_future(&entry.sender, nonce) >= self.max_future_entries_per_sender
+            {
+                log::debug!(
+                    "QueueLen: {} | future quota exceeded: {:?} {} entry_nonce {} nonce {}",
+                    self.len(),
+                    entry.item.kind(),
+                    hex::encode(entry.item.hash().as_slice()),
+                    entry.item.nonce(),
+                    nonce
+                );
+                reject(&handle, FeeQueueRejectReason::FutureQuota);
+                return Ok(AddResult::FutureQuotaExceeded);
+            }
+        }
+
+        Ok(self.add(entry, handle))
+    }
+
     #[instrument(skip_all, fields(count = self.len()))]
-    pub fn add(&mut self, entry: FeeEntry, handle: T) {
+    pub fn add(&mut self, entry: FeeEntry, handle: T) -> AddResult {
         // push to queue
         log::debug!(
             "QueueLen: {} | add entry: {:?} {}",
@@ -46,29 +131,71 @@ impl<T: TelemetryContext> FeeQueue<T> {
             entry.item.kind(),
             hex::encode(entry.item.hash().as_slice())
         );
+
+        let key = (entry.sender, entry.item.nonce());
+        let mut result = AddResult::Added;
+        if let Some(queued) = self.nonce_entries.get(&key) {
+            let queued_hash = queued.item.hash();
+            if !entry.fee_rate_exceeds(queued, self.replace_by_fee_bump_percentage) {
+                log::debug!(
+                    "QueueLen: {} | underpriced entry: {:?} {} queued {}",
+                    self.len(),
+                    entry.item.kind(),
+                    hex::encode(entry.item.hash().as_slice()),
+                    hex::encode(queued_hash.as_slice()),
+                );
+                reject(&handle, FeeQueueRejectReason::Underpriced);
+                return AddResult::Underpriced(queued_hash);
+            }
+
+            if let Some((queued, queued_handle)) = self.queue.remove_entry(queued) {
+                log::debug!(
+                    "QueueLen: {} | replace entry: {:?} {} by {}",
+                    self.len(),
+                    queued.item.kind(),
+                    hex::encode(queued_hash.as_slice()),
+                    hex::encode(entry.item.hash().as_slice()),
+                );
+                evict(&queued_handle, FeeQueueEvictReason::Replaced);
+                self.dropped
+                    .push((queued_hash, DropReason::Replaced(entry.item.hash())));
+            }
+            result = AddResult::Replaced(queued_hash);
+        } else if self.sender_len(&entry.sender) >= self.max_queued_entries_per_sender {
+            log::debug!(
+                "QueueLen: {} | sender quota exceeded: {:?} {}",
+                self.len(),
+                entry.item.kind(),
+                hex::encode(entry.item.hash().as_slice()),
+            );
+            reject(&handle, FeeQueueRejectReason::SenderQuota);
+            return AddResult::SenderQuotaExceeded;
+        }
+        self.insert_index(&entry);
         self.queue.insert(entry, handle);
 
         // drop items if full
         if self.is_full() {
-            #[allow(clippy::mutable_key_type)]
-            if let Some(first_to_keep) = self.queue.keys().nth(DROP_SIZE + 1).cloned() {
-                let keep = self.queue.split_off(&first_to_keep);
-                let drop = std::mem::replace(&mut self.queue, keep);
-
-                for (_, handle) in drop.iter() {
-                    if let Some(cx) = handle.telemetry_context() {
-                        let span = cx.span();
-                        span.record_error(anyhow!("queue is full").as_ref());
-                        span.set_status(gw_telemetry::trace::Status::error("queue is full"));
-                    }
-                }
-            }
+            self.evict_entries(DROP_SIZE);
             log::debug!(
                 "QueueLen: {} | Fee queue is full, drop {} items",
                 self.len(),
                 DROP_SIZE,
             );
         }
+
+        result
+    }
+
+    /// Take the hashes and drop reasons of the entries dropped from the queue.
+    /// Fetched entries and the new entries rejected by `add` are not included.
+    pub fn take_dropped(&mut self) -> Vec<(H256, DropReason)> {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Fee rate of the `n`th entry in priority order, nonces are not checked.
+    pub fn fee_rate_at(&self, n: usize) -> Option<u128> {
+        self.queue.keys().rev().nth(n).map(FeeEntry::fee_rate)
     }
 
     #[inline]
@@ -76,14 +203,100 @@ impl<T: TelemetryContext> FeeQueue<T> {
         self.queue.len() > MAX_QUEUE_SIZE
     }
 
-    fn pop_last(&mut self) -> Option<(FeeEntry, T)> {
-        if let Some(entry) = self.queue.keys().next_back().cloned() {
-            self.queue.remove_entry(&entry)
-        } else {
-            None
+    fn sender_len(&self, sender: &FeeItemSender) -> usize {
+        self.sender_nonces
+            .get(sender)
+            .map_or(0, |nonces| nonces.len())
+    }
+
+    fn count_future(&self, sender: &FeeItemSender, nonce: u32) -> usize {
+        match (self.sender_nonces.get(sender), nonce.checked_add(1)) {
+            (Some(nonces), Some(next)) => nonces.range(next..).count(),
+            _ => 0,
+        }
+    }
+
+    fn insert_index(&mut self, entry: &FeeEntry) {
+        let nonce = entry.item.nonce();
+        self.nonce_entries
+            .insert((entry.sender, nonce), entry.clone());
+        self.sender_nonces
+            .entry(entry.sender)
+            .or_default()
+            .insert(nonce);
+    }
+
+    fn remove_index(&mut self, entry: &FeeEntry) {
+        let nonce = entry.item.nonce();
+        self.nonce_entries.remove(&(entry.sender, nonce));
+        if let Some(nonces) = self.sender_nonces.get_mut(&entry.sender) {
+            nonces.remove(&nonce);
+            if nonces.is_empty() {
+                self.sender_nonces.remove(&entry.sender);
+            }
         }
     }
 
+    /// Evict `count` entries. Prefer the highest nonce entries of the senders
+    /// with the most entries, so a single sender can't push out everyone else.
+    /// Fallback to the lowest fee rate entries if every sender has only one
+    /// entry.
+    fn evict_entries(&mut self, count: usize) {
+        let mut senders = Vec::with_capacity(self.sender_nonces.len());
+        let mut heap = BinaryHeap::with_capacity(self.sender_nonces.len());
+        for (sender, nonces) in self.sender_nonces.iter() {
+            heap.push((nonces.len(), senders.len()));
+            senders.push(*sender);
+        }
+
+        let mut evicted = 0;
+        while evicted < count {
+            let (len, i) = match heap.pop() {
+                Some((len, i)) if len > 1 => (len, i),
+                _ => break,
+            };
+            let sender = senders[i];
+            let nonce = match self
+                .sender_nonces
+                .get(&sender)
+                .and_then(|nonces| nonces.iter().next_back())
+            {
+                Some(&nonce) => nonce,
+                None => continue,
+            };
+            if let Some(entry) = self.nonce_entries.get(&(sender, nonce)).cloned() {
+                self.remove_index(&entry);
+                if let Some(handle) = self.queue.remove(&entry) {
+                    evict(&handle, FeeQueueEvictReason::QueueFull);
+                    self.dropped
+                        .push((entry.item.hash(), DropReason::QueueFull));
+                }
+                evicted += 1;
+            }
+            heap.push((len - 1, i));
+        }
+
+        while evicted < count {
+            let entry = match self.queue.keys().next().cloned() {
+                Some(entry) => entry,
+                None => break,
+            };
+            self.remove_index(&entry);
+            if let Some(handle) = self.queue.remove(&entry) {
+                evict(&handle, FeeQueueEvictReason::QueueFull);
+                self.dropped
+                    .push((entry.item.hash(), DropReason::QueueFull));
+            }
+            evicted += 1;
+        }
+    }
+
+    fn pop_last(&mut self) -> Option<(FeeEntry, T)> {
+        let entry = self.queue.keys().next_back().cloned()?;
+        self.remove_index(&entry);
+        self.queue.remove_entry(&entry)
+    }
+
     /// Fetch items by fee sort
     #[instrument(skip_all, err(Debug), fields(count = count, remain = field::Empty))]
     pub fn fetch(&mut self, state: &impl State, count: usize) -> Result<Vec<(FeeEntry, T)>> {
@@ -132,6 +345,8 @@ impl<T: TelemetryContext> FeeQueue<T> {
                         entry.item.nonce(),
                         nonce
                     );
+                    self.dropped
+                        .push((entry.item.hash(), DropReason::StaleNonce));
                 }
             }
 
@@ -160,6 +375,8 @@ impl<T: TelemetryContext> FeeQueue<T> {
                     hex::encode(entry.item.hash().as_slice()),
                     entry.item.nonce(),
                 );
+                self.dropped
+                    .push((entry.item.hash(), DropReason::FutureNonce));
             }
         }
 
@@ -179,6 +396,24 @@ impl<T: TelemetryContext> FeeQueue<T> {
     }
 }
 
+fn reject<T: TelemetryContext>(handle: &T, reason: FeeQueueRejectReason) {
+    gw_metrics::rpc().fee_queue_rejected(reason.clone()).inc();
+    record_drop(handle, reason.as_str());
+}
+
+fn evict<T: TelemetryContext>(handle: &T, reason: FeeQueueEvictReason) {
+    gw_metrics::rpc().fee_queue_evicted(reason.clone()).inc();
+    record_drop(handle, reason.as_str());
+}
+
+fn record_drop<T: TelemetryContext>(handle: &T, reason: &'static str) {
+    if let Some(cx) = handle.telemetry_context() {
+        let span = cx.span();
+        span.record_error(anyhow!(reason).as_ref());
+        span.set_status(gw_telemetry::trace::Status::error(reason));
+    }
+}
+
 impl<T: TelemetryContext> Default for FeeQueue<T> {
     #[inline]
     fn default() -> Self {
@@ -189,7 +424,7 @@ impl<T: TelemetryContext> Default for FeeQueue<T> {
 #[cfg(test)]
 mod tests {
     use gw_common::state::State;
-    use gw_config::GenesisConfig;
+    use gw_config::{FeeConfig, GenesisConfig};
     use gw_generator::genesis::init_genesis;
     use gw_store::{
         state::{history::history_state::RWConfig, BlockStateDB, MemStateDB},
@@ -204,7 +439,7 @@ mod tests {
     };
 
     use crate::fee::{
-        queue::MAX_QUEUE_SIZE,
+        queue::{AddResult, DropReason, DROP_SIZE, MAX_QUEUE_SIZE},
         types::{FeeEntry, FeeItem, FeeItemSender},
     };
 
@@ -462,14 +697,21 @@ mod tests {
                     .raw(RawL2Transaction::new_builder().nonce(0u32.pack()).build())
                     .build(),
             ),
-            fee: (101 * 1000u64).into(),
+            fee: (110 * 1000u64).into(),
             cycles_limit: 1000,
             sender: FeeItemSender::AccountId(2),
             order: queue.len(),
         };
 
-        queue.add(entry1, ());
-        queue.add(entry2, ());
+        let entry1_hash = entry1.item.hash();
+        let entry2_hash = entry2.item.hash();
+        assert_eq!(queue.add(entry1, ()), AddResult::Added);
+        assert_eq!(queue.add(entry2, ()), AddResult::Replaced(entry1_hash));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(
+            queue.take_dropped(),
+            vec![(entry1_hash, DropReason::Replaced(entry2_hash))]
+        );
 
         let snap = store.get_snapshot();
         let tree = MemStateDB::from_store(snap).unwrap();
@@ -478,13 +720,139 @@ mod tests {
         {
             let items = queue.fetch(&tree, 3).expect("fetch");
             assert_eq!(items.len(), 1);
-            assert_eq!(items[0].0.fee, (101 * 1000u64).into());
+            assert_eq!(items[0].0.fee, (110 * 1000u64).into());
             // try fetch remain items
             let items = queue.fetch(&tree, 1).expect("fetch");
             assert_eq!(items.len(), 0);
         }
     }
 
+    #[test]
+    fn test_reject_underpriced_replacement() {
+        let mut queue = FeeQueue::new();
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+
+        let new_entry = |fee: u64, signature: u8| FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(0u32.pack()).build())
+                    .signature(vec![signature].pack())
+                    .build(),
+            ),
+            fee: (fee * 1000).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(2),
+            order: 0,
+        };
+
+        let entry1 = new_entry(100, 1);
+        let entry1_hash = entry1.item.hash();
+        assert_eq!(queue.add(entry1, ()), AddResult::Added);
+        // 9% bump isn't enough
+        assert_eq!(
+            queue.add(new_entry(109, 2), ()),
+            AddResult::Underpriced(entry1_hash)
+        );
+        assert_eq!(queue.len(), 1);
+
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+        let items = queue.fetch(&tree, 3).expect("fetch");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0.item.hash(), entry1_hash);
+    }
+
+    #[test]
+    fn test_take_dropped_on_fetch() {
+        let mut queue = FeeQueue::new();
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+        {
+            let mut db = store.begin_transaction();
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+            // create accounts
+            for i in 0..4 {
+                state.create_account(H256::from_u32(i)).unwrap();
+            }
+            state.set_nonce(2, 1).unwrap();
+
+            db.commit().expect("commit");
+        }
+
+        let stale_entry = FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(0u32.pack()).build())
+                    .build(),
+            ),
+            fee: (100 * 1000u64).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(2),
+            order: queue.len(),
+        };
+        let stale_hash = stale_entry.item.hash();
+        queue.add(stale_entry, ());
+
+        let future_entry = FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(5u32.pack()).build())
+                    .build(),
+            ),
+            fee: (100 * 1000u64).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(3),
+            order: queue.len(),
+        };
+        let future_hash = future_entry.item.hash();
+        queue.add(future_entry, ());
+
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+
+        let items = queue.fetch(&tree, 3).expect("fetch");
+        assert!(items.is_empty());
+        assert!(queue.is_empty());
+        assert_eq!(
+            queue.take_dropped(),
+            vec![
+                (stale_hash, DropReason::StaleNonce),
+                (future_hash, DropReason::FutureNonce)
+            ]
+        );
+        assert!(queue.take_dropped().is_empty());
+    }
+
+    #[test]
+    fn test_fee_rate_at() {
+        let mut queue = FeeQueue::new();
+
+        let new_entry = |fee: u64, sender: u32| FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(0u32.pack()).build())
+                    .build(),
+            ),
+            fee: (fee * 1000).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(sender),
+            order: 0,
+        };
+
+        assert_eq!(queue.fee_rate_at(0), None);
+        queue.add(new_entry(100, 2), ());
+        queue.add(new_entry(300, 3), ());
+        queue.add(new_entry(200, 4), ());
+        assert_eq!(queue.fee_rate_at(0), Some(300));
+        assert_eq!(queue.fee_rate_at(1), Some(200));
+        assert_eq!(queue.fee_rate_at(2), Some(100));
+        assert_eq!(queue.fee_rate_at(3), None);
+    }
+
     #[test]
     fn test_drop_items() {
         let mut queue = FeeQueue::new();
@@ -505,16 +873,21 @@ mod tests {
             db.commit().expect("commit");
         }
 
+        // spread items to senders to stay within the per sender quota
         for i in 0..(MAX_QUEUE_SIZE as u32) {
             let entry1 = FeeEntry {
                 item: FeeItem::Tx(
                     L2Transaction::new_builder()
-                        .raw(RawL2Transaction::new_builder().nonce(i.pack()).build())
+                        .raw(
+                            RawL2Transaction::new_builder()
+                                .nonce((i / 1000).pack())
+                                .build(),
+                        )
                         .build(),
                 ),
                 fee: (100 * 1000u64).into(),
                 cycles_limit: 1000,
-                sender: FeeItemSender::AccountId(2),
+                sender: FeeItemSender::AccountId(i % 1000),
                 order: queue.len(),
             };
             queue.add(entry1, ());
@@ -529,14 +902,14 @@ mod tests {
                     L2Transaction::new_builder()
                         .raw(
                             RawL2Transaction::new_builder()
-                                .nonce(10001u32.pack())
+                                .nonce((MAX_QUEUE_SIZE as u32 / 1000).pack())
                                 .build(),
                         )
                         .build(),
                 ),
                 fee: (100 * 1000u64).into(),
                 cycles_limit: 1000,
-                sender: FeeItemSender::AccountId(2),
+                sender: FeeItemSender::AccountId(0),
                 order: queue.len(),
             };
             queue.add(entry1, ());
@@ -544,6 +917,127 @@ mod tests {
 
         // we should trigger the drop
         assert!(queue.len() < MAX_QUEUE_SIZE);
+        assert_eq!(queue.take_dropped().len(), DROP_SIZE);
+    }
+
+    #[test]
+    fn test_sender_quota() {
+        let fee_config = FeeConfig {
+            max_queued_entries_per_sender: 2,
+            ..Default::default()
+        };
+        let mut queue = FeeQueue::with_config(&fee_config);
+
+        let new_entry = |nonce: u32, sender: u32| FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+                    .build(),
+            ),
+            fee: (100 * 1000u64).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(sender),
+            order: 0,
+        };
+
+        assert_eq!(queue.add(new_entry(0, 2), ()), AddResult::Added);
+        assert_eq!(queue.add(new_entry(1, 2), ()), AddResult::Added);
+        assert_eq!(
+            queue.add(new_entry(2, 2), ()),
+            AddResult::SenderQuotaExceeded
+        );
+        // other senders are not affected
+        assert_eq!(queue.add(new_entry(0, 3), ()), AddResult::Added);
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_future_quota() {
+        let fee_config = FeeConfig {
+            max_future_entries_per_sender: 2,
+            ..Default::default()
+        };
+        let mut queue = FeeQueue::with_config(&fee_config);
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+        {
+            let mut db = store.begin_transaction();
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+            // create accounts
+            for i in 0..4 {
+                state.create_account(H256::from_u32(i)).unwrap();
+            }
+
+            db.commit().expect("commit");
+        }
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+
+        let new_entry = |nonce: u32| FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+                    .build(),
+            ),
+            fee: (100 * 1000u64).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(2),
+            order: 0,
+        };
+
+        let add = |queue: &mut FeeQueue<()>, nonce| {
+            queue.add_checked(&tree, new_entry(nonce), ()).unwrap()
+        };
+        assert_eq!(add(&mut queue, 1), AddResult::Added);
+        assert_eq!(add(&mut queue, 2), AddResult::Added);
+        assert_eq!(add(&mut queue, 3), AddResult::FutureQuotaExceeded);
+        // the current nonce is not limited
+        assert_eq!(add(&mut queue, 0), AddResult::Added);
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_evict_sender_with_most_entries() {
+        let mut queue = FeeQueue::new();
+
+        let new_entry = |nonce: u32, sender: u32, fee: u64, order: usize| FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+                    .build(),
+            ),
+            fee: (fee * 1000).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(sender),
+            order,
+        };
+
+        // the spammer pays a higher fee and has the most entries
+        let spammer = 1;
+        for nonce in 0..200 {
+            let entry = new_entry(nonce, spammer, 200, queue.len());
+            assert_eq!(queue.add(entry, ()), AddResult::Added);
+        }
+        let mut sender = 10;
+        while queue.len() < MAX_QUEUE_SIZE {
+            for nonce in 0..100 {
+                let entry = new_entry(nonce, sender, 100, queue.len());
+                assert_eq!(queue.add(entry, ()), AddResult::Added);
+            }
+            sender += 1;
+        }
+        assert_eq!(queue.len(), MAX_QUEUE_SIZE);
+
+        // trigger the drop
+        queue.add(new_entry(0, sender, 100, queue.len()), ());
+
+        assert_eq!(queue.len(), MAX_QUEUE_SIZE + 1 - DROP_SIZE);
+        let spammer_nonces = &queue.sender_nonces[&FeeItemSender::AccountId(spammer)];
+        assert_eq!(spammer_nonces.len(), 200 - DROP_SIZE);
+        // the highest nonces are evicted
+        assert_eq!(spammer_nonces.iter().next_back(), Some(&99));
     }
 
     #[test]
@@ -784,14 +1278,16 @@ mod tests {
                     .raw(RawL2Transaction::new_builder().nonce(0u32.pack()).build())
                     .build(),
             ),
-            fee: (101 * 1000u64).into(),
+            fee: (110 * 1000u64).into(),
             cycles_limit: 1000,
             sender: FeeItemSender::PendingCreate(H256::from_u32(2)),
             order: queue.len(),
         };
 
-        queue.add(entry1, ());
-        queue.add(entry2, ());
+        let entry1_hash = entry1.item.hash();
+        assert_eq!(queue.add(entry1, ()), AddResult::Added);
+        assert_eq!(queue.add(entry2, ()), AddResult::Replaced(entry1_hash));
+        assert_eq!(queue.len(), 1);
 
         let snap = store.get_snapshot();
         let tree = MemStateDB::from_store(snap).unwrap();
@@ -800,7 +1296,7 @@ mod tests {
         {
             let items = queue.fetch(&tree, 3).expect("fetch");
             assert_eq!(items.len(), 1);
-            assert_eq!(items[0].0.fee, (101 * 1000u64).into());
+            assert_eq!(items[0].0.fee, (110 * 1000u64).into());
             // try fetch remain items
             let items = queue.fetch(&tree, 1).expect("fetch");
             assert_eq!(items.len(), 0);
@@ -814,16 +1310,21 @@ mod tests {
         let store = Store::open_tmp().expect("open store");
         setup_genesis(&store);
 
+        // spread items to senders to stay within the per sender quota
         for i in 0..(MAX_QUEUE_SIZE as u32) {
             let entry1 = FeeEntry {
                 item: FeeItem::Tx(
                     L2Transaction::new_builder()
-                        .raw(RawL2Transaction::new_builder().nonce(i.pack()).build())
+                        .raw(
+                            RawL2Transaction::new_builder()
+                                .nonce((i / 1000).pack())
+                                .build(),
+                        )
                         .build(),
                 ),
                 fee: (100 * 1000u64).into(),
                 cycles_limit: 1000,
-                sender: FeeItemSender::PendingCreate(H256::from_u32(2)),
+                sender: FeeItemSender::PendingCreate(H256::from_u32(i % 1000)),
                 order: queue.len(),
             };
             queue.add(entry1, ());
@@ -838,14 +1339,14 @@ mod tests {
                     L2Transaction::new_builder()
                         .raw(
                             RawL2Transaction::new_builder()
-                                .nonce(10001u32.pack())
+                                .nonce((MAX_QUEUE_SIZE as u32 / 1000).pack())
                                 .build(),
                         )
                         .build(),
                 ),
                 fee: (100 * 1000u64).into(),
                 cycles_limit: 1000,
-                sender: FeeItemSender::PendingCreate(H256::from_u32(2)),
+                sender: FeeItemSender::PendingCreate(H256::from_u32(0)),
                 order: queue.len(),
             };
             queue.add(entry1, ());
@@ -869,6 +1370,7 @@ mod tests {
             timestamp: 0,
             meta_contract_validator_type_hash: [100u8; 32].into(),
             eth_registry_validator_type_hash: [101u8; 32].into(),
+            ckb_registry_validator_type_hash: None,
             rollup_config: rollup_config.into(),
             rollup_type_hash: rollup_type_hash.into(),
             secp_data_dep: Default::default(),
@@ -133,6 +133,27 @@ impl Ord for FeeEntry {
 }
 
 impl FeeEntry {
+    /// Fee per cycles limit, rounded down. It is the gas price of Polyjuice
+    /// txs.
+    pub fn fee_rate(&self) -> u128 {
+        self.fee / u128::from(self.cycles_limit.max(1))
+    }
+
+    /// Returns true if the fee rate of self is higher than `other` by at least
+    /// `percentage`.
+    pub fn fee_rate_exceeds(&self, other: &FeeEntry, percentage: u64) -> bool {
+        // A / B >= C / D * (100 + P) / 100 => A * D * 100 >= C * B * (100 + P)
+        let rate = self
+            .fee
+            .saturating_mul(other.cycles_limit.into())
+            .saturating_mul(100);
+        let required = other
+            .fee
+            .saturating_mul(self.cycles_limit.into())
+            .saturating_mul(100u128.saturating_add(percentage.into()));
+        rate >= required
+    }
+
     pub fn from_tx(
         tx: L2Transaction,
         gasless_tx_support_config: Option<&GaslessTxSupportConfig>,
@@ -222,7 +243,8 @@ fn parse_l2tx_fee_rate(
 
             Ok(L2Fee { fee, cycles_limit })
         }
-        BackendType::EthAddrReg => {
+        // The CKB registry shares the args of the ETH registry
+        BackendType::EthAddrReg | BackendType::CkbAddrReg => {
             let eth_addr_reg_args = ETHAddrRegArgs::from_slice(raw_l2tx_args.as_ref())?;
             let fee = match eth_addr_reg_args.to_enum() {
                 ETHAddrRegArgsUnion::EthToGw(_) | ETHAddrRegArgsUnion::GwToEth(_) => 0,
@@ -13,6 +13,7 @@ pub mod fee;
 pub mod mem_block;
 pub mod pool;
 pub mod restore_manager;
+pub mod subscription;
 pub mod traits;
 mod types;
 pub mod withdrawal;
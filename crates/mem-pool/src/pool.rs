@@ -61,6 +61,7 @@ use crate::{
     block_sync_server::BlockSyncServerState,
     mem_block::MemBlock,
     restore_manager::RestoreManager,
+    subscription::SubscriptionPublisher,
     traits::MemPoolProvider,
     types::EntryList,
     withdrawal::Generator as WithdrawalGenerator,
@@ -108,6 +109,10 @@ pub struct MemPool {
     cycles_pool: CyclesPool,
     /// Account creator
     account_creator: Option<AccountCreator>,
+    /// New blocks, pending transactions and logs for RPC subscriptions
+    subscription: Arc<SubscriptionPublisher>,
+    /// Pushed txs to publish after the DB transaction is committed
+    unpublished_tx_hashes: Vec<H256>,
 }
 
 pub struct MemPoolCreateArgs {
@@ -216,9 +221,14 @@ impl MemPool {
             account_creator,
             polyjuice_contract_creator_allowlist,
             sudt_proxy_account_allowlist,
+            subscription: Default::default(),
+            unpublished_tx_hashes: Vec::new(),
         };
-        mem_pool.restore_pending_withdrawals().await?;
-        mem_pool.remove_reinjected_failed_txs()?;
+        // The mem pool records in a secondary store belong to the primary node.
+        if node_mode != NodeMode::Secondary {
+            mem_pool.restore_pending_withdrawals().await?;
+            mem_pool.remove_reinjected_failed_txs()?;
+        }
 
         // update mem block info
         let mut shared = mem_pool.mem_pool_state().load_shared();
@@ -226,7 +236,7 @@ impl MemPool {
         mem_pool.mem_pool_state().store_shared(Arc::new(shared));
 
         // set tip
-        if matches!(node_mode, NodeMode::ReadOnly) {
+        if matches!(node_mode, NodeMode::ReadOnly | NodeMode::Secondary) {
             mem_pool.reset_read_only(Some(tip_hash), true)?;
         } else {
             mem_pool
@@ -250,6 +260,10 @@ impl MemPool {
         self.mem_pool_state.clone()
     }
 
+    pub fn subscription(&self) -> Arc<SubscriptionPublisher> {
+        self.subscription.clone()
+    }
+
     pub fn cycles_pool(&self) -> &CyclesPool {
         &self.cycles_pool
     }
@@ -321,6 +335,7 @@ impl MemPool {
             self.push_transaction_with_db(&mut db, &mut state, tx)?;
             db.commit()?;
             self.mem_pool_state.store_state_db(state);
+            self.publish_pending_transactions();
 
             Ok(())
         })
@@ -359,7 +374,11 @@ impl MemPool {
         )
         .verify(&tx, self.mem_block.block_info().number().unpack())?;
         // verify signature
-        self.generator.check_transaction_signature(state, &tx)?;
+        self.generator.check_transaction_signature(
+            state,
+            &tx,
+            self.mem_block.block_info().number().unpack(),
+        )?;
 
         // instantly run tx in background & update local state
         let t = Instant::now();
@@ -384,10 +403,18 @@ impl MemPool {
         db.insert_mem_pool_transaction(&tx_hash, tx.clone())?;
         let entry_list = self.pending.entry(account_id).or_default();
         entry_list.txs.push(tx);
+        self.unpublished_tx_hashes.push(tx_hash);
 
         Ok(())
     }
 
+    /// Publish txs pushed by committed DB transactions to RPC subscribers.
+    fn publish_pending_transactions(&mut self) {
+        for tx_hash in self.unpublished_tx_hashes.drain(..) {
+            self.subscription.publish_pending_transaction(tx_hash);
+        }
+    }
+
     /// Push a withdrawal request into pool
     #[instrument(skip_all, err(Debug), fields(withdrawal = %withdrawal.hash().pack()))]
     pub async fn push_withdrawal_request(
@@ -565,10 +592,13 @@ impl MemPool {
         new_tip: Option<H256>,
         local_cells_manager: &LocalCellsManager,
     ) -> Result<()> {
-        self.reset_full(old_tip, new_tip, local_cells_manager).await
+        let (old_tip_hash, old_tip_number, _) = self.current_tip;
+        self.reset_full(old_tip, new_tip, local_cells_manager).await?;
+        self.publish_new_tip(old_tip_hash, old_tip_number);
+        Ok(())
     }
 
-    /// Only **ReadOnly** node.
+    /// Only **ReadOnly** and **Secondary** node.
     /// update current tip. Reset mem pool state if `update_state` is true.
     #[instrument(skip_all)]
     pub fn reset_read_only(&mut self, new_tip: Option<H256>, update_state: bool) -> Result<()> {
@@ -584,6 +614,7 @@ impl MemPool {
             .store
             .get_block_post_global_state(&new_tip)?
             .expect("new tip global state");
+        let (old_tip_hash, old_tip_number, _) = self.current_tip;
         self.current_tip = (
             new_tip,
             new_tip_block.raw().number().unpack(),
@@ -602,10 +633,28 @@ impl MemPool {
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
         }
+        self.publish_new_tip(old_tip_hash, old_tip_number);
 
         Ok(())
     }
 
+    /// Publish new blocks to RPC subscribers if the tip has changed.
+    fn publish_new_tip(&self, old_tip_hash: H256, old_tip_number: u64) {
+        if self.current_tip.0 == old_tip_hash {
+            return;
+        }
+        let publish = || -> Result<()> {
+            let new_tip = self
+                .store
+                .get_block(&self.current_tip.0)?
+                .context("new tip block")?;
+            self.subscription.publish_new_tip(&self.store, old_tip_number, &new_tip)
+        };
+        if let Err(err) = publish() {
+            log::warn!("[mem-pool] publish new tip error: {:#}", err);
+        }
+    }
+
     /// Only **Full** node and **Test** node.
     /// reset mem pool state
     #[instrument(skip_all)]
@@ -843,6 +892,7 @@ impl MemPool {
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
             db.commit()?;
+            self.publish_pending_transactions();
 
             Ok(())
         })
@@ -1009,12 +1059,10 @@ impl MemPool {
         // refresh
         let state = self.mem_pool_state.load_state_db();
         let mem_account_count = state.get_account_count()?;
-        let tip_account_count: u32 = {
-            let new_tip_block = db
-                .get_block(&new_block_hash)?
-                .ok_or_else(|| anyhow!("can't find new tip block"))?;
-            new_tip_block.raw().post_account().count().unpack()
-        };
+        let new_tip_block = db
+            .get_block(&new_block_hash)?
+            .ok_or_else(|| anyhow!("can't find new tip block"))?;
+        let tip_account_count: u32 = new_tip_block.raw().post_account().count().unpack();
 
         log::debug!(
             "[mem-pool] refresh pending deposits, mem_account_count: {}, tip_account_count: {}",
@@ -1025,11 +1073,18 @@ impl MemPool {
             .provider
             .collect_deposit_cells(local_cells_manager)
             .await?;
+        // deposits are packaged in the next block
+        let next_block_number: u64 = new_tip_block.raw().number().unpack() + 1;
+        let registries = self
+            .generator
+            .rollup_context()
+            .registries(&state, next_block_number)?;
         self.pending_deposits = crate::deposit::sanitize_deposit_cells(
             self.generator.rollup_context(),
             &self.mem_block_config.deposit_timeout_config,
             cells,
             &state,
+            registries,
         );
         log::debug!(
             "[mem-pool] refreshed deposits: {}",
@@ -1051,7 +1106,11 @@ impl MemPool {
         let mut post_states = Vec::with_capacity(deposits.len());
         let mut touched_keys_vec = Vec::with_capacity(deposits.len());
         for deposit in deposits {
-            state.apply_deposit_request(self.generator.rollup_context(), &deposit)?;
+            state.apply_deposit_request(
+                self.generator.rollup_context(),
+                self.mem_block.block_info().number().unpack(),
+                &deposit,
+            )?;
             let touched_keys = state.state_tracker().unwrap().touched_keys();
             touched_keys_vec.push(touched_keys.lock().unwrap().drain().collect());
             state.finalise()?;
@@ -1410,6 +1469,7 @@ impl MemPool {
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
             db.commit()?;
+            self.publish_pending_transactions();
 
             let mem_block = &self.mem_block;
             log::info!(
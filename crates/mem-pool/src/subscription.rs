@@ -0,0 +1,349 @@
+//! Publisher of new blocks, pending transactions and logs for RPC
+//! subscriptions.
+//!
+//! Events are sent through tokio broadcast channels. Slow subscribers will
+//! miss events instead of blocking the mem pool.
+//!
+//! Blocks are published in order. Subscribers of new blocks and logs are
+//! notified with a `ChainEvent` when published blocks are reverted or when
+//! blocks are skipped.
+
+use std::{cmp::min, collections::VecDeque, sync::Mutex};
+
+use anyhow::Result;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{
+    h256::*,
+    packed::{L2Block, LogItem},
+    prelude::*,
+};
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+
+/// Do not publish more than this number of blocks for a single tip update,
+/// e.g. when a readonly node catches up after restart.
+/// Skipped blocks are notified with `ChainEvent::Gap`.
+const MAX_PUBLISH_BLOCKS: u64 = 64;
+
+/// Number of published blocks remembered to detect reorgs.
+const PUBLISHED_BLOCKS_HISTORY: usize = 256;
+
+/// Changes of the main chain other than new blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainEvent {
+    /// Published blocks since `from_number` are no longer on the main chain.
+    /// Blocks of the new main chain are published after this event.
+    Reorg { from_number: u64 },
+    /// Blocks `from_number..=to_number` are not published.
+    Gap { from_number: u64, to_number: u64 },
+}
+
+#[derive(Clone)]
+pub enum Event<T> {
+    Item(T),
+    Chain(ChainEvent),
+}
+
+/// A log item emitted by a committed transaction.
+#[derive(Clone)]
+pub struct LogEvent {
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub tx_hash: H256,
+    pub tx_index: u32,
+    pub log: LogItem,
+}
+
+pub struct SubscriptionPublisher {
+    new_heads: Sender<Event<L2Block>>,
+    pending_transactions: Sender<H256>,
+    logs: Sender<Event<LogEvent>>,
+    /// Number and hash of recently published blocks.
+    published: Mutex<VecDeque<(u64, H256)>>,
+}
+
+/// Blocks to publish for a new tip.
+#[derive(Debug, PartialEq, Eq)]
+struct PublishPlan {
+    reorg: Option<ChainEvent>,
+    gap: Option<ChainEvent>,
+    /// Publish blocks from `start` to the new tip.
+    start: u64,
+}
+
+impl SubscriptionPublisher {
+    pub fn new(channel_capacity: usize) -> Self {
+        let (new_heads, _) = channel(channel_capacity);
+        let (pending_transactions, _) = channel(channel_capacity);
+        let (logs, _) = channel(channel_capacity);
+        Self {
+            new_heads,
+            pending_transactions,
+            logs,
+            published: Default::default(),
+        }
+    }
+
+    pub fn subscribe_new_heads(&self) -> Receiver<Event<L2Block>> {
+        self.new_heads.subscribe()
+    }
+
+    pub fn subscribe_pending_transactions(&self) -> Receiver<H256> {
+        self.pending_transactions.subscribe()
+    }
+
+    pub fn subscribe_logs(&self) -> Receiver<Event<LogEvent>> {
+        self.logs.subscribe()
+    }
+
+    pub fn publish_pending_transaction(&self, tx_hash: H256) {
+        let _ = self.pending_transactions.send(tx_hash);
+    }
+
+    /// Publish blocks on the main chain after the tip changed from
+    /// `old_tip_number` to `new_tip`.
+    ///
+    /// Subscribers are notified with `ChainEvent::Reorg` if published blocks
+    /// are reverted, and with `ChainEvent::Gap` if more than
+    /// `MAX_PUBLISH_BLOCKS` blocks are added.
+    pub fn publish_new_tip(
+        &self,
+        store: &Store,
+        old_tip_number: u64,
+        new_tip: &L2Block,
+    ) -> Result<()> {
+        let mut published = self.published.lock().expect("lock published blocks");
+        if self.new_heads.receiver_count() == 0 && self.logs.receiver_count() == 0 {
+            published.clear();
+            return Ok(());
+        }
+
+        let snap = store.get_snapshot();
+        let new_tip_number: u64 = new_tip.raw().number().unpack();
+        let plan = plan_publish(&mut published, old_tip_number, new_tip_number, |number| {
+            snap.get_block_hash_by_number(number)
+        })?;
+        for event in plan.reorg.into_iter().chain(plan.gap) {
+            self.publish_chain_event(event);
+        }
+
+        for number in plan.start..=new_tip_number {
+            let block = if number == new_tip_number {
+                new_tip.clone()
+            } else {
+                let block_hash = match snap.get_block_hash_by_number(number)? {
+                    Some(block_hash) => block_hash,
+                    None => continue,
+                };
+                match snap.get_block(&block_hash)? {
+                    Some(block) => block,
+                    None => continue,
+                }
+            };
+            self.publish_block(&snap, &block)?;
+            published.push_back((number, block.hash()));
+        }
+        while published.len() > PUBLISHED_BLOCKS_HISTORY {
+            published.pop_front();
+        }
+        Ok(())
+    }
+
+    fn publish_chain_event(&self, event: ChainEvent) {
+        let _ = self.new_heads.send(Event::Chain(event));
+        let _ = self.logs.send(Event::Chain(event));
+    }
+
+    fn publish_block(&self, snap: &impl ChainStore, block: &L2Block) -> Result<()> {
+        let _ = self.new_heads.send(Event::Item(block.clone()));
+
+        if self.logs.receiver_count() == 0 {
+            return Ok(());
+        }
+        let block_number = block.raw().number().unpack();
+        let block_hash = block.hash();
+        for (tx_index, tx) in block.transactions().into_iter().enumerate() {
+            let tx_hash = tx.hash();
+            let receipt = match snap.get_transaction_receipt(&tx_hash)? {
+                Some(receipt) => receipt,
+                None => continue,
+            };
+            for log in receipt.logs() {
+                let _ = self.logs.send(Event::Item(LogEvent {
+                    block_number,
+                    block_hash,
+                    tx_hash,
+                    tx_index: tx_index as u32,
+                    log,
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for SubscriptionPublisher {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+/// Pop published blocks which are no longer on the main chain and find the
+/// blocks to publish for the new tip.
+fn plan_publish(
+    published: &mut VecDeque<(u64, H256)>,
+    old_tip_number: u64,
+    new_tip_number: u64,
+    get_block_hash_by_number: impl Fn(u64) -> Result<Option<H256>>,
+) -> Result<PublishPlan> {
+    let mut reorg_from = None;
+    while let Some(&(number, block_hash)) = published.back() {
+        if number <= new_tip_number && get_block_hash_by_number(number)? == Some(block_hash) {
+            break;
+        }
+        published.pop_back();
+        reorg_from = Some(number);
+    }
+
+    let mut start = match (reorg_from, published.back()) {
+        (Some(number), _) => number,
+        (None, Some((number, _))) => number + 1,
+        // Nothing published yet, always publish the new tip.
+        (None, None) => min(old_tip_number + 1, new_tip_number),
+    };
+    let mut gap = None;
+    if new_tip_number >= start + MAX_PUBLISH_BLOCKS {
+        let to_number = new_tip_number - MAX_PUBLISH_BLOCKS;
+        gap = Some(ChainEvent::Gap {
+            from_number: start,
+            to_number,
+        });
+        start = to_number + 1;
+    }
+
+    Ok(PublishPlan {
+        reorg: reorg_from.map(|from_number| ChainEvent::Reorg { from_number }),
+        gap,
+        start,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use anyhow::Result;
+    use gw_types::h256::*;
+
+    use super::{plan_publish, ChainEvent, PublishPlan, MAX_PUBLISH_BLOCKS};
+
+    fn block_hash(number: u64, fork: u8) -> H256 {
+        let mut hash = [0u8; 32];
+        hash[..8].copy_from_slice(&number.to_le_bytes());
+        hash[8] = fork;
+        hash
+    }
+
+    fn chain(numbers: impl Iterator<Item = (u64, u8)>) -> impl Fn(u64) -> Result<Option<H256>> {
+        let hashes: HashMap<u64, H256> = numbers
+            .map(|(number, fork)| (number, block_hash(number, fork)))
+            .collect();
+        move |number| Ok(hashes.get(&number).copied())
+    }
+
+    fn published(numbers: impl Iterator<Item = (u64, u8)>) -> VecDeque<(u64, H256)> {
+        numbers
+            .map(|(number, fork)| (number, block_hash(number, fork)))
+            .collect()
+    }
+
+    #[test]
+    fn test_publish_new_blocks() {
+        let mut published = published((1..=5).map(|n| (n, 0)));
+        let plan = plan_publish(&mut published, 5, 8, chain((0..=8).map(|n| (n, 0)))).unwrap();
+        assert_eq!(
+            plan,
+            PublishPlan {
+                reorg: None,
+                gap: None,
+                start: 6
+            }
+        );
+        assert_eq!(published.len(), 5);
+    }
+
+    #[test]
+    fn test_publish_first_tip() {
+        let mut published = VecDeque::new();
+        let plan = plan_publish(&mut published, 5, 6, chain((0..=6).map(|n| (n, 0)))).unwrap();
+        assert_eq!(plan.start, 6);
+
+        // Rollback before anything is published
+        let plan = plan_publish(&mut published, 5, 3, chain((0..=3).map(|n| (n, 0)))).unwrap();
+        assert_eq!(
+            plan,
+            PublishPlan {
+                reorg: None,
+                gap: None,
+                start: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_publish_gap() {
+        let mut published = published((1..=5).map(|n| (n, 0)));
+        let new_tip_number = 5 + MAX_PUBLISH_BLOCKS + 10;
+        let plan = plan_publish(
+            &mut published,
+            5,
+            new_tip_number,
+            chain((0..=new_tip_number).map(|n| (n, 0))),
+        )
+        .unwrap();
+        assert_eq!(
+            plan,
+            PublishPlan {
+                reorg: None,
+                gap: Some(ChainEvent::Gap {
+                    from_number: 6,
+                    to_number: 15
+                }),
+                start: 16
+            }
+        );
+        assert_eq!(new_tip_number - plan.start + 1, MAX_PUBLISH_BLOCKS);
+    }
+
+    #[test]
+    fn test_publish_reorg() {
+        // Blocks 4 and 5 are replaced, new tip is 6
+        let mut published = published((1..=5).map(|n| (n, 0)));
+        let new_chain = chain((0..=3).map(|n| (n, 0)).chain((4..=6).map(|n| (n, 1))));
+        let plan = plan_publish(&mut published, 5, 6, new_chain).unwrap();
+        assert_eq!(
+            plan,
+            PublishPlan {
+                reorg: Some(ChainEvent::Reorg { from_number: 4 }),
+                gap: None,
+                start: 4
+            }
+        );
+        assert_eq!(published.back(), Some(&(3, block_hash(3, 0))));
+    }
+
+    #[test]
+    fn test_publish_rollback() {
+        // Block 5 is reverted, new tip is 4
+        let mut published = published((1..=5).map(|n| (n, 0)));
+        let plan = plan_publish(&mut published, 5, 4, chain((0..=4).map(|n| (n, 0)))).unwrap();
+        assert_eq!(
+            plan,
+            PublishPlan {
+                reorg: Some(ChainEvent::Reorg { from_number: 5 }),
+                gap: None,
+                start: 5
+            }
+        );
+        assert_eq!(published.len(), 4);
+    }
+}
@@ -15,10 +15,44 @@ pub enum RequestKind {
     Withdrawal,
 }
 
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+pub enum FeeQueueRejectReason {
+    Underpriced,
+    SenderQuota,
+    FutureQuota,
+}
+
+impl FeeQueueRejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Underpriced => "underpriced replacement",
+            Self::SenderQuota => "sender quota exceeded",
+            Self::FutureQuota => "future nonce quota exceeded",
+        }
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+pub enum FeeQueueEvictReason {
+    Replaced,
+    QueueFull,
+}
+
+impl FeeQueueEvictReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Replaced => "replaced by fee",
+            Self::QueueFull => "queue is full",
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct RPCMetrics {
     execute_transactions: Family<ExecutionLabel, Counter>,
     in_queue_requests: Family<RequestLabel, Gauge>,
+    fee_queue_rejected: Family<RejectLabel, Counter>,
+    fee_queue_evicted: Family<EvictLabel, Counter>,
 }
 
 impl RPCMetrics {
@@ -35,6 +69,16 @@ impl RPCMetrics {
                 "Number of in queue requests",
                 Box::new(self.in_queue_requests.clone()),
             );
+            registry.register(
+                "fee_queue_rejected",
+                "Number of requests rejected by the fee queue",
+                Box::new(self.fee_queue_rejected.clone()),
+            );
+            registry.register(
+                "fee_queue_evicted",
+                "Number of requests evicted from the fee queue",
+                Box::new(self.fee_queue_evicted.clone()),
+            );
         }
     }
 
@@ -49,6 +93,18 @@ impl RPCMetrics {
             .get_or_create(&RequestLabel { kind })
             .clone()
     }
+
+    pub fn fee_queue_rejected(&self, reason: FeeQueueRejectReason) -> Counter {
+        self.fee_queue_rejected
+            .get_or_create(&RejectLabel { reason })
+            .clone()
+    }
+
+    pub fn fee_queue_evicted(&self, reason: FeeQueueEvictReason) -> Counter {
+        self.fee_queue_evicted
+            .get_or_create(&EvictLabel { reason })
+            .clone()
+    }
 }
 
 // Label for the execute_transactions metric.
@@ -68,3 +124,13 @@ impl Encode for ExecutionLabel {
 struct RequestLabel {
     kind: RequestKind,
 }
+
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+struct RejectLabel {
+    reason: FeeQueueRejectReason,
+}
+
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+struct EvictLabel {
+    reason: FeeQueueEvictReason,
+}
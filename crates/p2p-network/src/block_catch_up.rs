@@ -0,0 +1,301 @@
+//! Historical block catch-up.
+//!
+//! A request/response protocol for fetching blocks of an arbitrary range from
+//! a trusted full node. The client sends a `BlockCatchUpRequest`, the server
+//! responds with the blocks as `ExportedBlock`s one by one, followed by a
+//! `BlockCatchUpEnd`, or a `BlockCatchUpError` if it fails to serve the
+//! request.
+//!
+//! Requests from the same peer are throttled by the server.
+
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use futures::TryStreamExt;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{
+    offchain::ExportedBlock,
+    packed::{
+        self, BlockCatchUpEnd, BlockCatchUpError, BlockCatchUpRequest, BlockCatchUpRequestReader,
+        BlockCatchUpResponse, BlockCatchUpResponseReader, BlockCatchUpResponseUnionReader,
+    },
+    prelude::*,
+};
+use gw_utils::{
+    compression::{StreamDecoder, StreamEncoder},
+    export_block::export_block,
+};
+use tentacle::{
+    builder::MetaBuilder,
+    secio::PeerId,
+    service::{ProtocolMeta, ServiceAsyncControl},
+    utils::extract_peer_id,
+    SessionId, SubstreamReadPart,
+};
+use tokio::task::block_in_place;
+
+use crate::{FnSpawn, P2P_BLOCK_CATCH_UP_PROTOCOL, P2P_BLOCK_CATCH_UP_PROTOCOL_NAME};
+
+/// Max number of blocks the server sends for one request.
+pub const MAX_CATCH_UP_BLOCKS: u64 = 256;
+
+/// The client gives up if the server doesn't send the next message in time.
+pub const CATCH_UP_MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Min interval between two requests from the same peer.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+pub enum CatchUpMessage {
+    Block(Box<ExportedBlock>),
+    /// No more blocks for the request.
+    End {
+        tip_number: u64,
+    },
+}
+
+/// Delay requests so that a peer sends at most one request every
+/// `MIN_REQUEST_INTERVAL`, across all of its sessions.
+#[derive(Default)]
+struct RateLimiter {
+    next_request_at: HashMap<Option<PeerId>, Instant>,
+}
+
+impl RateLimiter {
+    /// Returns how long the request should be delayed.
+    fn delay(&mut self, peer_id: Option<PeerId>, now: Instant) -> Duration {
+        self.next_request_at.retain(|_, at| *at > now);
+        let at = self.next_request_at.entry(peer_id).or_insert(now);
+        let delay = at.saturating_duration_since(now);
+        *at = max(*at, now) + MIN_REQUEST_INTERVAL;
+        delay
+    }
+}
+
+pub fn block_catch_up_server_protocol(store: Store) -> ProtocolMeta {
+    let rate_limiter = Arc::new(Mutex::new(RateLimiter::default()));
+    let spawn = FnSpawn(move |context, control, mut read_part| {
+        let store = store.clone();
+        let control = control.clone();
+        let rate_limiter = rate_limiter.clone();
+        let session_id = context.id;
+        let peer_id = extract_peer_id(&context.address);
+        tokio::spawn(async move {
+            let mut encoder = StreamEncoder::new(3).expect("create StreamEncoder");
+            while let Some(msg) = read_part.try_next().await? {
+                let mut send = |response: BlockCatchUpResponse| {
+                    let compressed: Bytes = encoder
+                        .encode(&response.as_bytes())
+                        .expect("compress")
+                        .into();
+                    control.send_message_to(session_id, P2P_BLOCK_CATCH_UP_PROTOCOL, compressed)
+                };
+                let request = match BlockCatchUpRequestReader::from_slice(msg.as_ref()) {
+                    Ok(request) => request.to_entity(),
+                    Err(err) => {
+                        send(error_response(&anyhow!("invalid request: {}", err))).await?;
+                        break;
+                    }
+                };
+
+                let delay = rate_limiter
+                    .lock()
+                    .unwrap()
+                    .delay(peer_id.clone(), Instant::now());
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+
+                let snap = store.get_snapshot();
+                let (range, tip_number) = match catch_up_range(&snap, &request) {
+                    Ok(r) => r,
+                    Err(err) => {
+                        log::warn!("session {} catch up: {:#}", session_id, err);
+                        send(error_response(&err)).await?;
+                        continue;
+                    }
+                };
+                log::info!(
+                    "session {} catch up blocks [{}, {})",
+                    session_id,
+                    range.start,
+                    range.end
+                );
+                let mut result = Ok(());
+                for block_number in range {
+                    match block_in_place(|| block_response(&snap, block_number)) {
+                        Ok(response) => send(response).await?,
+                        Err(err) => {
+                            result = Err(err);
+                            break;
+                        }
+                    }
+                }
+                match result {
+                    Ok(()) => send(end_response(tip_number)).await?,
+                    Err(err) => {
+                        log::warn!("session {} catch up: {:#}", session_id, err);
+                        send(error_response(&err)).await?;
+                    }
+                }
+            }
+            anyhow::Ok(())
+        });
+    });
+    MetaBuilder::new()
+        .name(|_| P2P_BLOCK_CATCH_UP_PROTOCOL_NAME.into())
+        .id(P2P_BLOCK_CATCH_UP_PROTOCOL)
+        .protocol_spawn(spawn)
+        .build()
+}
+
+/// Blocks to send for the request and the tip block number of the server.
+pub fn catch_up_range(
+    snap: &impl ChainStore,
+    request: &BlockCatchUpRequest,
+) -> Result<(Range<u64>, u64)> {
+    let tip_number = snap.get_last_valid_tip_block()?.raw().number().unpack();
+    let start = request.start().unpack();
+    let earliest = snap.get_earliest_block_number();
+    if start < earliest {
+        bail!(
+            "blocks before {} are not available, the store is restored from a state snapshot",
+            earliest
+        );
+    }
+    let count = min(request.count().unpack(), MAX_CATCH_UP_BLOCKS);
+    let end = min(start.saturating_add(count), tip_number + 1);
+    Ok((start..max(start, end), tip_number))
+}
+
+pub fn block_response(snap: &impl ChainStore, block_number: u64) -> Result<BlockCatchUpResponse> {
+    let exported = export_block(snap, block_number)?;
+    Ok(BlockCatchUpResponse::new_builder()
+        .set(packed::ExportedBlock::from(exported))
+        .build())
+}
+
+pub fn end_response(tip_number: u64) -> BlockCatchUpResponse {
+    let end = BlockCatchUpEnd::new_builder()
+        .tip_number(tip_number.pack())
+        .build();
+    BlockCatchUpResponse::new_builder().set(end).build()
+}
+
+pub fn error_response(err: &anyhow::Error) -> BlockCatchUpResponse {
+    let message = format!("{:#}", err);
+    let error = BlockCatchUpError::new_builder()
+        .message(message.as_bytes().pack())
+        .build();
+    BlockCatchUpResponse::new_builder().set(error).build()
+}
+
+/// Parse a decompressed response. `BlockCatchUpError` is returned as an error.
+pub fn parse_response(msg: &[u8]) -> Result<CatchUpMessage> {
+    let response = BlockCatchUpResponseReader::from_slice(msg)?;
+    let msg = match response.to_enum() {
+        BlockCatchUpResponseUnionReader::ExportedBlock(block) => {
+            CatchUpMessage::Block(Box::new(block.to_entity().into()))
+        }
+        BlockCatchUpResponseUnionReader::BlockCatchUpEnd(end) => CatchUpMessage::End {
+            tip_number: end.tip_number().unpack(),
+        },
+        BlockCatchUpResponseUnionReader::BlockCatchUpError(error) => {
+            let message = String::from_utf8_lossy(error.message().raw_data());
+            return Err(anyhow!("server error: {}", message));
+        }
+    };
+    Ok(msg)
+}
+
+pub struct BlockCatchUpStream {
+    id: SessionId,
+    control: ServiceAsyncControl,
+    read_part: SubstreamReadPart,
+    decoder: StreamDecoder,
+}
+
+impl BlockCatchUpStream {
+    /// Request blocks [start, start + count). The server may send less blocks
+    /// than requested.
+    pub async fn request(&mut self, start: u64, count: u64) -> Result<()> {
+        let request = BlockCatchUpRequest::new_builder()
+            .start(start.pack())
+            .count(count.pack())
+            .build();
+        self.control
+            .send_message_to(self.id, P2P_BLOCK_CATCH_UP_PROTOCOL, request.as_bytes())
+            .await?;
+        Ok(())
+    }
+
+    /// Receive the next message, fails if it doesn't arrive within
+    /// `CATCH_UP_MESSAGE_TIMEOUT`.
+    pub async fn recv(&mut self) -> Result<CatchUpMessage> {
+        let msg = tokio::time::timeout(CATCH_UP_MESSAGE_TIMEOUT, self.read_part.try_next())
+            .await
+            .context("receive message timeout")??
+            .context("unexpected end of stream")?;
+        let msg = self.decoder.decode(&msg)?;
+        parse_response(&msg)
+    }
+
+    pub async fn disconnect(&mut self) -> Result<()> {
+        self.control.disconnect(self.id).await?;
+        Ok(())
+    }
+}
+
+/// The p2p protocol just sends the catch-up stream to the inbox.
+pub fn block_catch_up_client_protocol(
+    stream_inbox: Arc<std::sync::Mutex<Option<BlockCatchUpStream>>>,
+) -> ProtocolMeta {
+    let spawn = FnSpawn(move |context, control, read_part| {
+        let stream = BlockCatchUpStream {
+            id: context.id,
+            control: control.clone(),
+            read_part,
+            decoder: StreamDecoder::new(),
+        };
+        *stream_inbox.lock().unwrap() = Some(stream);
+    });
+    MetaBuilder::new()
+        .name(|_| P2P_BLOCK_CATCH_UP_PROTOCOL_NAME.into())
+        .id(P2P_BLOCK_CATCH_UP_PROTOCOL)
+        .protocol_spawn(spawn)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tentacle::secio::PeerId;
+
+    use super::{RateLimiter, MIN_REQUEST_INTERVAL};
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::default();
+        let peer = Some(PeerId::random());
+        let other = Some(PeerId::random());
+        let now = Instant::now();
+
+        assert_eq!(limiter.delay(peer.clone(), now), Duration::ZERO);
+        assert_eq!(limiter.delay(peer.clone(), now), MIN_REQUEST_INTERVAL);
+        assert_eq!(limiter.delay(peer.clone(), now), MIN_REQUEST_INTERVAL * 2);
+        // Other peers are not affected.
+        assert_eq!(limiter.delay(other, now), Duration::ZERO);
+
+        // Requests after the interval are not delayed.
+        let later = now + MIN_REQUEST_INTERVAL * 3;
+        assert_eq!(limiter.delay(peer, later), Duration::ZERO);
+        assert_eq!(limiter.next_request_at.len(), 1);
+    }
+}
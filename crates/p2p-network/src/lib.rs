@@ -22,6 +22,8 @@ use tentacle::{
     ProtocolId, SubstreamReadPart,
 };
 
+pub mod block_catch_up;
+
 const RECONNECT_BASE_DURATION: Duration = Duration::from_secs(2);
 
 /// Wrapper for tentacle Service. Automatically reconnect dial addresses.
@@ -225,3 +227,7 @@ impl<F: Fn(Arc<SessionContext>, &ServiceAsyncControl, SubstreamReadPart)> Protoc
 // blocks and mem block transactions.
 pub const P2P_SYNC_PROTOCOL: ProtocolId = ProtocolId::new(3);
 pub const P2P_SYNC_PROTOCOL_NAME: &str = "/p2p/sync";
+
+// Request historical blocks of a range.
+pub const P2P_BLOCK_CATCH_UP_PROTOCOL: ProtocolId = ProtocolId::new(4);
+pub const P2P_BLOCK_CATCH_UP_PROTOCOL_NAME: &str = "/p2p/block-catch-up";
@@ -5,7 +5,10 @@ use ckb_types::bytes::Bytes;
 use gw_chain::chain::Chain;
 use gw_config::{Config, StoreConfig};
 use gw_generator::{
-    account_lock_manage::{secp256k1::Secp256k1Eth, AccountLockManage},
+    account_lock_manage::{
+        secp256k1::Secp256k1Eth, secp256k1_blake160::Secp256k1Blake160,
+        secp256r1_webauthn::Secp256r1WebAuthn, AccountLockManage,
+    },
     backend_manage::BackendManage,
     genesis::init_genesis,
     Generator,
@@ -44,6 +47,9 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
         path: to_db_store,
         options_file: config.store.options_file.clone(),
         cache_size: config.store.cache_size,
+        index_account_history: config.store.index_account_history,
+        state_history: config.store.state_history.clone(),
+        secondary: None,
     };
     let local_store = Store::open(&store_config, COLUMNS).unwrap();
     let rollup_type_script = {
@@ -110,6 +116,26 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
             eth_lock_script_type_hash.hash().unpack(),
             Arc::new(Secp256k1Eth::default()),
         );
+        // secp256k1-blake160 EoA is optional
+        if let Some(ckb_lock_script_type_hash) = allowed_eoa_type_hashes
+            .iter()
+            .find(|th| th.type_().to_entity() == AllowedEoaType::Secp256k1Blake160.into())
+        {
+            account_lock_manage.register_lock_algorithm(
+                ckb_lock_script_type_hash.hash().unpack(),
+                Arc::new(Secp256k1Blake160::default()),
+            );
+        }
+        // WebAuthn EoA is optional
+        if let Some(webauthn_lock_script_type_hash) = allowed_eoa_type_hashes
+            .iter()
+            .find(|th| th.type_().to_entity() == AllowedEoaType::WebAuthn.into())
+        {
+            account_lock_manage.register_lock_algorithm(
+                webauthn_lock_script_type_hash.hash().unpack(),
+                Arc::new(Secp256r1WebAuthn::default()),
+            );
+        }
         Arc::new(Generator::new(
             backend_manage,
             account_lock_manage,
@@ -132,6 +158,9 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
             path: from_db_store,
             options_file: config.store.options_file.clone(),
             cache_size: config.store.cache_size,
+            index_account_history: false,
+            state_history: Default::default(),
+            secondary: None,
         };
         Store::open(&store_config, from_db_columns).unwrap()
     };
@@ -16,6 +16,7 @@ impl GWClient {
         script_hash: JsonH256,
         registry_id: Uint32,
     ) -> Result<Option<RegistryAddress>>;
+    pub async fn gw_create_checkpoint(&self, name: String) -> Result<()>;
 }
 
 impl GWClient {
@@ -0,0 +1,157 @@
+//! L1 queries and transaction submission used by block producer components.
+//!
+//! [`RPCClient`] implements it with the CKB node and indexer RPCs. The
+//! `mock_l1_client` module, enabled by the `mock` feature, has an in-process
+//! implementation for tests.
+
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use gw_jsonrpc_types::ckb_jsonrpc_types::{JsonBytes, Status, Uint32};
+use gw_types::{
+    h256::H256,
+    offchain::{CellInfo, CellWithStatus},
+    packed::{Block, NumberHash, OutPoint, Transaction},
+};
+
+use crate::{
+    indexer_types::{Cell, Order, Pagination, SearchKey, Tx},
+    rpc_client::RPCClient,
+};
+
+#[async_trait]
+pub trait L1Client: Send + Sync {
+    /// Tip of the (indexed) L1 chain.
+    async fn get_tip(&self) -> Result<NumberHash>;
+
+    async fn get_transaction_status(&self, tx_hash: H256) -> Result<Option<Status>>;
+
+    /// Number of the block that committed the transaction.
+    async fn get_transaction_block_number(&self, tx_hash: H256) -> Result<Option<u64>>;
+
+    async fn get_packed_transaction(&self, tx_hash: H256) -> Result<Option<Transaction>>;
+
+    async fn get_cell(&self, out_point: OutPoint) -> Result<Option<CellWithStatus>>;
+
+    async fn get_block_median_time(&self, block_hash: H256) -> Result<Option<Duration>>;
+
+    async fn get_block_by_number(&self, number: u64) -> Result<Option<Block>>;
+
+    /// Live cells matching the search key, same as the indexer `get_cells` RPC.
+    async fn get_cells(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Cell>>;
+
+    /// Committed transactions matching the search key, same as the indexer
+    /// `get_transactions` RPC.
+    async fn get_transactions(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>>;
+
+    async fn query_rollup_cell(&self) -> Result<Option<CellInfo>>;
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<H256>;
+
+    /// Returns cycles.
+    async fn dry_run_transaction(&self, tx: &Transaction) -> Result<u64>;
+
+    async fn wait_tx_proposed(&self, tx_hash: H256) -> Result<()> {
+        loop {
+            match self.get_transaction_status(tx_hash).await? {
+                Some(Status::Proposed) | Some(Status::Committed) => return Ok(()),
+                Some(Status::Rejected) => bail!("rejected"),
+                _ => (),
+            }
+
+            tokio::time::sleep(Duration::new(3, 0)).await;
+        }
+    }
+
+    async fn wait_tx_committed(&self, tx_hash: H256) -> Result<()> {
+        loop {
+            match self.get_transaction_status(tx_hash).await? {
+                Some(Status::Committed) => return Ok(()),
+                Some(Status::Rejected) => bail!("rejected"),
+                _ => (),
+            }
+
+            tokio::time::sleep(Duration::new(3, 0)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl L1Client for RPCClient {
+    async fn get_tip(&self) -> Result<NumberHash> {
+        RPCClient::get_tip(self).await
+    }
+
+    async fn get_transaction_status(&self, tx_hash: H256) -> Result<Option<Status>> {
+        self.ckb.get_transaction_status(tx_hash).await
+    }
+
+    async fn get_transaction_block_number(&self, tx_hash: H256) -> Result<Option<u64>> {
+        self.ckb.get_transaction_block_number(tx_hash).await
+    }
+
+    async fn get_packed_transaction(&self, tx_hash: H256) -> Result<Option<Transaction>> {
+        self.ckb.get_packed_transaction(tx_hash).await
+    }
+
+    async fn get_cell(&self, out_point: OutPoint) -> Result<Option<CellWithStatus>> {
+        RPCClient::get_cell(self, out_point).await
+    }
+
+    async fn get_block_median_time(&self, block_hash: H256) -> Result<Option<Duration>> {
+        RPCClient::get_block_median_time(self, block_hash).await
+    }
+
+    async fn get_block_by_number(&self, number: u64) -> Result<Option<Block>> {
+        RPCClient::get_block_by_number(self, number).await
+    }
+
+    async fn get_cells(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Cell>> {
+        self.indexer
+            .get_cells(search_key, order, limit, cursor)
+            .await
+    }
+
+    async fn get_transactions(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>> {
+        self.indexer
+            .get_transactions(search_key, order, limit, cursor)
+            .await
+    }
+
+    async fn query_rollup_cell(&self) -> Result<Option<CellInfo>> {
+        RPCClient::query_rollup_cell(self).await
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<H256> {
+        RPCClient::send_transaction(self, tx).await
+    }
+
+    async fn dry_run_transaction(&self, tx: &Transaction) -> Result<u64> {
+        RPCClient::dry_run_transaction(self, tx).await
+    }
+}
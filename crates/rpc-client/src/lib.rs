@@ -4,6 +4,9 @@ pub mod error;
 pub mod gw_client;
 pub mod indexer_client;
 pub mod indexer_types;
+pub mod l1_client;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_l1_client;
 pub mod rpc_client;
 mod utils;
 pub mod withdrawal;
@@ -0,0 +1,600 @@
+#![allow(clippy::mutable_key_type)]
+
+//! In-process L1 chain for tests.
+//!
+//! Keeps a fake cell set built from committed transactions. Transactions sent
+//! with `send_transaction` stay in the pool until `commit_block` is called.
+//! `rollback_to` forks the chain: the reverted transactions go back to the
+//! pool, like CKB does.
+//!
+//! Only cell resolving is checked, scripts are NOT run. Resolving errors are
+//! returned as JSON-RPC errors with the CKB error codes. The median time of a
+//! block is its own timestamp.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use gw_common::blake2b::new_blake2b;
+use gw_jsonrpc_types::ckb_jsonrpc_types::{JsonBytes, Status, Uint32, Uint64};
+use gw_types::{
+    bytes::Bytes,
+    h256::H256,
+    offchain::{CellInfo, CellStatus, CellWithStatus},
+    packed::{
+        Block, CellOutput, Header, NumberHash, OutPoint, RawHeader, Script, Transaction,
+        TransactionVec,
+    },
+    prelude::*,
+};
+
+use crate::{
+    error::CkbRpcError,
+    indexer_types::{Cell, IOType, Order, Pagination, ScriptType, SearchKey, Tx},
+    l1_client::L1Client,
+};
+
+/// (block number, tx index, io type, io index). Also used as the cursor.
+type IndexKey = (u64, u32, u8, u32);
+
+const IO_TYPE_INPUT: u8 = 0;
+const IO_TYPE_OUTPUT: u8 = 1;
+
+struct MockBlock {
+    number: u64,
+    hash: H256,
+    parent_hash: H256,
+    /// Milliseconds.
+    timestamp: u64,
+    transactions: Vec<Transaction>,
+}
+
+struct MockCell {
+    out_point: OutPoint,
+    output: CellOutput,
+    data: Bytes,
+    live: bool,
+}
+
+#[derive(Default)]
+struct MockChain {
+    blocks: Vec<MockBlock>,
+    pool: Vec<Transaction>,
+    rejected: HashSet<H256>,
+    /// Incremented on every fork so that blocks at the same height get
+    /// different hashes.
+    forks: u64,
+    // Indexes of the blocks.
+    cells: BTreeMap<IndexKey, MockCell>,
+    out_points: HashMap<OutPoint, IndexKey>,
+    committed: HashMap<H256, (u64, u32)>,
+}
+
+pub struct MockL1Client {
+    rollup_type_script: Script,
+    chain: Mutex<MockChain>,
+}
+
+impl MockL1Client {
+    /// Create a chain with only an empty genesis block.
+    pub fn new(rollup_type_script: Script) -> Self {
+        let mut chain = MockChain::default();
+        let genesis_hash = chain.block_hash(&H256::default(), 0, &[]);
+        chain.blocks.push(MockBlock {
+            number: 0,
+            hash: genesis_hash,
+            parent_hash: H256::default(),
+            timestamp: now_millis(),
+            transactions: Vec::new(),
+        });
+        Self {
+            rollup_type_script,
+            chain: Mutex::new(chain),
+        }
+    }
+
+    /// Commit pool transactions in a new block. Transactions that can't be
+    /// resolved any more, e.g. double spending ones, are rejected.
+    pub fn commit_block(&self) -> NumberHash {
+        let mut chain = self.chain.lock().unwrap();
+        let tip = chain.blocks.last().expect("tip");
+        let (number, parent_hash) = (tip.number + 1, tip.hash);
+        let timestamp = now_millis().max(tip.timestamp + 1);
+
+        let mut transactions = Vec::new();
+        for tx in std::mem::take(&mut chain.pool) {
+            if chain.resolve(&tx, false).is_err() {
+                chain.rejected.insert(tx.hash());
+                continue;
+            }
+            chain.apply(&tx, number, transactions.len() as u32);
+            transactions.push(tx);
+        }
+        let hash = chain.block_hash(&parent_hash, number, &transactions);
+        chain.blocks.push(MockBlock {
+            number,
+            hash,
+            parent_hash,
+            timestamp,
+            transactions,
+        });
+
+        NumberHash::new_builder()
+            .number(number.pack())
+            .block_hash(hash.pack())
+            .build()
+    }
+
+    /// Fork the chain: revert blocks after `number`. Transactions in reverted
+    /// blocks go back to the pool.
+    pub fn rollback_to(&self, number: u64) -> Result<()> {
+        let mut chain = self.chain.lock().unwrap();
+        let tip_number = chain.blocks.last().expect("tip").number;
+        if number > tip_number {
+            bail!("rollback to {} beyond tip {}", number, tip_number);
+        }
+
+        let reverted = chain.blocks.split_off(number as usize + 1);
+        let mut pool: Vec<Transaction> = reverted
+            .into_iter()
+            .flat_map(|block| block.transactions)
+            .collect();
+        pool.append(&mut chain.pool);
+        chain.pool = pool;
+        chain.forks += 1;
+        chain.rebuild_indexes();
+        Ok(())
+    }
+
+    /// Remove a transaction from the pool, e.g. to simulate it being
+    /// replaced on L1. Returns false if it's not in the pool.
+    pub fn drop_pending(&self, tx_hash: &H256) -> bool {
+        let mut chain = self.chain.lock().unwrap();
+        let len = chain.pool.len();
+        chain.pool.retain(|tx| &tx.hash() != tx_hash);
+        chain.pool.len() != len
+    }
+}
+
+impl MockChain {
+    fn block_hash(&self, parent_hash: &H256, number: u64, txs: &[Transaction]) -> H256 {
+        let mut hasher = new_blake2b();
+        hasher.update(parent_hash);
+        hasher.update(&number.to_le_bytes());
+        hasher.update(&self.forks.to_le_bytes());
+        for tx in txs {
+            hasher.update(&tx.hash());
+        }
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash
+    }
+
+    fn rebuild_indexes(&mut self) {
+        self.cells.clear();
+        self.out_points.clear();
+        self.committed.clear();
+
+        let blocks = std::mem::take(&mut self.blocks);
+        for block in blocks.iter() {
+            for (tx_index, tx) in block.transactions.iter().enumerate() {
+                self.apply(tx, block.number, tx_index as u32);
+            }
+        }
+        self.blocks = blocks;
+    }
+
+    fn apply(&mut self, tx: &Transaction, block_number: u64, tx_index: u32) {
+        let tx_hash = tx.hash();
+        for input in tx.raw().inputs().into_iter() {
+            if let Some(key) = self.out_points.get(&input.previous_output()) {
+                if let Some(cell) = self.cells.get_mut(key) {
+                    cell.live = false;
+                }
+            }
+        }
+        let outputs = tx.raw().outputs().into_iter();
+        for (index, (output, data)) in outputs.zip(tx.raw().outputs_data()).enumerate() {
+            let out_point = OutPoint::new_builder()
+                .tx_hash(tx_hash.pack())
+                .index((index as u32).pack())
+                .build();
+            let key = (block_number, tx_index, IO_TYPE_OUTPUT, index as u32);
+            self.out_points.insert(out_point.clone(), key);
+            self.cells.insert(
+                key,
+                MockCell {
+                    out_point,
+                    output,
+                    data: data.unpack(),
+                    live: true,
+                },
+            );
+        }
+        self.committed.insert(tx_hash, (block_number, tx_index));
+    }
+
+    /// Check that inputs and cell deps are live. With `with_pool`, outputs
+    /// of pool transactions are live and inputs of pool transactions are
+    /// dead.
+    fn resolve(&self, tx: &Transaction, with_pool: bool) -> Result<()> {
+        let tx_hash = tx.hash();
+        if self.committed.contains_key(&tx_hash)
+            || (with_pool && self.pool.iter().any(|p| p.hash() == tx_hash))
+        {
+            return Err(rpc_error(
+                CkbRpcError::PoolRejectedDuplicatedTransaction,
+                format!("PoolRejectedDuplicatedTransaction: {}", tx_hash.pack()),
+            ));
+        }
+
+        let spent_by_pool: HashSet<OutPoint> = if with_pool {
+            self.pool
+                .iter()
+                .flat_map(|p| p.raw().inputs().into_iter())
+                .map(|input| input.previous_output())
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        let is_live = |out_point: &OutPoint| {
+            if spent_by_pool.contains(out_point) {
+                return false;
+            }
+            if let Some(key) = self.out_points.get(out_point) {
+                return self.cells.get(key).map_or(false, |c| c.live);
+            }
+            with_pool
+                && self.pool.iter().any(|p| {
+                    let index: u32 = out_point.index().unpack();
+                    p.hash().as_slice() == out_point.tx_hash().as_slice()
+                        && (index as usize) < p.raw().outputs().len()
+                })
+        };
+
+        let mut inputs = HashSet::new();
+        for input in tx.raw().inputs().into_iter() {
+            let out_point = input.previous_output();
+            if !is_live(&out_point) || !inputs.insert(out_point.clone()) {
+                return Err(resolve_error(&out_point));
+            }
+        }
+        for dep in tx.raw().cell_deps().into_iter() {
+            let out_point = dep.out_point();
+            if !is_live(&out_point) {
+                return Err(resolve_error(&out_point));
+            }
+        }
+        Ok(())
+    }
+
+    /// Inputs and outputs of committed transactions, in the order of
+    /// `IndexKey`.
+    fn io_cells(&self) -> Vec<(IndexKey, H256, &MockCell)> {
+        let mut io = Vec::new();
+        for block in self.blocks.iter() {
+            for (tx_index, tx) in block.transactions.iter().enumerate() {
+                let tx_hash = tx.hash();
+                let tx_index = tx_index as u32;
+                for (index, input) in tx.raw().inputs().into_iter().enumerate() {
+                    let cell = self
+                        .out_points
+                        .get(&input.previous_output())
+                        .and_then(|key| self.cells.get(key));
+                    if let Some(cell) = cell {
+                        let key = (block.number, tx_index, IO_TYPE_INPUT, index as u32);
+                        io.push((key, tx_hash, cell));
+                    }
+                }
+                let outputs_len = tx.raw().outputs().len();
+                for index in 0..outputs_len as u32 {
+                    let key = (block.number, tx_index, IO_TYPE_OUTPUT, index);
+                    if let Some(cell) = self.cells.get(&key) {
+                        io.push((key, tx_hash, cell));
+                    }
+                }
+            }
+        }
+        io
+    }
+}
+
+fn now_millis() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH);
+    now.expect("timestamp").as_millis() as u64
+}
+
+fn rpc_error(code: CkbRpcError, message: String) -> anyhow::Error {
+    jsonrpc_core::Error {
+        code: jsonrpc_core::ErrorCode::ServerError(code as i64),
+        message,
+        data: None,
+    }
+    .into()
+}
+
+fn resolve_error(out_point: &OutPoint) -> anyhow::Error {
+    rpc_error(
+        CkbRpcError::TransactionFailedToResolve,
+        format!(
+            "TransactionFailedToResolve: Resolve failed Dead({})",
+            out_point
+        ),
+    )
+}
+
+fn script_prefix_matches(prefix: &Script, script: &Script) -> bool {
+    prefix.code_hash().as_slice() == script.code_hash().as_slice()
+        && prefix.hash_type() == script.hash_type()
+        && script
+            .args()
+            .raw_data()
+            .starts_with(&prefix.args().raw_data())
+}
+
+fn in_range(range: &Option<[Uint64; 2]>, value: u64) -> bool {
+    match range {
+        Some([start, end]) => (start.value()..end.value()).contains(&value),
+        None => true,
+    }
+}
+
+fn search_key_matches(search_key: &SearchKey, cell: &MockCell, block_number: u64) -> bool {
+    let prefix: Script = search_key.script.clone().into();
+    let (script, other) = match search_key.script_type {
+        ScriptType::Lock => (Some(cell.output.lock()), cell.output.type_().to_opt()),
+        ScriptType::Type => (cell.output.type_().to_opt(), Some(cell.output.lock())),
+    };
+    if !script.map_or(false, |s| script_prefix_matches(&prefix, &s)) {
+        return false;
+    }
+    let filter = match search_key.filter {
+        Some(ref filter) => filter,
+        None => return true,
+    };
+    if let Some(ref filter_script) = filter.script {
+        let filter_script: Script = filter_script.clone().into();
+        if !other.map_or(false, |s| script_prefix_matches(&filter_script, &s)) {
+            return false;
+        }
+    }
+    let capacity: u64 = cell.output.capacity().unpack();
+    in_range(&filter.output_data_len_range, cell.data.len() as u64)
+        && in_range(&filter.output_capacity_range, capacity)
+        && in_range(&filter.block_range, block_number)
+}
+
+fn encode_cursor(key: &IndexKey) -> JsonBytes {
+    let mut buf = Vec::with_capacity(17);
+    buf.extend_from_slice(&key.0.to_be_bytes());
+    buf.extend_from_slice(&key.1.to_be_bytes());
+    buf.push(key.2);
+    buf.extend_from_slice(&key.3.to_be_bytes());
+    JsonBytes::from_vec(buf)
+}
+
+fn decode_cursor(cursor: &Option<JsonBytes>) -> Result<Option<IndexKey>> {
+    let cursor = match cursor {
+        Some(cursor) if !cursor.is_empty() => cursor.as_bytes(),
+        _ => return Ok(None),
+    };
+    if cursor.len() != 17 {
+        bail!("invalid cursor");
+    }
+    Ok(Some((
+        u64::from_be_bytes(cursor[..8].try_into()?),
+        u32::from_be_bytes(cursor[8..12].try_into()?),
+        cursor[12],
+        u32::from_be_bytes(cursor[13..].try_into()?),
+    )))
+}
+
+/// Sort, skip to the cursor and limit.
+fn paginate<T>(
+    mut objects: Vec<(IndexKey, T)>,
+    order: &Order,
+    limit: Uint32,
+    cursor: &Option<JsonBytes>,
+) -> Result<Pagination<T>> {
+    let cursor = decode_cursor(cursor)?;
+    objects.sort_by_key(|(key, _)| *key);
+    if let Order::Desc = order {
+        objects.reverse();
+    }
+    let objects: Vec<_> = objects
+        .into_iter()
+        .filter(|(key, _)| match (cursor, order) {
+            (None, _) => true,
+            (Some(cursor), Order::Asc) => *key > cursor,
+            (Some(cursor), Order::Desc) => *key < cursor,
+        })
+        .take(limit.value() as usize)
+        .collect();
+    let last_cursor = match objects.last() {
+        Some((key, _)) => encode_cursor(key),
+        None => JsonBytes::default(),
+    };
+    Ok(Pagination {
+        objects: objects.into_iter().map(|(_, o)| o).collect(),
+        last_cursor,
+    })
+}
+
+#[async_trait]
+impl L1Client for MockL1Client {
+    async fn get_tip(&self) -> Result<NumberHash> {
+        let chain = self.chain.lock().unwrap();
+        let tip = chain.blocks.last().expect("tip");
+        Ok(NumberHash::new_builder()
+            .number(tip.number.pack())
+            .block_hash(tip.hash.pack())
+            .build())
+    }
+
+    async fn get_transaction_status(&self, tx_hash: H256) -> Result<Option<Status>> {
+        let chain = self.chain.lock().unwrap();
+        let status = if chain.committed.contains_key(&tx_hash) {
+            Some(Status::Committed)
+        } else if chain.pool.iter().any(|tx| tx.hash() == tx_hash) {
+            Some(Status::Pending)
+        } else if chain.rejected.contains(&tx_hash) {
+            Some(Status::Rejected)
+        } else {
+            None
+        };
+        Ok(status)
+    }
+
+    async fn get_transaction_block_number(&self, tx_hash: H256) -> Result<Option<u64>> {
+        let chain = self.chain.lock().unwrap();
+        Ok(chain.committed.get(&tx_hash).map(|(number, _)| *number))
+    }
+
+    async fn get_packed_transaction(&self, tx_hash: H256) -> Result<Option<Transaction>> {
+        let chain = self.chain.lock().unwrap();
+        if let Some((number, tx_index)) = chain.committed.get(&tx_hash) {
+            let block = &chain.blocks[*number as usize];
+            return Ok(block.transactions.get(*tx_index as usize).cloned());
+        }
+        Ok(chain.pool.iter().find(|tx| tx.hash() == tx_hash).cloned())
+    }
+
+    async fn get_cell(&self, out_point: OutPoint) -> Result<Option<CellWithStatus>> {
+        let chain = self.chain.lock().unwrap();
+        let cell = chain
+            .out_points
+            .get(&out_point)
+            .and_then(|key| chain.cells.get(key));
+        let cell_with_status = match cell {
+            Some(cell) if cell.live => CellWithStatus {
+                cell: Some(CellInfo {
+                    out_point,
+                    output: cell.output.clone(),
+                    data: cell.data.clone(),
+                }),
+                status: CellStatus::Live,
+            },
+            Some(_) => CellWithStatus {
+                cell: None,
+                status: CellStatus::Dead,
+            },
+            None => CellWithStatus {
+                cell: None,
+                status: CellStatus::Unknown,
+            },
+        };
+        Ok(Some(cell_with_status))
+    }
+
+    async fn get_block_median_time(&self, block_hash: H256) -> Result<Option<Duration>> {
+        let chain = self.chain.lock().unwrap();
+        let block = chain.blocks.iter().find(|b| b.hash == block_hash);
+        Ok(block.map(|b| Duration::from_millis(b.timestamp)))
+    }
+
+    async fn get_block_by_number(&self, number: u64) -> Result<Option<Block>> {
+        let chain = self.chain.lock().unwrap();
+        let block = match chain.blocks.get(number as usize) {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let raw = RawHeader::new_builder()
+            .number(block.number.pack())
+            .timestamp(block.timestamp.pack())
+            .parent_hash(block.parent_hash.pack())
+            .build();
+        let transactions = TransactionVec::new_builder()
+            .extend(block.transactions.iter().cloned())
+            .build();
+        Ok(Some(
+            Block::new_builder()
+                .header(Header::new_builder().raw(raw).build())
+                .transactions(transactions)
+                .build(),
+        ))
+    }
+
+    async fn get_cells(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Cell>> {
+        let chain = self.chain.lock().unwrap();
+        let cells = chain
+            .cells
+            .iter()
+            .filter(|(key, cell)| cell.live && search_key_matches(search_key, cell, key.0))
+            .map(|(key, cell)| {
+                let object = Cell {
+                    output: cell.output.clone().into(),
+                    output_data: JsonBytes::from_bytes(cell.data.clone()),
+                    out_point: cell.out_point.clone().into(),
+                    block_number: key.0.into(),
+                    tx_index: key.1.into(),
+                };
+                (*key, object)
+            })
+            .collect();
+        paginate(cells, order, limit, cursor)
+    }
+
+    async fn get_transactions(
+        &self,
+        search_key: &SearchKey,
+        order: &Order,
+        limit: Uint32,
+        cursor: &Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>> {
+        let chain = self.chain.lock().unwrap();
+        let txs = chain
+            .io_cells()
+            .into_iter()
+            .filter(|(key, _, cell)| search_key_matches(search_key, cell, key.0))
+            .map(|(key, tx_hash, _)| {
+                let io_type = match key.2 {
+                    IO_TYPE_INPUT => IOType::Input,
+                    _ => IOType::Output,
+                };
+                let object = Tx {
+                    tx_hash: tx_hash.into(),
+                    block_number: key.0.into(),
+                    tx_index: key.1.into(),
+                    io_index: key.3.into(),
+                    io_type,
+                };
+                (key, object)
+            })
+            .collect();
+        paginate(txs, order, limit, cursor)
+    }
+
+    async fn query_rollup_cell(&self) -> Result<Option<CellInfo>> {
+        let search_key = SearchKey::with_type(self.rollup_type_script.clone());
+        let mut cells = self
+            .get_cells(&search_key, &Order::Desc, 1.into(), &None)
+            .await?;
+        Ok(cells.objects.pop().map(|cell| cell.info()))
+    }
+
+    async fn send_transaction(&self, tx: &Transaction) -> Result<H256> {
+        let mut chain = self.chain.lock().unwrap();
+        chain.resolve(tx, true)?;
+        let tx_hash = tx.hash();
+        chain.rejected.remove(&tx_hash);
+        chain.pool.push(tx.clone());
+        Ok(tx_hash)
+    }
+
+    async fn dry_run_transaction(&self, tx: &Transaction) -> Result<u64> {
+        let chain = self.chain.lock().unwrap();
+        chain.resolve(tx, true).context("dry run")?;
+        // Scripts are not run.
+        Ok(0)
+    }
+}
@@ -2,7 +2,10 @@ use std::{convert::TryInto, sync::Arc, time::Instant};
 
 use anyhow::{anyhow, Result};
 use ckb_fixed_hash::H256 as JsonH256;
-use gw_jsonrpc_types::{ckb_jsonrpc_types::Uint64, debug::DebugRunResult};
+use gw_jsonrpc_types::{
+    ckb_jsonrpc_types::Uint64,
+    debug::{DebugRunResult, DebugTracer},
+};
 use gw_store::{
     chain_view::ChainView,
     state::{
@@ -22,6 +25,7 @@ pub(crate) async fn replay_transaction(
     ctx: Arc<Registry>,
     tx_hash: JsonH256,
     max_cycles: Option<Uint64>,
+    tracer: Option<DebugTracer>,
 ) -> Result<Option<DebugRunResult>> {
     let tx_hash = to_h256(tx_hash);
     let max_cycles: Option<u64> = max_cycles.map(Into::into);
@@ -84,14 +88,26 @@ pub(crate) async fn replay_transaction(
         let tx = block.transactions().get(tx_index as usize).unwrap();
         let raw_tx = tx.raw();
         let t = Instant::now();
-        let run_result = ctx.debug_generator.execute_transaction(
-            &chain_view,
-            &mut hist_state,
-            &block_info,
-            &raw_tx,
-            max_cycles,
-            None,
-        )?;
+        let run_result = match tracer {
+            Some(DebugTracer::Syscall) => {
+                ctx.debug_generator.execute_transaction_with_syscall_trace(
+                    &chain_view,
+                    &mut hist_state,
+                    &block_info,
+                    &raw_tx,
+                    max_cycles,
+                    None,
+                )?
+            }
+            None => ctx.debug_generator.execute_transaction(
+                &chain_view,
+                &mut hist_state,
+                &block_info,
+                &raw_tx,
+                max_cycles,
+                None,
+            )?,
+        };
         let execution_time = t.elapsed();
 
         // finalise
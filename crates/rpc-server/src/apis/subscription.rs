@@ -0,0 +1,139 @@
+//! `gw_subscribe` / `gw_unsubscribe` over WebSocket.
+//!
+//! Supported subscriptions:
+//!
+//! - `["newHeads"]`: new L2 blocks on the main chain.
+//! - `["pendingTransactions"]`: hashes of transactions pushed to the mem pool.
+//! - `["logs", filter]`: logs of committed transactions, see `LogFilter`.
+//!
+//! Reorgs, skipped blocks and dropped notifications are notified with a
+//! `SubscriptionEvent`.
+
+use std::sync::Arc;
+
+use futures::{future, stream::BoxStream, Stream, StreamExt};
+use gw_jsonrpc_types::godwoken::{
+    L2BlockHeaderView, LogFilter, LogItem, LogView, SubscriptionEvent, SubscriptionKind,
+};
+use gw_mem_pool::subscription::{ChainEvent, Event, SubscriptionPublisher};
+use jsonrpc_core::{MetaIoHandler, Params, Value};
+use jsonrpc_utils::pub_sub::{add_pub_sub, PublishMsg, Session};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::utils::to_jsonh256;
+
+pub(crate) fn add_subscription_methods(
+    handler: &mut MetaIoHandler<Option<Session>>,
+    publisher: Arc<SubscriptionPublisher>,
+) {
+    add_pub_sub(
+        handler,
+        "gw_subscribe",
+        "gw_subscription".into(),
+        "gw_unsubscribe",
+        move |params: Params| subscribe(&publisher, params),
+    );
+}
+
+fn subscribe(
+    publisher: &SubscriptionPublisher,
+    params: Params,
+) -> Result<BoxStream<'static, PublishMsg<Value>>, jsonrpc_core::Error> {
+    let mut params: Vec<Value> = params.parse()?;
+    if params.is_empty() || params.len() > 2 {
+        return Err(jsonrpc_core::Error::invalid_params(
+            "expect subscription kind and optional filter",
+        ));
+    }
+    let filter = if params.len() == 2 {
+        params.pop()
+    } else {
+        None
+    };
+    let kind: SubscriptionKind = serde_json::from_value(params.remove(0))
+        .map_err(|err| jsonrpc_core::Error::invalid_params(err.to_string()))?;
+
+    let stream = match kind {
+        SubscriptionKind::NewHeads => chain_stream(publisher.subscribe_new_heads())
+            .map(|event| match event {
+                Ok(block) => to_publish_msg(L2BlockHeaderView::from(block)),
+                Err(event) => to_publish_msg(event),
+            })
+            .boxed(),
+        SubscriptionKind::PendingTransactions => {
+            broadcast_stream(publisher.subscribe_pending_transactions())
+                .map(|event| match event {
+                    Ok(tx_hash) => to_publish_msg(to_jsonh256(tx_hash)),
+                    Err(event) => to_publish_msg(event),
+                })
+                .boxed()
+        }
+        SubscriptionKind::Logs => {
+            let filter: LogFilter = match filter {
+                Some(filter) => serde_json::from_value(filter)
+                    .map_err(|err| jsonrpc_core::Error::invalid_params(err.to_string()))?,
+                None => LogFilter::default(),
+            };
+            chain_stream(publisher.subscribe_logs())
+                .filter_map(move |event| {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(event) => return future::ready(Some(to_publish_msg(event))),
+                    };
+                    let log = LogItem::from(event.log);
+                    let view = filter.matches(&log).then(|| LogView {
+                        block_number: event.block_number.into(),
+                        block_hash: to_jsonh256(event.block_hash),
+                        transaction_hash: to_jsonh256(event.tx_hash),
+                        transaction_index: event.tx_index.into(),
+                        log,
+                    });
+                    future::ready(view.map(to_publish_msg))
+                })
+                .boxed()
+        }
+    };
+    Ok(stream)
+}
+
+fn to_publish_msg<T: serde::Serialize>(value: T) -> PublishMsg<Value> {
+    match serde_json::to_value(value) {
+        Ok(value) => PublishMsg::result(&value),
+        Err(_) => PublishMsg::error(&jsonrpc_core::Error::internal_error()),
+    }
+}
+
+/// Notify lagged subscribers instead of ending the subscription.
+fn broadcast_stream<T: Clone + Send + 'static>(
+    rx: Receiver<T>,
+) -> impl Stream<Item = Result<T, SubscriptionEvent>> {
+    futures::stream::unfold(rx, |mut rx| async move {
+        match rx.recv().await {
+            Ok(item) => Some((Ok(item), rx)),
+            Err(RecvError::Lagged(n)) => {
+                log::warn!("subscription lagged, skipped {} messages", n);
+                let event = SubscriptionEvent::Lagged { skipped: n.into() };
+                Some((Err(event), rx))
+            }
+            Err(RecvError::Closed) => None,
+        }
+    })
+}
+
+fn chain_stream<T: Clone + Send + 'static>(
+    rx: Receiver<Event<T>>,
+) -> impl Stream<Item = Result<T, SubscriptionEvent>> {
+    broadcast_stream(rx).map(|event| match event? {
+        Event::Item(item) => Ok(item),
+        Event::Chain(ChainEvent::Reorg { from_number }) => Err(SubscriptionEvent::Reorg {
+            from_number: from_number.into(),
+        }),
+        Event::Chain(ChainEvent::Gap {
+            from_number,
+            to_number,
+        }) => Err(SubscriptionEvent::Gap {
+            from_number: from_number.into(),
+            to_number: to_number.into(),
+        }),
+    })
+}
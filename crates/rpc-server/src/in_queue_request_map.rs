@@ -1,17 +1,41 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::{collections::HashMap, sync::Weak};
 
+use gw_jsonrpc_types::godwoken::TransactionDropReason;
 use gw_types::h256::*;
 use gw_types::packed::{L2Transaction, WithdrawalRequestExtra};
+use lru::LruCache;
 
 use crate::registry::Request;
 
+/// Max number of dropped request hashes to remember.
+const DROPPED_CACHE_SIZE: usize = 10_000;
+
+/// A request dropped from the queue or failed to push to the mem pool.
+#[derive(Clone)]
+pub(crate) struct DroppedRequest {
+    pub reason: TransactionDropReason,
+    pub message: String,
+    /// Replacement request hash if it's replaced by fee.
+    pub replaced_by: Option<H256>,
+}
+
 /// Hold in queue transactions and withdrawal requests.
 ///
 /// (For get_transaction and get_withdrawal RPC calls.)
-#[derive(Default)]
 pub struct InQueueRequestMap {
     map: RwLock<HashMap<H256, Request>>,
+    /// Recently dropped requests.
+    dropped: Mutex<LruCache<H256, DroppedRequest>>,
+}
+
+impl Default for InQueueRequestMap {
+    fn default() -> Self {
+        Self {
+            map: Default::default(),
+            dropped: Mutex::new(LruCache::new(DROPPED_CACHE_SIZE)),
+        }
+    }
 }
 
 impl InQueueRequestMap {
@@ -55,6 +79,30 @@ impl InQueueRequestMap {
     pub(crate) fn contains(&self, k: &H256) -> bool {
         self.map.read().unwrap().contains_key(k)
     }
+
+    /// Record that request `k` was replaced by fee by request `by`.
+    pub(crate) fn set_replaced(&self, k: H256, by: H256) {
+        self.set_dropped(
+            k,
+            DroppedRequest {
+                reason: TransactionDropReason::ReplacedByFee,
+                message: format!("replaced by 0x{}", faster_hex::hex_string(&by)),
+                replaced_by: Some(by),
+            },
+        );
+    }
+
+    pub(crate) fn get_replaced_by(&self, k: &H256) -> Option<H256> {
+        self.get_dropped(k)?.replaced_by
+    }
+
+    pub(crate) fn set_dropped(&self, k: H256, dropped: DroppedRequest) {
+        self.dropped.lock().unwrap().put(k, dropped);
+    }
+
+    pub(crate) fn get_dropped(&self, k: &H256) -> Option<DroppedRequest> {
+        self.dropped.lock().unwrap().get(k).cloned()
+    }
 }
 
 /// RAII guard for the request in an InQueueRequestMap.
@@ -3,4 +3,5 @@ pub mod registry;
 pub mod server;
 
 mod apis;
+mod state_override;
 mod utils;
@@ -1,15 +1,18 @@
 use std::{
     convert::TryInto,
     fmt::Display,
-    sync::Arc,
+    path::{Component, Path},
+    sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
-use gw_common::blake2b::new_blake2b;
-use gw_common::builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID};
-use gw_common::state::State;
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_common::state::{
+    build_account_field_key, build_account_key, State, GW_ACCOUNT_NONCE_TYPE,
+    GW_ACCOUNT_SCRIPT_HASH_TYPE,
+};
 use gw_config::{
     BackendForkConfig, ChainConfig, FeeConfig, GaslessTxSupportConfig, MemPoolConfig, NodeMode,
     RPCMethods, RPCRateLimit, RPCServerConfig, SyscallCyclesConfig, SystemTypeScriptConfig,
@@ -23,19 +26,24 @@ use gw_generator::{
 };
 use gw_jsonrpc_types::{
     ckb_jsonrpc_types::{JsonBytes, Script, Uint32, Uint64},
-    debug::DebugRunResult,
+    debug::{DebugRunResult, DebugTracer},
     godwoken::*,
     test_mode::TestModePayload,
     JsonCalcHash,
 };
-use gw_mem_pool::fee::{
-    queue::FeeQueue,
-    types::{FeeEntry, FeeItem, FeeItemKind, FeeItemSender},
+use gw_mem_pool::{
+    fee::{
+        queue::{AddResult, DropReason, FeeQueue},
+        types::{FeeEntry, FeeItem, FeeItemKind, FeeItemSender},
+    },
+    subscription::SubscriptionPublisher,
 };
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
 use gw_rpc_client::rpc_client::RPCClient;
-use gw_store::state::history::history_state::RWConfig;
-use gw_store::state::{BlockStateDB, MemStateDB};
+use gw_smt::smt::SMTH256;
+use gw_store::account_history::{HistoryCursor, HistoryItem};
+use gw_store::state::history::{history_state::RWConfig, pruning::StateHistoryPruned};
+use gw_store::state::{overlay::mem_store::MemStore, BlockStateDB, MemStateDB};
 use gw_store::{
     chain_view::ChainView, mem_pool_state::MemPoolState, traits::chain_store::ChainStore,
     CfMemStat, Store,
@@ -45,7 +53,9 @@ use gw_traits::CodeStore;
 use gw_types::packed::RawL2Transaction;
 use gw_types::{
     bytes::Bytes,
+    core::Timepoint,
     h256::*,
+    offchain::CompatibleFinalizedTimepoint,
     packed::{self, BlockInfo, Byte32, L2Transaction, RollupConfig, WithdrawalRequestExtra},
     prelude::*,
     U256,
@@ -62,7 +72,9 @@ use tokio::sync::{mpsc, Mutex};
 use tracing::instrument;
 
 use crate::apis::debug::replay_transaction;
-use crate::in_queue_request_map::{InQueueRequestHandle, InQueueRequestMap};
+use crate::apis::subscription::add_subscription_methods;
+use crate::in_queue_request_map::{DroppedRequest, InQueueRequestHandle, InQueueRequestMap};
+use crate::state_override::{apply_state_override, check_state_override};
 use crate::utils::{to_h256, to_jsonh256};
 
 static PROFILER_GUARD: Lazy<tokio::sync::Mutex<Option<ProfilerGuard>>> =
@@ -79,8 +91,19 @@ const HEADER_NOT_FOUND_ERR_CODE: i64 = -32000;
 const INVALID_NONCE_ERR_CODE: i64 = -32001;
 const BUSY_ERR_CODE: i64 = -32006;
 const CUSTODIAN_NOT_ENOUGH_CODE: i64 = -32007;
+const STATE_HISTORY_PRUNED_ERR_CODE: i64 = -32009;
+
+const MAX_ACCOUNT_HISTORY_LIMIT: u32 = 1000;
+const MAX_PROOF_KEYS: usize = 256;
+/// Reverting the state costs about the same as applying the blocks.
+const MAX_PROOF_BLOCKS_BEHIND_TIP: u64 = 1000;
+const MAX_SUBMIT_TXS: usize = 1000;
+/// Recent blocks to suggest the fee rate.
+const FEE_SUGGESTION_BLOCKS: u64 = 20;
 
 type SendTransactionRateLimiter = Mutex<LruCache<u32, Instant>>;
+/// Lowest fee rates of recent blocks, by block hash.
+type BlockFeeRateCache = std::sync::Mutex<LruCache<H256, Option<u128>>>;
 
 /// Wrapper of jsonrpc_core::Error that implements From<E> where E: Display.
 pub struct MyRpcError(pub jsonrpc_core::Error);
@@ -130,6 +153,24 @@ fn header_not_found_err() -> MyRpcError {
     rpc_error(HEADER_NOT_FOUND_ERR_CODE, "header not found")
 }
 
+fn invalid_state_override(err: anyhow::Error) -> MyRpcError {
+    rpc_error(
+        ErrorCode::InvalidParams,
+        format!("invalid state override: {:#}", err),
+    )
+}
+
+fn check_state_history_available(db: &impl ChainStore, block_number: u64) -> Result<()> {
+    if db.is_state_history_available(block_number) {
+        return Ok(());
+    }
+    let pruned = StateHistoryPruned {
+        block_number,
+        pruned_number: db.get_state_history_pruned_number(),
+    };
+    Err(rpc_error(STATE_HISTORY_PRUNED_ERR_CODE, pruned.to_string()))
+}
+
 #[rpc]
 #[async_trait]
 pub trait TestModeRpc {
@@ -206,12 +247,15 @@ pub struct Registry {
     pub(crate) chain_config: ChainConfig,
     pub(crate) gasless_tx_support_config: Option<GaslessTxSupportConfig>,
     pub(crate) mem_pool_state: Arc<MemPoolState>,
+    pub(crate) subscription: Option<Arc<SubscriptionPublisher>>,
     pub(crate) in_queue_request_map: Option<Arc<InQueueRequestMap>>,
     pub(crate) polyjuice_sender_recover: Arc<PolyjuiceSenderRecover>,
     pub(crate) debug_generator: Arc<Generator>,
     pub(crate) system_type_script_config: SystemTypeScriptConfig,
     pub(crate) system_type_scripts: SystemTypeScripts,
     pub(crate) fee_config: FeeConfig,
+    pub(crate) fee_queue_stats: Arc<RwLock<FeeQueueStats>>,
+    pub(crate) block_fee_rates: BlockFeeRateCache,
 }
 
 impl Registry {
@@ -237,29 +281,34 @@ impl Registry {
 
         let backend_info = get_backend_info(generator.clone());
 
-        let mem_pool_state = match mem_pool.as_ref() {
+        let (mem_pool_state, subscription) = match mem_pool.as_ref() {
             Some(pool) => {
                 let mem_pool = pool.lock().await;
-                mem_pool.mem_pool_state()
+                (mem_pool.mem_pool_state(), Some(mem_pool.subscription()))
+            }
+            None => {
+                let mem_pool_state = Arc::new(MemPoolState::new(
+                    MemStateDB::from_store(store.get_snapshot()).expect("mem state DB"),
+                    true,
+                ));
+                (mem_pool_state, None)
             }
-            None => Arc::new(MemPoolState::new(
-                MemStateDB::from_store(store.get_snapshot()).expect("mem state DB"),
-                true,
-            )),
         };
         let in_queue_request_map = if matches!(node_mode, NodeMode::FullNode | NodeMode::Test) {
             Some(Arc::new(InQueueRequestMap::default()))
         } else {
             None
         };
+        let fee_queue_stats = Arc::new(RwLock::new(FeeQueueStats::default()));
         let (submit_tx, submit_rx) = mpsc::channel(RequestSubmitter::MAX_CHANNEL_SIZE);
         let polyjuice_sender_recover = Arc::new(polyjuice_sender_recover);
         if let Some(mem_pool) = mem_pool.as_ref().to_owned() {
             let submitter = RequestSubmitter {
                 mem_pool: Arc::clone(mem_pool),
                 submit_rx,
-                queue: FeeQueue::new(),
+                queue: FeeQueue::with_config(&fee_config),
                 queue_order: QueueOrder(0),
+                in_queue_request_map: in_queue_request_map.clone(),
                 fee_config: fee_config.clone(),
                 generator: generator.clone(),
                 mem_pool_state: mem_pool_state.clone(),
@@ -267,6 +316,7 @@ impl Registry {
                 polyjuice_sender_recover: Arc::clone(&polyjuice_sender_recover),
                 mem_pool_config: mem_pool_config.clone(),
                 gasless_tx_support_config: gasless_tx_support_config.clone(),
+                fee_queue_stats: fee_queue_stats.clone(),
             };
             tokio::spawn(submitter.in_background());
         }
@@ -320,10 +370,15 @@ impl Registry {
             gasless_tx_support_config,
             system_type_script_config,
             mem_pool_state,
+            subscription,
             in_queue_request_map,
             polyjuice_sender_recover,
             debug_generator,
             system_type_scripts,
+            fee_queue_stats,
+            block_fee_rates: std::sync::Mutex::new(LruCache::new(
+                2 * FEE_SUGGESTION_BLOCKS as usize,
+            )),
         }
         .into())
     }
@@ -333,6 +388,9 @@ impl Registry {
         if let Some(ref tests_rpc_impl) = self.tests_rpc_impl {
             add_test_mode_rpc_methods(&mut handler, tests_rpc_impl.clone());
         }
+        if let Some(ref subscription) = self.subscription {
+            add_subscription_methods(&mut handler, subscription.clone());
+        }
         add_gw_rpc_methods(&mut handler, self);
         handler
     }
@@ -358,6 +416,38 @@ impl Request {
             Request::Withdrawal(withdrawal) => ckb_types::H256(withdrawal.hash()),
         }
     }
+
+    /// Hash of the request in the fee queue.
+    fn queue_hash(&self) -> H256 {
+        let item = match self {
+            Request::Tx(tx) => FeeItem::Tx(tx.clone()),
+            Request::Withdrawal(withdrawal) => FeeItem::Withdrawal(withdrawal.clone()),
+        };
+        item.hash()
+    }
+
+    fn nonce(&self) -> u32 {
+        match self {
+            Request::Tx(tx) => tx.raw().nonce().unpack(),
+            Request::Withdrawal(withdrawal) => withdrawal.raw().nonce().unpack(),
+        }
+    }
+}
+
+/// Journal the requests submitted to the fee queue in one transaction, so that
+/// they're replayed after a restart.
+fn journal_requests(store: &Store, requests: &[Request]) -> anyhow::Result<()> {
+    let mut db = store.begin_transaction();
+    for request in requests {
+        let hash = request.queue_hash();
+        match request {
+            Request::Tx(tx) => db.insert_queued_transaction(&hash, tx.clone())?,
+            Request::Withdrawal(withdrawal) => {
+                db.insert_queued_withdrawal(&hash, withdrawal.clone())?
+            }
+        }
+    }
+    db.commit()
 }
 
 struct QueueOrder(usize);
@@ -380,6 +470,7 @@ struct RequestSubmitter {
     submit_rx: mpsc::Receiver<(Request, RequestContext)>,
     queue: FeeQueue<RequestContext>,
     queue_order: QueueOrder,
+    in_queue_request_map: Option<Arc<InQueueRequestMap>>,
     fee_config: FeeConfig,
     generator: Arc<Generator>,
     mem_pool_state: Arc<MemPoolState>,
@@ -387,6 +478,24 @@ struct RequestSubmitter {
     polyjuice_sender_recover: Arc<PolyjuiceSenderRecover>,
     mem_pool_config: MemPoolConfig,
     gasless_tx_support_config: Option<GaslessTxSupportConfig>,
+    fee_queue_stats: Arc<RwLock<FeeQueueStats>>,
+}
+
+/// Fee queue stats published by the request submitter.
+#[derive(Default, Clone, Copy)]
+pub(crate) struct FeeQueueStats {
+    len: usize,
+    /// Fee rate of the last entry fits in a mem block.
+    block_fee_rate: Option<u128>,
+}
+
+impl FeeQueueStats {
+    fn new<T: TelemetryContext>(queue: &FeeQueue<T>, block_txs: usize) -> Self {
+        FeeQueueStats {
+            len: queue.len(),
+            block_fee_rate: block_txs.checked_sub(1).and_then(|n| queue.fee_rate_at(n)),
+        }
+    }
 }
 
 #[instrument(skip_all, fields(req_kind = req.kind()))]
@@ -430,11 +539,262 @@ fn req_to_entry(
     }
 }
 
+/// Add a new request to the queue, record it if it replaces a queued request.
+/// Returns false if the request is dropped.
+fn add_to_queue(
+    queue: &mut FeeQueue<RequestContext>,
+    state: &impl State,
+    entry: FeeEntry,
+    ctx: RequestContext,
+    in_queue_request_map: Option<&InQueueRequestMap>,
+) -> bool {
+    let kind = entry.item.kind();
+    let hash = entry.item.hash();
+    let result = match queue.add_checked(state, entry, ctx) {
+        Ok(result) => result,
+        Err(err) => {
+            log::error!("add {:?} {} to queue error: {}", kind, hash.pack(), err);
+            record_dropped(
+                in_queue_request_map,
+                hash,
+                TransactionDropReason::Invalid,
+                err.to_string(),
+            );
+            return false;
+        }
+    };
+    match result {
+        AddResult::Added => true,
+        AddResult::Replaced(replaced) => {
+            log::info!(
+                "{:?} {} replaced {} by fee",
+                kind,
+                hash.pack(),
+                replaced.pack()
+            );
+            if let Some(map) = in_queue_request_map {
+                map.set_replaced(replaced, hash);
+            }
+            true
+        }
+        AddResult::Underpriced(queued) => {
+            log::info!(
+                "{:?} {} is underpriced to replace {}, drop it",
+                kind,
+                hash.pack(),
+                queued.pack()
+            );
+            record_dropped(
+                in_queue_request_map,
+                hash,
+                TransactionDropReason::Underpriced,
+                format!("underpriced to replace {}", queued.pack()),
+            );
+            false
+        }
+        AddResult::SenderQuotaExceeded | AddResult::FutureQuotaExceeded => {
+            log::info!("{:?} {} {:?}, drop it", kind, hash.pack(), result);
+            let (reason, message) = match result {
+                AddResult::SenderQuotaExceeded => (
+                    TransactionDropReason::SenderQuotaExceeded,
+                    "sender has too many queued requests",
+                ),
+                _ => (
+                    TransactionDropReason::FutureQuotaExceeded,
+                    "sender has too many future nonce requests",
+                ),
+            };
+            record_dropped(in_queue_request_map, hash, reason, message.to_string());
+            false
+        }
+    }
+}
+
+fn record_dropped(
+    in_queue_request_map: Option<&InQueueRequestMap>,
+    hash: H256,
+    reason: TransactionDropReason,
+    message: String,
+) {
+    if let Some(map) = in_queue_request_map {
+        let dropped = DroppedRequest {
+            reason,
+            message,
+            replaced_by: None,
+        };
+        map.set_dropped(hash, dropped);
+    }
+}
+
+/// Record the requests dropped by the queue. Returns their hashes, which should
+/// be removed from the journal.
+fn handle_queue_dropped(
+    in_queue_request_map: Option<&InQueueRequestMap>,
+    dropped: Vec<(H256, DropReason)>,
+) -> Vec<H256> {
+    let mut hashes = Vec::with_capacity(dropped.len());
+    for (hash, reason) in dropped {
+        let (reason, message) = match reason {
+            DropReason::Replaced(by) => {
+                if let Some(map) = in_queue_request_map {
+                    map.set_replaced(hash, by);
+                }
+                hashes.push(hash);
+                continue;
+            }
+            DropReason::QueueFull => (TransactionDropReason::QueueFull, "fee queue is full"),
+            DropReason::StaleNonce => (
+                TransactionDropReason::StaleNonce,
+                "nonce is lower than the sender's nonce",
+            ),
+            DropReason::FutureNonce => (
+                TransactionDropReason::FutureNonce,
+                "nonce is higher than the sender's nonce",
+            ),
+        };
+        record_dropped(in_queue_request_map, hash, reason, message.to_string());
+        hashes.push(hash);
+    }
+    hashes
+}
+
+/// Remove the journaled requests which are pushed to the mem pool or dropped,
+/// off the async runtime.
+async fn remove_journaled_requests(store: &Store, hashes: Vec<H256>) {
+    if hashes.is_empty() {
+        return;
+    }
+    let store = store.clone();
+    let len = hashes.len();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut db = store.begin_transaction();
+        hashes
+            .iter()
+            .try_for_each(|hash| db.remove_queued_request(hash))?;
+        db.commit()
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
+    if let Err(err) = result {
+        log::error!("remove {} journaled requests error: {}", len, err);
+    }
+}
+
 impl RequestSubmitter {
     const MAX_CHANNEL_SIZE: usize = 10000;
     const MAX_BATCH_SIZE: usize = 20;
     const INTERVAL_MS: Duration = Duration::from_millis(100);
 
+    /// Add a submitted request to the queue. Returns the hash of the request if
+    /// it's dropped, which should be removed from the journal.
+    fn enqueue(
+        &mut self,
+        state: &(impl State + CodeStore),
+        req: Request,
+        mut ctx: RequestContext,
+    ) -> Option<H256> {
+        gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
+        ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
+        let _entered = ctx.in_queue_span.clone().entered();
+
+        let kind = req.kind();
+        let hash = req.hash();
+        let queue_hash = req.queue_hash();
+        let queued = match req_to_entry(
+            &self.fee_config,
+            self.gasless_tx_support_config.as_ref(),
+            self.generator.clone(),
+            req,
+            state,
+            self.queue_order.next(&self.queue),
+        ) {
+            Ok(entry) => {
+                if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
+                    log::info!(
+                        "req kind {} hash {} exceeded mem block max cycles limit, drop it",
+                        kind,
+                        hash,
+                    );
+                    record_dropped(
+                        self.in_queue_request_map.as_deref(),
+                        queue_hash,
+                        TransactionDropReason::ExceededCyclesLimit,
+                        format!(
+                            "cycles limit {} exceeds mem block max cycles limit {}",
+                            entry.cycles_limit, self.mem_pool_config.mem_block.max_cycles_limit
+                        ),
+                    );
+                    false
+                } else {
+                    add_to_queue(
+                        &mut self.queue,
+                        state,
+                        entry,
+                        ctx,
+                        self.in_queue_request_map.as_deref(),
+                    )
+                }
+            }
+            Err(err) => {
+                log::error!(
+                    "Failed to convert req to entry kind: {}, hash: {}, err: {}",
+                    kind,
+                    hash,
+                    err
+                );
+                record_dropped(
+                    self.in_queue_request_map.as_deref(),
+                    queue_hash,
+                    TransactionDropReason::Invalid,
+                    err.to_string(),
+                );
+                false
+            }
+        };
+        if queued {
+            None
+        } else {
+            Some(queue_hash)
+        }
+    }
+
+    /// Replay the requests journaled before the restart.
+    async fn replay_journaled_requests(&mut self) {
+        let mut requests: Vec<(H256, Request)> = {
+            let db = self.store.begin_transaction();
+            let txs = db
+                .get_queued_transaction_iter()
+                .map(|(hash, tx)| (hash, Request::Tx(tx)));
+            let withdrawals = db
+                .get_queued_withdrawal_iter()
+                .map(|(hash, withdrawal)| (hash, Request::Withdrawal(withdrawal)));
+            txs.chain(withdrawals).collect()
+        };
+        log::info!("replay journaled requests {}", requests.len());
+        // Lower nonces first, so that they aren't rejected as future nonces.
+        requests.sort_by_key(|(_, req)| req.nonce());
+
+        let in_queue_request_map = self
+            .in_queue_request_map
+            .clone()
+            .expect("in_queue_request_map");
+        let state = self.mem_pool_state.load_state_db();
+        let mut dropped = Vec::new();
+        for (hash, req) in requests {
+            // Skip if it's submitted again after the restart.
+            if let Some(handle) = in_queue_request_map.insert(hash, req.clone()) {
+                let ctx = RequestContext {
+                    _in_queue_handle: handle,
+                    trace: gw_telemetry::current_context(),
+                    in_queue_span: tracing::info_span!("journal.replay"),
+                };
+                dropped.extend(self.enqueue(&state, req, ctx));
+            }
+        }
+        remove_journaled_requests(&self.store, dropped).await;
+    }
+
     async fn in_background(mut self) {
         // First mem pool reinject txs
         {
@@ -471,6 +831,9 @@ impl RequestSubmitter {
             *mem_pool.cycles_pool_mut() = org_cycles_pool;
         }
 
+        // Then replay the requests in the fee queue before the restart
+        self.replay_journaled_requests().await;
+
         loop {
             // check mem block empty slots
             loop {
@@ -494,98 +857,32 @@ impl RequestSubmitter {
             }
 
             // mem-pool can process more txs
-            let queue = &mut self.queue;
-            let queue_order = &mut self.queue_order;
-
+            let mut submitted = Vec::new();
             // wait next tx if queue is empty
-            if queue.is_empty() {
+            if self.queue.is_empty() {
                 // blocking current task until we receive a tx
-                let (req, mut ctx) = match self.submit_rx.recv().await {
-                    Some(req) => req,
+                match self.submit_rx.recv().await {
+                    Some(req) => submitted.push(req),
                     None => {
                         log::error!("rpc submit tx is closed");
                         return;
                     }
-                };
-
-                gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
-                ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
-                let _entered = ctx.in_queue_span.clone().entered();
-
-                let state = self.mem_pool_state.load_state_db();
-
-                let kind = req.kind();
-                let hash = req.hash();
-                match req_to_entry(
-                    &self.fee_config,
-                    self.gasless_tx_support_config.as_ref(),
-                    self.generator.clone(),
-                    req,
-                    &state,
-                    queue_order.next(queue),
-                ) {
-                    Ok(entry) => {
-                        if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
-                            log::info!(
-                                "req kind {} hash {} exceeded mem block max cycles limit, drop it",
-                                kind,
-                                hash,
-                            );
-                        } else {
-                            queue.add(entry, ctx);
-                        }
-                    }
-                    Err(err) => {
-                        log::error!(
-                            "Failed to convert req to entry kind: {}, hash: {}, err: {}",
-                            kind,
-                            hash,
-                            err
-                        );
-                    }
                 }
             }
+            while let Ok(req) = self.submit_rx.try_recv() {
+                submitted.push(req);
+            }
 
-            // push txs to fee priority queue
+            // push txs to fee priority queue, they are journaled on submission
             let state = self.mem_pool_state.load_state_db();
-            while let Ok((req, mut ctx)) = self.submit_rx.try_recv() {
-                gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
-                ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
-                let _entered = ctx.in_queue_span.clone().entered();
-
-                let kind = req.kind();
-                let hash = req.hash();
-                match req_to_entry(
-                    &self.fee_config,
-                    self.gasless_tx_support_config.as_ref(),
-                    self.generator.clone(),
-                    req,
-                    &state,
-                    queue_order.next(queue),
-                ) {
-                    Ok(entry) => {
-                        if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
-                            log::info!(
-                                "req kind {} hash {} exceeded mem block max cycles limit, drop it",
-                                kind,
-                                hash,
-                            );
-                        } else {
-                            queue.add(entry, ctx);
-                        }
-                    }
-                    Err(err) => {
-                        log::error!(
-                            "Failed to convert req to entry kind: {}, hash: {}, err: {}",
-                            kind,
-                            hash,
-                            err
-                        );
-                    }
-                }
+            let mut dropped = Vec::new();
+            for (req, ctx) in submitted {
+                dropped.extend(self.enqueue(&state, req, ctx));
             }
+            remove_journaled_requests(&self.store, dropped).await;
 
             // fetch items from PQ
+            let queue = &mut self.queue;
             let items = match queue.fetch(&state, Self::MAX_BATCH_SIZE) {
                 Ok(items) => items,
                 Err(err) => {
@@ -598,6 +895,11 @@ impl RequestSubmitter {
                     continue;
                 }
             };
+            *self.fee_queue_stats.write().expect("fee queue stats") =
+                FeeQueueStats::new(queue, self.mem_pool_config.mem_block.max_txs);
+            let dropped =
+                handle_queue_dropped(self.in_queue_request_map.as_deref(), queue.take_dropped());
+            remove_journaled_requests(&self.store, dropped).await;
 
             if !items.is_empty() {
                 // recover accounts for polyjuice tx from id zero
@@ -643,6 +945,8 @@ impl RequestSubmitter {
 
                 let state = self.mem_pool_state.load_state_db();
                 let mut block_cycles_limit_reached = false;
+                // pushed or dropped requests
+                let mut finished = Vec::with_capacity(items.len());
 
                 for (entry, ctx) in items {
                     gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
@@ -674,6 +978,13 @@ impl RequestSubmitter {
                                 Ok(id) => id,
                                 Err(err) => {
                                     log::info!("[from tx zero] {:x} {}", tx.hash().pack(), err);
+                                    record_dropped(
+                                        self.in_queue_request_map.as_deref(),
+                                        entry.item.hash(),
+                                        TransactionDropReason::PushFailed,
+                                        err.to_string(),
+                                    );
+                                    finished.push(entry.item.hash());
                                     continue;
                                 }
                             };
@@ -711,8 +1022,16 @@ impl RequestSubmitter {
                         }
 
                         log::info!("push {:?} {} failed {}", entry.item.kind(), hash, err);
+                        record_dropped(
+                            self.in_queue_request_map.as_deref(),
+                            entry.item.hash(),
+                            TransactionDropReason::PushFailed,
+                            err.to_string(),
+                        );
                     }
+                    finished.push(entry.item.hash());
                 }
+                remove_journaled_requests(&self.store, finished).await;
 
                 if block_cycles_limit_reached {
                     drop(mem_pool);
@@ -732,8 +1051,13 @@ pub trait GwRpc {
         tx_hash: JsonH256,
         verbose: Option<GetVerbose>,
     ) -> Result<Option<L2TransactionWithStatus>>;
+    async fn gw_get_transaction_status(
+        &self,
+        tx_hash: JsonH256,
+    ) -> Result<Option<TransactionStatus>>;
     async fn gw_get_pending_tx_hashes(&self) -> Result<Vec<JsonH256>>;
     async fn gw_is_request_in_queue(&self, hash: JsonH256) -> Result<bool>;
+    async fn gw_get_request_replaced_by(&self, hash: JsonH256) -> Result<Option<JsonH256>>;
     async fn gw_get_block_committed_info(
         &self,
         block_hash: JsonH256,
@@ -749,11 +1073,21 @@ pub trait GwRpc {
         tx: RawL2TransactionJsonBytes,
         block_number: Option<Uint64>,
         registry_address: Option<RegistryAddressJsonBytes>,
+        state_override: Option<StateOverride>,
     ) -> Result<RunResult>;
+    async fn gw_estimate_cycles(
+        &self,
+        tx: RawL2TransactionJsonBytes,
+        registry_address: Option<RegistryAddressJsonBytes>,
+    ) -> Result<CyclesEstimate>;
     async fn gw_submit_l2transaction(
         &self,
         l2tx: L2TransactionJsonBytes,
     ) -> Result<Option<JsonH256>>;
+    async fn gw_submit_l2transactions(
+        &self,
+        l2txs: Vec<L2TransactionJsonBytes>,
+    ) -> Result<Vec<SubmitL2TransactionResult>>;
     async fn gw_submit_withdrawal_request(
         &self,
         withdrawal_request: WithdrawalRequestExtraJsonBytes,
@@ -763,6 +1097,18 @@ pub trait GwRpc {
         hash: JsonH256,
         verbose: Option<GetVerbose>,
     ) -> Result<Option<WithdrawalWithStatus>>;
+    async fn gw_get_transactions_by_account(
+        &self,
+        account_id: AccountID,
+        limit: Uint32,
+        cursor: Option<JsonBytes>,
+    ) -> Result<AccountHistoryPage<AccountTransaction>>;
+    async fn gw_get_withdrawals_by_address(
+        &self,
+        address: RegistryAddressJsonBytes,
+        limit: Uint32,
+        cursor: Option<JsonBytes>,
+    ) -> Result<AccountHistoryPage<AccountWithdrawal>>;
     async fn gw_get_balance(
         &self,
         address: RegistryAddressJsonBytes,
@@ -775,6 +1121,12 @@ pub trait GwRpc {
         key: JsonH256,
         block_number: Option<Uint64>,
     ) -> Result<JsonH256>;
+    async fn gw_get_proof(
+        &self,
+        account_id: AccountID,
+        keys: Vec<JsonH256>,
+        block_number: Option<Uint64>,
+    ) -> Result<AccountProof>;
     async fn gw_get_account_id_by_script_hash(
         &self,
         script_hash: JsonH256,
@@ -807,6 +1159,7 @@ pub trait GwRpc {
     async fn gw_get_node_info(&self) -> Result<NodeInfo>;
     async fn gw_get_last_submitted_info(&self) -> Result<LastL2BlockCommittedInfo>;
     async fn gw_get_fee_config(&self) -> Result<gw_jsonrpc_types::godwoken::FeeConfig>;
+    async fn gw_get_fee_suggestion(&self) -> Result<FeeSuggestion>;
     async fn gw_get_mem_pool_state_root(&self) -> Result<JsonH256>;
     async fn gw_get_mem_pool_state_ready(&self) -> Result<bool>;
 
@@ -815,11 +1168,13 @@ pub trait GwRpc {
 
     async fn gw_get_rocksdb_memory_stats(&self) -> Result<Vec<CfMemStat>>;
     async fn gw_dump_jemalloc_profiling(&self) -> Result<()>;
+    async fn gw_create_checkpoint(&self, name: String) -> Result<()>;
 
     async fn debug_replay_transaction(
         &self,
         tx_hash: JsonH256,
         max_cycles: Option<Uint64>,
+        tracer: Option<DebugTracer>,
     ) -> Result<Option<DebugRunResult>>;
 }
 
@@ -835,6 +1190,12 @@ impl GwRpc for Arc<Registry> {
     ) -> Result<Option<L2TransactionWithStatus>> {
         gw_get_transaction(self, tx_hash, verbose).await
     }
+    async fn gw_get_transaction_status(
+        &self,
+        tx_hash: JsonH256,
+    ) -> Result<Option<TransactionStatus>> {
+        gw_get_transaction_status(self, tx_hash).await
+    }
     #[instrument(skip_all)]
     async fn gw_get_pending_tx_hashes(&self) -> Result<Vec<JsonH256>> {
         let snap = self.store.get_snapshot();
@@ -853,6 +1214,16 @@ impl GwRpc for Arc<Registry> {
             .as_deref()
             .map_or(false, |m| m.contains(&hash)))
     }
+    #[instrument(skip_all)]
+    async fn gw_get_request_replaced_by(&self, hash: JsonH256) -> Result<Option<JsonH256>> {
+        let hash = to_h256(hash);
+
+        let replaced_by = self
+            .in_queue_request_map
+            .as_deref()
+            .and_then(|m| m.get_replaced_by(&hash));
+        Ok(replaced_by.map(to_jsonh256))
+    }
     async fn gw_get_block_committed_info(
         &self,
         block_hash: JsonH256,
@@ -882,23 +1253,47 @@ impl GwRpc for Arc<Registry> {
         tx: RawL2TransactionJsonBytes,
         block_number: Option<Uint64>,
         registry_address: Option<RegistryAddressJsonBytes>,
+        state_override: Option<StateOverride>,
     ) -> Result<RunResult> {
-        gw_execute_raw_l2transaction(self.clone(), tx, block_number, registry_address).await
+        gw_execute_raw_l2transaction(
+            self.clone(),
+            tx,
+            block_number,
+            registry_address,
+            state_override,
+        )
+        .await
+    }
+    async fn gw_estimate_cycles(
+        &self,
+        tx: RawL2TransactionJsonBytes,
+        registry_address: Option<RegistryAddressJsonBytes>,
+    ) -> Result<CyclesEstimate> {
+        gw_estimate_cycles(self.clone(), tx, registry_address).await
     }
     async fn gw_submit_l2transaction(
         &self,
         l2tx: L2TransactionJsonBytes,
     ) -> Result<Option<JsonH256>> {
-        if self.node_mode == NodeMode::ReadOnly {
+        if matches!(self.node_mode, NodeMode::ReadOnly | NodeMode::Secondary) {
             return Err(method_not_found());
         }
         gw_submit_l2transaction(self, l2tx).await
     }
+    async fn gw_submit_l2transactions(
+        &self,
+        l2txs: Vec<L2TransactionJsonBytes>,
+    ) -> Result<Vec<SubmitL2TransactionResult>> {
+        if matches!(self.node_mode, NodeMode::ReadOnly | NodeMode::Secondary) {
+            return Err(method_not_found());
+        }
+        gw_submit_l2transactions(self, l2txs).await
+    }
     async fn gw_submit_withdrawal_request(
         &self,
         withdrawal_request: WithdrawalRequestExtraJsonBytes,
     ) -> Result<JsonH256> {
-        if self.node_mode == NodeMode::ReadOnly {
+        if matches!(self.node_mode, NodeMode::ReadOnly | NodeMode::Secondary) {
             return Err(method_not_found());
         }
         gw_submit_withdrawal_request(self, withdrawal_request).await
@@ -910,6 +1305,22 @@ impl GwRpc for Arc<Registry> {
     ) -> Result<Option<WithdrawalWithStatus>> {
         gw_get_withdrawal(self, hash, verbose).await
     }
+    async fn gw_get_transactions_by_account(
+        &self,
+        account_id: AccountID,
+        limit: Uint32,
+        cursor: Option<JsonBytes>,
+    ) -> Result<AccountHistoryPage<AccountTransaction>> {
+        gw_get_transactions_by_account(self, account_id, limit, cursor).await
+    }
+    async fn gw_get_withdrawals_by_address(
+        &self,
+        address: RegistryAddressJsonBytes,
+        limit: Uint32,
+        cursor: Option<JsonBytes>,
+    ) -> Result<AccountHistoryPage<AccountWithdrawal>> {
+        gw_get_withdrawals_by_address(self, address, limit, cursor).await
+    }
     async fn gw_get_balance(
         &self,
         address: RegistryAddressJsonBytes,
@@ -926,6 +1337,14 @@ impl GwRpc for Arc<Registry> {
     ) -> Result<JsonH256> {
         gw_get_storage_at(self, account_id, key, block_number).await
     }
+    async fn gw_get_proof(
+        &self,
+        account_id: AccountID,
+        keys: Vec<JsonH256>,
+        block_number: Option<Uint64>,
+    ) -> Result<AccountProof> {
+        gw_get_proof(self, account_id, keys, block_number).await
+    }
     async fn gw_get_account_id_by_script_hash(
         &self,
         script_hash: JsonH256,
@@ -1027,6 +1446,9 @@ impl GwRpc for Arc<Registry> {
         };
         Ok(fee_config)
     }
+    async fn gw_get_fee_suggestion(&self) -> Result<FeeSuggestion> {
+        gw_get_fee_suggestion(self).await
+    }
     #[instrument(skip_all)]
     async fn gw_get_mem_pool_state_root(&self) -> Result<JsonH256> {
         let state = self.mem_pool_state.load_state_db();
@@ -1128,12 +1550,49 @@ impl GwRpc for Arc<Registry> {
 
         Ok(())
     }
+    #[instrument(skip_all)]
+    async fn gw_create_checkpoint(&self, name: String) -> Result<()> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        let checkpoint_root = match self.server_config.checkpoint_root {
+            Some(ref root) => root,
+            None => {
+                return Err(rpc_error(
+                    ErrorCode::InvalidRequest,
+                    "rpc_server.checkpoint_root is not configured",
+                ))
+            }
+        };
+        // Only a directory under the checkpoint root.
+        let name = Path::new(&name);
+        let mut components = name.components().peekable();
+        if components.peek().is_none() || !components.all(|c| matches!(c, Component::Normal(_))) {
+            return Err(rpc_error(
+                ErrorCode::InvalidParams,
+                format!("invalid checkpoint name {}", name.display()),
+            ));
+        }
+
+        let store = self.store.clone();
+        let path = checkpoint_root.join(name);
+        log::info!("create store checkpoint in {}", path.display());
+        tokio::task::spawn_blocking(move || store.create_checkpoint(&path))
+            .await
+            .context("create checkpoint")??;
+        Ok(())
+    }
 
     #[instrument(skip_all)]
     async fn debug_replay_transaction(
         &self,
         tx_hash: JsonH256,
         max_cycles: Option<Uint64>,
+        tracer: Option<DebugTracer>,
     ) -> Result<Option<DebugRunResult>> {
         if !self
             .server_config
@@ -1143,7 +1602,7 @@ impl GwRpc for Arc<Registry> {
             return Err(method_not_found());
         }
 
-        Ok(replay_transaction(self.clone(), tx_hash, max_cycles).await?)
+        Ok(replay_transaction(self.clone(), tx_hash, max_cycles, tracer).await?)
     }
 }
 
@@ -1186,6 +1645,37 @@ async fn gw_get_transaction(
     }))
 }
 
+#[instrument(skip_all)]
+async fn gw_get_transaction_status(
+    ctx: &Registry,
+    tx_hash: JsonH256,
+) -> Result<Option<TransactionStatus>> {
+    let tx_hash = to_h256(tx_hash);
+    let in_queue_request_map = ctx.in_queue_request_map.as_deref();
+
+    if in_queue_request_map.map_or(false, |m| m.contains(&tx_hash)) {
+        return Ok(Some(TransactionStatus::Queued));
+    }
+    let db = ctx.store.get_snapshot();
+    if let Some(tx_info) = db.get_transaction_info(&tx_hash)? {
+        let block_number: u64 = tx_info.block_number().unpack();
+        let block_hash: H256 = tx_info.key().block_hash().unpack();
+        return Ok(Some(TransactionStatus::Packaged {
+            block_number: block_number.into(),
+            block_hash: to_jsonh256(block_hash),
+        }));
+    }
+    if db.get_mem_pool_transaction(&tx_hash)?.is_some() {
+        return Ok(Some(TransactionStatus::InMemBlock));
+    }
+    let dropped = in_queue_request_map.and_then(|m| m.get_dropped(&tx_hash));
+    Ok(dropped.map(|dropped| TransactionStatus::Dropped {
+        reason: dropped.reason,
+        message: dropped.message,
+        replaced_by: dropped.replaced_by.map(to_jsonh256),
+    }))
+}
+
 #[instrument(skip_all)]
 async fn gw_get_block_committed_info(
     block_hash: JsonH256,
@@ -1331,14 +1821,16 @@ fn verify_sender_balance<S: State + CodeStore>(
     ctx: &RollupContext,
     state: &S,
     raw_tx: &RawL2Transaction,
+    block_number: u64,
 ) -> anyhow::Result<()> {
     use gw_generator::typed_transaction::types::TypedRawTransaction;
 
     let sender_id: u32 = raw_tx.from_id().unpack();
     // verify balance
     let sender_script_hash = state.get_script_hash(sender_id)?;
+    let registries = ctx.registries(state, block_number)?;
     let sender_address = state
-        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+        .find_registry_address_by_script_hash(&registries, &sender_script_hash)?
         .ok_or_else(|| anyhow!("Can't find address for sender: {}", sender_id))?;
     // get balance
     let balance = state.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &sender_address)?;
@@ -1387,7 +1879,12 @@ async fn gw_execute_l2transaction(
     let from_id: u32 = tx.raw().from_id().unpack();
     if 0 != from_id {
         let state = ctx.mem_pool_state.load_state_db();
-        if let Err(err) = verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw()) {
+        if let Err(err) = verify_sender_balance(
+            ctx.generator.rollup_context(),
+            &state,
+            &tx.raw(),
+            block_info.number().unpack(),
+        ) {
             return Err(rpc_error(
                 ErrorCode::InvalidRequest,
                 format!("check balance err: {}", err),
@@ -1412,8 +1909,13 @@ async fn gw_execute_l2transaction(
         let eth_recover = &ctx.polyjuice_sender_recover.eth;
         let tx = eth_recover.mock_sender_if_not_exists(tx, &mut state)?;
         if 0 == from_id {
-            verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw())
-                .map_err(|err| anyhow!("check balance err: {}", err))?;
+            verify_sender_balance(
+                ctx.generator.rollup_context(),
+                &state,
+                &tx.raw(),
+                block_info.number().unpack(),
+            )
+            .map_err(|err| anyhow!("check balance err: {}", err))?;
         }
 
         // tx basic verification
@@ -1426,7 +1928,8 @@ async fn gw_execute_l2transaction(
         )
         .verify(&tx, block_info.number().unpack())?;
         // verify tx signature
-        ctx.generator.check_transaction_signature(&state, &tx)?;
+        ctx.generator
+            .check_transaction_signature(&state, &tx, block_info.number().unpack())?;
         // execute tx
         let raw_tx = tx.raw();
         let run_result = ctx.generator.execute_transaction(
@@ -1470,10 +1973,13 @@ async fn gw_execute_raw_l2transaction(
     raw_l2tx: RawL2TransactionJsonBytes,
     block_number_opt: Option<Uint64>,
     registry_address_opt: Option<RegistryAddressJsonBytes>,
+    state_override: Option<StateOverride>,
 ) -> Result<RunResult> {
     let block_number_opt = block_number_opt.map(|n| n.value());
     let raw_l2tx = raw_l2tx.0;
     let registry_address_opt = registry_address_opt.map(|r| r.0);
+    let state_override = state_override.unwrap_or_default();
+    check_state_override(&state_override).map_err(invalid_state_override)?;
 
     let mut db_txn = ctx.store.begin_transaction();
 
@@ -1488,6 +1994,7 @@ async fn gw_execute_raw_l2transaction(
                 Some(block) => block.raw(),
                 None => return Err(header_not_found_err()),
             };
+            check_state_history_available(db, block_number)?;
             let block_producer = raw_block.block_producer();
             let timestamp = raw_block.timestamp();
             let number: u64 = raw_block.number().unpack();
@@ -1518,13 +2025,37 @@ async fn gw_execute_raw_l2transaction(
     if 0 != from_id {
         let check_balance_result = match block_number_opt {
             Some(block_number) => {
-                let state =
+                let mut state =
                     BlockStateDB::from_store(&mut db_txn, RWConfig::history_block(block_number))?;
-                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx)
+                apply_state_override(
+                    &mut state,
+                    ctx.generator.rollup_context(),
+                    block_info.number().unpack(),
+                    &state_override,
+                )
+                .map_err(invalid_state_override)?;
+                verify_sender_balance(
+                    ctx.generator.rollup_context(),
+                    &state,
+                    &raw_l2tx,
+                    block_info.number().unpack(),
+                )
             }
             None => {
-                let state = ctx.mem_pool_state.load_state_db();
-                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx)
+                let mut state = ctx.mem_pool_state.load_state_db();
+                apply_state_override(
+                    &mut state,
+                    ctx.generator.rollup_context(),
+                    block_info.number().unpack(),
+                    &state_override,
+                )
+                .map_err(invalid_state_override)?;
+                verify_sender_balance(
+                    ctx.generator.rollup_context(),
+                    &state,
+                    &raw_l2tx,
+                    block_info.number().unpack(),
+                )
             }
         };
         if let Err(err) = check_balance_result {
@@ -1552,14 +2083,26 @@ async fn gw_execute_raw_l2transaction(
             Some(block_number) => {
                 let mut state =
                     BlockStateDB::from_store(&mut db_txn, RWConfig::history_block(block_number))?;
+                apply_state_override(
+                    &mut state,
+                    ctx.generator.rollup_context(),
+                    block_info.number().unpack(),
+                    &state_override,
+                )
+                .map_err(invalid_state_override)?;
                 let raw_l2tx = eth_recover.mock_sender_if_not_exists_from_raw_registry(
                     raw_l2tx,
                     registry_address_opt,
                     &mut state,
                 )?;
                 if 0 == from_id {
-                    verify_sender_balance(rollup_context, &state, &raw_l2tx)
-                        .map_err(|err| anyhow!("check balance err {}", err))?;
+                    verify_sender_balance(
+                        rollup_context,
+                        &state,
+                        &raw_l2tx,
+                        block_info.number().unpack(),
+                    )
+                    .map_err(|err| anyhow!("check balance err {}", err))?;
                 }
 
                 ctx.generator.execute_transaction(
@@ -1573,14 +2116,26 @@ async fn gw_execute_raw_l2transaction(
             }
             None => {
                 let mut state = ctx.mem_pool_state.load_state_db();
+                apply_state_override(
+                    &mut state,
+                    ctx.generator.rollup_context(),
+                    block_info.number().unpack(),
+                    &state_override,
+                )
+                .map_err(invalid_state_override)?;
                 let raw_l2tx = eth_recover.mock_sender_if_not_exists_from_raw_registry(
                     raw_l2tx,
                     registry_address_opt,
                     &mut state,
                 )?;
                 if 0 == from_id {
-                    verify_sender_balance(rollup_context, &state, &raw_l2tx)
-                        .map_err(|err| anyhow!("check balance err {}", err))?;
+                    verify_sender_balance(
+                        rollup_context,
+                        &state,
+                        &raw_l2tx,
+                        block_info.number().unpack(),
+                    )
+                    .map_err(|err| anyhow!("check balance err {}", err))?;
                 }
 
                 ctx.generator.execute_transaction(
@@ -1593,7 +2148,7 @@ async fn gw_execute_raw_l2transaction(
                 )?
             }
         };
-        anyhow::Ok(run_result)
+        Ok::<_, MyRpcError>(run_result)
     })
     .await??;
     gw_metrics::rpc()
@@ -1618,31 +2173,252 @@ async fn gw_execute_raw_l2transaction(
     Ok(run_result.into())
 }
 
+/// Minimal `max_cycles` of a raw tx against the mem pool state.
 #[instrument(skip_all)]
-async fn gw_submit_l2transaction(
-    ctx: &Registry,
-    l2tx: L2TransactionJsonBytes,
-) -> Result<Option<JsonH256>> {
-    let tx = l2tx.0;
-    let tx_hash: H256 = tx.hash();
+async fn gw_estimate_cycles(
+    ctx: Arc<Registry>,
+    raw_l2tx: RawL2TransactionJsonBytes,
+    registry_address_opt: Option<RegistryAddressJsonBytes>,
+) -> Result<CyclesEstimate> {
+    let raw_l2tx = raw_l2tx.0;
+    let registry_address_opt = registry_address_opt.map(|r| r.0);
+    let block_info = ctx
+        .mem_pool_state
+        .get_mem_pool_block_info()
+        .expect("get mem pool block info");
+    let tx_hash: H256 = raw_l2tx.hash();
+    let block_number: u64 = block_info.number().unpack();
+
+    // check sender's balance
+    // NOTE: for tx from id zero, its balance will be verified after mock account
+    let from_id: u32 = raw_l2tx.from_id().unpack();
+    if 0 != from_id {
+        let state = ctx.mem_pool_state.load_state_db();
+        if let Err(err) = verify_sender_balance(
+            ctx.generator.rollup_context(),
+            &state,
+            &raw_l2tx,
+            block_info.number().unpack(),
+        ) {
+            return Err(rpc_error(
+                ErrorCode::InvalidRequest,
+                format!("check balance err: {}", err),
+            ));
+        }
+    }
+
+    let execution_span = tracing::info_span!("execution");
+    let (max_cycles, mut run_result) = tokio::task::spawn_blocking(move || {
+        let _entered = execution_span.entered();
+
+        let snap = ctx.store.get_snapshot();
+        let chain_view = ChainView::new(&snap, snap.get_last_valid_tip_block_hash()?);
+        let execute = |max_cycles: u64| -> anyhow::Result<_> {
+            let mut state = ctx.mem_pool_state.load_state_db();
+            let raw_l2tx = ctx
+                .polyjuice_sender_recover
+                .eth
+                .mock_sender_if_not_exists_from_raw_registry(
+                    raw_l2tx.clone(),
+                    registry_address_opt.clone(),
+                    &mut state,
+                )?;
+            if 0 == from_id {
+                verify_sender_balance(
+                    ctx.generator.rollup_context(),
+                    &state,
+                    &raw_l2tx,
+                    block_info.number().unpack(),
+                )
+                .map_err(|err| anyhow!("check balance err {}", err))?;
+            }
+            let mut cycles_pool = CyclesPool::new(
+                ctx.mem_pool_config.mem_block.max_cycles_limit,
+                ctx.mem_pool_config.mem_block.syscall_cycles.clone(),
+            );
+            ctx.generator.execute_transaction(
+                &chain_view,
+                &mut state,
+                &block_info,
+                &raw_l2tx,
+                Some(max_cycles),
+                Some(&mut cycles_pool),
+            )
+        };
+
+        let run_result = execute(ctx.mem_pool_config.execute_l2tx_max_cycles)?;
+        if run_result.exit_code != 0 {
+            return Ok::<_, MyRpcError>((0, run_result));
+        }
+        let max_cycles = bisect_max_cycles(run_result.cycles.execution, |max_cycles| {
+            Ok(execute(max_cycles)?.exit_code == 0)
+        })?;
+        Ok((max_cycles, run_result))
+    })
+    .await??;
+
+    if run_result.exit_code != 0 {
+        let receipt = gw_types::offchain::ErrorTxReceipt {
+            tx_hash,
+            block_number,
+            return_data: run_result.return_data,
+            last_log: run_result.logs.pop(),
+            exit_code: run_result.exit_code,
+        };
+        return Err(rpc_error_with_data(
+            ErrorCode::InvalidRequest,
+            TransactionError::InvalidExitCode(run_result.exit_code).to_string(),
+            ErrorTxReceipt::from(receipt),
+        ));
+    }
+
+    Ok(CyclesEstimate {
+        max_cycles: max_cycles.into(),
+        virtual_cycles: run_result.cycles.r#virtual.into(),
+    })
+}
+
+/// Minimal max cycles that `execute` succeeds with, `execute` must succeed
+/// with `used_cycles`.
+///
+/// The execution cycles used by the first run are the answer unless the
+/// execution depends on the cycles limit, check it first and bisect
+/// otherwise.
+fn bisect_max_cycles(
+    used_cycles: u64,
+    mut execute: impl FnMut(u64) -> anyhow::Result<bool>,
+) -> anyhow::Result<u64> {
+    if used_cycles == 0 || !execute(used_cycles - 1)? {
+        return Ok(used_cycles);
+    }
+    // execute(lo) fails and execute(hi) succeeds
+    let (mut lo, mut hi) = (0, used_cycles - 1);
+    while lo + 1 < hi {
+        let mid = lo + (hi - lo) / 2;
+        if execute(mid)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Ok(hi)
+}
 
+/// Suggest a fee rate from the fee queue and the lowest fee rates of the
+/// recent blocks.
+#[instrument(skip_all)]
+async fn gw_get_fee_suggestion(ctx: &Registry) -> Result<FeeSuggestion> {
+    let stats = *ctx.fee_queue_stats.read().expect("fee queue stats");
+    // Outbid the last entry fits in a mem block.
+    let queue_fee_rate = stats.block_fee_rate.map(|rate| rate.saturating_add(1));
+
+    let snap = ctx.store.get_snapshot();
+    let tip_number: u64 = snap.get_last_valid_tip_block()?.raw().number().unpack();
+    let mut lowest_fee_rates = Vec::new();
+    // Only new blocks are scanned, the others are cached.
+    tokio::task::block_in_place(|| -> anyhow::Result<()> {
+        let state = ctx.mem_pool_state.load_state_db();
+        for number in tip_number.saturating_sub(FEE_SUGGESTION_BLOCKS - 1)..=tip_number {
+            let block_hash = match snap.get_block_hash_by_number(number)? {
+                Some(block_hash) => block_hash,
+                None => continue,
+            };
+            let cached = ctx
+                .block_fee_rates
+                .lock()
+                .expect("block fee rates")
+                .get(&block_hash)
+                .copied();
+            let lowest = match cached {
+                Some(lowest) => lowest,
+                None => {
+                    let lowest = snap
+                        .get_block(&block_hash)?
+                        .into_iter()
+                        .flat_map(|block| block.transactions().into_iter())
+                        .filter_map(|tx| {
+                            req_to_entry(
+                                &ctx.fee_config,
+                                ctx.gasless_tx_support_config.as_ref(),
+                                ctx.generator.clone(),
+                                Request::Tx(tx),
+                                &state,
+                                0,
+                            )
+                            .ok()
+                        })
+                        .map(|entry| entry.fee_rate())
+                        .min();
+                    ctx.block_fee_rates
+                        .lock()
+                        .expect("block fee rates")
+                        .put(block_hash, lowest);
+                    lowest
+                }
+            };
+            lowest_fee_rates.extend(lowest);
+        }
+        Ok(())
+    })?;
+    lowest_fee_rates.sort_unstable();
+    let recent_blocks_fee_rate = lowest_fee_rates.get(lowest_fee_rates.len() / 2).copied();
+
+    let fee_rate = queue_fee_rate
+        .unwrap_or_default()
+        .max(recent_blocks_fee_rate.unwrap_or_default());
+    Ok(FeeSuggestion {
+        fee_rate: fee_rate.into(),
+        queue_fee_rate: queue_fee_rate.map(Into::into),
+        queue_len: (stats.len as u32).into(),
+        recent_blocks_fee_rate: recent_blocks_fee_rate.map(Into::into),
+    })
+}
+
+/// Checks of a submitted tx except the sender's nonce and the rate limit.
+fn check_submit_tx(ctx: &Registry, tx: &L2Transaction, max_tx_size: usize) -> Result<()> {
     let sender_id: u32 = tx.raw().from_id().unpack();
-    let eth_recover = &ctx.polyjuice_sender_recover.eth;
-    if 0 == sender_id && eth_recover.opt_account_creator.is_none() {
+    if 0 == sender_id
+        && ctx
+            .polyjuice_sender_recover
+            .eth
+            .opt_account_creator
+            .is_none()
+    {
         return Err("tx from zero is disabled".into());
     }
 
-    // Return None for tx from zero because its from id will be updated after account creation.
-    let tx_hash_json = if 0 == sender_id {
-        None
-    } else {
-        Some(to_jsonh256(tx.hash()))
-    };
+    // TODO use TransactionVerifier after remove sender auto creator
+    // verify tx size
+    if tx.as_slice().len() > max_tx_size {
+        let err = TransactionError::ExceededMaxTxSize {
+            max_size: max_tx_size,
+            tx_size: tx.as_slice().len(),
+        };
+        return Err(rpc_error(ErrorCode::InvalidRequest, err.to_string()));
+    }
 
-    // check rate limit
+    Ok(())
+}
+
+fn submit_max_tx_size(ctx: &Registry) -> usize {
+    // block info
+    let block_info = ctx
+        .mem_pool_state
+        .load_shared()
+        .mem_block
+        .expect("mem block info");
+    ctx.generator
+        .fork_config()
+        .max_tx_size(block_info.number().unpack())
+}
+
+fn rate_limit_err() -> MyRpcError {
+    "Rate limit, please wait few seconds and try again".into()
+}
+
+async fn check_send_tx_rate_limit(ctx: &Registry, sender_id: u32) -> Result<()> {
     if let Some(ref rate_limiter) = ctx.send_tx_rate_limit {
         let mut rate_limiter = rate_limiter.lock().await;
-        let sender_id: u32 = tx.raw().from_id().unpack();
         if let Some(last_touch) = rate_limiter.get(&sender_id) {
             if last_touch.elapsed().as_secs()
                 < ctx
@@ -1651,97 +2427,269 @@ async fn gw_submit_l2transaction(
                     .map(|c| c.seconds)
                     .unwrap_or_default()
             {
-                return Err("Rate limit, please wait few seconds and try again".into());
+                return Err(rate_limit_err());
             }
         }
         rate_limiter.put(sender_id, Instant::now());
     }
+    Ok(())
+}
 
-    // TODO use TransactionVerifier after remove sender auto creator
-    // verify tx size
-    {
-        // block info
-        let block_info = ctx
-            .mem_pool_state
-            .load_shared()
-            .mem_block
-            .expect("mem block info");
-        // check tx size
-        let max_tx_size = ctx
-            .generator
-            .fork_config()
-            .max_tx_size(block_info.number().unpack());
-        if tx.as_slice().len() > max_tx_size {
-            let err = TransactionError::ExceededMaxTxSize {
-                max_size: max_tx_size,
-                tx_size: tx.as_slice().len(),
-            };
-            return Err(rpc_error(ErrorCode::InvalidRequest, err.to_string()));
-        }
+fn check_submit_tx_nonce(tx: &L2Transaction, expected_nonce: u32) -> Result<()> {
+    let tx_nonce: u32 = tx.raw().nonce().unpack();
+    if expected_nonce != tx_nonce {
+        let err = TransactionError::Nonce {
+            account_id: tx.raw().from_id().unpack(),
+            expected: expected_nonce,
+            actual: tx_nonce,
+        };
+        log::info!(
+            "[RPC] reject to submit tx {:?}, err: {}",
+            faster_hex::hex_string(&tx.hash()),
+            err
+        );
+        return Err(rpc_error(INVALID_NONCE_ERR_CODE, err.to_string()));
+    }
+    Ok(())
+}
+
+/// Check the fee queue quotas of a sender who already has `count` txs accepted
+/// in the same batch.
+fn check_submit_tx_sender_quota(ctx: &Registry, count: usize) -> Result<()> {
+    if count >= ctx.fee_config.max_queued_entries_per_sender {
+        return Err(rpc_error(
+            ErrorCode::InvalidRequest,
+            "sender has too many queued requests",
+        ));
+    }
+    // All but the first tx have future nonces.
+    if count > ctx.fee_config.max_future_entries_per_sender {
+        return Err(rpc_error(
+            ErrorCode::InvalidRequest,
+            "sender has too many future nonce requests",
+        ));
+    }
+    Ok(())
+}
+
+fn reserve_submit_permit(ctx: &Registry) -> Result<mpsc::Permit<'_, (Request, RequestContext)>> {
+    ctx.submit_tx.try_reserve().map_err(|err| match err {
+        mpsc::error::TrySendError::Full(_) => rpc_error(BUSY_ERR_CODE, "mem pool service busy"),
+        e => e.into(),
+    })
+}
+
+/// Insert a request into the in queue request map. Returns the context to send
+/// it with, or None if it's already in the queue.
+///
+/// Use permit to insert before send so that remove won't happen before insert.
+fn insert_in_queue(ctx: &Registry, request: &Request) -> Option<RequestContext> {
+    let handle = ctx
+        .in_queue_request_map
+        .as_ref()
+        .expect("in_queue_request_map")
+        .insert(request.queue_hash(), request.clone())?;
+    let in_queue_span = tracing::info_span!("submit_queue.send");
+    let _entered = in_queue_span.clone().entered();
+    Some(RequestContext {
+        _in_queue_handle: handle,
+        trace: gw_telemetry::current_context(),
+        in_queue_span,
+    })
+}
+
+/// Journal the requests off the async runtime before they're sent to the
+/// request submitter, so that accepted requests survive a restart.
+async fn journal_submitted_requests(ctx: &Registry, requests: Vec<Request>) -> Result<()> {
+    if requests.is_empty() {
+        return Ok(());
+    }
+    let store = ctx.store.clone();
+    tokio::task::spawn_blocking(move || journal_requests(&store, &requests))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result)?;
+    Ok(())
+}
+
+/// Send a tx to the request submitter, unless it's already in the queue.
+async fn send_submit_tx(
+    ctx: &Registry,
+    permit: mpsc::Permit<'_, (Request, RequestContext)>,
+    tx: L2Transaction,
+) -> Result<()> {
+    let request = Request::Tx(tx);
+    if let Some(request_ctx) = insert_in_queue(ctx, &request) {
+        journal_submitted_requests(ctx, vec![request.clone()]).await?;
+        permit.send((request, request_ctx));
+    }
+    Ok(())
+}
+
+/// Return None for tx from zero because its from id will be updated after account creation.
+fn submit_tx_hash(tx: &L2Transaction) -> Option<JsonH256> {
+    let sender_id: u32 = tx.raw().from_id().unpack();
+    if 0 == sender_id {
+        None
+    } else {
+        Some(to_jsonh256(tx.hash()))
     }
+}
+
+#[instrument(skip_all)]
+async fn gw_submit_l2transaction(
+    ctx: &Registry,
+    l2tx: L2TransactionJsonBytes,
+) -> Result<Option<JsonH256>> {
+    let tx = l2tx.0;
+    let sender_id: u32 = tx.raw().from_id().unpack();
+
+    check_send_tx_rate_limit(ctx, sender_id).await?;
+    check_submit_tx(ctx, &tx, submit_max_tx_size(ctx))?;
 
     // check sender's nonce
     {
         // fetch mem-pool state
         let state = ctx.mem_pool_state.load_state_db();
-
-        let tx_nonce: u32 = tx.raw().nonce().unpack();
         let sender_nonce: u32 = if 0 == sender_id {
             0
         } else {
             state.get_nonce(sender_id)?
         };
-        if sender_nonce != tx_nonce {
-            let err = TransactionError::Nonce {
-                account_id: sender_id,
-                expected: sender_nonce,
-                actual: tx_nonce,
+        check_submit_tx_nonce(&tx, sender_nonce)?;
+    }
+
+    let permit = reserve_submit_permit(ctx)?;
+    let tx_hash = submit_tx_hash(&tx);
+    send_submit_tx(ctx, permit, tx).await?;
+
+    Ok(tx_hash)
+}
+
+fn submit_rejected(err: MyRpcError) -> SubmitL2TransactionResult {
+    SubmitL2TransactionResult::Rejected {
+        code: err.0.code.code(),
+        message: err.0.message,
+    }
+}
+
+/// Like `gw_submit_l2transaction`, but txs of a sender may have consecutive
+/// nonces. The accepted txs are sent in nonce order, all or none.
+#[instrument(skip_all, fields(count = l2txs.len()))]
+async fn gw_submit_l2transactions(
+    ctx: &Registry,
+    l2txs: Vec<L2TransactionJsonBytes>,
+) -> Result<Vec<SubmitL2TransactionResult>> {
+    if l2txs.len() > MAX_SUBMIT_TXS {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("too many txs, max: {}", MAX_SUBMIT_TXS),
+        ));
+    }
+    let txs: Vec<L2Transaction> = l2txs.into_iter().map(|tx| tx.0).collect();
+    let mut results: Vec<Option<SubmitL2TransactionResult>> = vec![None; txs.len()];
+
+    // Check txs in nonce order, so that a sender's txs can be submitted in
+    // any order.
+    let mut order: Vec<usize> = (0..txs.len()).collect();
+    order.sort_by_key(|&i| {
+        let raw = txs[i].raw();
+        let sender_id: u32 = raw.from_id().unpack();
+        let nonce: u32 = raw.nonce().unpack();
+        (sender_id, nonce)
+    });
+
+    let max_tx_size = submit_max_tx_size(ctx);
+    let state = ctx.mem_pool_state.load_state_db();
+    // sender id -> whether it's rate limited
+    let mut rate_limited: HashMap<u32, bool> = HashMap::new();
+    // sender id -> nonce of the next tx
+    let mut next_nonces: HashMap<u32, u32> = HashMap::new();
+    // sender id -> number of accepted txs
+    let mut sender_counts: HashMap<u32, usize> = HashMap::new();
+    let mut accepted = Vec::new();
+    for i in order {
+        let tx = &txs[i];
+        let sender_id: u32 = tx.raw().from_id().unpack();
+        // A batch counts as a single submission of its senders.
+        let is_rate_limited = match rate_limited.get(&sender_id) {
+            Some(limited) => *limited,
+            None => {
+                let limited = check_send_tx_rate_limit(ctx, sender_id).await.is_err();
+                rate_limited.insert(sender_id, limited);
+                limited
+            }
+        };
+        let mut check = || -> Result<()> {
+            if is_rate_limited {
+                return Err(rate_limit_err());
+            }
+            check_submit_tx(ctx, tx, max_tx_size)?;
+            let expected_nonce = match next_nonces.get(&sender_id) {
+                Some(nonce) => *nonce,
+                None if 0 == sender_id => 0,
+                None => state.get_nonce(sender_id)?,
             };
-            log::info!(
-                "[RPC] reject to submit tx {:?}, err: {}",
-                faster_hex::hex_string(&tx.hash()),
-                err
-            );
-            return Err(rpc_error(INVALID_NONCE_ERR_CODE, err.to_string()));
+            check_submit_tx_nonce(tx, expected_nonce)?;
+            // Txs from zero are from different accounts.
+            if 0 != sender_id {
+                // The first accepted tx of a sender has the current nonce, the
+                // rest have future nonces. Enforce the fee queue quotas here,
+                // otherwise the txs would be dropped after being reported as
+                // submitted.
+                let count = sender_counts.get(&sender_id).copied().unwrap_or(0);
+                check_submit_tx_sender_quota(ctx, count)?;
+                sender_counts.insert(sender_id, count + 1);
+                next_nonces.insert(sender_id, expected_nonce + 1);
+            }
+            Ok(())
+        };
+        match check() {
+            Ok(()) => accepted.push(i),
+            Err(err) => results[i] = Some(submit_rejected(err)),
         }
     }
 
-    let permit = ctx.submit_tx.try_reserve().map_err(|err| match err {
-        mpsc::error::TrySendError::Full(_) => rpc_error(BUSY_ERR_CODE, "mem pool service busy"),
-        e => e.into(),
-    })?;
-
-    let tx_hash_in_queue = match tx_hash_json {
-        Some(_) => tx_hash,
-        None => {
-            let mut hasher = new_blake2b();
-            let sig: Bytes = tx.signature().unpack();
-            hasher.update(&sig);
-            let mut hash = [0u8; 32];
-            hasher.finalize(&mut hash);
-            hash
+    let mut permits = Vec::with_capacity(accepted.len());
+    for _ in 0..accepted.len() {
+        match reserve_submit_permit(ctx) {
+            Ok(permit) => permits.push(permit),
+            Err(err) => {
+                // Release the reserved permits, none of the txs is sent.
+                drop(permits);
+                let rejected = submit_rejected(err);
+                for i in accepted {
+                    results[i] = Some(rejected.clone());
+                }
+                return Ok(results.into_iter().map(|r| r.expect("result")).collect());
+            }
+        }
+    }
+    let mut sending = Vec::with_capacity(accepted.len());
+    for (i, permit) in accepted.into_iter().zip(permits) {
+        let tx = txs[i].clone();
+        results[i] = Some(SubmitL2TransactionResult::Submitted {
+            tx_hash: submit_tx_hash(&tx),
+        });
+        let request = Request::Tx(tx);
+        if let Some(request_ctx) = insert_in_queue(ctx, &request) {
+            sending.push((i, permit, request, request_ctx));
         }
-    };
-    let request = Request::Tx(tx);
-    // Use permit to insert before send so that remove won't happen before insert.
-    if let Some(handle) = ctx
-        .in_queue_request_map
-        .as_ref()
-        .expect("in_queue_request_map")
-        .insert(tx_hash_in_queue, request.clone())
-    {
-        // Send if the request wasn't already in the map.
-        let in_queue_span = tracing::info_span!("submit_queue.send");
-        let _entered = in_queue_span.clone().entered();
-        let ctx = RequestContext {
-            _in_queue_handle: handle,
-            trace: gw_telemetry::current_context(),
-            in_queue_span,
-        };
-        permit.send((request, ctx));
+    }
+    let requests = sending.iter().map(|(_, _, req, _)| req.clone()).collect();
+    if let Err(err) = journal_submitted_requests(ctx, requests).await {
+        // None of the txs is sent.
+        let rejected = submit_rejected(err);
+        for (i, ..) in sending {
+            results[i] = Some(rejected.clone());
+        }
+        return Ok(results.into_iter().map(|r| r.expect("result")).collect());
+    }
+    for (_, permit, request, request_ctx) in sending {
+        permit.send((request, request_ctx));
     }
 
-    Ok(tx_hash_json)
+    Ok(results.into_iter().map(|r| r.expect("result")).collect())
 }
 
 #[instrument(skip_all)]
@@ -1778,28 +2726,13 @@ async fn gw_submit_withdrawal_request(
         return Err(rpc_error(ErrorCode::InvalidRequest, err.to_string()));
     }
 
-    let permit = ctx.submit_tx.try_reserve().map_err(|err| match err {
-        mpsc::error::TrySendError::Full(_) => rpc_error(BUSY_ERR_CODE, "mem pool service busy"),
-        e => e.into(),
-    })?;
+    let permit = reserve_submit_permit(ctx)?;
 
     let request = Request::Withdrawal(withdrawal);
-    // Use permit to insert before send so that remove won't happen before insert.
-    if let Some(handle) = ctx
-        .in_queue_request_map
-        .as_ref()
-        .expect("in_queue_request_map")
-        .insert(withdrawal_hash, request.clone())
-    {
-        // Send if the request wasn't already in the map.
-        let in_queue_span = tracing::info_span!("submit_queue.send");
-        let _entered = in_queue_span.clone().entered();
-        let ctx = RequestContext {
-            _in_queue_handle: handle,
-            trace: gw_telemetry::current_context(),
-            in_queue_span,
-        };
-        permit.send((request, ctx));
+    // Send if the request wasn't already in the queue.
+    if let Some(request_ctx) = insert_in_queue(ctx, &request) {
+        journal_submitted_requests(ctx, vec![request.clone()]).await?;
+        permit.send((request, request_ctx));
     }
 
     Ok(withdrawal_hash.into())
@@ -1849,17 +2782,78 @@ async fn gw_get_withdrawal(
                 withdrawal_index: l2_withdrawal_index.into(),
             });
             let l1_committed_info = gw_get_block_committed_info(l2_block_hash.into(), ctx).await?;
+            let (finalized, estimated_finalized_at) =
+                withdrawal_finality(ctx, &db, &l2_block_hash)?;
+            let unlock_tx_hash = db.get_withdrawal_unlock_tx_hash(&withdrawal_hash);
+            let status = if unlock_tx_hash.is_some() {
+                WithdrawalStatus::Unlocked
+            } else if finalized {
+                WithdrawalStatus::Finalized
+            } else {
+                WithdrawalStatus::Committed
+            };
             return Ok(Some(WithdrawalWithStatus {
-                status: WithdrawalStatus::Committed,
+                status,
                 withdrawal: withdrawal_opt,
                 l2_committed_info,
                 l1_committed_info,
+                estimated_finalized_at: estimated_finalized_at.map(Into::into),
+                unlock_tx_hash: unlock_tx_hash.map(to_jsonh256),
             }));
         }
     }
     Ok(None)
 }
 
+/// Whether withdrawals in the block are finalized by the last valid tip, and
+/// the estimated finalization time in milliseconds.
+///
+/// For legacy block number timepoints, the time is estimated with the average
+/// block interval since the withdrawal block.
+fn withdrawal_finality(
+    ctx: &Registry,
+    db: &impl ChainStore,
+    block_hash: &H256,
+) -> Result<(bool, Option<u64>)> {
+    let block = db.get_block(block_hash)?.context("get withdrawal block")?;
+    let block_number: u64 = block.raw().number().unpack();
+    let block_timestamp: u64 = block.raw().timestamp().unpack();
+    let timepoint = gw_utils::finalized_timepoint(
+        &ctx.rollup_config,
+        ctx.generator.fork_config(),
+        block_number,
+        block_timestamp,
+    );
+
+    let tip = db.get_last_valid_tip_block()?;
+    let tip_global_state = db
+        .get_block_post_global_state(&tip.hash())?
+        .context("get tip global state")?;
+    let finality_blocks: u64 = ctx.rollup_config.finality_blocks().unpack();
+    let finalized =
+        CompatibleFinalizedTimepoint::from_global_state(&tip_global_state, finality_blocks)
+            .is_finalized(&timepoint);
+
+    let estimated_finalized_at = match timepoint {
+        Timepoint::Timestamp(timestamp) => Some(timestamp),
+        Timepoint::BlockNumber(number) => {
+            let tip_number: u64 = tip.raw().number().unpack();
+            let tip_timestamp: u64 = tip.raw().timestamp().unpack();
+            let remaining_blocks = (number + finality_blocks).saturating_sub(tip_number);
+            if 0 == remaining_blocks {
+                Some(tip_timestamp)
+            } else if tip_number > block_number {
+                let interval =
+                    tip_timestamp.saturating_sub(block_timestamp) / (tip_number - block_number);
+                Some(tip_timestamp + remaining_blocks * interval)
+            } else {
+                None
+            }
+        }
+    };
+    Ok((finalized, estimated_finalized_at))
+}
+
 #[instrument(skip_all)]
 async fn gw_get_balance(
     ctx: &Registry,
@@ -1871,6 +2865,7 @@ async fn gw_get_balance(
     let balance = match block_number {
         Some(block_number) => {
             let mut db = ctx.store.begin_transaction();
+            check_state_history_available(&db, block_number.into())?;
             let tree =
                 BlockStateDB::from_store(&mut db, RWConfig::history_block(block_number.into()))?;
             tree.get_sudt_balance(sudt_id.into(), &address)?
@@ -1893,6 +2888,7 @@ async fn gw_get_storage_at(
     let value = match block_number {
         Some(block_number) => {
             let mut db = ctx.store.begin_transaction();
+            check_state_history_available(&db, block_number.into())?;
             let tree =
                 BlockStateDB::from_store(&mut db, RWConfig::history_block(block_number.into()))?;
             let key: H256 = to_h256(key);
@@ -1909,6 +2905,122 @@ async fn gw_get_storage_at(
     Ok(json_value)
 }
 
+/// The account SMT only keeps the state of the last valid tip block. Proofs of
+/// an earlier block are generated by reverting the blocks after it in memory
+/// with the state history, so they need the history and cost more the further
+/// the block is behind the tip.
+#[instrument(skip_all)]
+async fn gw_get_proof(
+    ctx: &Registry,
+    account_id: AccountID,
+    keys: Vec<JsonH256>,
+    block_number: Option<Uint64>,
+) -> Result<AccountProof> {
+    if keys.len() > MAX_PROOF_KEYS {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("too many keys, max {}", MAX_PROOF_KEYS),
+        ));
+    }
+    let account_id: u32 = account_id.into();
+
+    // The tip block and the SMT are read from the same transaction.
+    let db = ctx.store.begin_transaction();
+    let tip_block = db.get_last_valid_tip_block()?;
+    let tip_number: u64 = tip_block.raw().number().unpack();
+    let block = match block_number {
+        Some(block_number) if u64::from(block_number) != tip_number => {
+            let block_number: u64 = block_number.into();
+            if block_number > tip_number {
+                return Err(rpc_error(
+                    ErrorCode::InvalidParams,
+                    format!(
+                        "block {} is beyond the tip block {}",
+                        block_number, tip_number
+                    ),
+                ));
+            }
+            if tip_number - block_number > MAX_PROOF_BLOCKS_BEHIND_TIP {
+                return Err(rpc_error(
+                    ErrorCode::InvalidParams,
+                    format!(
+                        "block {} is more than {} blocks behind the tip block {}",
+                        block_number, MAX_PROOF_BLOCKS_BEHIND_TIP, tip_number
+                    ),
+                ));
+            }
+            check_state_history_available(&db, block_number)?;
+            let block_hash = db
+                .get_block_hash_by_number(block_number)?
+                .context("block hash")?;
+            db.get_block(&block_hash)?.context("block")?
+        }
+        _ => tip_block,
+    };
+    let block_number: u64 = block.raw().number().unpack();
+    let post_account = block.raw().post_account();
+
+    let mut state = BlockStateDB::from_store(MemStore::new(db), RWConfig::detach_block())?;
+    tokio::task::block_in_place(|| -> anyhow::Result<()> {
+        for number in (block_number + 1..=tip_number).rev() {
+            state.detach_block_state(number)?;
+        }
+        Ok(())
+    })?;
+    let smt = state.inner_smt_tree();
+    let root: H256 = (*smt.root()).into();
+    let expected_root: H256 = post_account.merkle_root().unpack();
+    if root != expected_root {
+        return Err(anyhow!(
+            "reverted state root {} mismatches block {} root {}",
+            root.pack(),
+            block_number,
+            expected_root.pack()
+        )
+        .into());
+    }
+
+    let mut storage_keys: Vec<H256> = keys.into_iter().map(to_h256).collect();
+    storage_keys.sort_unstable();
+    storage_keys.dedup();
+
+    let script_hash_key = build_account_field_key(account_id, GW_ACCOUNT_SCRIPT_HASH_TYPE);
+    let nonce_key = build_account_field_key(account_id, GW_ACCOUNT_NONCE_TYPE);
+    let mut leaf_keys: Vec<SMTH256> = vec![script_hash_key.into(), nonce_key.into()];
+    leaf_keys.extend(
+        storage_keys
+            .iter()
+            .map(|key| SMTH256::from(build_account_key(account_id, key.as_slice()))),
+    );
+    let merkle_proof = smt.merkle_proof(leaf_keys.clone())?.compile(leaf_keys)?;
+
+    let script_hash: H256 = smt.get(&script_hash_key.into())?.into();
+    let nonce: H256 = smt.get(&nonce_key.into())?.into();
+    let storage = storage_keys
+        .into_iter()
+        .map(|key| {
+            let value: H256 = smt
+                .get(&build_account_key(account_id, key.as_slice()).into())?
+                .into();
+            Ok(StorageValue {
+                key: to_jsonh256(key),
+                value: to_jsonh256(value),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(AccountProof {
+        block_number: block_number.into(),
+        block_hash: to_jsonh256(block.hash()),
+        account_merkle_state: post_account.into(),
+        account_id: account_id.into(),
+        script_hash: to_jsonh256(script_hash),
+        nonce: nonce.to_u32().into(),
+        storage,
+        merkle_proof: JsonBytes::from_vec(merkle_proof.0),
+    })
+}
+
 #[instrument(skip_all)]
 async fn gw_get_account_id_by_script_hash(
     ctx: &Registry,
@@ -1934,6 +3046,7 @@ async fn gw_get_nonce(
     let nonce = match block_number {
         Some(block_number) => {
             let mut db = ctx.store.begin_transaction();
+            check_state_history_available(&db, block_number.into())?;
             let tree =
                 BlockStateDB::from_store(&mut db, RWConfig::history_block(block_number.into()))?;
             tree.get_nonce(account_id.into())?
@@ -1975,6 +3088,103 @@ async fn gw_get_script_hash_by_registry_address(
     Ok(script_hash_opt.map(to_jsonh256))
 }
 
+fn check_account_history_params(
+    ctx: &Registry,
+    limit: Uint32,
+    cursor: Option<JsonBytes>,
+) -> Result<(usize, Option<HistoryCursor>)> {
+    if !ctx.store.index_account_history() {
+        return Err(rpc_error(
+            ErrorCode::InvalidRequest,
+            "account history is not indexed, enable store.index_account_history",
+        ));
+    }
+    let limit = limit.value();
+    if limit == 0 || limit > MAX_ACCOUNT_HISTORY_LIMIT {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("limit should be in [1, {}]", MAX_ACCOUNT_HISTORY_LIMIT),
+        ));
+    }
+    let cursor = match cursor.map(JsonBytes::into_bytes) {
+        Some(c) if c.is_empty() => None,
+        Some(c) if c.len() == 12 => {
+            let block_number = u64::from_be_bytes(c[..8].try_into().unwrap());
+            let index = u32::from_be_bytes(c[8..].try_into().unwrap());
+            Some((block_number, index))
+        }
+        Some(_) => return Err(rpc_error(ErrorCode::InvalidParams, "invalid cursor")),
+        None => None,
+    };
+    Ok((limit as usize, cursor))
+}
+
+fn account_history_cursor(items: &[HistoryItem]) -> JsonBytes {
+    match items.last() {
+        Some(item) => {
+            let mut cursor = item.block_number.to_be_bytes().to_vec();
+            cursor.extend_from_slice(&item.index.to_be_bytes());
+            JsonBytes::from_vec(cursor)
+        }
+        None => JsonBytes::default(),
+    }
+}
+
+#[instrument(skip_all)]
+async fn gw_get_transactions_by_account(
+    ctx: &Registry,
+    account_id: AccountID,
+    limit: Uint32,
+    cursor: Option<JsonBytes>,
+) -> Result<AccountHistoryPage<AccountTransaction>> {
+    let (limit, cursor) = check_account_history_params(ctx, limit, cursor)?;
+    let snap = ctx.store.get_snapshot();
+    let items = snap.get_account_transactions(account_id.value(), cursor, limit);
+    Ok(AccountHistoryPage {
+        last_cursor: account_history_cursor(&items),
+        objects: items
+            .into_iter()
+            .map(|item| AccountTransaction {
+                transaction_hash: to_jsonh256(item.hash),
+                block_number: item.block_number.into(),
+                transaction_index: item.index.into(),
+            })
+            .collect(),
+    })
+}
+
+#[instrument(skip_all)]
+async fn gw_get_withdrawals_by_address(
+    ctx: &Registry,
+    address: RegistryAddressJsonBytes,
+    limit: Uint32,
+    cursor: Option<JsonBytes>,
+) -> Result<AccountHistoryPage<AccountWithdrawal>> {
+    let (limit, cursor) = check_account_history_params(ctx, limit, cursor)?;
+    let script_hash = {
+        let state = ctx.mem_pool_state.load_state_db();
+        state.get_script_hash_by_registry_address(&address.0)?
+    };
+    let items = match script_hash {
+        Some(script_hash) => {
+            let snap = ctx.store.get_snapshot();
+            snap.get_account_withdrawals(&script_hash, cursor, limit)
+        }
+        None => Vec::new(),
+    };
+    Ok(AccountHistoryPage {
+        last_cursor: account_history_cursor(&items),
+        objects: items
+            .into_iter()
+            .map(|item| AccountWithdrawal {
+                withdrawal_hash: to_jsonh256(item.hash),
+                block_number: item.block_number.into(),
+                withdrawal_index: item.index.into(),
+            })
+            .collect(),
+    })
+}
+
 #[instrument(skip_all)]
 async fn gw_get_registry_address_by_script_hash(
     ctx: &Registry,
@@ -2006,6 +3216,7 @@ fn get_backend_info(generator: Arc<Generator>) -> Vec<BackendInfo> {
 fn to_rpc_backend_type(b_type: &gw_config::BackendType) -> BackendType {
     match b_type {
         gw_config::BackendType::EthAddrReg => BackendType::EthAddrReg,
+        gw_config::BackendType::CkbAddrReg => BackendType::CkbAddrReg,
         gw_config::BackendType::Meta => BackendType::Meta,
         gw_config::BackendType::Sudt => BackendType::Sudt,
         gw_config::BackendType::Polyjuice => BackendType::Polyjuice,
@@ -2166,7 +3377,8 @@ pub fn to_eoa_scripts(
 pub fn to_rpc_node_mode(node_mode: &NodeMode) -> RpcNodeMode {
     match node_mode {
         NodeMode::FullNode => RpcNodeMode::FullNode,
-        NodeMode::ReadOnly => RpcNodeMode::ReadOnly,
+        // Secondary nodes serve RPC the same way as read only nodes.
+        NodeMode::ReadOnly | NodeMode::Secondary => RpcNodeMode::ReadOnly,
         NodeMode::Test => RpcNodeMode::Test,
     }
 }
@@ -2,7 +2,7 @@ use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use axum::{
-    extract::State,
+    extract::{State, WebSocketUpgrade},
     http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{get, post},
@@ -16,7 +16,11 @@ use gw_telemetry::{
 use gw_utils::liveness::Liveness;
 use hyper::server::conn::AddrIncoming;
 use jsonrpc_core::MetaIoHandler;
-use jsonrpc_utils::{axum_utils::handle_jsonrpc, pub_sub::Session};
+use jsonrpc_utils::{
+    axum_utils::{handle_jsonrpc, handle_jsonrpc_ws},
+    pub_sub::Session,
+    stream::StreamServerConfig,
+};
 use tokio::{
     net::TcpListener,
     sync::{broadcast, mpsc},
@@ -34,7 +38,7 @@ pub async fn start_jsonrpc_server(
 
     // Format the full address.
     let url = format!("http://{}", listener.local_addr()?);
-    log::info!("JSONRPC server listening on {}", url);
+    log::info!("JSONRPC server listening on {} (websocket: GET)", url);
 
     let mut incoming = AddrIncoming::from_listener(listener)?;
     incoming.set_keepalive(Some(Duration::from_secs(10)));
@@ -44,8 +48,14 @@ pub async fn start_jsonrpc_server(
         .route("/livez", get(serve_liveness))
         .with_state(liveness)
         .route("/metrics", get(serve_metrics))
-        .route("/", post(handle_jsonrpc_with_tracing))
-        .route("/*path", post(handle_jsonrpc_with_tracing))
+        .route(
+            "/",
+            post(handle_jsonrpc_with_tracing).get(handle_jsonrpc_ws_upgrade),
+        )
+        .route(
+            "/*path",
+            post(handle_jsonrpc_with_tracing).get(handle_jsonrpc_ws_upgrade),
+        )
         .with_state(handler);
 
     let server = axum::Server::builder(incoming).serve(app.into_make_service());
@@ -71,6 +81,14 @@ async fn handle_jsonrpc_with_tracing(
         .await
 }
 
+async fn handle_jsonrpc_ws_upgrade(
+    State(handler): State<Arc<MetaIoHandler<Option<Session>>>>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let config = StreamServerConfig::default().with_keep_alive(true);
+    handle_jsonrpc_ws(Extension(handler), Extension(config), ws).await
+}
+
 async fn serve_liveness(l: State<Arc<Liveness>>) -> impl IntoResponse {
     if l.is_live() {
         StatusCode::OK
@@ -0,0 +1,138 @@
+//! State overrides of `gw_execute_raw_l2transaction`.
+//!
+//! Overrides are written to the state used by the execution only, they are
+//! discarded with it.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::{
+    blake2b::new_blake2b, builtins::CKB_SUDT_ACCOUNT_ID, registry::Registries, state::State,
+};
+use gw_generator::traits::StateExt;
+use gw_jsonrpc_types::godwoken::{AccountOverride, StateOverride};
+use gw_store::state::traits::JournalDB;
+use gw_traits::CodeStore;
+use gw_types::{h256::*, packed::Script, prelude::*};
+use gw_utils::RollupContext;
+
+/// See `POLYJUICE_SYSTEM_PREFIX` in polyjuice.h.
+const POLYJUICE_SYSTEM_PREFIX: u8 = 0xff;
+/// See `POLYJUICE_CONTRACT_CODE` in polyjuice.h.
+const POLYJUICE_CONTRACT_CODE: u8 = 0x01;
+
+fn polyjuice_contract_code_key(account_id: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key[..4].copy_from_slice(&account_id.to_le_bytes());
+    key[4] = POLYJUICE_SYSTEM_PREFIX;
+    key[5] = POLYJUICE_CONTRACT_CODE;
+    key
+}
+
+/// Checks that don't need the state.
+pub(crate) fn check_state_override(state_override: &StateOverride) -> Result<()> {
+    for (script_hash, account) in state_override {
+        if let Some(ref script) = account.script {
+            let script: Script = script.clone().into();
+            ensure!(
+                script.hash() == script_hash.0,
+                "account {:#x}: script hash mismatch",
+                script_hash
+            );
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn apply_state_override<S: State + CodeStore + JournalDB>(
+    state: &mut S,
+    rollup_context: &RollupContext,
+    block_number: u64,
+    state_override: &StateOverride,
+) -> Result<()> {
+    let registries = rollup_context.registries(state, block_number)?;
+    // Sort to create new accounts in a deterministic order.
+    let mut accounts: Vec<_> = state_override.iter().collect();
+    accounts.sort_unstable_by_key(|(script_hash, _)| script_hash.0);
+    for (script_hash, account) in accounts {
+        apply_account_override(state, &registries, script_hash.0, account)
+            .with_context(|| format!("account {:#x}", script_hash))?;
+    }
+    Ok(())
+}
+
+fn apply_account_override<S: State + CodeStore + JournalDB>(
+    state: &mut S,
+    registries: &Registries,
+    script_hash: H256,
+    account: &AccountOverride,
+) -> Result<()> {
+    if let Some(ref script) = account.script {
+        if state.get_account_id_by_script_hash(&script_hash)?.is_none() {
+            state.create_account_from_script(script.clone().into())?;
+        }
+    }
+    let account_id = state
+        .get_account_id_by_script_hash(&script_hash)?
+        .ok_or_else(|| anyhow!("account not found, set `script` to create it"))?;
+
+    if let Some(ref address) = account.registry_address {
+        let valid = registries
+            .get(address.0.registry_id)
+            .map_or(false, |registry| {
+                address.0.address.len() == registry.address_len()
+            });
+        ensure!(valid, "invalid registry address");
+        match state.get_registry_address_by_script_hash(address.0.registry_id, &script_hash)? {
+            Some(mapped) if mapped != address.0 => {
+                bail!("account is mapped to another registry address")
+            }
+            Some(_) => {}
+            None => state.mapping_registry_address_to_script_hash_in(
+                registries,
+                address.0.clone(),
+                script_hash,
+            )?,
+        }
+    }
+
+    if let Some(nonce) = account.nonce {
+        state.set_nonce(account_id, nonce.value())?;
+    }
+
+    if let Some(balance) = account.balance {
+        let address = state
+            .find_registry_address_by_script_hash(registries, &script_hash)?
+            .ok_or_else(|| anyhow!("balance needs a registry address"))?;
+        // Mint or burn the difference to keep the total supply consistent.
+        let current = state.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &address)?;
+        if balance > current {
+            state.mint_sudt(CKB_SUDT_ACCOUNT_ID, &address, balance - current)?;
+        } else {
+            state.burn_sudt(CKB_SUDT_ACCOUNT_ID, &address, current - balance)?;
+        }
+    }
+
+    if let Some(ref code) = account.code {
+        let code = code.clone().into_bytes();
+        let data_hash = {
+            let mut hasher = new_blake2b();
+            hasher.update(&code);
+            let mut hash = [0u8; 32];
+            hasher.finalize(&mut hash);
+            hash
+        };
+        state.insert_data(data_hash, code);
+        state.store_data_hash(data_hash)?;
+        state.update_value(
+            account_id,
+            &polyjuice_contract_code_key(account_id),
+            data_hash,
+        )?;
+    }
+
+    if let Some(ref storage) = account.storage {
+        for (key, value) in storage {
+            state.update_value(account_id, &key.0, value.0)?;
+        }
+    }
+    Ok(())
+}
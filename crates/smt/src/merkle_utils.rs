@@ -1,6 +1,6 @@
 use crate::{
     blake2b::new_blake2b,
-    smt::{default_store::DefaultStore, Error, SMT, SMTH256},
+    smt::{default_store::DefaultStore, Blake2bHasher, CompiledMerkleProof, Error, SMT, SMTH256},
     smt_h256_ext::SMTH256Ext,
 };
 use gw_types::h256::{H256Ext, H256};
@@ -26,3 +26,18 @@ pub fn calculate_merkle_root(leaves: Vec<H256>) -> Result<H256, Error> {
     }
     Ok((*tree.root()).into())
 }
+
+/// Verify a compiled merkle proof of `leaves` against the merkle `root`.
+///
+/// Leaves are `(key, value)` pairs, the value of an absent key is zero.
+pub fn verify_merkle_proof(
+    root: &H256,
+    proof: Vec<u8>,
+    leaves: Vec<(H256, H256)>,
+) -> Result<bool, Error> {
+    let leaves = leaves
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect();
+    CompiledMerkleProof(proof).verify::<Blake2bHasher>(&(*root).into(), leaves)
+}
@@ -0,0 +1,208 @@
+//! Per-account transaction and withdrawal history.
+//!
+//! See [`COLUMN_ACCOUNT_TRANSACTION`] and [`COLUMN_ACCOUNT_WITHDRAWAL`] for
+//! the key layout. Items of an account are ordered by (block number, index).
+//!
+//! Blocks are indexed when they are attached. Blocks attached before the index
+//! is enabled are indexed by [`Store::backfill_account_history`].
+
+use anyhow::{Context, Result};
+use autorocks::{DbIterator, Direction};
+use gw_types::{h256::H256, packed, prelude::*};
+
+use crate::{
+    schema::{
+        COLUMN_ACCOUNT_TRANSACTION, COLUMN_ACCOUNT_WITHDRAWAL, COLUMN_META,
+        META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY,
+    },
+    snapshot::StoreSnapshot,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreRead, KVStoreWrite},
+    },
+    transaction::StoreTransaction,
+    Store,
+};
+
+/// Backfill progress is committed every this number of blocks.
+const BACKFILL_COMMIT_INTERVAL: u64 = 1000;
+
+/// (block number, index in block).
+pub type HistoryCursor = (u64, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryItem {
+    pub block_number: u64,
+    pub index: u32,
+    /// Transaction or withdrawal hash.
+    pub hash: H256,
+}
+
+fn history_key(prefix: &[u8], (block_number, index): HistoryCursor) -> Vec<u8> {
+    let mut key = Vec::with_capacity(prefix.len() + 12);
+    key.extend_from_slice(prefix);
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+/// Newest first. Items at or after `before` are skipped.
+fn iter_history<T>(
+    mut iter: DbIterator<T>,
+    prefix: &[u8],
+    before: Option<HistoryCursor>,
+    limit: usize,
+) -> Vec<HistoryItem> {
+    let seek_key = history_key(prefix, before.unwrap_or((u64::MAX, u32::MAX)));
+    iter.seek_for_prev(&seek_key);
+    iter.take_while(|(key, _)| key.starts_with(prefix))
+        .filter(|(key, _)| before.is_none() || key[..] != seek_key[..])
+        .take(limit)
+        .map(|(key, value)| {
+            let suffix = &key[prefix.len()..];
+            HistoryItem {
+                block_number: u64::from_be_bytes(suffix[..8].try_into().unwrap()),
+                index: u32::from_be_bytes(suffix[8..].try_into().unwrap()),
+                hash: value[..].try_into().unwrap(),
+            }
+        })
+        .collect()
+}
+
+/// Sender and receiver of the transaction.
+fn tx_accounts(tx: &packed::L2Transaction) -> Vec<u32> {
+    let from_id: u32 = tx.raw().from_id().unpack();
+    let to_id: u32 = tx.raw().to_id().unpack();
+    if from_id == to_id {
+        vec![from_id]
+    } else {
+        vec![from_id, to_id]
+    }
+}
+
+impl StoreTransaction {
+    pub(crate) fn insert_account_history(&mut self, block: &packed::L2Block) -> Result<()> {
+        let block_number = block.raw().number().unpack();
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let tx_hash = tx.hash();
+            for account_id in tx_accounts(&tx) {
+                let key = history_key(&account_id.to_be_bytes(), (block_number, index as u32));
+                self.insert_raw(COLUMN_ACCOUNT_TRANSACTION, &key, &tx_hash)?;
+            }
+        }
+        for (index, withdrawal) in block.withdrawals().into_iter().enumerate() {
+            let account_script_hash = withdrawal.raw().account_script_hash();
+            let key = history_key(account_script_hash.as_slice(), (block_number, index as u32));
+            self.insert_raw(COLUMN_ACCOUNT_WITHDRAWAL, &key, &withdrawal.hash())?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn delete_account_history(&mut self, block: &packed::L2Block) -> Result<()> {
+        let block_number = block.raw().number().unpack();
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            for account_id in tx_accounts(&tx) {
+                let key = history_key(&account_id.to_be_bytes(), (block_number, index as u32));
+                self.delete(COLUMN_ACCOUNT_TRANSACTION, &key)?;
+            }
+        }
+        for (index, withdrawal) in block.withdrawals().into_iter().enumerate() {
+            let account_script_hash = withdrawal.raw().account_script_hash();
+            let key = history_key(account_script_hash.as_slice(), (block_number, index as u32));
+            self.delete(COLUMN_ACCOUNT_WITHDRAWAL, &key)?;
+        }
+        Ok(())
+    }
+}
+
+impl Store {
+    /// Index account history of the blocks attached before
+    /// `index_account_history` was enabled, resuming from the last committed
+    /// progress.
+    ///
+    /// If the index is disabled, the progress is removed, because blocks
+    /// attached from now on are not indexed. Enabling it again backfills from
+    /// scratch.
+    pub fn backfill_account_history(&self) -> Result<()> {
+        let mut db = self.begin_transaction();
+        let indexed_number = db
+            .get(COLUMN_META, META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY)
+            .map(|v| u64::from_be_bytes(v.as_ref().try_into().expect("u64")));
+        if !self.index_account_history() {
+            if indexed_number.is_some() {
+                db.delete(COLUMN_META, META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY)?;
+                db.commit()?;
+            }
+            return Ok(());
+        }
+        let start = match indexed_number {
+            Some(u64::MAX) => return Ok(()),
+            Some(number) => number,
+            None => db.get_earliest_block_number(),
+        };
+        // An empty store, all blocks will be indexed when attached.
+        let end = if db.has_genesis()? {
+            db.get_last_valid_tip_block()?.raw().number().unpack() + 1
+        } else {
+            start
+        };
+
+        if start < end {
+            log::info!("backfill account history of blocks [{}, {})", start, end);
+        }
+        for number in start..end {
+            let block_hash = db
+                .get_block_hash_by_number(number)?
+                .with_context(|| format!("block hash {}", number))?;
+            let block = db
+                .get_block(&block_hash)?
+                .with_context(|| format!("block {}", number))?;
+            db.insert_account_history(&block)?;
+            if (number + 1) % BACKFILL_COMMIT_INTERVAL == 0 {
+                db.insert_raw(
+                    COLUMN_META,
+                    META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY,
+                    &(number + 1).to_be_bytes(),
+                )?;
+                db.commit()?;
+                db = self.begin_transaction();
+                log::info!("account history backfilled to block {}", number);
+            }
+        }
+        db.insert_raw(
+            COLUMN_META,
+            META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY,
+            &u64::MAX.to_be_bytes(),
+        )?;
+        db.commit()?;
+        Ok(())
+    }
+}
+
+impl StoreSnapshot {
+    /// Transactions sent from or to the account, newest first.
+    pub fn get_account_transactions(
+        &self,
+        account_id: u32,
+        before: Option<HistoryCursor>,
+        limit: usize,
+    ) -> Vec<HistoryItem> {
+        let iter = self
+            .inner
+            .iter(COLUMN_ACCOUNT_TRANSACTION, Direction::Backward);
+        iter_history(iter, &account_id.to_be_bytes(), before, limit)
+    }
+
+    /// Withdrawals of the account, newest first.
+    pub fn get_account_withdrawals(
+        &self,
+        account_script_hash: &H256,
+        before: Option<HistoryCursor>,
+        limit: usize,
+    ) -> Vec<HistoryItem> {
+        let iter = self
+            .inner
+            .iter(COLUMN_ACCOUNT_WITHDRAWAL, Direction::Backward);
+        iter_history(iter, account_script_hash, before, limit)
+    }
+}
@@ -1,5 +1,6 @@
 pub extern crate autorocks;
 
+pub mod account_history;
 pub mod chain_view;
 pub mod mem_pool_state;
 pub mod migrate;
@@ -8,6 +9,7 @@ pub mod schema;
 pub mod smt;
 pub mod snapshot;
 pub mod state;
+pub mod state_snapshot;
 mod store_impl;
 pub mod traits;
 pub mod transaction;
@@ -2,7 +2,7 @@
 // And check present db version is still compatible. Godwoken must run on a valid db.
 // If godwoken with an advanced verion runs on an old db, this is the time we can run migrations.
 
-use std::{cmp::Ordering, collections::BTreeMap};
+use std::{cmp::Ordering, collections::BTreeMap, path::Path};
 
 use anyhow::{bail, Result};
 use autorocks::{
@@ -55,6 +55,23 @@ pub fn open_or_create_db(config: &StoreConfig, factory: MigrationFactory) -> Res
     }
 }
 
+/// Open the db as a secondary instance. Migrations must have been done by the
+/// primary instance.
+pub fn open_secondary_db(
+    config: &StoreConfig,
+    secondary_path: &Path,
+    factory: MigrationFactory,
+) -> Result<TransactionDb> {
+    let read_only_db = DbOptions::new(&config.path, 1).open_read_only()?;
+    if check_readonly_db_version(&read_only_db, factory.last_db_version())? != Ordering::Equal {
+        bail!(
+            "The database version doesn't match, please upgrade and start the primary node first"
+        );
+    }
+    drop(read_only_db);
+    Ok(Store::open_secondary(config, secondary_path, COLUMNS)?.into_inner())
+}
+
 //TODO: Replace with migration db version when we have our first migration impl.
 pub(crate) fn init_db_version(db: &TransactionDb, db_ver: Option<&str>) -> Result<()> {
     if let Some(db_ver) = db_ver {
@@ -254,6 +271,9 @@ mod tests {
             path: dir.path().to_owned(),
             options_file: None,
             cache_size: None,
+            index_account_history: false,
+            state_history: Default::default(),
+            secondary: None,
         };
         let old_db = Store::open(&config, COLUMNS)?.into_inner();
         let factory = init_migration_factory();
@@ -280,6 +300,9 @@ mod tests {
             path: dir.path().to_owned(),
             options_file: None,
             cache_size: None,
+            index_account_history: false,
+            state_history: Default::default(),
+            secondary: None,
         };
         let db = open_or_create_db(&config, init_migration_factory())?;
         {
@@ -3,7 +3,7 @@
 /// Column families alias type
 pub type Col = usize;
 /// Total column number
-pub const COLUMNS: usize = 37;
+pub const COLUMNS: usize = 43;
 /// Column store meta data
 pub const COLUMN_META: Col = 0;
 /// Column store chain index
@@ -79,6 +79,35 @@ pub const COLUMN_BLOCK_SUBMIT_TX_HASH: Col = 7;
 pub const COLUMN_BLOCK_DEPOSIT_INFO_VEC: Col = 16;
 /// block number (in big endian) -> FinalizedCustodianCapacity.
 pub const COLUMN_BLOCK_POST_FINALIZED_CUSTODIAN_CAPACITY: Col = 36;
+/// account id (big endian) | block number (big endian) | tx index (big endian)
+/// -> tx hash.
+///
+/// Transactions sent from or to the account. Only filled when
+/// `store.index_account_history` is enabled.
+pub const COLUMN_ACCOUNT_TRANSACTION: Col = 37;
+/// account script hash | block number (big endian) | withdrawal index (big
+/// endian) -> withdrawal hash.
+///
+/// Only filled when `store.index_account_history` is enabled.
+pub const COLUMN_ACCOUNT_WITHDRAWAL: Col = 38;
+/// Hash in the fee queue -> L2Transaction.
+///
+/// Transactions submitted to the fee queue but not pushed to the mem pool yet,
+/// replayed on restart.
+pub const COLUMN_QUEUED_TRANSACTION: Col = 39;
+/// Withdrawal hash -> WithdrawalRequestExtra.
+///
+/// Withdrawals submitted to the fee queue but not pushed to the mem pool yet,
+/// replayed on restart.
+pub const COLUMN_QUEUED_WITHDRAWAL: Col = 40;
+/// Withdrawal hash -> hash of the L1 tx unlocking the withdrawal cell to its
+/// owner.
+pub const COLUMN_WITHDRAWAL_UNLOCK_TX: Col = 41;
+/// L1 block number (big endian) | withdrawal hash -> empty.
+///
+/// Entries of `COLUMN_WITHDRAWAL_UNLOCK_TX` by the L1 block of the unlock tx,
+/// to remove them when the L1 block is reverted.
+pub const COLUMN_WITHDRAWAL_UNLOCK_L1_BLOCK: Col = 42;
 
 /// chain id
 pub const META_CHAIN_ID_KEY: &[u8] = b"CHAIN_ID";
@@ -95,6 +124,27 @@ pub const META_LAST_VALID_TIP_BLOCK_HASH_KEY: &[u8] = b"LAST_VALID_TIP_BLOCK_HAS
 pub const META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY: &[u8] = b"LAST_CONFIRMED_BLOCK_NUMBER";
 /// track the last submitted l2 block NumberAndHash
 pub const META_LAST_SUBMITTED_BLOCK_NUMBER_HASH_KEY: &[u8] = b"LAST_SUBMITTED_BLOCK_NUMBER";
+/// State history of the blocks before this block number (u64, big endian) is
+/// pruned, except for the checkpoints.
+pub const META_STATE_HISTORY_PRUNED_NUMBER_KEY: &[u8] = b"STATE_HISTORY_PRUNED_NUMBER";
+/// Checkpoint interval (u64, big endian) of the pruned state history, 0 means
+/// no checkpoints.
+pub const META_STATE_HISTORY_CHECKPOINT_INTERVAL_KEY: &[u8] = b"STATE_HISTORY_CHECKPOINT_INTERVAL";
+/// State history of the blocks before this block number (u64, big endian) is
+/// not in the store, checkpoints included. Set when the store is restored from
+/// a state snapshot.
+pub const META_STATE_HISTORY_START_NUMBER_KEY: &[u8] = b"STATE_HISTORY_START_NUMBER";
+/// Blocks before this block number (u64, big endian) are not in the store, only
+/// their hashes are indexed. Set when the store is restored from a state
+/// snapshot.
+pub const META_EARLIEST_BLOCK_NUMBER_KEY: &[u8] = b"EARLIEST_BLOCK_NUMBER";
+/// Set before anything else is written when a state snapshot is imported, and
+/// removed once the restored state is checked. A store with it is incomplete.
+pub const META_STATE_RESTORING_KEY: &[u8] = b"STATE_RESTORING";
+/// Account history of the blocks before this block number (u64, big endian) is
+/// backfilled, `u64::MAX` when all blocks are indexed. Removed when
+/// `store.index_account_history` is disabled.
+pub const META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY: &[u8] = b"ACCOUNT_HISTORY_INDEXED_NUMBER";
 
 /// CHAIN_SPEC_HASH_KEY tracks the hash of chain spec which created current database
 pub const CHAIN_SPEC_HASH_KEY: &[u8] = b"chain-spec-hash";
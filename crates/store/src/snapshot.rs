@@ -6,7 +6,7 @@ use crate::{
 };
 
 pub struct StoreSnapshot {
-    inner: Snapshot,
+    pub(crate) inner: Snapshot,
 }
 
 impl StoreSnapshot {
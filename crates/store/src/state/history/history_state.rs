@@ -163,6 +163,10 @@ impl<Store: HistoryStateStore + CodeStore + KVStore> HistoryState<Store> {
             .build()
     }
 
+    pub fn smt(&self) -> &SMT<SMTStateStore<Store>> {
+        &self.tree
+    }
+
     /// Detach block state from state tree
     pub fn detach_block_state(&mut self, block_number: u64) -> Result<()> {
         // reset states to previous value
@@ -1,2 +1,3 @@
 pub mod block_state_record;
 pub mod history_state;
+pub mod pruning;
@@ -0,0 +1,118 @@
+//! State history pruning.
+//!
+//! Pruning the history up to block `n` removes every record that is shadowed
+//! by a newer record of the same state key at or before `n`, so the state of
+//! block `n` and later blocks can still be read. Records needed by the state of
+//! checkpoint blocks (multiples of the checkpoint interval) are kept.
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+use autorocks::Direction;
+use gw_types::h256::H256;
+
+use crate::{
+    schema::{
+        COLUMN_BLOCK_STATE_RECORD, COLUMN_BLOCK_STATE_REVERSE_RECORD, COLUMN_META,
+        META_STATE_HISTORY_CHECKPOINT_INTERVAL_KEY, META_STATE_HISTORY_PRUNED_NUMBER_KEY,
+    },
+    state::history::{
+        block_state_record::{BlockStateRecordKey, BlockStateRecordKeyReverse},
+        history_state::HistoryStateStore,
+    },
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    transaction::StoreTransaction,
+};
+
+/// The state history of the block is pruned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateHistoryPruned {
+    pub block_number: u64,
+    pub pruned_number: u64,
+}
+
+impl fmt::Display for StateHistoryPruned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "state history of block {} is pruned, available since block {}",
+            self.block_number, self.pruned_number
+        )
+    }
+}
+
+impl std::error::Error for StateHistoryPruned {}
+
+impl StoreTransaction {
+    /// Prune the state history of the blocks before `block_number`.
+    pub fn prune_state_history(
+        &mut self,
+        block_number: u64,
+        checkpoint_interval: Option<u64>,
+    ) -> Result<()> {
+        let pruned_number = self.get_state_history_pruned_number();
+        if block_number <= pruned_number {
+            return Ok(());
+        }
+        let current_interval = self.get_state_history_checkpoint_interval();
+        // A store restored from a state snapshot has no checkpoints before
+        // the snapshot block, the interval can be set by the first pruning.
+        if pruned_number > self.get_state_history_start_number()
+            && current_interval != checkpoint_interval
+        {
+            bail!(
+                "state history checkpoint interval can't be changed once pruned, current: {:?}",
+                current_interval
+            );
+        }
+
+        for number in pruned_number + 1..=block_number {
+            for record_key in self.iter_block_state_record(number) {
+                self.prune_prev_state_record(number, &record_key.state_key(), checkpoint_interval)?;
+            }
+        }
+        self.insert_raw(
+            COLUMN_META,
+            META_STATE_HISTORY_PRUNED_NUMBER_KEY,
+            &block_number.to_be_bytes(),
+        )?;
+        self.insert_raw(
+            COLUMN_META,
+            META_STATE_HISTORY_CHECKPOINT_INTERVAL_KEY,
+            &checkpoint_interval.unwrap_or(0).to_be_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Remove the record of `state_key` before `block_number`, unless it's
+    /// needed by a checkpoint.
+    fn prune_prev_state_record(
+        &mut self,
+        block_number: u64,
+        state_key: &H256,
+        checkpoint_interval: Option<u64>,
+    ) -> Result<()> {
+        let prev_key = {
+            let key = BlockStateRecordKeyReverse::new(block_number - 1, state_key);
+            let mut iter = self.get_iter(COLUMN_BLOCK_STATE_REVERSE_RECORD, Direction::Forward);
+            iter.seek_for_prev(key.as_slice());
+            match iter.key() {
+                Some(prev_key) if &prev_key[..32] == state_key.as_slice() => {
+                    BlockStateRecordKeyReverse::from_slice(prev_key)
+                }
+                _ => return Ok(()),
+            }
+        };
+        let prev_number = prev_key.block_number();
+        if let Some(interval) = checkpoint_interval {
+            let next_checkpoint = (prev_number + interval - 1) / interval * interval;
+            if next_checkpoint < block_number {
+                return Ok(());
+            }
+        }
+        let forward_key = BlockStateRecordKey::new(prev_number, state_key);
+        self.delete(COLUMN_BLOCK_STATE_RECORD, forward_key.as_slice())?;
+        self.delete(COLUMN_BLOCK_STATE_REVERSE_RECORD, prev_key.as_slice())?;
+        Ok(())
+    }
+}
@@ -28,7 +28,10 @@ use gw_types::h256::{H256Ext, H256};
 use crate::{
     smt::smt_store::SMTStateStore,
     snapshot::StoreSnapshot,
-    state::history::history_state::{HistoryState, HistoryStateStore},
+    state::history::{
+        history_state::{HistoryState, HistoryStateStore, ReadOpt},
+        pruning::StateHistoryPruned,
+    },
     traits::{chain_store::ChainStore, kv_store::KVStore},
 };
 
@@ -183,6 +186,15 @@ impl MemStateDB {
 impl<Store: ChainStore + HistoryStateStore + CodeStore + KVStore> BlockStateDB<Store> {
     /// From store
     pub fn from_store(store: Store, rw_config: RWConfig) -> Result<Self> {
+        if let ReadOpt::Block(block_number) = rw_config.read {
+            if !store.is_state_history_available(block_number) {
+                return Err(StateHistoryPruned {
+                    block_number,
+                    pruned_number: store.get_state_history_pruned_number(),
+                }
+                .into());
+            }
+        }
         // build from last valid block
         let block = store.get_last_valid_tip_block()?;
         let tip_state = block.raw().post_account();
@@ -192,6 +204,10 @@ impl<Store: ChainStore + HistoryStateStore + CodeStore + KVStore> BlockStateDB<S
         Ok(Self::new(inner))
     }
 
+    pub fn inner_smt_tree(&self) -> &SMT<SMTStateStore<Store>> {
+        self.state.smt()
+    }
+
     /// Detach block state
     /// The caller must avoid has dirty state, otherwise, the state may inconsisted after the detaching
     pub fn detach_block_state(&mut self, block_number: u64) -> Result<()> {
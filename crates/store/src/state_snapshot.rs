@@ -0,0 +1,176 @@
+//! Reading and restoring the state of a block, used to export and import
+//! state snapshots.
+//!
+//! A restored store only has the state of the snapshot block and the blocks
+//! after `ChainStore::get_earliest_block_number`; earlier blocks are indexed
+//! by hash only.
+
+use anyhow::{Context, Result};
+use autorocks::Direction;
+use gw_types::{
+    bytes::Bytes,
+    from_box_should_be_ok,
+    h256::*,
+    packed::{self, RawL2Block, Script, TransactionKey, WithdrawalKey},
+    prelude::*,
+};
+
+use crate::{
+    schema::{
+        COLUMN_ASSET_SCRIPT, COLUMN_BLOCK, COLUMN_BLOCK_GLOBAL_STATE, COLUMN_BLOCK_STATE_RECORD,
+        COLUMN_BLOCK_STATE_REVERSE_RECORD, COLUMN_DATA, COLUMN_INDEX, COLUMN_META, COLUMN_SCRIPT,
+        COLUMN_TRANSACTION, COLUMN_WITHDRAWAL, META_EARLIEST_BLOCK_NUMBER_KEY,
+        META_STATE_HISTORY_PRUNED_NUMBER_KEY, META_STATE_HISTORY_START_NUMBER_KEY,
+        META_STATE_RESTORING_KEY,
+    },
+    snapshot::StoreSnapshot,
+    state::history::block_state_record::{BlockStateRecordKey, BlockStateRecordKeyReverse},
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    transaction::StoreTransaction,
+};
+
+impl StoreSnapshot {
+    /// Non-zero state of the block, i.e. the latest history record of every
+    /// state key at or before the block, ordered by state key.
+    ///
+    /// The state history of the block must be available, see
+    /// `ChainStore::is_state_history_available`.
+    pub fn iter_block_state(&self, block_number: u64) -> impl Iterator<Item = (H256, H256)> + '_ {
+        let mut records = self
+            .inner
+            .iter(COLUMN_BLOCK_STATE_REVERSE_RECORD, Direction::Forward)
+            .map(|(key, _)| BlockStateRecordKeyReverse::from_slice(&key))
+            .filter(move |key| key.block_number() <= block_number)
+            .peekable();
+        std::iter::from_fn(move || loop {
+            let key = records.next()?;
+            // Records of a state key are ordered by block number, skip to the
+            // latest one.
+            if matches!(records.peek(), Some(next) if next.state_key() == key.state_key()) {
+                continue;
+            }
+            let record_key = BlockStateRecordKey::new(key.block_number(), &key.state_key());
+            let value: H256 = self
+                .get(COLUMN_BLOCK_STATE_RECORD, record_key.as_slice())
+                .expect("block state record")
+                .as_ref()
+                .try_into()
+                .expect("H256");
+            if !value.is_zero() {
+                return Some((key.state_key(), value));
+            }
+        })
+    }
+
+    pub fn iter_scripts(&self) -> impl Iterator<Item = Script> + '_ {
+        self.inner
+            .iter(COLUMN_SCRIPT, Direction::Forward)
+            .map(|(_, value)| from_box_should_be_ok!(packed::ScriptReader, value))
+    }
+
+    /// (data hash, data)
+    pub fn iter_data(&self) -> impl Iterator<Item = (H256, Bytes)> + '_ {
+        self.inner
+            .iter(COLUMN_DATA, Direction::Forward)
+            .map(|(key, value)| {
+                let data_hash = key.as_ref().try_into().expect("H256");
+                (data_hash, Bytes::from(value.to_vec()))
+            })
+    }
+
+    pub fn iter_asset_scripts(&self) -> impl Iterator<Item = Script> + '_ {
+        self.inner
+            .iter(COLUMN_ASSET_SCRIPT, Direction::Forward)
+            .map(|(_, value)| from_box_should_be_ok!(packed::ScriptReader, value))
+    }
+}
+
+impl StoreTransaction {
+    /// See `ChainStore::is_state_restoring`.
+    pub fn set_state_restoring(&mut self, restoring: bool) -> Result<()> {
+        if restoring {
+            self.insert_raw(COLUMN_META, META_STATE_RESTORING_KEY, &[1])
+        } else {
+            self.delete(COLUMN_META, META_STATE_RESTORING_KEY)
+        }
+    }
+
+    pub fn set_earliest_block_number(&mut self, block_number: u64) -> Result<()> {
+        self.insert_raw(
+            COLUMN_META,
+            META_EARLIEST_BLOCK_NUMBER_KEY,
+            &block_number.to_be_bytes(),
+        )
+    }
+
+    /// State history before the block is not available, used after restoring
+    /// the state of the block. The history is treated as pruned up to the
+    /// block, without checkpoints.
+    pub fn set_state_history_start_number(&mut self, block_number: u64) -> Result<()> {
+        self.insert_raw(
+            COLUMN_META,
+            META_STATE_HISTORY_START_NUMBER_KEY,
+            &block_number.to_be_bytes(),
+        )?;
+        self.insert_raw(
+            COLUMN_META,
+            META_STATE_HISTORY_PRUNED_NUMBER_KEY,
+            &block_number.to_be_bytes(),
+        )
+    }
+
+    /// Index a block that is not in the store and add it to the block SMT.
+    pub fn attach_block_hash(&mut self, block_number: u64, block_hash: &H256) -> Result<()> {
+        let number = block_number.pack();
+        self.insert_raw(COLUMN_INDEX, number.as_slice(), block_hash.as_slice())?;
+        self.insert_raw(COLUMN_INDEX, block_hash.as_slice(), number.as_slice())?;
+
+        let mut block_smt = self.block_smt()?;
+        block_smt
+            .update(
+                RawL2Block::compute_smt_key(block_number).into(),
+                (*block_hash).into(),
+            )
+            .context("update block smt")?;
+        let root = *block_smt.root();
+        self.set_block_smt_root(root.into())
+    }
+
+    /// Like `insert_block`, but the transaction receipts of the block are not
+    /// available and the block state isn't checked. Attach it with
+    /// `attach_block` afterwards.
+    pub fn insert_snapshot_block(
+        &mut self,
+        block: &packed::L2Block,
+        global_state: &packed::GlobalState,
+        deposit_info_vec: &packed::DepositInfoVec,
+        withdrawals: &[packed::WithdrawalRequestExtra],
+    ) -> Result<()> {
+        let block_hash = block.hash();
+        self.insert_raw(COLUMN_BLOCK, &block_hash, block.as_slice())?;
+        self.insert_raw(
+            COLUMN_BLOCK_GLOBAL_STATE,
+            &block_hash,
+            global_state.as_slice(),
+        )?;
+        self.set_block_deposit_info_vec(
+            block.raw().number().unpack(),
+            &deposit_info_vec.as_reader(),
+        )?;
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let key = TransactionKey::new_builder()
+                .block_hash(block_hash.pack())
+                .index(index.pack())
+                .build();
+            self.insert_raw(COLUMN_TRANSACTION, key.as_slice(), tx.as_slice())?;
+        }
+        for (index, withdrawal) in withdrawals.iter().enumerate() {
+            let key = WithdrawalKey::new_builder()
+                .block_hash(block_hash.pack())
+                .index(index.pack())
+                .build();
+            self.insert_raw(COLUMN_WITHDRAWAL, key.as_slice(), withdrawal.as_slice())?;
+        }
+        Ok(())
+    }
+}
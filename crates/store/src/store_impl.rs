@@ -1,5 +1,6 @@
 //! Storage implementation
 
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -10,7 +11,7 @@ use autorocks::moveit::{moveit, slot};
 use autorocks::{DbOptions, TransactionDb, WriteBatch};
 use gw_config::StoreConfig;
 use gw_smt::smt::Blake2bHasher;
-use gw_types::prelude::*;
+use gw_types::{packed::RawL2Block, prelude::*};
 use serde::Serialize;
 use tempfile::TempDir;
 
@@ -23,6 +24,7 @@ use crate::{snapshot::StoreSnapshot, transaction::StoreTransaction};
 #[derive(Clone)]
 pub struct Store {
     db: TransactionDb,
+    index_account_history: bool,
     _temp_dir: Option<Arc<TempDir>>,
 }
 
@@ -36,16 +38,43 @@ impl Store {
         opts.create_missing_column_families(true);
         let db = opts.open()?;
         // TODO: repair.
+        let mut store = Self::new(db);
+        store.set_index_account_history(config.index_account_history);
+        Ok(store)
+    }
+
+    /// Open as a secondary instance of the store at `config.path`, which is
+    /// read only and follows the primary with `try_catch_up_with_primary`.
+    pub fn open_secondary(
+        config: &StoreConfig,
+        secondary_path: &Path,
+        columns: usize,
+    ) -> Result<Self> {
+        let mut opts = DbOptions::new(&config.path, columns);
+        if let Some(ref opts_file) = config.options_file {
+            opts.load_options_from_file(opts_file, config.cache_size.unwrap_or(0))?;
+        }
+        let db = opts.open_as_secondary(secondary_path)?;
         Ok(Self::new(db))
     }
 
     pub fn new(db: TransactionDb) -> Self {
         Store {
             db,
+            index_account_history: false,
             _temp_dir: None,
         }
     }
 
+    /// Index transactions and withdrawals by account when attaching blocks.
+    pub fn set_index_account_history(&mut self, enabled: bool) {
+        self.index_account_history = enabled;
+    }
+
+    pub fn index_account_history(&self) -> bool {
+        self.index_account_history
+    }
+
     pub fn open_tmp() -> Result<Self> {
         let dir = tempfile::tempdir()?;
         Ok(Self {
@@ -53,6 +82,7 @@ impl Store {
                 .create_if_missing(true)
                 .create_missing_column_families(true)
                 .open()?,
+            index_account_history: false,
             _temp_dir: Some(dir.into()),
         })
     }
@@ -60,6 +90,7 @@ impl Store {
     pub fn begin_transaction(&self) -> StoreTransaction {
         StoreTransaction {
             inner: self.db.begin_transaction(),
+            index_account_history: self.index_account_history,
         }
     }
 
@@ -78,6 +109,7 @@ impl Store {
             inner: self
                 .db
                 .begin_transaction_with_options(&write_options, &transaction_options),
+            index_account_history: self.index_account_history,
         }
     }
 
@@ -132,18 +164,34 @@ impl Store {
             let tip_number: u64 = db.get_last_valid_tip_block()?.raw().number().unpack();
             let smt = SMTBlockStore::new(db).to_smt()?;
             for number in tip_number.saturating_sub(100)..tip_number {
+                // Blocks before the earliest block are indexed by hash only.
                 let block_hash = self.get_block_hash_by_number(number)?.expect("exist");
-                let block = self.get_block(&block_hash)?.expect("exist");
-                let key = block.smt_key();
+                let key = RawL2Block::compute_smt_key(number);
                 let proof = smt.merkle_proof(vec![key.into()])?;
                 let root =
-                    proof.compute_root::<Blake2bHasher>(vec![(key.into(), block.hash().into())])?;
+                    proof.compute_root::<Blake2bHasher>(vec![(key.into(), block_hash.into())])?;
                 assert_eq!(&root, smt.root(), "block smt root consistent");
             }
         }
         Ok(())
     }
 
+    /// Create a consistent copy of the store in `dir` while it's being
+    /// written, see [`TransactionDb::create_checkpoint`].
+    pub fn create_checkpoint(&self, dir: &Path) -> Result<()> {
+        self.db.create_checkpoint(dir)?;
+        Ok(())
+    }
+
+    pub fn is_secondary(&self) -> bool {
+        self.db.is_secondary()
+    }
+
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     pub fn get_snapshot(&self) -> StoreSnapshot {
         StoreSnapshot::new(self.db.snapshot())
     }
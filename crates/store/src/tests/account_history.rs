@@ -0,0 +1,141 @@
+use gw_types::{
+    packed::{
+        L2Block, L2Transaction, RawL2Block, RawL2Transaction, RawWithdrawalRequest,
+        WithdrawalRequest,
+    },
+    prelude::*,
+};
+
+use crate::{
+    schema::{
+        COLUMN_BLOCK, COLUMN_INDEX, COLUMN_META, META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY,
+        META_LAST_VALID_TIP_BLOCK_HASH_KEY,
+    },
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    Store,
+};
+
+fn tx(from_id: u32, to_id: u32, nonce: u32) -> L2Transaction {
+    let raw = RawL2Transaction::new_builder()
+        .from_id(from_id.pack())
+        .to_id(to_id.pack())
+        .nonce(nonce.pack())
+        .build();
+    L2Transaction::new_builder().raw(raw).build()
+}
+
+fn withdrawal(account_script_hash: [u8; 32], nonce: u32) -> WithdrawalRequest {
+    let raw = RawWithdrawalRequest::new_builder()
+        .account_script_hash(account_script_hash.pack())
+        .nonce(nonce.pack())
+        .build();
+    WithdrawalRequest::new_builder().raw(raw).build()
+}
+
+fn block(number: u64, txs: Vec<L2Transaction>, withdrawals: Vec<WithdrawalRequest>) -> L2Block {
+    L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(number.pack()).build())
+        .transactions(txs.pack())
+        .withdrawals(withdrawals.pack())
+        .build()
+}
+
+#[test]
+fn account_history() {
+    let store = Store::open_tmp().unwrap();
+    let block1 = block(1, vec![tx(2, 3, 0), tx(4, 4, 0)], vec![]);
+    let block2 = block(
+        2,
+        vec![tx(3, 2, 0), tx(2, 5, 1)],
+        vec![withdrawal([1; 32], 0), withdrawal([1; 32], 1)],
+    );
+    let mut db = store.begin_transaction();
+    db.insert_account_history(&block1).unwrap();
+    db.insert_account_history(&block2).unwrap();
+    db.commit().unwrap();
+
+    let snap = store.get_snapshot();
+    let items = snap.get_account_transactions(2, None, 10);
+    let cursors: Vec<_> = items.iter().map(|i| (i.block_number, i.index)).collect();
+    assert_eq!(cursors, vec![(2, 1), (2, 0), (1, 0)]);
+    assert_eq!(items[0].hash, block2.transactions().get(1).unwrap().hash());
+
+    // Pagination.
+    let page = snap.get_account_transactions(2, None, 2);
+    assert_eq!(page.len(), 2);
+    let next = snap.get_account_transactions(2, Some((2, 0)), 2);
+    assert_eq!(next.len(), 1);
+    assert_eq!((next[0].block_number, next[0].index), (1, 0));
+    assert!(snap.get_account_transactions(2, Some((1, 0)), 2).is_empty());
+
+    // Self transfer is indexed once; neighbouring accounts don't leak.
+    assert_eq!(snap.get_account_transactions(4, None, 10).len(), 1);
+    assert_eq!(snap.get_account_transactions(5, None, 10).len(), 1);
+    assert!(snap.get_account_transactions(6, None, 10).is_empty());
+
+    let withdrawals = snap.get_account_withdrawals(&[1; 32], None, 10);
+    let cursors: Vec<_> = withdrawals
+        .iter()
+        .map(|i| (i.block_number, i.index))
+        .collect();
+    assert_eq!(cursors, vec![(2, 1), (2, 0)]);
+    assert!(snap.get_account_withdrawals(&[2; 32], None, 10).is_empty());
+
+    // Detach.
+    let mut db = store.begin_transaction();
+    db.delete_account_history(&block2).unwrap();
+    db.commit().unwrap();
+    let snap = store.get_snapshot();
+    assert_eq!(snap.get_account_transactions(2, None, 10).len(), 1);
+    assert!(snap.get_account_transactions(5, None, 10).is_empty());
+    assert!(snap.get_account_withdrawals(&[1; 32], None, 10).is_empty());
+}
+
+#[test]
+fn backfill_account_history() {
+    let mut store = Store::open_tmp().unwrap();
+    let blocks = [
+        block(0, vec![], vec![]),
+        block(1, vec![tx(2, 3, 0)], vec![]),
+        block(2, vec![tx(2, 5, 1)], vec![withdrawal([1; 32], 0)]),
+    ];
+    // Blocks attached with the index disabled.
+    let mut db = store.begin_transaction();
+    for b in blocks.iter() {
+        db.insert_raw(COLUMN_BLOCK, &b.hash(), b.as_slice())
+            .unwrap();
+        db.insert_raw(COLUMN_INDEX, b.raw().number().as_slice(), &b.hash())
+            .unwrap();
+    }
+    db.insert_raw(
+        COLUMN_META,
+        META_LAST_VALID_TIP_BLOCK_HASH_KEY,
+        &blocks[2].hash(),
+    )
+    .unwrap();
+    db.commit().unwrap();
+
+    store.backfill_account_history().unwrap();
+    let snap = store.get_snapshot();
+    assert!(snap.get_account_transactions(2, None, 10).is_empty());
+
+    store.set_index_account_history(true);
+    store.backfill_account_history().unwrap();
+    let snap = store.get_snapshot();
+    assert_eq!(snap.get_account_transactions(2, None, 10).len(), 2);
+    assert_eq!(snap.get_account_transactions(3, None, 10).len(), 1);
+    assert_eq!(snap.get_account_withdrawals(&[1; 32], None, 10).len(), 1);
+    let indexed_number = store
+        .begin_transaction()
+        .get(COLUMN_META, META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY)
+        .unwrap();
+    assert_eq!(indexed_number.as_ref(), u64::MAX.to_be_bytes());
+
+    // Disabling the index forgets the progress.
+    store.set_index_account_history(false);
+    store.backfill_account_history().unwrap();
+    assert!(store
+        .begin_transaction()
+        .get(COLUMN_META, META_ACCOUNT_HISTORY_INDEXED_NUMBER_KEY)
+        .is_none());
+}
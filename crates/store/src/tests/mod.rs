@@ -1,2 +1,4 @@
+mod account_history;
 mod state_db;
+mod state_history_pruning;
 mod transaction;
@@ -0,0 +1,117 @@
+use gw_types::h256::*;
+
+use crate::{
+    state::history::history_state::HistoryStateStore, traits::chain_store::ChainStore, Store,
+};
+
+// block number -> [(state key, value)]
+const RECORDS: &[(u64, &[(u32, u32)])] = &[
+    (0, &[(1, 1), (2, 1)]),
+    (1, &[(1, 2)]),
+    (2, &[(1, 3)]),
+    (3, &[(2, 2)]),
+    (4, &[(1, 4)]),
+];
+
+fn setup() -> Store {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    for (block_number, records) in RECORDS {
+        for (key, value) in records.iter() {
+            db.record_block_state(*block_number, H256::from_u32(*key), H256::from_u32(*value))
+                .unwrap();
+        }
+    }
+    db.commit().unwrap();
+    store
+}
+
+fn history_state(store: &Store, block_number: u64, key: u32) -> Option<u32> {
+    let db = store.begin_transaction();
+    let value = db.get_history_state(block_number, &H256::from_u32(key))?;
+    Some((1..=4).find(|v| H256::from_u32(*v) == value).unwrap())
+}
+
+#[test]
+fn prune_state_history() {
+    let store = setup();
+    let mut db = store.begin_transaction();
+    db.prune_state_history(3, None).unwrap();
+    db.commit().unwrap();
+
+    let db = store.begin_transaction();
+    assert_eq!(db.get_state_history_pruned_number(), 3);
+    assert!(!db.is_state_history_available(2));
+    assert!(db.is_state_history_available(3));
+    // Shadowed records are removed.
+    assert!(db.iter_block_state_record(0).is_empty());
+    assert!(db.iter_block_state_record(1).is_empty());
+
+    assert_eq!(history_state(&store, 3, 1), Some(3));
+    assert_eq!(history_state(&store, 3, 2), Some(2));
+    assert_eq!(history_state(&store, 4, 1), Some(4));
+    assert_eq!(history_state(&store, 4, 2), Some(2));
+}
+
+#[test]
+fn prune_state_history_with_checkpoints() {
+    let store = setup();
+    let mut db = store.begin_transaction();
+    db.prune_state_history(2, Some(2)).unwrap();
+    db.prune_state_history(4, Some(2)).unwrap();
+    db.commit().unwrap();
+
+    let db = store.begin_transaction();
+    assert_eq!(db.get_state_history_checkpoint_interval(), Some(2));
+    let available: Vec<u64> = (0..=4)
+        .filter(|n| db.is_state_history_available(*n))
+        .collect();
+    assert_eq!(available, vec![0, 2, 4]);
+    assert!(db.iter_block_state_record(1).is_empty());
+
+    assert_eq!(history_state(&store, 0, 1), Some(1));
+    assert_eq!(history_state(&store, 0, 2), Some(1));
+    assert_eq!(history_state(&store, 2, 1), Some(3));
+    assert_eq!(history_state(&store, 2, 2), Some(1));
+    assert_eq!(history_state(&store, 4, 1), Some(4));
+    assert_eq!(history_state(&store, 4, 2), Some(2));
+
+    // The checkpoint interval can't be changed.
+    let mut db = store.begin_transaction();
+    assert!(db.prune_state_history(5, Some(3)).is_err());
+}
+
+#[test]
+fn iter_block_state() {
+    let store = setup();
+    let snap = store.get_snapshot();
+    let state: Vec<_> = snap.iter_block_state(2).collect();
+    assert_eq!(
+        state,
+        vec![
+            (H256::from_u32(1), H256::from_u32(3)),
+            (H256::from_u32(2), H256::from_u32(1)),
+        ]
+    );
+}
+
+#[test]
+fn restored_state_history() {
+    let store = setup();
+    let mut db = store.begin_transaction();
+    db.set_state_history_start_number(2).unwrap();
+    db.commit().unwrap();
+
+    // No checkpoints before the restored block.
+    let mut db = store.begin_transaction();
+    db.prune_state_history(4, Some(2)).unwrap();
+    db.commit().unwrap();
+
+    let db = store.begin_transaction();
+    let available: Vec<u64> = (0..=4)
+        .filter(|n| db.is_state_history_available(*n))
+        .collect();
+    assert_eq!(available, vec![2, 4]);
+    assert_eq!(history_state(&store, 2, 1), Some(3));
+    assert_eq!(history_state(&store, 4, 2), Some(2));
+}
@@ -96,6 +96,51 @@ pub trait ChainStore: KVStoreRead {
         Some(from_box_should_be_ok!(NumberHashReader, data))
     }
 
+    /// State history of the blocks before it is pruned, except for the
+    /// checkpoints.
+    fn get_state_history_pruned_number(&self) -> u64 {
+        self.get(COLUMN_META, META_STATE_HISTORY_PRUNED_NUMBER_KEY)
+            .map(|data| u64::from_be_bytes(data.as_ref().try_into().expect("u64")))
+            .unwrap_or(0)
+    }
+
+    fn get_state_history_checkpoint_interval(&self) -> Option<u64> {
+        self.get(COLUMN_META, META_STATE_HISTORY_CHECKPOINT_INTERVAL_KEY)
+            .map(|data| u64::from_be_bytes(data.as_ref().try_into().expect("u64")))
+            .filter(|&interval| interval > 0)
+    }
+
+    /// Blocks before it are not in the store, only their hashes are indexed.
+    /// It's 0 unless the store is restored from a state snapshot.
+    fn get_earliest_block_number(&self) -> u64 {
+        self.get(COLUMN_META, META_EARLIEST_BLOCK_NUMBER_KEY)
+            .map(|data| u64::from_be_bytes(data.as_ref().try_into().expect("u64")))
+            .unwrap_or(0)
+    }
+
+    /// Whether the store is being restored from a state snapshot, or the
+    /// restoring is interrupted.
+    fn is_state_restoring(&self) -> bool {
+        self.get(COLUMN_META, META_STATE_RESTORING_KEY).is_some()
+    }
+
+    /// State history of the blocks before it is not in the store, checkpoints
+    /// included.
+    fn get_state_history_start_number(&self) -> u64 {
+        self.get(COLUMN_META, META_STATE_HISTORY_START_NUMBER_KEY)
+            .map(|data| u64::from_be_bytes(data.as_ref().try_into().expect("u64")))
+            .unwrap_or(0)
+    }
+
+    /// Whether state at the block can be read with `RWConfig::history_block`.
+    fn is_state_history_available(&self, block_number: u64) -> bool {
+        block_number >= self.get_state_history_pruned_number()
+            || (block_number >= self.get_state_history_start_number()
+                && self
+                    .get_state_history_checkpoint_interval()
+                    .map_or(false, |interval| block_number % interval == 0))
+    }
+
     fn get_block_status(&self, block_number: u64) -> BlockStatus {
         if Some(block_number)
             <= self
@@ -257,6 +302,12 @@ pub trait ChainStore: KVStoreRead {
         Ok(withdrawal_info_opt)
     }
 
+    /// Hash of the L1 tx which unlocked the withdrawal cell to its owner.
+    fn get_withdrawal_unlock_tx_hash(&self, withdrawal_hash: &H256) -> Option<H256> {
+        let data = self.get(COLUMN_WITHDRAWAL_UNLOCK_TX, withdrawal_hash.as_slice())?;
+        Some(packed::Byte32Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
     fn get_withdrawal_by_key(
         &self,
         withdrawal_key: &WithdrawalKey,
@@ -28,6 +28,7 @@ use super::TransactionSnapshot;
 
 pub struct StoreTransaction {
     pub(crate) inner: autorocks::Transaction,
+    pub(crate) index_account_history: bool,
 }
 
 impl KVStoreRead for StoreTransaction {
@@ -465,6 +466,10 @@ impl StoreTransaction {
             self.insert_raw(COLUMN_WITHDRAWAL_INFO, &withdrawal_hash, info.as_slice())?;
         }
 
+        if self.index_account_history {
+            self.insert_account_history(&block)?;
+        }
+
         // build main chain index
         self.insert_raw(COLUMN_INDEX, raw_number.as_slice(), &block_hash)?;
         self.insert_raw(COLUMN_INDEX, &block_hash, raw_number.as_slice())?;
@@ -513,6 +518,9 @@ impl StoreTransaction {
             let withdrawal_hash = withdrawal.hash();
             self.delete(COLUMN_WITHDRAWAL_INFO, &withdrawal_hash)?;
         }
+        // account history, it may have been indexed before the option was
+        // turned off
+        self.delete_account_history(block)?;
 
         let block_hash: H256 = block.hash();
 
@@ -657,4 +665,88 @@ impl StoreTransaction {
                 )
             })
     }
+
+    /// Journal a transaction submitted to the fee queue. `hash` is the hash of
+    /// the tx in the queue, see `FeeItem::hash`.
+    pub fn insert_queued_transaction(
+        &mut self,
+        hash: &H256,
+        tx: packed::L2Transaction,
+    ) -> Result<()> {
+        self.insert_raw(COLUMN_QUEUED_TRANSACTION, hash.as_slice(), tx.as_slice())
+    }
+
+    pub fn insert_queued_withdrawal(
+        &mut self,
+        withdrawal_hash: &H256,
+        withdrawal: packed::WithdrawalRequestExtra,
+    ) -> Result<()> {
+        self.insert_raw(
+            COLUMN_QUEUED_WITHDRAWAL,
+            withdrawal_hash.as_slice(),
+            withdrawal.as_slice(),
+        )
+    }
+
+    pub fn set_withdrawal_unlock_tx_hash(
+        &mut self,
+        withdrawal_hash: &H256,
+        tx_hash: &H256,
+        l1_block_number: u64,
+    ) -> Result<()> {
+        self.insert_raw(
+            COLUMN_WITHDRAWAL_UNLOCK_TX,
+            withdrawal_hash.as_slice(),
+            tx_hash.as_slice(),
+        )?;
+        let mut key = l1_block_number.to_be_bytes().to_vec();
+        key.extend_from_slice(withdrawal_hash.as_slice());
+        self.insert_raw(COLUMN_WITHDRAWAL_UNLOCK_L1_BLOCK, &key, &[])
+    }
+
+    /// Remove the withdrawal unlock txs recorded from L1 blocks at or above
+    /// `l1_block_number`, e.g. on L1 revert.
+    pub fn remove_withdrawal_unlocks_from(&mut self, l1_block_number: u64) -> Result<()> {
+        let keys: Vec<Box<[u8]>> = {
+            let mut iter = self.get_iter(COLUMN_WITHDRAWAL_UNLOCK_L1_BLOCK, Direction::Forward);
+            iter.seek(&l1_block_number.to_be_bytes());
+            iter.map(|(key, _value)| key).collect()
+        };
+        for key in keys {
+            self.delete(COLUMN_WITHDRAWAL_UNLOCK_TX, &key[8..])?;
+            self.delete(COLUMN_WITHDRAWAL_UNLOCK_L1_BLOCK, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Remove a journaled transaction or withdrawal.
+    pub fn remove_queued_request(&mut self, hash: &H256) -> Result<()> {
+        self.delete(COLUMN_QUEUED_TRANSACTION, hash.as_slice())?;
+        self.delete(COLUMN_QUEUED_WITHDRAWAL, hash.as_slice())?;
+        Ok(())
+    }
+
+    pub fn get_queued_transaction_iter(
+        &self,
+    ) -> impl Iterator<Item = (H256, packed::L2Transaction)> + '_ {
+        self.get_iter(COLUMN_QUEUED_TRANSACTION, Direction::Forward)
+            .map(|(key, val)| {
+                (
+                    key.as_ref().try_into().unwrap(),
+                    from_box_should_be_ok!(packed::L2TransactionReader, val),
+                )
+            })
+    }
+
+    pub fn get_queued_withdrawal_iter(
+        &self,
+    ) -> impl Iterator<Item = (H256, packed::WithdrawalRequestExtra)> + '_ {
+        self.get_iter(COLUMN_QUEUED_WITHDRAWAL, Direction::Forward)
+            .map(|(key, val)| {
+                (
+                    key.as_ref().try_into().unwrap(),
+                    from_box_should_be_ok!(packed::WithdrawalRequestExtraReader, val),
+                )
+            })
+    }
 }
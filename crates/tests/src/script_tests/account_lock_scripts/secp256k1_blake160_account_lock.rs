@@ -0,0 +1,118 @@
+use super::{gen_tx, ERROR_WRONG_SIGNATURE};
+use crate::script_tests::programs::{
+    SECP256K1_BLAKE160_ACCOUNT_LOCK_CODE_HASH, SECP256K1_BLAKE160_ACCOUNT_LOCK_PROGRAM,
+};
+use crate::script_tests::utils::layer1::*;
+use ckb_crypto::secp::{Generator, Privkey, Pubkey};
+use ckb_error::assert_error_eq;
+use ckb_script::{ScriptError, TransactionScriptsVerifier};
+use ckb_types::{bytes::Bytes, packed::WitnessArgs, prelude::*};
+use gw_common::blake2b::new_blake2b;
+use gw_types::core::SigningType;
+use rand::{thread_rng, Rng};
+
+fn sign_message(key: &Privkey, message: [u8; 32]) -> Bytes {
+    let sig = key
+        .sign_recoverable(&ckb_types::H256::from(message))
+        .expect("sign");
+    let mut signature = [0u8; 65];
+    signature.copy_from_slice(&sig.serialize());
+    signature.to_vec().into()
+}
+
+fn blake160_pubkey_hash(pubkey: &Pubkey) -> Bytes {
+    let mut hasher = new_blake2b();
+    hasher.update(&pubkey.serialize());
+    let mut buf = [0u8; 32];
+    hasher.finalize(&mut buf);
+    buf[..20].to_vec().into()
+}
+
+fn verify(
+    privkey: &Privkey,
+    signing_type: SigningType,
+    message: [u8; 32],
+    signature: Bytes,
+) -> Result<ckb_types::core::Cycle, ckb_error::Error> {
+    let mut data_loader = DummyDataLoader::default();
+    let pubkey = privkey.pubkey().expect("pubkey");
+    let lock_args = {
+        let rollup_script_hash = [42u8; 32];
+        let mut args = rollup_script_hash.to_vec();
+        args.extend_from_slice(&blake160_pubkey_hash(&pubkey));
+        args.into()
+    };
+    let tx = gen_tx(
+        &mut data_loader,
+        &SECP256K1_BLAKE160_ACCOUNT_LOCK_PROGRAM,
+        lock_args,
+        signing_type,
+        message.to_vec().into(),
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(signature).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let mut verifier = TransactionScriptsVerifier::new(&resolved_tx, &data_loader);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+fn wrong_signature_error() -> ckb_error::Error {
+    let script_cell_index = 0;
+    ScriptError::ValidationFailure(
+        format!(
+            "by-data-hash/{}",
+            ckb_types::H256(*SECP256K1_BLAKE160_ACCOUNT_LOCK_CODE_HASH)
+        ),
+        ERROR_WRONG_SIGNATURE,
+    )
+    .input_lock_script(script_cell_index)
+}
+
+#[test]
+fn test_sign_blake160_message() {
+    let privkey = Generator::random_privkey();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = sign_message(&privkey, message);
+    verify(&privkey, SigningType::Raw, message, signature).expect("pass verification");
+}
+
+#[test]
+fn test_prefixed_message_is_not_supported() {
+    let privkey = Generator::random_privkey();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = sign_message(&privkey, message);
+    let err = verify(&privkey, SigningType::WithPrefix, message, signature).unwrap_err();
+    assert_error_eq!(err, wrong_signature_error());
+}
+
+#[test]
+fn test_wrong_signature() {
+    let privkey = Generator::random_privkey();
+    let mut rng = thread_rng();
+    let mut message = [0u8; 32];
+    rng.fill(&mut message);
+    let signature = {
+        let mut wrong_message = [0u8; 32];
+        rng.fill(&mut wrong_message);
+        sign_message(&privkey, wrong_message)
+    };
+    let err = verify(&privkey, SigningType::Raw, message, signature).unwrap_err();
+    assert_error_eq!(err, wrong_signature_error());
+
+    // Signed by another key
+    let other_privkey = Generator::random_privkey();
+    let signature = sign_message(&other_privkey, message);
+    let err = verify(&privkey, SigningType::Raw, message, signature).unwrap_err();
+    assert_error_eq!(err, wrong_signature_error());
+}
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use gw_builtin_binaries::{file_checksum, Resource};
 use gw_common::{
-    builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State,
+    builtins::ETH_REGISTRY_ACCOUNT_ID,
+    registry_address::RegistryAddress,
+    state::{build_account_key, State},
 };
 use gw_config::{BackendConfig, BackendForkConfig, BackendType};
 use gw_generator::{
@@ -30,6 +32,7 @@ use gw_types::{
     bytes::Bytes,
     core::{AllowedContractType, ScriptHashType},
     h256::*,
+    offchain::SyscallTraceData,
     packed::{AllowedTypeHash, RawL2Transaction, RollupConfig, Script},
     prelude::*,
     U256,
@@ -316,6 +319,47 @@ fn test_example_account_operation() {
             .execute_transaction(&chain_view, &mut tree, &block_info, &raw_tx, None, None)
             .expect("result");
         assert_eq!(run_result.return_data, Vec::<u8>::new());
+        assert!(run_result.syscall_trace.is_none());
+    }
+    // Store: syscall trace
+    {
+        let mut tree = tree.clone();
+        let args = AccountOp::Store {
+            account_id: 0,
+            key: [1u8; 32],
+            value: [2u8; 32],
+        };
+        let raw_tx = RawL2Transaction::new_builder()
+            .from_id(from_id.pack())
+            .to_id(contract_id.pack())
+            .args(Bytes::from(args.to_vec()).pack())
+            .build();
+        let run_result = generator
+            .execute_transaction_with_syscall_trace(
+                &chain_view,
+                &mut tree,
+                &block_info,
+                &raw_tx,
+                None,
+                None,
+            )
+            .expect("result");
+        let trace = run_result.syscall_trace.expect("syscall trace");
+        let store = trace
+            .iter()
+            .find(|step| step.name == "SYS_STORE")
+            .expect("SYS_STORE step");
+        assert_eq!(store.return_code, 0);
+        match store.data {
+            Some(SyscallTraceData::Store { key, value }) => {
+                assert_eq!(key, build_account_key(0, &[1u8; 32]));
+                assert_eq!(value, [2u8; 32]);
+            }
+            ref data => panic!("unexpected {:?}", data),
+        }
+        assert!(trace
+            .windows(2)
+            .all(|steps| steps[0].cycles <= steps[1].cycles));
     }
     // Store: account not found
     {
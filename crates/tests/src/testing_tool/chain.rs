@@ -499,6 +499,7 @@ pub async fn setup_chain_with_account_lock_manage(
         timestamp: 0,
         meta_contract_validator_type_hash: META_VALIDATOR_SCRIPT_TYPE_HASH.into(),
         eth_registry_validator_type_hash: ETH_REGISTRY_SCRIPT_TYPE_HASH.into(),
+        ckb_registry_validator_type_hash: None,
         rollup_config: rollup_config.clone().into(),
         rollup_type_hash: rollup_script_hash.into(),
         secp_data_dep: Default::default(),
@@ -5,7 +5,10 @@ use gw_chain::chain::Chain;
 use gw_config::{NodeMode::FullNode, RPCClientConfig, RPCMethods};
 use gw_jsonrpc_types::{
     ckb_jsonrpc_types::{JsonBytes, Uint64},
-    godwoken::{MolJsonBytes, RunResult},
+    godwoken::{
+        AccountProof, CyclesEstimate, FeeSuggestion, MolJsonBytes, RunResult, StateOverride,
+        SubmitL2TransactionResult, TransactionStatus,
+    },
 };
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
 use gw_rpc_client::{
@@ -98,6 +101,15 @@ impl RPCServer {
         Ok(r.map(Into::into))
     }
 
+    pub async fn submit_l2transactions(
+        &self,
+        txs: &[L2Transaction],
+    ) -> RpcResult<Vec<SubmitL2TransactionResult>> {
+        let txs = txs.iter().cloned().map(MolJsonBytes).collect();
+        let r = self.inner.gw_submit_l2transactions(txs).await?;
+        Ok(r)
+    }
+
     pub async fn execute_l2transaction(&self, tx: &L2Transaction) -> RpcResult<RunResult> {
         let r = self
             .inner
@@ -111,16 +123,73 @@ impl RPCServer {
         raw_tx: &RawL2Transaction,
         opt_block_number: Option<u64>,
         opt_registry_address: Option<Bytes>,
+    ) -> RpcResult<RunResult> {
+        self.execute_raw_l2transaction_with_state_override(
+            raw_tx,
+            opt_block_number,
+            opt_registry_address,
+            None,
+        )
+        .await
+    }
+
+    pub async fn execute_raw_l2transaction_with_state_override(
+        &self,
+        raw_tx: &RawL2Transaction,
+        opt_block_number: Option<u64>,
+        opt_registry_address: Option<Bytes>,
+        opt_state_override: Option<StateOverride>,
     ) -> RpcResult<RunResult> {
         let params = serde_json::to_value(&(
             MolJsonBytes(raw_tx.clone()),
             opt_block_number.map(Uint64::from),
             opt_registry_address.map(JsonBytes::from_bytes),
+            opt_state_override,
         ))
         .unwrap();
-        let (a, b, c) = serde_json::from_value(params)
+        let (a, b, c, d) = serde_json::from_value(params)
             .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
-        let r = self.inner.gw_execute_raw_l2transaction(a, b, c).await?;
+        let r = self.inner.gw_execute_raw_l2transaction(a, b, c, d).await?;
+        Ok(r)
+    }
+
+    pub async fn estimate_cycles(
+        &self,
+        raw_tx: &RawL2Transaction,
+        opt_registry_address: Option<Bytes>,
+    ) -> RpcResult<CyclesEstimate> {
+        let params = serde_json::to_value(&(
+            MolJsonBytes(raw_tx.clone()),
+            opt_registry_address.map(JsonBytes::from_bytes),
+        ))
+        .unwrap();
+        let (a, b) = serde_json::from_value(params)
+            .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+        let r = self.inner.gw_estimate_cycles(a, b).await?;
+        Ok(r)
+    }
+
+    pub async fn get_fee_suggestion(&self) -> RpcResult<FeeSuggestion> {
+        let r = self.inner.gw_get_fee_suggestion().await?;
+        Ok(r)
+    }
+
+    pub async fn get_proof(
+        &self,
+        account_id: u32,
+        keys: Vec<H256>,
+        opt_block_number: Option<u64>,
+    ) -> RpcResult<AccountProof> {
+        let keys = keys.into_iter().map(Into::into).collect();
+        let r = self
+            .inner
+            .gw_get_proof(account_id.into(), keys, opt_block_number.map(Into::into))
+            .await?;
+        Ok(r)
+    }
+
+    pub async fn get_transaction_status(&self, hash: H256) -> RpcResult<Option<TransactionStatus>> {
+        let r = self.inner.gw_get_transaction_status(hash.into()).await?;
         Ok(r)
     }
 
@@ -129,6 +198,11 @@ impl RPCServer {
         Ok(result)
     }
 
+    pub async fn create_checkpoint(&self, name: &str) -> RpcResult<()> {
+        self.inner.gw_create_checkpoint(name.to_string()).await?;
+        Ok(())
+    }
+
     pub async fn submit_withdrawal_request(&self, req: &WithdrawalRequestExtra) -> RpcResult<H256> {
         let r = self
             .inner
@@ -0,0 +1,70 @@
+use godwoken_bin::subcommand::backup::{
+    backup_checkpoint, remove_checkpoint, BackupCommand, CHECKPOINT_NAME,
+};
+use gw_config::StoreConfig;
+use gw_store::{schema::COLUMNS, traits::chain_store::ChainStore, Store};
+use gw_types::packed::Script;
+
+use crate::testing_tool::chain::{produce_empty_block, setup_chain};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_backup_and_restore() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut chain = setup_chain(Script::default()).await;
+    for _ in 0..5 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let tip_hash = chain.store().get_last_valid_tip_block_hash().unwrap();
+
+    let checkpoint_root = tempfile::tempdir().unwrap();
+    let checkpoint_dir = checkpoint_root.path().join(CHECKPOINT_NAME);
+    chain.store().create_checkpoint(&checkpoint_dir).unwrap();
+    let backup_dir = tempfile::tempdir().unwrap();
+    backup_checkpoint(&checkpoint_dir, backup_dir.path(), Some(1)).unwrap();
+    remove_checkpoint(&checkpoint_dir).unwrap();
+    assert!(!checkpoint_dir.exists());
+
+    // Only an empty store path is restored to.
+    let store_dir = tempfile::tempdir().unwrap();
+    std::fs::write(store_dir.path().join("file"), b"").unwrap();
+    let restore = |store_path| BackupCommand::Restore {
+        backup_dir: backup_dir.path().to_path_buf(),
+        store_path,
+    };
+    assert!(restore(store_dir.path().to_path_buf()).run().await.is_err());
+    let store_path = store_dir.path().join("db");
+    restore(store_path.clone()).run().await.unwrap();
+    let store = Store::open(
+        &StoreConfig {
+            path: store_path,
+            ..Default::default()
+        },
+        COLUMNS,
+    )
+    .unwrap();
+    assert_eq!(store.get_last_valid_tip_block_hash().unwrap(), tip_hash);
+}
+
+#[test]
+fn test_remove_checkpoint() {
+    let root = tempfile::tempdir().unwrap();
+    let checkpoint_dir = root.path().join(CHECKPOINT_NAME);
+    // Nothing to remove.
+    remove_checkpoint(&checkpoint_dir).unwrap();
+
+    // Not a checkpoint.
+    std::fs::create_dir(&checkpoint_dir).unwrap();
+    std::fs::write(checkpoint_dir.join("CURRENT"), b"").unwrap();
+    std::fs::create_dir(checkpoint_dir.join("data")).unwrap();
+    assert!(remove_checkpoint(&checkpoint_dir).is_err());
+    assert!(checkpoint_dir.join("data").exists());
+    std::fs::remove_dir(checkpoint_dir.join("data")).unwrap();
+    std::fs::write(checkpoint_dir.join("notes.txt"), b"").unwrap();
+    assert!(remove_checkpoint(&checkpoint_dir).is_err());
+    std::fs::remove_file(checkpoint_dir.join("notes.txt")).unwrap();
+
+    std::fs::write(checkpoint_dir.join("000001.sst"), b"").unwrap();
+    remove_checkpoint(&checkpoint_dir).unwrap();
+    assert!(!checkpoint_dir.exists());
+}
@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use gw_p2p_network::block_catch_up::{
+    block_response, catch_up_range, end_response, error_response, parse_response, CatchUpMessage,
+    MAX_CATCH_UP_BLOCKS,
+};
+use gw_store::traits::chain_store::ChainStore;
+use gw_types::{
+    offchain::ExportedBlock,
+    packed::{BlockCatchUpRequest, Script},
+    prelude::*,
+};
+use gw_utils::export_block::export_block;
+
+use crate::testing_tool::chain::{produce_empty_block, setup_chain};
+
+/// Serve the request like the server and parse the responses like the client.
+fn serve(snap: &impl ChainStore, start: u64, count: u64) -> Result<(Vec<ExportedBlock>, u64)> {
+    let request = BlockCatchUpRequest::new_builder()
+        .start(start.pack())
+        .count(count.pack())
+        .build();
+    let (range, tip_number) = catch_up_range(snap, &request)?;
+    let mut responses = range
+        .map(|block_number| block_response(snap, block_number))
+        .collect::<Result<Vec<_>>>()?;
+    responses.push(end_response(tip_number));
+
+    let mut blocks = Vec::new();
+    for response in responses {
+        match parse_response(&response.as_bytes())? {
+            CatchUpMessage::Block(exported) => blocks.push(*exported),
+            CatchUpMessage::End { tip_number } => return Ok((blocks, tip_number)),
+        }
+    }
+    unreachable!("no end response");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_block_catch_up_protocol() {
+    let mut chain = setup_chain(Script::default()).await;
+    for _ in 0..3 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let snap = chain.store().get_snapshot();
+
+    // Blocks after the genesis block, connected one by one.
+    let (blocks, tip_number) = serve(&snap, 1, MAX_CATCH_UP_BLOCKS).unwrap();
+    assert_eq!(tip_number, 3);
+    assert_eq!(blocks.len(), 3);
+    let mut parent_block_hash = snap.get_block_hash_by_number(0).unwrap().unwrap();
+    for (exported, block_number) in blocks.iter().zip(1..) {
+        assert_eq!(exported.block_number(), block_number);
+        assert_eq!(exported.parent_block_hash(), parent_block_hash);
+        assert_eq!(exported, &export_block(&snap, block_number).unwrap());
+        parent_block_hash = exported.block_hash();
+    }
+
+    // The server sends at most `count` blocks.
+    let (blocks, _) = serve(&snap, 2, 1).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].block_number(), 2);
+
+    // The client is at or beyond the server tip.
+    for start in [4, 100] {
+        let (blocks, tip_number) = serve(&snap, start, MAX_CATCH_UP_BLOCKS).unwrap();
+        assert!(blocks.is_empty());
+        assert_eq!(tip_number, 3);
+    }
+
+    // Errors are sent to the client instead of ending the stream silently.
+    let err = block_response(&snap, 100).unwrap_err();
+    let response = error_response(&err);
+    let err = parse_response(&response.as_bytes()).err().unwrap();
+    assert!(err.to_string().starts_with("server error"));
+
+    let response = error_response(&anyhow!("pruned"));
+    let err = parse_response(&response.as_bytes()).err().unwrap();
+    assert_eq!(err.to_string(), "server error: pruned");
+}
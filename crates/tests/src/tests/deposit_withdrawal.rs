@@ -6,6 +6,10 @@ use crate::testing_tool::chain::{
 };
 
 use anyhow::Result;
+use gw_block_producer::{
+    types::ChainEvent,
+    withdrawal_unlock_sync::{find_withdrawal_hash, WithdrawalUnlockSync},
+};
 use gw_chain::chain::{Chain, RevertL1ActionContext, RevertedL1Action};
 use gw_common::{
     builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
@@ -15,24 +19,29 @@ use gw_common::{
 use gw_generator::{
     error::{DepositError, WithdrawalError},
     sudt::build_l2_sudt_script,
+    utils::build_withdrawal_cell_output,
     Error,
 };
+use gw_rpc_client::{l1_client::L1Client, mock_l1_client::MockL1Client};
 use gw_store::{
     state::{history::history_state::RWConfig, BlockStateDB},
     traits::chain_store::ChainStore,
 };
 use gw_types::h256::*;
 use gw_types::{
-    core::ScriptHashType,
+    bytes::Bytes,
+    core::{ScriptHashType, Timepoint},
     packed::{
-        DepositInfoVec, DepositRequest, RawWithdrawalRequest, Script, WithdrawalRequest,
-        WithdrawalRequestExtra,
+        CellInput, CellOutput, DepositInfoVec, DepositRequest, OutPoint, RawTransaction,
+        RawWithdrawalRequest, Script, Transaction, UnlockWithdrawalViaFinalize,
+        UnlockWithdrawalWitness, UnlockWithdrawalWitnessUnion, WithdrawalRequest,
+        WithdrawalRequestExtra, WitnessArgs,
     },
     prelude::*,
     U256,
 };
 
-use std::{collections::HashSet, iter::FromIterator};
+use std::{collections::HashSet, iter::FromIterator, sync::Arc};
 
 /// Deposit, produce new block and update chain.
 async fn deposite_to_chain(
@@ -671,3 +680,172 @@ async fn test_produce_block_after_re_inject_withdrawal() {
         .unwrap()
         .is_none());
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sync_withdrawal_unlock_tx() {
+    let rollup_type_script = Script::default();
+    let rollup_script_hash = rollup_type_script.hash();
+    let mut chain = setup_chain(rollup_type_script.clone()).await;
+    let user_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args({
+            let mut args = rollup_script_hash.to_vec();
+            args.extend(&[42u8; 20]);
+            args.pack()
+        })
+        .build();
+    let user_script_hash = user_script.hash();
+    deposite_to_chain(
+        &mut chain,
+        user_script,
+        600_00000000,
+        H256::zero(),
+        Script::default(),
+        0,
+    )
+    .await
+    .unwrap();
+    // wait for deposit finalize
+    for _ in 0..DEFAULT_FINALITY_BLOCKS {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    withdrawal_from_chain(&mut chain, user_script_hash, 322_00000000, H256::zero(), 0)
+        .await
+        .unwrap();
+
+    let block = chain.store().get_tip_block().unwrap();
+    let block_number: u64 = block.raw().number().unpack();
+    let withdrawal = block.withdrawals().get(0).expect("withdrawal");
+    let (withdrawal_output, withdrawal_data) = {
+        let withdrawal_extra = WithdrawalRequestExtra::new_builder()
+            .request(withdrawal.clone())
+            .owner_lock(Script::default())
+            .build();
+        build_withdrawal_cell_output(
+            chain.generator().rollup_context(),
+            &withdrawal_extra,
+            &block.hash(),
+            &Timepoint::from_block_number(block_number),
+            None,
+        )
+        .unwrap()
+    };
+
+    // The submit tx creates the withdrawal cell after the rollup cell.
+    let l1_client = MockL1Client::new(rollup_type_script.clone());
+    let submit_tx = build_l1_tx(
+        vec![],
+        vec![
+            (CellOutput::default(), Bytes::new()),
+            (withdrawal_output, withdrawal_data),
+        ],
+        vec![],
+    );
+    l1_client.send_transaction(&submit_tx).await.unwrap();
+    l1_client.commit_block();
+    {
+        let mut db = chain.store().begin_transaction();
+        db.set_block_submit_tx(block_number, &submit_tx.as_reader())
+            .unwrap();
+        db.commit().unwrap();
+    }
+
+    // Anyone can unlock the finalized withdrawal cell to its owner.
+    let withdrawal_out_point = OutPoint::new_builder()
+        .tx_hash(submit_tx.hash().pack())
+        .index(1u32.pack())
+        .build();
+    let unlock_witness = {
+        let unlock = UnlockWithdrawalWitness::new_builder()
+            .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(
+                UnlockWithdrawalViaFinalize::new_builder().build(),
+            ))
+            .build();
+        WitnessArgs::new_builder()
+            .lock(Some(unlock.as_bytes()).pack())
+            .build()
+    };
+    let unlock_tx = build_l1_tx(
+        vec![withdrawal_out_point],
+        vec![(CellOutput::default(), Bytes::new())],
+        vec![unlock_witness.as_bytes()],
+    );
+    l1_client.send_transaction(&unlock_tx).await.unwrap();
+    let unlock_tip = l1_client.commit_block();
+    let l1_block = l1_client.get_block_by_number(2).await.unwrap().unwrap();
+
+    let rollup_context = chain.generator().rollup_context().clone();
+    let l1_client = Arc::new(l1_client);
+    let sync = WithdrawalUnlockSync::new(
+        chain.store().clone(),
+        l1_client.clone(),
+        rollup_context.clone(),
+    );
+    let event = ChainEvent::NewBlock { block: l1_block };
+    sync.handle_event(&event).await.unwrap();
+    assert_eq!(
+        chain
+            .store()
+            .get_withdrawal_unlock_tx_hash(&withdrawal.hash()),
+        Some(unlock_tx.hash())
+    );
+
+    // The L1 block of the unlock tx is reverted, and the new block doesn't
+    // include it.
+    l1_client.rollback_to(1).unwrap();
+    assert!(l1_client.drop_pending(&unlock_tx.hash()));
+    l1_client.commit_block();
+    let new_block = l1_client.get_block_by_number(2).await.unwrap().unwrap();
+    let event = ChainEvent::Reverted {
+        old_tip: unlock_tip,
+        new_block,
+    };
+    sync.handle_event(&event).await.unwrap();
+    assert_eq!(
+        chain
+            .store()
+            .get_withdrawal_unlock_tx_hash(&withdrawal.hash()),
+        None
+    );
+
+    // Not a withdrawal cell
+    let db = chain.store().begin_transaction();
+    assert_eq!(
+        find_withdrawal_hash(&db, &rollup_context, &submit_tx, 0).unwrap(),
+        None
+    );
+    // A withdrawal cell of another submit tx, e.g. of a reverted block
+    let other_submit_tx = build_l1_tx(
+        vec![],
+        vec![
+            (CellOutput::default(), Bytes::from(vec![1u8])),
+            (submit_tx.raw().outputs().get(1).unwrap(), Bytes::new()),
+        ],
+        vec![],
+    );
+    assert_eq!(
+        find_withdrawal_hash(&db, &rollup_context, &other_submit_tx, 1).unwrap(),
+        None
+    );
+}
+
+fn build_l1_tx(
+    inputs: Vec<OutPoint>,
+    outputs: Vec<(CellOutput, Bytes)>,
+    witnesses: Vec<Bytes>,
+) -> Transaction {
+    let inputs = inputs
+        .into_iter()
+        .map(|out_point| CellInput::new_builder().previous_output(out_point).build());
+    let (outputs, outputs_data): (Vec<_>, Vec<_>) = outputs.into_iter().unzip();
+    let raw = RawTransaction::new_builder()
+        .inputs(inputs.pack())
+        .outputs(outputs.pack())
+        .outputs_data(outputs_data.pack())
+        .build();
+    Transaction::new_builder()
+        .raw(raw)
+        .witnesses(witnesses.pack())
+        .build()
+}
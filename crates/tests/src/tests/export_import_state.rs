@@ -0,0 +1,108 @@
+use gw_p2p_network::block_catch_up::catch_up_range;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{
+    packed::{BlockCatchUpRequest, NumberHash, Script},
+    prelude::*,
+};
+use gw_utils::export_state::{
+    export_state, import_state, last_finalized_block_number, verify_state_snapshot,
+    StateSnapshotReader, StateSnapshotWriter,
+};
+
+use crate::testing_tool::chain::{produce_empty_block, setup_chain};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_export_import_state() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut chain = setup_chain(Script::default()).await;
+    for _ in 0..20 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let tip_block = chain.store().get_last_valid_tip_block().unwrap();
+    let mut tx_db = chain.store().begin_transaction();
+    let nh = NumberHash::new_builder()
+        .number(tip_block.raw().number())
+        .block_hash(tip_block.hash().pack())
+        .build();
+    tx_db
+        .set_last_confirmed_block_number_hash(&nh.as_reader())
+        .unwrap();
+    tx_db.commit().unwrap();
+
+    let rollup_context = chain.generator().rollup_context();
+    let snap = chain.store().get_snapshot();
+    let block_number = last_finalized_block_number(
+        &rollup_context.rollup_config,
+        &rollup_context.fork_config,
+        &snap,
+    )
+    .unwrap()
+    .unwrap();
+    let mut writer = StateSnapshotWriter::new(Vec::new()).unwrap();
+    export_state(
+        &snap,
+        &rollup_context.rollup_config,
+        &rollup_context.fork_config,
+        block_number,
+        &mut writer,
+    )
+    .unwrap();
+    let snapshot = writer.finish().unwrap();
+    let rollup_type_hash = rollup_context.rollup_script_hash;
+    assert_eq!(
+        verify_state_snapshot(&snapshot[..]).unwrap(),
+        (rollup_type_hash, block_number)
+    );
+
+    // Round trip
+    let store = Store::open_tmp().unwrap();
+    let mut reader = StateSnapshotReader::new(&snapshot[..]).unwrap();
+    let imported = import_state(&store, &mut reader, &rollup_type_hash).unwrap();
+    assert_eq!(imported, block_number);
+    assert!(!store.is_state_restoring());
+    let block_hash = snap
+        .get_block_hash_by_number(block_number)
+        .unwrap()
+        .unwrap();
+    let restored_tip = store.get_last_valid_tip_block().unwrap();
+    assert_eq!(restored_tip.hash(), block_hash);
+    assert_eq!(
+        restored_tip.raw().post_account(),
+        snap.get_block(&block_hash)
+            .unwrap()
+            .unwrap()
+            .raw()
+            .post_account()
+    );
+
+    // Blocks before the earliest block are indexed by hash only.
+    let earliest = store.get_earliest_block_number();
+    assert!(earliest > 0);
+    let block_hash = store.get_block_hash_by_number(earliest - 1).unwrap();
+    assert_eq!(
+        block_hash,
+        snap.get_block_hash_by_number(earliest - 1).unwrap()
+    );
+    assert!(store.get_block(&block_hash.unwrap()).unwrap().is_none());
+    let request = BlockCatchUpRequest::new_builder()
+        .start(0u64.pack())
+        .count(1u64.pack())
+        .build();
+    assert!(catch_up_range(&store.get_snapshot(), &request).is_err());
+    let request = BlockCatchUpRequest::new_builder()
+        .start(earliest.pack())
+        .count(1u64.pack())
+        .build();
+    assert!(catch_up_range(&store.get_snapshot(), &request).is_ok());
+
+    // An interrupted import leaves the store marked as restoring.
+    let store = Store::open_tmp().unwrap();
+    let truncated = &snapshot[..snapshot.len() - 41];
+    let mut reader = StateSnapshotReader::new(truncated).unwrap();
+    assert!(import_state(&store, &mut reader, &rollup_type_hash).is_err());
+    assert!(store.is_state_restoring());
+    let mut reader = StateSnapshotReader::new(&snapshot[..]).unwrap();
+    let err = import_state(&store, &mut reader, &rollup_type_hash).unwrap_err();
+    assert!(err.to_string().contains("interrupted"));
+}
@@ -0,0 +1,350 @@
+use std::{sync::Arc, time::Duration};
+
+use gw_block_producer::{
+    chain_updater::ChainUpdater,
+    produce_block::ProduceBlockResult,
+    sync_l1::{sync_l1, SyncL1Context},
+};
+use gw_chain::chain::Chain;
+use gw_jsonrpc_types::ckb_jsonrpc_types::Status;
+use gw_rpc_client::{
+    error::{get_jsonrpc_error_code, CkbRpcError},
+    indexer_types::{Order, SearchKey},
+    l1_client::L1Client,
+    mock_l1_client::MockL1Client,
+};
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    offchain::CellStatus,
+    packed::{CellInput, CellOutput, OutPoint, RawTransaction, Script, Transaction},
+    prelude::*,
+};
+use gw_utils::liveness::Liveness;
+use tokio::sync::Mutex;
+
+use crate::testing_tool::chain::{build_sync_tx, construct_block_with_timestamp, setup_chain};
+
+fn script(args: u8) -> Script {
+    Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(vec![args; 32].pack())
+        .build()
+}
+
+fn build_tx(inputs: Vec<OutPoint>, outputs: Vec<CellOutput>) -> Transaction {
+    let inputs = inputs
+        .into_iter()
+        .map(|out_point| CellInput::new_builder().previous_output(out_point).build());
+    let outputs_data = vec![Bytes::new(); outputs.len()];
+    let raw = RawTransaction::new_builder()
+        .inputs(inputs.pack())
+        .outputs(outputs.pack())
+        .outputs_data(outputs_data.pack())
+        .build();
+    Transaction::new_builder().raw(raw).build()
+}
+
+fn owner_cell(lock: Script) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(lock)
+        .build()
+}
+
+fn out_point(tx: &Transaction, index: u32) -> OutPoint {
+    OutPoint::new_builder()
+        .tx_hash(tx.hash().pack())
+        .index(index.pack())
+        .build()
+}
+
+#[tokio::test]
+async fn test_mock_l1_client_commit_and_query() {
+    let rollup_type_script = script(0);
+    let l1 = MockL1Client::new(rollup_type_script.clone());
+
+    let rollup_cell = CellOutput::new_builder()
+        .lock(script(1))
+        .type_(Some(rollup_type_script).pack())
+        .build();
+    let seed = build_tx(vec![], vec![rollup_cell, owner_cell(script(2))]);
+    l1.send_transaction(&seed).await.unwrap();
+    assert_eq!(
+        l1.get_transaction_status(seed.hash()).await.unwrap(),
+        Some(Status::Pending)
+    );
+    assert!(l1.query_rollup_cell().await.unwrap().is_none());
+
+    let tip = l1.commit_block();
+    assert_eq!(Unpack::<u64>::unpack(&tip.number()), 1);
+    assert_eq!(l1.get_tip().await.unwrap().as_slice(), tip.as_slice());
+    assert_eq!(
+        l1.get_transaction_status(seed.hash()).await.unwrap(),
+        Some(Status::Committed)
+    );
+    assert_eq!(
+        l1.get_transaction_block_number(seed.hash()).await.unwrap(),
+        Some(1)
+    );
+    let rollup = l1.query_rollup_cell().await.unwrap().unwrap();
+    assert_eq!(rollup.out_point.as_slice(), out_point(&seed, 0).as_slice());
+
+    // Spend the rollup cell.
+    let spend = build_tx(vec![out_point(&seed, 0)], vec![owner_cell(script(3))]);
+    l1.send_transaction(&spend).await.unwrap();
+    // Double spending is rejected by the pool.
+    let double_spend = build_tx(vec![out_point(&seed, 0)], vec![owner_cell(script(4))]);
+    let err = l1.send_transaction(&double_spend).await.unwrap_err();
+    assert_eq!(
+        get_jsonrpc_error_code(&err),
+        Some(CkbRpcError::TransactionFailedToResolve as i64)
+    );
+    l1.commit_block();
+    assert!(l1.query_rollup_cell().await.unwrap().is_none());
+    let spent = l1.get_cell(out_point(&seed, 0)).await.unwrap().unwrap();
+    assert!(matches!(spent.status, CellStatus::Dead));
+    let live = l1.get_cell(out_point(&spend, 0)).await.unwrap().unwrap();
+    assert!(matches!(live.status, CellStatus::Live));
+    let block = l1.get_block_by_number(2).await.unwrap().unwrap();
+    assert_eq!(block.transactions().len(), 1);
+    assert!(l1.get_block_by_number(3).await.unwrap().is_none());
+
+    let search_key = SearchKey::with_lock(script(3));
+    let cells = l1
+        .get_cells(&search_key, &Order::Asc, 10.into(), &None)
+        .await
+        .unwrap();
+    assert_eq!(cells.objects.len(), 1);
+    assert!(!cells.last_cursor.is_empty());
+    let next = l1
+        .get_cells(
+            &search_key,
+            &Order::Asc,
+            10.into(),
+            &Some(cells.last_cursor),
+        )
+        .await
+        .unwrap();
+    assert!(next.objects.is_empty());
+    assert!(next.last_cursor.is_empty());
+
+    // Input and output of the seed tx, and the input of the spending tx.
+    let search_key = SearchKey::with_lock(script(1));
+    let txs = l1
+        .get_transactions(&search_key, &Order::Asc, 10.into(), &None)
+        .await
+        .unwrap();
+    let tx_hashes: Vec<[u8; 32]> = txs.objects.iter().map(|tx| tx.tx_hash.0).collect();
+    assert_eq!(tx_hashes, vec![seed.hash(), spend.hash()]);
+}
+
+#[tokio::test]
+async fn test_mock_l1_client_fork() {
+    let l1 = MockL1Client::new(script(0));
+
+    let seed = build_tx(vec![], vec![owner_cell(script(1))]);
+    l1.send_transaction(&seed).await.unwrap();
+    l1.commit_block();
+    let spend = build_tx(vec![out_point(&seed, 0)], vec![owner_cell(script(2))]);
+    l1.send_transaction(&spend).await.unwrap();
+    let old_tip = l1.commit_block();
+
+    // Fork: the spending tx goes back to the pool.
+    l1.rollback_to(1).unwrap();
+    let tip = l1.get_tip().await.unwrap();
+    assert_eq!(Unpack::<u64>::unpack(&tip.number()), 1);
+    assert_eq!(
+        l1.get_transaction_status(spend.hash()).await.unwrap(),
+        Some(Status::Pending)
+    );
+    assert_eq!(
+        l1.get_cells(
+            &SearchKey::with_lock(script(1)),
+            &Order::Asc,
+            10.into(),
+            &None
+        )
+        .await
+        .unwrap()
+        .objects
+        .len(),
+        1
+    );
+
+    // The spending tx is replaced by a conflicting one on the new fork.
+    assert!(l1.drop_pending(&spend.hash()));
+    let conflict = build_tx(vec![out_point(&seed, 0)], vec![owner_cell(script(3))]);
+    l1.send_transaction(&conflict).await.unwrap();
+    let new_tip = l1.commit_block();
+    assert_eq!(Unpack::<u64>::unpack(&new_tip.number()), 2);
+    assert_ne!(
+        new_tip.block_hash().as_slice(),
+        old_tip.block_hash().as_slice()
+    );
+    assert_eq!(l1.get_transaction_status(spend.hash()).await.unwrap(), None);
+    assert_eq!(
+        l1.get_transaction_status(conflict.hash()).await.unwrap(),
+        Some(Status::Committed)
+    );
+
+    assert!(l1.rollback_to(3).is_err());
+}
+
+struct SyncContext {
+    store: Store,
+    l1_client: Arc<MockL1Client>,
+    chain: Arc<Mutex<Chain>>,
+    chain_updater: ChainUpdater,
+    rollup_type_script: Script,
+    liveness: Liveness,
+}
+
+impl SyncL1Context for SyncContext {
+    fn store(&self) -> &Store {
+        &self.store
+    }
+    fn l1_client(&self) -> &dyn L1Client {
+        &*self.l1_client
+    }
+    fn chain(&self) -> &Mutex<Chain> {
+        &self.chain
+    }
+    fn chain_updater(&self) -> &ChainUpdater {
+        &self.chain_updater
+    }
+    fn rollup_type_script(&self) -> &Script {
+        &self.rollup_type_script
+    }
+    fn liveness(&self) -> &Liveness {
+        &self.liveness
+    }
+}
+
+/// Produce a block on top of genesis and submit it, spending the rollup cell.
+async fn submit_block(
+    producer: &Chain,
+    l1: &MockL1Client,
+    rollup_cell: (CellOutput, OutPoint),
+    timestamp: u64,
+) -> (ProduceBlockResult, Transaction) {
+    let block_result = {
+        let mem_pool = producer.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block_with_timestamp(producer, &mut mem_pool, Default::default(), timestamp, true)
+            .await
+            .unwrap()
+    };
+    let tx = build_sync_tx(rollup_cell.0, block_result.clone());
+    let input = CellInput::new_builder()
+        .previous_output(rollup_cell.1)
+        .build();
+    let raw = tx.raw().as_builder().inputs(vec![input].pack()).build();
+    let tx = tx.as_builder().raw(raw).build();
+    l1.send_transaction(&tx).await.unwrap();
+    (block_result, tx)
+}
+
+/// `sync_l1` retries forever, fail instead.
+async fn sync(ctx: &SyncContext) {
+    tokio::time::timeout(Duration::from_secs(60), sync_l1(ctx))
+        .await
+        .expect("sync l1 timeout")
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sync_l1_with_mock_l1_client() {
+    let rollup_type_script = script(0);
+    let l1 = Arc::new(MockL1Client::new(rollup_type_script.clone()));
+    let producer = setup_chain(rollup_type_script.clone()).await;
+    let follower = setup_chain(rollup_type_script.clone()).await;
+    let store = follower.store().clone();
+
+    // Deploy the rollup cell with the genesis global state.
+    let rollup_cell = CellOutput::new_builder()
+        .lock(script(1))
+        .type_(Some(rollup_type_script.clone()).pack())
+        .build();
+    let genesis_hash = store.get_block_hash_by_number(0).unwrap().unwrap();
+    let genesis_state = store
+        .get_block_post_global_state(&genesis_hash)
+        .unwrap()
+        .unwrap();
+    let deploy = {
+        let raw = RawTransaction::new_builder()
+            .outputs(vec![rollup_cell.clone()].pack())
+            .outputs_data(vec![genesis_state.as_bytes()].pack())
+            .build();
+        Transaction::new_builder().raw(raw).build()
+    };
+    l1.send_transaction(&deploy).await.unwrap();
+    l1.commit_block();
+    let mut store_tx = store.begin_transaction();
+    store_tx
+        .set_block_submit_tx_hash(0, &deploy.hash())
+        .unwrap();
+    store_tx.commit().unwrap();
+
+    let rollup_context = follower.generator().rollup_context().clone();
+    let chain = Arc::new(Mutex::new(follower));
+    let ctx = SyncContext {
+        store: store.clone(),
+        l1_client: l1.clone(),
+        chain: chain.clone(),
+        chain_updater: ChainUpdater::new(
+            chain,
+            l1.clone(),
+            rollup_context,
+            rollup_type_script.clone(),
+        ),
+        rollup_type_script,
+        liveness: Liveness::new(Duration::from_secs(60)),
+    };
+
+    // Produce and submit a block, then confirm it on L1.
+    let (block, submit_tx) = submit_block(
+        &producer,
+        &l1,
+        (rollup_cell.clone(), out_point(&deploy, 0)),
+        1000,
+    )
+    .await;
+    sync(&ctx).await;
+    // Not on L1 yet.
+    assert!(store.get_block_hash_by_number(1).unwrap().is_none());
+
+    l1.commit_block();
+    sync(&ctx).await;
+    let last_confirmed = store.get_last_confirmed_block_number_hash().unwrap();
+    assert_eq!(Unpack::<u64>::unpack(&last_confirmed.number()), 1);
+    assert_eq!(
+        last_confirmed.block_hash().as_slice(),
+        block.block.hash().as_slice()
+    );
+    assert_eq!(store.get_block_submit_tx_hash(1), Some(submit_tx.hash()));
+
+    // L1 fork: the submission is replaced by a conflicting block.
+    l1.rollback_to(1).unwrap();
+    assert!(l1.drop_pending(&submit_tx.hash()));
+    let (conflict, conflict_tx) =
+        submit_block(&producer, &l1, (rollup_cell, out_point(&deploy, 0)), 2000).await;
+    assert_ne!(conflict.block.hash(), block.block.hash());
+    l1.commit_block();
+
+    // The follower reverts the block and syncs the conflicting one.
+    sync(&ctx).await;
+    let last_confirmed = store.get_last_confirmed_block_number_hash().unwrap();
+    assert_eq!(Unpack::<u64>::unpack(&last_confirmed.number()), 1);
+    assert_eq!(
+        last_confirmed.block_hash().as_slice(),
+        conflict.block.hash().as_slice()
+    );
+    assert_eq!(
+        store.get_last_valid_tip_block_hash().unwrap(),
+        conflict.block.hash()
+    );
+    assert_eq!(store.get_block_submit_tx_hash(1), Some(conflict_tx.hash()));
+}
@@ -1,12 +1,17 @@
+mod backup;
+mod block_catch_up;
 mod calc_finalizing_range;
 mod chain;
 mod deposit_withdrawal;
 mod export_import_block;
+mod export_import_state;
 mod mem_block_repackage;
 mod mem_pool_ckb_transfer_create_new_recipient_account;
 mod meta_contract_args;
+mod mock_l1_client;
 mod polyjuice_sender_recover;
 mod restore_mem_block;
 mod restore_mem_pool_pending_withdrawal;
+mod secondary_catch_up;
 mod rpc_server;
 mod unlock_withdrawal_to_owner;
@@ -0,0 +1,55 @@
+use std::{collections::HashSet, iter::FromIterator};
+
+use gw_config::RPCMethods;
+use gw_store::{autorocks::DbOptions, schema::COLUMNS};
+use gw_types::packed::Script;
+use jsonrpc_core::ErrorCode;
+
+use crate::testing_tool::{chain::TestChain, rpc_server::RPCServer};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_create_checkpoint() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script.clone()).await;
+    let checkpoint_root = tempfile::tempdir().unwrap();
+    let build_server = |checkpoint_root| {
+        let mut args =
+            RPCServer::default_registry_args(&chain.inner, rollup_type_script.clone(), None);
+        args.server_config.enable_methods = HashSet::from_iter(vec![RPCMethods::Admin]);
+        args.server_config.checkpoint_root = checkpoint_root;
+        RPCServer::build_from_registry_args(args)
+    };
+
+    // The checkpoint root must be configured.
+    let rpc_server = build_server(None).await.unwrap();
+    let err = rpc_server
+        .create_checkpoint("checkpoint")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+    let rpc_server = build_server(Some(checkpoint_root.path().to_path_buf()))
+        .await
+        .unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    for name in [
+        "",
+        ".",
+        "..",
+        "../checkpoint",
+        "a/../../checkpoint",
+        outside.path().to_str().unwrap(),
+    ] {
+        let err = rpc_server.create_checkpoint(name).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidParams, "{}", name);
+    }
+    assert_eq!(outside.path().read_dir().unwrap().count(), 0);
+
+    rpc_server.create_checkpoint("checkpoint").await.unwrap();
+    let checkpoint_dir = checkpoint_root.path().join("checkpoint");
+    DbOptions::new(&checkpoint_dir, COLUMNS).open().unwrap();
+    // The checkpoint dir must not exist.
+    assert!(rpc_server.create_checkpoint("checkpoint").await.is_err());
+}
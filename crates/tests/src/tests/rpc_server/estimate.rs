@@ -0,0 +1,114 @@
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_generator::generator::CyclesPool;
+use gw_store::{chain_view::ChainView, state::traits::JournalDB, traits::chain_store::ChainStore};
+use gw_types::{
+    h256::*,
+    packed::{RawL2Transaction, Script},
+    prelude::*,
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount, PolyjuiceSystemLog},
+    rpc_server::RPCServer,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_estimate_cycles_and_fee_suggestion() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let deployer_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deployer_id = deployer_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    // Deploy erc20 for test
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(deployer_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = deployer_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(deploy_tx).unwrap();
+    }
+
+    let system_log = PolyjuiceSystemLog::parse_from_tx_hash(&chain, deploy_tx_hash).unwrap();
+    assert_eq!(system_log.status_code, 0);
+    let state = mem_pool_state.load_state_db();
+    let erc20_contract_account_id = system_log.contract_account_id(&state).unwrap();
+
+    // No txs in the recent blocks yet.
+    let suggestion = rpc_server.get_fee_suggestion().await.unwrap();
+    assert_eq!(suggestion.recent_blocks_fee_rate, None);
+    assert_eq!(u32::from(suggestion.queue_len), 0);
+
+    let balance_args = SudtErc20ArgsBuilder::balance_of(deployer_wallet.reg_address()).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(deployer_id.pack())
+        .to_id(erc20_contract_account_id.pack())
+        .nonce(1u32.pack())
+        .args(balance_args.pack())
+        .build();
+    let estimate = rpc_server.estimate_cycles(&raw_tx, None).await.unwrap();
+    let max_cycles: u64 = estimate.max_cycles.into();
+    assert!(max_cycles > 0);
+
+    // The estimated max cycles is the minimum.
+    let generator = chain.inner.generator();
+    let snap = chain.store().get_snapshot();
+    let chain_view = ChainView::new(&snap, snap.get_last_valid_tip_block_hash().unwrap());
+    let block_info = mem_pool_state.get_mem_pool_block_info().unwrap();
+    let execute = |max_cycles: u64| {
+        let mut state = mem_pool_state.load_state_db();
+        let mut cycles_pool = CyclesPool::new(u64::MAX, Default::default());
+        generator
+            .execute_transaction(
+                &chain_view,
+                &mut state,
+                &block_info,
+                &raw_tx,
+                Some(max_cycles),
+                Some(&mut cycles_pool),
+            )
+            .unwrap()
+            .exit_code
+    };
+    assert_eq!(execute(max_cycles), 0);
+    assert_ne!(execute(max_cycles - 1), 0);
+
+    // The deploy tx is in the tip block now, its gas price is 1.
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    let suggestion = rpc_server.get_fee_suggestion().await.unwrap();
+    assert_eq!(suggestion.recent_blocks_fee_rate, Some(1u128.into()));
+    assert_eq!(u128::from(suggestion.fee_rate), 1);
+
+    // Cached fee rates of the earlier blocks are reused after a new block.
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    let suggestion = rpc_server.get_fee_suggestion().await.unwrap();
+    assert_eq!(suggestion.recent_blocks_fee_rate, Some(1u128.into()));
+}
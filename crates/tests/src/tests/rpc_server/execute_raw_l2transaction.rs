@@ -24,6 +24,7 @@ use crate::testing_tool::{
 };
 
 pub mod block_max_cycles_limit;
+pub mod state_override;
 
 const META_CONTRACT_ACCOUNT_ID: u32 = RESERVED_ACCOUNT_ID;
 
@@ -0,0 +1,120 @@
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_jsonrpc_types::godwoken::{AccountOverride, RegistryAddressJsonBytes, StateOverride};
+use gw_store::state::traits::JournalDB;
+use gw_types::{
+    h256::*,
+    packed::{RawL2Transaction, Script},
+    prelude::*,
+    U256,
+};
+use jsonrpc_core::ErrorCode;
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount, PolyjuiceSystemLog},
+    rpc_server::RPCServer,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_state_override() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let deployer_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deployer_id = deployer_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    // Deploy erc20 for test
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(deployer_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = deployer_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(deploy_tx).unwrap();
+    }
+
+    let system_log = PolyjuiceSystemLog::parse_from_tx_hash(&chain, deploy_tx_hash).unwrap();
+    assert_eq!(system_log.status_code, 0);
+    let state = mem_pool_state.load_state_db();
+    let erc20_contract_account_id = system_log.contract_account_id(&state).unwrap();
+
+    // The account doesn't exist, create it with some balance.
+    let test_wallet = EthWallet::random(chain.rollup_type_hash());
+    let test_balance: U256 = 424242u128.into();
+    let account_override = AccountOverride {
+        script: Some(test_wallet.account_script().to_owned().into()),
+        registry_address: Some(RegistryAddressJsonBytes(
+            test_wallet.reg_address().to_owned(),
+        )),
+        balance: Some(test_balance),
+        ..Default::default()
+    };
+    let state_override: StateOverride =
+        vec![(test_wallet.account_script_hash().into(), account_override)]
+            .into_iter()
+            .collect();
+
+    let balance_args = SudtErc20ArgsBuilder::balance_of(test_wallet.reg_address()).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(0u32.pack())
+        .to_id(erc20_contract_account_id.pack())
+        .nonce(0u32.pack())
+        .args(balance_args.pack())
+        .build();
+    let reg_addr_bytes = test_wallet.reg_address().to_bytes().into();
+    let run_result = rpc_server
+        .execute_raw_l2transaction_with_state_override(
+            &raw_tx,
+            None,
+            Some(reg_addr_bytes),
+            Some(state_override.clone()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        test_balance,
+        U256::from_big_endian(run_result.return_data.as_bytes())
+    );
+
+    // Overrides are discarded after the execution.
+    let state = mem_pool_state.load_state_db();
+    assert!(state
+        .get_account_id_by_script_hash(&test_wallet.account_script_hash())
+        .unwrap()
+        .is_none());
+    assert!(state
+        .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, test_wallet.reg_address())
+        .unwrap()
+        .is_zero());
+
+    // Script doesn't match the account script hash.
+    let mut state_override = state_override;
+    state_override
+        .values_mut()
+        .for_each(|account| account.script = Some(deployer_wallet.account_script().clone().into()));
+    let err = rpc_server
+        .execute_raw_l2transaction_with_state_override(&raw_tx, None, None, Some(state_override))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+}
@@ -0,0 +1,150 @@
+use gw_common::{
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    state::{
+        build_account_field_key, build_account_key, build_sudt_key, State, GW_ACCOUNT_NONCE_TYPE,
+        GW_ACCOUNT_SCRIPT_HASH_TYPE, SUDT_KEY_FLAG_BALANCE,
+    },
+};
+use gw_jsonrpc_types::godwoken::{AccountMerkleState, AccountProof};
+use gw_smt::merkle_utils::verify_merkle_proof;
+use gw_store::traits::chain_store::ChainStore;
+use gw_types::{
+    h256::*,
+    packed::{DepositInfoVec, DepositRequest, Script},
+    prelude::*,
+};
+use jsonrpc_core::ErrorCode;
+
+use crate::testing_tool::{
+    chain::{into_deposit_info_cell, TestChain},
+    eth_wallet::EthWallet,
+    rpc_server::RPCServer,
+};
+
+fn proof_leaves(proof: &AccountProof) -> Vec<(H256, H256)> {
+    let account_id: u32 = proof.account_id.into();
+    let nonce: u32 = proof.nonce.into();
+    let mut leaves = vec![
+        (
+            build_account_field_key(account_id, GW_ACCOUNT_SCRIPT_HASH_TYPE),
+            proof.script_hash.0,
+        ),
+        (
+            build_account_field_key(account_id, GW_ACCOUNT_NONCE_TYPE),
+            H256::from_u32(nonce),
+        ),
+    ];
+    leaves.extend(
+        proof
+            .storage
+            .iter()
+            .map(|item| (build_account_key(account_id, &item.key.0), item.value.0)),
+    );
+    leaves
+}
+
+fn verify(proof: &AccountProof, leaves: Vec<(H256, H256)>) -> bool {
+    verify_merkle_proof(
+        &proof.account_merkle_state.merkle_root.0,
+        proof.merkle_proof.as_bytes().to_vec(),
+        leaves,
+    )
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_proof() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    // Deposit test account
+    const DEPOSIT_CAPACITY: u64 = 12345768 * 10u64.pow(8);
+    let test_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deposit = DepositRequest::new_builder()
+        .capacity(DEPOSIT_CAPACITY.pack())
+        .sudt_script_hash(H256::zero().pack())
+        .amount(0.pack())
+        .script(test_wallet.account_script().to_owned())
+        .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+        .build();
+    let deposit_info_vec = DepositInfoVec::new_builder()
+        .push(into_deposit_info_cell(chain.inner.generator().rollup_context(), deposit).pack())
+        .build();
+    chain.produce_block(deposit_info_vec, vec![]).await.unwrap();
+
+    let tip_block = chain.last_valid_block();
+    let tip_number: u64 = tip_block.raw().number().unpack();
+    let state = chain.mem_pool_state().await.load_state_db();
+
+    // Balance of the test account
+    let balance_key: H256 = build_sudt_key(SUDT_KEY_FLAG_BALANCE, test_wallet.reg_address())
+        .as_slice()
+        .try_into()
+        .unwrap();
+    let proof = rpc_server
+        .get_proof(CKB_SUDT_ACCOUNT_ID, vec![balance_key, balance_key], None)
+        .await
+        .unwrap();
+    assert_eq!(u64::from(proof.block_number), tip_number);
+    assert_eq!(proof.block_hash.0, tip_block.hash());
+    assert_eq!(
+        proof.account_merkle_state,
+        AccountMerkleState::from(tip_block.raw().post_account())
+    );
+    assert_eq!(proof.storage.len(), 1);
+    assert_eq!(proof.storage[0].key.0, balance_key);
+    let balance = state
+        .get_value(CKB_SUDT_ACCOUNT_ID, balance_key.as_slice())
+        .unwrap();
+    assert!(!balance.is_zero());
+    assert_eq!(proof.storage[0].value.0, balance);
+    assert!(verify(&proof, proof_leaves(&proof)));
+
+    // Tampered balance
+    let mut leaves = proof_leaves(&proof);
+    leaves.last_mut().unwrap().1 = H256::from_u64(1);
+    assert!(!verify(&proof, leaves));
+
+    // Nonce and script hash of the test account, and a non-existent key
+    let account_id = state
+        .get_account_id_by_script_hash(&test_wallet.account_script_hash())
+        .unwrap()
+        .unwrap();
+    let proof = rpc_server
+        .get_proof(account_id, vec![[42u8; 32]], Some(tip_number))
+        .await
+        .unwrap();
+    assert_eq!(proof.script_hash.0, test_wallet.account_script_hash());
+    assert_eq!(u32::from(proof.nonce), 0);
+    assert!(proof.storage[0].value.0.is_zero());
+    assert!(verify(&proof, proof_leaves(&proof)));
+
+    // Before the deposit
+    let parent_block = chain
+        .store()
+        .get_block(&tip_block.raw().parent_block_hash().unpack())
+        .unwrap()
+        .unwrap();
+    let proof = rpc_server
+        .get_proof(CKB_SUDT_ACCOUNT_ID, vec![balance_key], Some(tip_number - 1))
+        .await
+        .unwrap();
+    assert_eq!(u64::from(proof.block_number), tip_number - 1);
+    assert_eq!(proof.block_hash.0, parent_block.hash());
+    assert_eq!(
+        proof.account_merkle_state,
+        AccountMerkleState::from(parent_block.raw().post_account())
+    );
+    assert!(proof.storage[0].value.0.is_zero());
+    assert!(verify(&proof, proof_leaves(&proof)));
+
+    // Beyond the tip block
+    let err = rpc_server
+        .get_proof(account_id, vec![], Some(tip_number + 1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_config::{MemBlockConfig, MemPoolConfig};
+use gw_jsonrpc_types::godwoken::{TransactionDropReason, TransactionStatus};
+use gw_store::state::traits::JournalDB;
+use gw_types::{
+    h256::*,
+    packed::{RawL2Transaction, Script},
+    prelude::*,
+};
+
+use crate::{
+    testing_tool::{
+        chain::TestChain,
+        eth_wallet::EthWallet,
+        polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount},
+        rpc_server::{wait_tx_committed, RPCServer},
+    },
+    tests::rpc_server::BLOCK_MAX_CYCLES_LIMIT,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_transaction_status() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mem_pool_config = MemPoolConfig {
+        mem_block: MemBlockConfig {
+            max_cycles_limit: BLOCK_MAX_CYCLES_LIMIT,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rollup_type_script = Script::default();
+    let chain = {
+        let chain = TestChain::setup(rollup_type_script).await;
+        chain.update_mem_pool_config(mem_pool_config).await
+    };
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let deployer_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deployer_id = deployer_wallet
+        .create_account(&mut state, 1000000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(deployer_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(
+            SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18)
+                .finish()
+                .pack(),
+        )
+        .build();
+    let deploy_tx = deployer_wallet
+        .sign_polyjuice_tx(&state, raw_tx.clone())
+        .unwrap();
+    let exceeded_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18)
+        .gas_limit(BLOCK_MAX_CYCLES_LIMIT + 1)
+        .finish();
+    let exceeded_raw_tx = raw_tx
+        .as_builder()
+        .nonce(1u32.pack())
+        .args(exceeded_args.pack())
+        .build();
+    let exceeded_tx = deployer_wallet
+        .sign_polyjuice_tx(&state, exceeded_raw_tx)
+        .unwrap();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+
+    let unknown_status = rpc_server
+        .get_transaction_status(deploy_tx.hash())
+        .await
+        .unwrap();
+    assert_eq!(unknown_status, None);
+
+    let deploy_tx_hash = rpc_server
+        .submit_l2transaction(&deploy_tx)
+        .await
+        .unwrap()
+        .unwrap();
+    wait_tx_committed(&chain, &deploy_tx_hash, Duration::from_secs(30))
+        .await
+        .unwrap();
+    let status = rpc_server
+        .get_transaction_status(deploy_tx_hash)
+        .await
+        .unwrap();
+    assert_eq!(status, Some(TransactionStatus::InMemBlock));
+
+    let exceeded_tx_hash = rpc_server
+        .submit_l2transaction(&exceeded_tx)
+        .await
+        .unwrap()
+        .unwrap();
+    let now = Instant::now();
+    let status = loop {
+        let status = rpc_server
+            .get_transaction_status(exceeded_tx_hash)
+            .await
+            .unwrap();
+        if !matches!(status, Some(TransactionStatus::Queued)) {
+            break status;
+        }
+        assert!(now.elapsed() < Duration::from_secs(30), "still queued");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    match status {
+        Some(TransactionStatus::Dropped {
+            reason,
+            replaced_by,
+            ..
+        }) => {
+            assert_eq!(reason, TransactionDropReason::ExceededCyclesLimit);
+            assert_eq!(replaced_by, None);
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+}
@@ -1,6 +1,11 @@
 pub(crate) const BLOCK_MAX_CYCLES_LIMIT: u64 = 300_0000;
 
+pub mod create_checkpoint;
+pub mod estimate;
 pub mod execute_l2transaction;
 pub mod execute_raw_l2transaction;
+pub mod get_proof;
+pub mod get_transaction_status;
+pub mod restore_queued_requests;
 pub mod submit_l2transaction;
 pub mod submit_withdrawal_request;
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_store::state::traits::JournalDB;
+use gw_types::{
+    h256::*,
+    packed::{RawL2Transaction, Script},
+    prelude::*,
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount},
+    rpc_server::{wait_tx_committed, RPCServer},
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_restore_queued_requests() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let deployer_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deployer_id = deployer_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(deployer_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = deployer_wallet
+        .sign_polyjuice_tx(&state, raw_tx.clone())
+        .unwrap();
+    // Its nonce never catches up, it should be dropped after replay.
+    let future_raw_tx = raw_tx.as_builder().nonce(5u32.pack()).build();
+    let future_tx = deployer_wallet
+        .sign_polyjuice_tx(&state, future_raw_tx)
+        .unwrap();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+
+    // Queued requests journaled before the restart.
+    {
+        let mut db = chain.store().begin_transaction();
+        db.insert_queued_transaction(&deploy_tx.hash(), deploy_tx.clone())
+            .unwrap();
+        db.insert_queued_transaction(&future_tx.hash(), future_tx.clone())
+            .unwrap();
+        db.commit().unwrap();
+    }
+
+    // The request submitter replays the journaled requests.
+    let _rpc_server = RPCServer::build(&chain, None).await.unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash();
+    wait_tx_committed(&chain, &deploy_tx_hash, Duration::from_secs(30))
+        .await
+        .unwrap();
+
+    let now = Instant::now();
+    while chain
+        .store()
+        .begin_transaction()
+        .get_queued_transaction_iter()
+        .count()
+        > 0
+    {
+        assert!(
+            now.elapsed() < Duration::from_secs(30),
+            "journal not cleared"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let mem_pool = chain.mem_pool().await;
+    assert!(!mem_pool.mem_block().txs_set().contains(&future_tx.hash()));
+}
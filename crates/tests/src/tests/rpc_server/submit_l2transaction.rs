@@ -4,6 +4,7 @@ use gw_common::{
     builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
     state::State,
 };
+use gw_jsonrpc_types::godwoken::SubmitL2TransactionResult;
 use gw_smt::blake2b::new_blake2b;
 use gw_store::state::traits::JournalDB;
 use gw_types::prelude::*;
@@ -512,3 +513,127 @@ async fn test_invalid_polyjuice_tx_from_id_zero() {
         "unrecoverable txs should not be committed"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_submit_l2transactions() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let creator_wallet = EthWallet::random(chain.rollup_type_hash());
+    let creator_account_id = creator_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_tx = |nonce: u32| {
+        let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain.chain_id().pack())
+            .from_id(creator_account_id.pack())
+            .to_id(polyjuice_account.id.pack())
+            .nonce(nonce.pack())
+            .args(deploy_args.pack())
+            .build();
+        creator_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap()
+    };
+    // Out of nonce order, and a nonce gap.
+    let txs = vec![deploy_tx(1), deploy_tx(0), deploy_tx(3)];
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    let results = rpc_server.submit_l2transactions(&txs).await.unwrap();
+    assert_eq!(results.len(), 3);
+    for (tx, result) in txs.iter().zip(&results).take(2) {
+        assert_eq!(
+            result,
+            &SubmitL2TransactionResult::Submitted {
+                tx_hash: Some(tx.hash().into())
+            }
+        );
+    }
+    match &results[2] {
+        SubmitL2TransactionResult::Rejected { code, .. } => assert_eq!(*code, -32001),
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    for tx in &txs[..2] {
+        wait_tx_committed(&chain, &tx.hash(), Duration::from_secs(30))
+            .await
+            .unwrap();
+    }
+    let state = mem_pool_state.load_state_db();
+    assert_eq!(state.get_nonce(creator_account_id).unwrap(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_submit_l2transactions_sender_quota() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = {
+        let mut args = RPCServer::default_registry_args(
+            &chain.inner,
+            chain.rollup_type_script.to_owned(),
+            None,
+        );
+        args.fee_config.max_future_entries_per_sender = 2;
+        RPCServer::build_from_registry_args(args).await.unwrap()
+    };
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let creator_wallet = EthWallet::random(chain.rollup_type_hash());
+    let creator_account_id = creator_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_tx = |nonce: u32| {
+        let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain.chain_id().pack())
+            .from_id(creator_account_id.pack())
+            .to_id(polyjuice_account.id.pack())
+            .nonce(nonce.pack())
+            .args(deploy_args.pack())
+            .build();
+        creator_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap()
+    };
+    // One tx with the current nonce and 2 future ones fit in the quota.
+    let txs: Vec<_> = (0..4).map(deploy_tx).collect();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    let results = rpc_server.submit_l2transactions(&txs).await.unwrap();
+    assert_eq!(results.len(), 4);
+    for (tx, result) in txs.iter().zip(&results).take(3) {
+        assert_eq!(
+            result,
+            &SubmitL2TransactionResult::Submitted {
+                tx_hash: Some(tx.hash().into())
+            }
+        );
+    }
+    match &results[3] {
+        SubmitL2TransactionResult::Rejected { message, .. } => {
+            assert_eq!(message, "sender has too many future nonce requests")
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+
+    for tx in &txs[..3] {
+        wait_tx_committed(&chain, &tx.hash(), Duration::from_secs(30))
+            .await
+            .unwrap();
+    }
+    let state = mem_pool_state.load_state_db();
+    assert_eq!(state.get_nonce(creator_account_id).unwrap(), 3);
+}
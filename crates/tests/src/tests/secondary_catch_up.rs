@@ -0,0 +1,83 @@
+use std::{sync::Arc, time::Duration};
+
+use gw_block_producer::secondary_catch_up::SecondaryCatchUp;
+use gw_config::StoreConfig;
+use gw_generator::account_lock_manage::{always_success::AlwaysSuccess, AccountLockManage};
+use gw_store::{schema::COLUMNS, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    core::AllowedEoaType,
+    packed::{AllowedTypeHash, RollupConfig, Script},
+    prelude::*,
+};
+use gw_utils::liveness::Liveness;
+
+use crate::testing_tool::chain::{
+    produce_empty_block, setup_chain_with_account_lock_manage, ALWAYS_SUCCESS_CODE_HASH,
+    DEFAULT_FINALITY_BLOCKS,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_secondary_catch_up() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let store_dir = tempfile::tempdir().unwrap();
+    let store_config = StoreConfig {
+        path: store_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let mut chain = {
+        let rollup_config = RollupConfig::new_builder()
+            .allowed_eoa_type_hashes(
+                vec![AllowedTypeHash::new(
+                    AllowedEoaType::Eth,
+                    *ALWAYS_SUCCESS_CODE_HASH,
+                )]
+                .pack(),
+            )
+            .finality_blocks(DEFAULT_FINALITY_BLOCKS.pack())
+            .build();
+        let mut account_lock_manage = AccountLockManage::default();
+        account_lock_manage
+            .register_lock_algorithm(*ALWAYS_SUCCESS_CODE_HASH, Arc::new(AlwaysSuccess));
+        let store = Store::open(&store_config, COLUMNS).unwrap();
+        setup_chain_with_account_lock_manage(
+            Script::default(),
+            rollup_config,
+            account_lock_manage,
+            Some(store),
+            None,
+            None,
+        )
+        .await
+    };
+    produce_empty_block(&mut chain).await.unwrap();
+
+    let secondary_dir = tempfile::tempdir().unwrap();
+    let secondary = Store::open_secondary(&store_config, secondary_dir.path(), COLUMNS).unwrap();
+    assert!(secondary.is_secondary());
+    let liveness = Arc::new(Liveness::new(Duration::from_secs(60)));
+    let mut catch_up = SecondaryCatchUp::new(
+        secondary.clone(),
+        None,
+        Duration::from_secs(1),
+        liveness.clone(),
+    );
+    catch_up.catch_up().await.unwrap();
+    assert_eq!(
+        secondary.get_last_valid_tip_block_hash().unwrap(),
+        chain.store().get_last_valid_tip_block_hash().unwrap()
+    );
+
+    // New blocks of the primary are only visible after catching up.
+    let old_tip = secondary.get_last_valid_tip_block_hash().unwrap();
+    for _ in 0..3 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let new_tip = chain.store().get_last_valid_tip_block_hash().unwrap();
+    assert_ne!(new_tip, old_tip);
+    assert_eq!(secondary.get_last_valid_tip_block_hash().unwrap(), old_tip);
+    catch_up.catch_up().await.unwrap();
+    assert_eq!(secondary.get_last_valid_tip_block_hash().unwrap(), new_tip);
+    assert!(secondary.get_block(&new_tip).unwrap().is_some());
+    assert!(liveness.is_live());
+}
@@ -0,0 +1,51 @@
+//! Encrypt a private key file into a keystore, see `gw_utils::keystore`.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::{App, Arg, ArgMatches, Command};
+use gw_utils::keystore::{Keystore, DEFAULT_ITERATIONS};
+
+use crate::account::read_privkey;
+
+pub(crate) const COMMAND: &str = "create-keystore";
+
+pub(crate) fn command() -> App<'static> {
+    Command::new(COMMAND)
+        .about("Encrypt a private key file into a keystore file")
+        .arg(
+            Arg::with_name("privkey-path")
+                .long("privkey-path")
+                .short('k')
+                .takes_value(true)
+                .required(true)
+                .help("The private key file path"),
+        )
+        .arg(
+            Arg::with_name("passphrase-env")
+                .long("passphrase-env")
+                .takes_value(true)
+                .required(true)
+                .help("The environment variable holding the passphrase"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short('o')
+                .takes_value(true)
+                .required(true)
+                .help("The output keystore file path"),
+        )
+}
+
+pub(crate) fn run(m: &ArgMatches) -> Result<()> {
+    let privkey = read_privkey(Path::new(m.value_of("privkey-path").unwrap()))?;
+    let passphrase_env = m.value_of("passphrase-env").unwrap();
+    let passphrase = std::env::var(passphrase_env)
+        .with_context(|| format!("read passphrase from env {}", passphrase_env))?;
+
+    let keystore = Keystore::encrypt(&privkey.0, &passphrase, DEFAULT_ITERATIONS)?;
+    let output = serde_json::to_string_pretty(&keystore)?;
+    std::fs::write(m.value_of("output").unwrap(), output)?;
+    Ok(())
+}
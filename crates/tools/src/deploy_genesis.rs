@@ -254,6 +254,7 @@ pub async fn deploy_rollup_cell(args: DeployRollupCellArgs<'_>) -> Result<Rollup
             .eth_addr_reg_validator
             .script_type_hash
             .clone(),
+        ckb_registry_validator_type_hash: None,
         rollup_type_hash: rollup_script_hash.clone(),
         rollup_config: rollup_config.clone().into(),
         secp_data_dep,
@@ -179,7 +179,10 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
         rewards_receiver_lock: user_rollup_config.reward_lock.clone(),
     };
 
-    let wallet_config = cmd.privkey_path.map(|p| WalletConfig { privkey_path: p });
+    let wallet_config = cmd.privkey_path.map(|p| WalletConfig {
+        privkey_path: p,
+        signer: None,
+    });
 
     let backends: Vec<BackendConfig> = vec![
         {
@@ -268,6 +271,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
         rollup_type_hash: rollup_type_hash.clone(),
         meta_contract_validator_type_hash,
         eth_registry_validator_type_hash,
+        ckb_registry_validator_type_hash: None,
         rollup_config,
         secp_data_dep,
     };
@@ -276,6 +280,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
         backend_forks,
         increase_max_l2_tx_cycles_to_500m: None,
         upgrade_global_state_version_to_v2: Some(0),
+        enable_ckb_registry: None,
         genesis,
         chain,
         system_type_scripts,
@@ -286,6 +291,9 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
         path: cmd.store_path.unwrap_or_else(|| "./gw-db".into()),
         options_file: None,
         cache_size: None,
+        index_account_history: false,
+        state_history: Default::default(),
+        secondary: None,
     };
     let rpc_client: RPCClientConfig = RPCClientConfig {
         indexer_url: cmd.ckb_indexer_rpc,
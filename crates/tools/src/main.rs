@@ -3,6 +3,7 @@
 mod account;
 mod address;
 mod create_creator_account;
+mod create_keystore;
 mod deploy_genesis;
 mod deploy_scripts;
 mod deposit_ckb;
@@ -761,6 +762,7 @@ async fn main() -> Result<()> {
                         .help("input file"),
                 ))
         .subcommand(scan_eth_address::command())
+        .subcommand(create_keystore::command())
         ;
 
     let matches = app.clone().get_matches();
@@ -1366,6 +1368,9 @@ async fn main() -> Result<()> {
         Some((scan_eth_address::COMMAND, m)) => {
             scan_eth_address::run(m).await.unwrap();
         }
+        Some((create_keystore::COMMAND, m)) => {
+            create_keystore::run(m)?;
+        }
         _ => {
             app.print_help().expect("print help");
         }
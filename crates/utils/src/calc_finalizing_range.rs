@@ -67,6 +67,9 @@ fn find_finalized_upper_bound(
     // NOTE: To ensure that at least one finalized block is found below, start a binary search at
     // `upgrade_global_state_version_to_v2 - 1`.
     l = l.saturating_sub(1);
+    // Blocks before the earliest block are finalized for the earliest block and the blocks after,
+    // and they are not in the store.
+    l = l.max(db.get_earliest_block_number().saturating_sub(1));
     let mut r = block.raw().number().unpack().saturating_sub(1);
     while l < r {
         let mid = l + (r - l + 1) / 2;
@@ -2,9 +2,7 @@ use std::io::{ErrorKind, Read, Seek, SeekFrom};
 
 use anyhow::{anyhow, bail, Context, Result};
 use gw_smt::smt_h256_ext::SMTH256Ext;
-use gw_store::{
-    readonly::StoreReadonly, traits::chain_store::ChainStore, transaction::StoreTransaction,
-};
+use gw_store::{traits::chain_store::ChainStore, transaction::StoreTransaction};
 use gw_types::{
     bytes::Bytes,
     h256::*,
@@ -13,7 +11,7 @@ use gw_types::{
     prelude::*,
 };
 
-pub fn export_block(snap: &StoreReadonly, block_number: u64) -> Result<ExportedBlock> {
+pub fn export_block(snap: &impl ChainStore, block_number: u64) -> Result<ExportedBlock> {
     let block_hash = snap
         .get_block_hash_by_number(block_number)?
         .ok_or_else(|| anyhow!("block {} not found", block_number))?;
@@ -240,7 +238,10 @@ pub fn check_block_post_state(
     Ok(())
 }
 
-fn get_bad_block_hashes(snap: &StoreReadonly, block_number: u64) -> Result<Option<Vec<Vec<H256>>>> {
+fn get_bad_block_hashes(
+    snap: &impl ChainStore,
+    block_number: u64,
+) -> Result<Option<Vec<Vec<H256>>>> {
     let parent_reverted_block_root = {
         let parent_block_number = block_number.saturating_sub(1);
         get_block_reverted_block_root(snap, parent_block_number)?
@@ -251,13 +252,14 @@ fn get_bad_block_hashes(snap: &StoreReadonly, block_number: u64) -> Result<Optio
     }
 
     let mut bad_block_hashes = Vec::with_capacity(2);
-    let reverted_root_iter = snap.iter_reverted_block_smt_root(reverted_block_root);
-    for (reverted_block_root, reverted_block_hashes) in reverted_root_iter {
-        if reverted_block_root == parent_reverted_block_root {
-            break;
-        }
-
-        bad_block_hashes.push(reverted_block_hashes);
+    let mut root = reverted_block_root;
+    while root != parent_reverted_block_root {
+        let reverted = match snap.get_reverted_block_hashes_by_root(&root)? {
+            Some(reverted) => reverted,
+            None => break,
+        };
+        bad_block_hashes.push(reverted.block_hashes);
+        root = reverted.prev_smt_root;
     }
 
     bad_block_hashes.reverse();
@@ -0,0 +1,520 @@
+//! State snapshots, to bootstrap a node at a finalized block without importing
+//! or syncing the blocks before it.
+//!
+//! A snapshot of block `n` contains the account SMT leaves, scripts and data
+//! at block `n`, the hashes of the blocks up to `n` for the block SMT, and the
+//! blocks that are not finalized for block `n`, which are needed to
+//! calculate the finalized custodians of the following blocks.
+//!
+//! The file is a magic number followed by records of `tag (u8) | payload
+//! length (u32, little endian) | payload`. The last record is the blake2b
+//! checksum of everything before it.
+
+#![allow(clippy::mutable_key_type)]
+
+use std::io::{ErrorKind, Read, Write};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::blake2b::{new_blake2b, Blake2b};
+use gw_config::ForkConfig;
+use gw_store::{
+    snapshot::StoreSnapshot, state::history::history_state::HistoryStateStore,
+    traits::chain_store::ChainStore, transaction::StoreTransaction, Store,
+};
+use gw_traits::CodeStore;
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    offchain::ExportedBlock,
+    packed::{self, FinalizedCustodianCapacity, NumberHash, RollupConfig, Script},
+    prelude::*,
+};
+
+use crate::{
+    calc_finalizing_range,
+    export_block::{check_block_post_state, export_block, insert_bad_block_hashes},
+};
+
+const MAGIC: &[u8; 8] = b"GWSTATE1";
+
+const TAG_HEADER: u8 = 0;
+const TAG_BLOCK_HASH: u8 = 1;
+const TAG_BLOCK: u8 = 2;
+const TAG_FINALIZED_CUSTODIANS: u8 = 3;
+const TAG_REVERTED_BLOCK_HASHES: u8 = 4;
+const TAG_SCRIPT: u8 = 5;
+const TAG_DATA: u8 = 6;
+const TAG_ASSET_SCRIPT: u8 = 7;
+const TAG_STATE: u8 = 8;
+const TAG_CHECKSUM: u8 = 0xff;
+
+/// Commit the restored state every this many leaves, so that the transaction
+/// doesn't use too much memory.
+const COMMIT_INTERVAL: usize = 4096;
+
+pub enum StateSnapshotRecord {
+    Header {
+        rollup_type_hash: H256,
+        block_number: u64,
+    },
+    /// A block before the earliest block in the snapshot.
+    BlockHash {
+        block_number: u64,
+        block_hash: H256,
+    },
+    Block(packed::ExportedBlock),
+    FinalizedCustodians(FinalizedCustodianCapacity),
+    /// Reverted block hashes, oldest first.
+    RevertedBlockHashes(Vec<H256>),
+    Script(Script),
+    Data {
+        data_hash: H256,
+        data: Bytes,
+    },
+    AssetScript(Script),
+    State {
+        key: H256,
+        value: H256,
+    },
+}
+
+impl StateSnapshotRecord {
+    fn encode(&self) -> (u8, Vec<u8>) {
+        match self {
+            Self::Header {
+                rollup_type_hash,
+                block_number,
+            } => (
+                TAG_HEADER,
+                [&rollup_type_hash[..], &block_number.to_le_bytes()[..]].concat(),
+            ),
+            Self::BlockHash {
+                block_number,
+                block_hash,
+            } => (
+                TAG_BLOCK_HASH,
+                [&block_number.to_le_bytes()[..], &block_hash[..]].concat(),
+            ),
+            Self::Block(block) => (TAG_BLOCK, block.as_slice().to_vec()),
+            Self::FinalizedCustodians(custodians) => {
+                (TAG_FINALIZED_CUSTODIANS, custodians.as_slice().to_vec())
+            }
+            Self::RevertedBlockHashes(block_hashes) => (
+                TAG_REVERTED_BLOCK_HASHES,
+                block_hashes.pack().as_slice().to_vec(),
+            ),
+            Self::Script(script) => (TAG_SCRIPT, script.as_slice().to_vec()),
+            Self::Data { data_hash, data } => (TAG_DATA, [&data_hash[..], &data[..]].concat()),
+            Self::AssetScript(script) => (TAG_ASSET_SCRIPT, script.as_slice().to_vec()),
+            Self::State { key, value } => (TAG_STATE, [&key[..], &value[..]].concat()),
+        }
+    }
+
+    fn decode(tag: u8, payload: Vec<u8>) -> Result<Self> {
+        let record = match tag {
+            TAG_HEADER => {
+                ensure!(payload.len() == 40, "invalid header");
+                Self::Header {
+                    rollup_type_hash: payload[..32].try_into()?,
+                    block_number: u64::from_le_bytes(payload[32..].try_into()?),
+                }
+            }
+            TAG_BLOCK_HASH => {
+                ensure!(payload.len() == 40, "invalid block hash");
+                Self::BlockHash {
+                    block_number: u64::from_le_bytes(payload[..8].try_into()?),
+                    block_hash: payload[8..].try_into()?,
+                }
+            }
+            TAG_BLOCK => {
+                packed::ExportedBlockReader::verify(&payload, false)?;
+                Self::Block(packed::ExportedBlock::new_unchecked(payload.into()))
+            }
+            TAG_FINALIZED_CUSTODIANS => {
+                packed::FinalizedCustodianCapacityReader::verify(&payload, false)?;
+                Self::FinalizedCustodians(FinalizedCustodianCapacity::new_unchecked(payload.into()))
+            }
+            TAG_REVERTED_BLOCK_HASHES => {
+                let block_hashes = packed::Byte32VecReader::from_slice(&payload)?;
+                Self::RevertedBlockHashes(block_hashes.unpack())
+            }
+            TAG_SCRIPT => Self::Script(Script::from_slice(&payload)?),
+            TAG_DATA => {
+                ensure!(payload.len() >= 32, "invalid data");
+                Self::Data {
+                    data_hash: payload[..32].try_into()?,
+                    data: Bytes::from(payload[32..].to_vec()),
+                }
+            }
+            TAG_ASSET_SCRIPT => Self::AssetScript(Script::from_slice(&payload)?),
+            TAG_STATE => {
+                ensure!(payload.len() == 64, "invalid state");
+                Self::State {
+                    key: payload[..32].try_into()?,
+                    value: payload[32..].try_into()?,
+                }
+            }
+            tag => bail!("unknown record tag {}", tag),
+        };
+        Ok(record)
+    }
+}
+
+pub struct StateSnapshotWriter<W: Write> {
+    inner: W,
+    hasher: Blake2b,
+}
+
+impl<W: Write> StateSnapshotWriter<W> {
+    pub fn new(mut inner: W) -> Result<Self> {
+        let mut hasher = new_blake2b();
+        inner.write_all(MAGIC)?;
+        hasher.update(MAGIC);
+        Ok(StateSnapshotWriter { inner, hasher })
+    }
+
+    pub fn write(&mut self, record: &StateSnapshotRecord) -> Result<()> {
+        let (tag, payload) = record.encode();
+        let len: u32 = payload.len().try_into().context("record too large")?;
+        for buf in [&[tag][..], &len.to_le_bytes()[..], &payload[..]] {
+            self.inner.write_all(buf)?;
+            self.hasher.update(buf);
+        }
+        Ok(())
+    }
+
+    /// Write the checksum.
+    pub fn finish(mut self) -> Result<W> {
+        let mut checksum = [0u8; 32];
+        self.hasher.finalize(&mut checksum);
+        self.inner.write_all(&[TAG_CHECKSUM])?;
+        self.inner.write_all(&32u32.to_le_bytes())?;
+        self.inner.write_all(&checksum)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads records and verifies the checksum at the end of the file.
+///
+/// Records are returned before the checksum is verified, use
+/// [`verify_state_snapshot`] before importing them.
+pub struct StateSnapshotReader<R: Read> {
+    inner: R,
+    hasher: Option<Blake2b>,
+}
+
+impl<R: Read> StateSnapshotReader<R> {
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        inner.read_exact(&mut magic).context("read magic")?;
+        ensure!(&magic == MAGIC, "not a state snapshot");
+        let mut hasher = new_blake2b();
+        hasher.update(MAGIC);
+        Ok(StateSnapshotReader {
+            inner,
+            hasher: Some(hasher),
+        })
+    }
+
+    /// Returns `None` after the checksum is verified.
+    pub fn read(&mut self) -> Result<Option<StateSnapshotRecord>> {
+        let mut hasher = match self.hasher.take() {
+            Some(hasher) => hasher,
+            None => return Ok(None),
+        };
+        let mut tag = [0u8; 1];
+        match self.inner.read_exact(&mut tag) {
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => bail!("checksum not found"),
+            r => r?,
+        }
+        let mut len = [0u8; 4];
+        self.inner.read_exact(&mut len)?;
+        let mut payload = vec![0u8; u32::from_le_bytes(len) as usize];
+        self.inner.read_exact(&mut payload)?;
+
+        if tag[0] == TAG_CHECKSUM {
+            let mut checksum = [0u8; 32];
+            hasher.finalize(&mut checksum);
+            ensure!(payload == checksum, "checksum mismatch");
+            return Ok(None);
+        }
+        for buf in [&tag[..], &len[..], &payload[..]] {
+            hasher.update(buf);
+        }
+        self.hasher = Some(hasher);
+        StateSnapshotRecord::decode(tag[0], payload).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for StateSnapshotReader<R> {
+    type Item = Result<StateSnapshotRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+/// Reads through the snapshot, returns its header.
+pub fn verify_state_snapshot(reader: impl Read) -> Result<(H256, u64)> {
+    let mut reader = StateSnapshotReader::new(reader)?;
+    let header = match reader.read()? {
+        Some(StateSnapshotRecord::Header {
+            rollup_type_hash,
+            block_number,
+        }) => (rollup_type_hash, block_number),
+        _ => bail!("header not found"),
+    };
+    for record in reader {
+        record?;
+    }
+    Ok(header)
+}
+
+/// The highest finalized block, i.e. finalized for the last confirmed block.
+pub fn last_finalized_block_number(
+    rollup_config: &RollupConfig,
+    fork_config: &ForkConfig,
+    snap: &impl ChainStore,
+) -> Result<Option<u64>> {
+    let last_confirmed = snap
+        .get_last_confirmed_block_number_hash()
+        .context("last confirmed block not found")?;
+    let block = snap
+        .get_block(&last_confirmed.block_hash().unpack())?
+        .context("last confirmed block not found")?;
+    let finalizing_range = calc_finalizing_range(rollup_config, fork_config, snap, &block)?;
+    Ok(finalizing_range.end.checked_sub(1))
+}
+
+pub fn export_state<W: Write>(
+    snap: &StoreSnapshot,
+    rollup_config: &RollupConfig,
+    fork_config: &ForkConfig,
+    block_number: u64,
+    writer: &mut StateSnapshotWriter<W>,
+) -> Result<()> {
+    match last_finalized_block_number(rollup_config, fork_config, snap)? {
+        Some(finalized) if block_number <= finalized => {}
+        _ => bail!("block {} is not finalized", block_number),
+    }
+    if !snap.is_state_history_available(block_number) {
+        bail!(
+            "state history of block {} is pruned, available since block {}",
+            block_number,
+            snap.get_state_history_pruned_number()
+        );
+    }
+    let block_hash = snap
+        .get_block_hash_by_number(block_number)?
+        .ok_or_else(|| anyhow!("block {} not found", block_number))?;
+    let block = snap
+        .get_block(&block_hash)?
+        .ok_or_else(|| anyhow!("block {} not found", block_number))?;
+    let global_state = snap
+        .get_block_post_global_state(&block_hash)?
+        .ok_or_else(|| anyhow!("block {} post global state not found", block_number))?;
+
+    writer.write(&StateSnapshotRecord::Header {
+        rollup_type_hash: snap.get_chain_id()?,
+        block_number,
+    })?;
+
+    // Blocks not finalized for the snapshot block are exported as a whole.
+    // Export the snapshot block anyway.
+    let finalizing_range = calc_finalizing_range(rollup_config, fork_config, snap, &block)?;
+    let earliest_block_number = finalizing_range.end.min(block_number);
+    for number in 0..earliest_block_number {
+        let block_hash = snap
+            .get_block_hash_by_number(number)?
+            .ok_or_else(|| anyhow!("block {} not found", number))?;
+        writer.write(&StateSnapshotRecord::BlockHash {
+            block_number: number,
+            block_hash,
+        })?;
+    }
+    for number in earliest_block_number..=block_number {
+        let exported = export_block(snap, number)?;
+        writer.write(&StateSnapshotRecord::Block(exported.into()))?;
+    }
+
+    let finalized_custodians = snap
+        .get_block_post_finalized_custodian_capacity(block_number)
+        .ok_or_else(|| anyhow!("block {} finalized custodians not found", block_number))?;
+    writer.write(&StateSnapshotRecord::FinalizedCustodians(
+        finalized_custodians,
+    ))?;
+
+    let mut reverted_block_hashes = Vec::new();
+    let mut root: H256 = global_state.reverted_block_root().unpack();
+    while let Some(reverted) = snap.get_reverted_block_hashes_by_root(&root)? {
+        reverted_block_hashes.push(reverted.block_hashes);
+        root = reverted.prev_smt_root;
+    }
+    for block_hashes in reverted_block_hashes.into_iter().rev() {
+        writer.write(&StateSnapshotRecord::RevertedBlockHashes(block_hashes))?;
+    }
+
+    // Scripts and data of all blocks are exported, those created after the
+    // snapshot block are not reachable from its state.
+    for script in snap.iter_scripts() {
+        writer.write(&StateSnapshotRecord::Script(script))?;
+    }
+    for (data_hash, data) in snap.iter_data() {
+        writer.write(&StateSnapshotRecord::Data { data_hash, data })?;
+    }
+    for script in snap.iter_asset_scripts() {
+        writer.write(&StateSnapshotRecord::AssetScript(script))?;
+    }
+    for (key, value) in snap.iter_block_state(block_number) {
+        writer.write(&StateSnapshotRecord::State { key, value })?;
+    }
+    Ok(())
+}
+
+/// Restore an empty store from a snapshot verified by
+/// [`verify_state_snapshot`], and check the restored state against the post
+/// global state of the snapshot block.
+///
+/// The restored state is committed in batches. The store is marked as
+/// restoring until the state is checked, nodes refuse to start with a store
+/// whose import is interrupted.
+pub fn import_state(
+    store: &Store,
+    reader: &mut StateSnapshotReader<impl Read>,
+    rollup_type_hash: &H256,
+) -> Result<u64> {
+    ensure!(
+        !store.is_state_restoring(),
+        "a previous import is interrupted, remove the store and import again"
+    );
+    ensure!(!store.has_genesis()?, "store is not empty");
+    let block_number = match reader.read()? {
+        Some(StateSnapshotRecord::Header {
+            rollup_type_hash: snapshot_rollup_type_hash,
+            block_number,
+        }) => {
+            ensure!(
+                &snapshot_rollup_type_hash == rollup_type_hash,
+                "snapshot of another rollup {}",
+                snapshot_rollup_type_hash.pack()
+            );
+            block_number
+        }
+        _ => bail!("header not found"),
+    };
+
+    let mut tx_db = store.begin_transaction_skip_concurrency_control();
+    tx_db.set_state_restoring(true)?;
+    tx_db.setup_chain_id(*rollup_type_hash)?;
+    tx_db.commit()?;
+    let mut tx_db = store.begin_transaction_skip_concurrency_control();
+    let mut earliest_block_number = None;
+    let mut global_state = None;
+    let mut state_leaves = 0;
+    while let Some(record) = reader.read()? {
+        match record {
+            StateSnapshotRecord::Header { .. } => bail!("duplicated header"),
+            StateSnapshotRecord::BlockHash {
+                block_number: number,
+                block_hash,
+            } => tx_db.attach_block_hash(number, &block_hash)?,
+            StateSnapshotRecord::Block(exported) => {
+                // Bad block hashes are restored from the reverted block hashes.
+                let ExportedBlock {
+                    block,
+                    post_global_state,
+                    deposit_info_vec,
+                    deposit_asset_scripts,
+                    withdrawals,
+                    bad_block_hashes: _,
+                    submit_tx_hash,
+                } = exported.into();
+                let number = block.raw().number().unpack();
+                earliest_block_number.get_or_insert(number);
+                tx_db.insert_snapshot_block(
+                    &block,
+                    &post_global_state,
+                    &deposit_info_vec,
+                    &withdrawals,
+                )?;
+                tx_db.insert_asset_scripts(deposit_asset_scripts.into_iter().collect())?;
+                if let Some(hash) = submit_tx_hash {
+                    tx_db.set_block_submit_tx_hash(number, &hash)?;
+                }
+                tx_db.attach_block(block)?;
+                global_state = Some(post_global_state);
+            }
+            StateSnapshotRecord::FinalizedCustodians(custodians) => {
+                tx_db.set_block_post_finalized_custodian_capacity(
+                    block_number,
+                    &custodians.as_reader(),
+                )?;
+            }
+            StateSnapshotRecord::RevertedBlockHashes(block_hashes) => {
+                insert_bad_block_hashes(&mut tx_db, vec![block_hashes])?;
+            }
+            StateSnapshotRecord::Script(script) => tx_db.insert_script(script.hash(), script),
+            StateSnapshotRecord::Data { data_hash, data } => tx_db.insert_data(data_hash, data),
+            StateSnapshotRecord::AssetScript(script) => {
+                tx_db.insert_asset_scripts([script].into_iter().collect())?;
+            }
+            StateSnapshotRecord::State { key, value } => {
+                restore_state_leaf(&mut tx_db, block_number, key, value)?;
+                state_leaves += 1;
+                if state_leaves % COMMIT_INTERVAL == 0 {
+                    tx_db.commit()?;
+                    tx_db = store.begin_transaction_skip_concurrency_control();
+                }
+            }
+        }
+    }
+
+    let global_state = global_state.context("snapshot block not found")?;
+    let earliest_block_number = earliest_block_number.context("snapshot block not found")?;
+    check_restored_state(&mut tx_db, block_number, &global_state)?;
+
+    let nh = NumberHash::new_builder()
+        .number(block_number.pack())
+        .block_hash(global_state.tip_block_hash())
+        .build();
+    tx_db.set_last_submitted_block_number_hash(&nh.as_reader())?;
+    tx_db.set_last_confirmed_block_number_hash(&nh.as_reader())?;
+    tx_db.set_earliest_block_number(earliest_block_number)?;
+    tx_db.set_state_history_start_number(block_number)?;
+    tx_db.set_state_restoring(false)?;
+    tx_db.commit()?;
+
+    Ok(block_number)
+}
+
+fn restore_state_leaf(
+    tx_db: &mut StoreTransaction,
+    block_number: u64,
+    key: H256,
+    value: H256,
+) -> Result<()> {
+    let mut state_smt = tx_db.state_smt()?;
+    state_smt
+        .update(key.into(), value.into())
+        .context("update state smt")?;
+    tx_db.record_block_state(block_number, key, value)
+}
+
+fn check_restored_state(
+    tx_db: &mut StoreTransaction,
+    block_number: u64,
+    global_state: &packed::GlobalState,
+) -> Result<()> {
+    let expected_root: H256 = global_state.account().merkle_root().unpack();
+    let root: H256 = (*tx_db.state_smt()?.root()).into();
+    if root != expected_root {
+        bail!(
+            "restored state root {} of block {} diff, expected {}",
+            root.pack(),
+            block_number,
+            expected_root.pack()
+        );
+    }
+    check_block_post_state(tx_db, block_number, global_state)
+}
@@ -0,0 +1,124 @@
+//! Private key encrypted with a passphrase.
+//!
+//! The encryption key is derived from the passphrase with PBKDF2-HMAC-SHA256,
+//! and the private key is encrypted with ChaCha20-Poly1305. The derived key
+//! and the decrypted private key bytes are zeroized after use.
+
+use std::path::Path;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use ckb_crypto::secp::Privkey;
+use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+use hmac::Hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+const VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-hmac-sha256";
+const CIPHER: &str = "chacha20-poly1305";
+pub const DEFAULT_ITERATIONS: u32 = 262_144;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Keystore {
+    pub version: u32,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: JsonBytes,
+    pub cipher: String,
+    pub nonce: JsonBytes,
+    pub ciphertext: JsonBytes,
+}
+
+impl Keystore {
+    pub fn encrypt(privkey: &[u8; 32], passphrase: &str, iterations: u32) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let key = derive_key(passphrase.as_bytes(), &salt, iterations);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&*key))
+            .encrypt(Nonce::from_slice(&nonce), &privkey[..])
+            .map_err(|err| anyhow!("encrypt keystore: {}", err))?;
+        Ok(Keystore {
+            version: VERSION,
+            kdf: KDF.into(),
+            iterations,
+            salt: JsonBytes::from_vec(salt.to_vec()),
+            cipher: CIPHER.into(),
+            nonce: JsonBytes::from_vec(nonce.to_vec()),
+            ciphertext: JsonBytes::from_vec(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<Privkey> {
+        ensure!(
+            self.version == VERSION,
+            "unsupported version {}",
+            self.version
+        );
+        ensure!(self.kdf == KDF, "unsupported kdf {}", self.kdf);
+        ensure!(self.cipher == CIPHER, "unsupported cipher {}", self.cipher);
+        ensure!(self.nonce.len() == 12, "invalid nonce length");
+
+        let key = derive_key(passphrase.as_bytes(), self.salt.as_bytes(), self.iterations);
+        let privkey = ChaCha20Poly1305::new(Key::from_slice(&*key))
+            .decrypt(
+                Nonce::from_slice(self.nonce.as_bytes()),
+                self.ciphertext.as_bytes(),
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow!("decrypt keystore: wrong passphrase or corrupted file"))?;
+        ensure!(privkey.len() == 32, "invalid privkey length");
+        Ok(Privkey::from_slice(&privkey))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("read keystore {}", path.display()))?;
+        serde_json::from_str(&content).context("parse keystore")
+    }
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8], iterations: u32) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase, salt, iterations, &mut *key);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_key() {
+        // RFC 7914 section 11
+        let key = derive_key(b"passwd", b"salt", 1);
+        assert_eq!(
+            hex_literal::hex!("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"),
+            *key
+        );
+    }
+
+    #[test]
+    fn test_keystore() {
+        let privkey = [7u8; 32];
+        let keystore = Keystore::encrypt(&privkey, "secret", 16).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore: Keystore = serde_json::from_str(&json).unwrap();
+
+        let decrypted = keystore.decrypt("secret").unwrap();
+        assert_eq!(
+            decrypted.pubkey().unwrap(),
+            Privkey::from_slice(&privkey).pubkey().unwrap()
+        );
+        assert!(keystore.decrypt("wrong").is_err());
+    }
+}
@@ -3,15 +3,18 @@ mod calc_finalizing_range;
 pub mod compression;
 pub mod exponential_backoff;
 pub mod export_block;
+pub mod export_state;
 pub mod fee;
 pub mod gasless;
 pub mod genesis_info;
+pub mod keystore;
 pub mod liveness;
 pub mod local_cells;
 pub mod polyjuice_parser;
 mod query_rollup_cell;
 mod rollup_context;
 pub mod script_log;
+pub mod signer;
 pub mod since;
 pub mod timepoint;
 pub mod transaction_skeleton;
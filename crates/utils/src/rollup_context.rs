@@ -1,3 +1,4 @@
+use gw_common::{error::Error, registry::Registries, state::State};
 use gw_config::ForkConfig;
 use gw_jsonrpc_types::blockchain::CellDep;
 use gw_types::{core::H256, packed::RollupConfig};
@@ -17,6 +18,19 @@ impl RollupContext {
         self.fork_config.global_state_version(block_number)
     }
 
+    /// Returns the registries at `block_number`, the CKB registry is enabled
+    /// by `ForkConfig::enable_ckb_registry`.
+    pub fn registries<S: State + ?Sized>(
+        &self,
+        state: &S,
+        block_number: u64,
+    ) -> Result<Registries, Error> {
+        if !self.fork_config.ckb_registry_enabled(block_number) {
+            return Ok(Registries::eth_only());
+        }
+        Registries::resolve(state, &self.rollup_script_hash, &self.rollup_config)
+    }
+
     pub fn rollup_config_cell_dep(&self) -> &CellDep {
         &self.fork_config.chain.rollup_config_cell_dep
     }
@@ -0,0 +1,297 @@
+//! Signers of [`crate::wallet::Wallet`].
+//!
+//! # Remote signer protocol
+//!
+//! JSON-RPC 2.0 over a unix or TCP socket, one request per connection and
+//! one JSON object per line. Bytes are `0x` prefixed hex strings.
+//!
+//! - `get_pubkey`: no params, returns the 33 bytes compressed secp256k1
+//!   public key.
+//! - `sign_message`: params `[message]`, message is 32 bytes. Returns the 65
+//!   bytes recoverable signature `r | s | recovery id`.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use ckb_crypto::secp::{Privkey, Signature};
+use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task::block_in_place,
+};
+
+pub trait Signer: Send + Sync {
+    /// Compressed secp256k1 public key.
+    fn pubkey(&self) -> Result<[u8; 33]>;
+
+    /// Recoverable signature `r | s | recovery id`.
+    fn sign_message(&self, message: [u8; 32]) -> Result<[u8; 65]>;
+}
+
+pub struct PrivkeySigner {
+    privkey: Privkey,
+}
+
+impl PrivkeySigner {
+    pub fn new(privkey: Privkey) -> Self {
+        PrivkeySigner { privkey }
+    }
+}
+
+impl Signer for PrivkeySigner {
+    fn pubkey(&self) -> Result<[u8; 33]> {
+        let mut pubkey = [0u8; 33];
+        pubkey.copy_from_slice(&self.privkey.pubkey()?.serialize());
+        Ok(pubkey)
+    }
+
+    fn sign_message(&self, message: [u8; 32]) -> Result<[u8; 65]> {
+        let signature = self
+            .privkey
+            .sign_recoverable(&message.into())
+            .map_err(|err| anyhow!("signing error: {}", err))?;
+        let mut inner = [0u8; 65];
+        inner.copy_from_slice(&signature.serialize());
+        Ok(inner)
+    }
+}
+
+enum RemoteAddress {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+/// Signs by calling a signer service, see the [module docs](self).
+///
+/// Calls are blocking, the signer is expected to run on the same host or in
+/// the same private network. In a multi-threaded tokio runtime, calls run in
+/// [`tokio::task::block_in_place`] so that they don't stall other tasks on the
+/// worker thread.
+pub struct RemoteSigner {
+    address: RemoteAddress,
+    timeout: Duration,
+    next_id: AtomicU64,
+    pubkey: [u8; 33],
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<Value>,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at `unix:<path>` or `tcp:<host>:<port>` and
+    /// fetch its public key.
+    pub fn connect(address: &str, timeout: Duration) -> Result<Self> {
+        let address = if let Some(addr) = address.strip_prefix("tcp:") {
+            RemoteAddress::Tcp(addr.to_string())
+        } else if let Some(path) = address.strip_prefix("unix:") {
+            RemoteAddress::Unix(path.into())
+        } else {
+            bail!("invalid remote signer address {}", address);
+        };
+        let mut signer = RemoteSigner {
+            address,
+            timeout,
+            next_id: AtomicU64::new(0),
+            pubkey: [0u8; 33],
+        };
+        let pubkey = signer.call_bytes("get_pubkey", vec![])?;
+        ensure!(pubkey.len() == 33, "invalid pubkey length {}", pubkey.len());
+        signer.pubkey.copy_from_slice(&pubkey);
+        Ok(signer)
+    }
+
+    fn call(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        let mut request = serde_json::to_string(&request)?;
+        request.push('\n');
+
+        let line = blocking(|| self.send_request(&request))?;
+
+        let response: RpcResponse =
+            serde_json::from_str(&line).context("invalid remote signer response")?;
+        if let Some(err) = response.error {
+            bail!("remote signer {} error: {}", method, err);
+        }
+        response
+            .result
+            .with_context(|| format!("remote signer {} returns no result", method))
+    }
+
+    fn send_request(&self, request: &str) -> Result<String> {
+        match self.address {
+            RemoteAddress::Unix(ref path) => {
+                let stream = UnixStream::connect(path)
+                    .with_context(|| format!("connect remote signer {}", path.display()))?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                round_trip(stream, request)
+            }
+            RemoteAddress::Tcp(ref addr) => {
+                let socket_addr = addr
+                    .to_socket_addrs()?
+                    .next()
+                    .with_context(|| format!("resolve remote signer {}", addr))?;
+                let stream = TcpStream::connect_timeout(&socket_addr, self.timeout)
+                    .with_context(|| format!("connect remote signer {}", addr))?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                round_trip(stream, request)
+            }
+        }
+    }
+
+    fn call_bytes(&self, method: &str, params: Vec<Value>) -> Result<Vec<u8>> {
+        let result: JsonBytes = serde_json::from_value(self.call(method, params)?)?;
+        Ok(result.into_bytes().to_vec())
+    }
+}
+
+/// Run blocking I/O without blocking other tasks of a multi-threaded runtime.
+/// `block_in_place` panics in a current thread runtime, so just run it there.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => block_in_place(f),
+        _ => f(),
+    }
+}
+
+fn round_trip<S: Read + Write>(mut stream: S, request: &str) -> Result<String> {
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(line)
+}
+
+impl Signer for RemoteSigner {
+    fn pubkey(&self) -> Result<[u8; 33]> {
+        Ok(self.pubkey)
+    }
+
+    fn sign_message(&self, message: [u8; 32]) -> Result<[u8; 65]> {
+        let message_json = serde_json::to_value(JsonBytes::from_vec(message.to_vec()))?;
+        let sig = self.call_bytes("sign_message", vec![message_json])?;
+        ensure!(sig.len() == 65, "invalid signature length {}", sig.len());
+
+        // Don't trust the signer blindly.
+        let recovered = Signature::from_slice(&sig)
+            .and_then(|s| s.recover(&message.into()))
+            .map_err(|err| anyhow!("invalid signature: {}", err))?;
+        ensure!(
+            recovered.serialize() == self.pubkey,
+            "signature is not signed by the signer pubkey"
+        );
+
+        let mut inner = [0u8; 65];
+        inner.copy_from_slice(&sig);
+        Ok(inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        time::Duration,
+    };
+
+    use ckb_crypto::secp::Generator;
+    use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+    use serde_json::{json, Value};
+
+    use super::{PrivkeySigner, RemoteSigner, Signer};
+
+    /// Serve `connections` requests with `signer`.
+    fn serve(signer: PrivkeySigner, connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("tcp:{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let result = match request["method"].as_str().unwrap() {
+                    "get_pubkey" => signer.pubkey().unwrap().to_vec(),
+                    "sign_message" => {
+                        let message: JsonBytes =
+                            serde_json::from_value(request["params"][0].clone()).unwrap();
+                        let message = message.as_bytes().try_into().unwrap();
+                        signer.sign_message(message).unwrap().to_vec()
+                    }
+                    method => panic!("unexpected method {}", method),
+                };
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": JsonBytes::from_vec(result),
+                });
+                writeln!(stream, "{}", response).unwrap();
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let privkey = Generator::random_privkey();
+        let local = PrivkeySigner::new(privkey.clone());
+        let address = serve(PrivkeySigner::new(privkey), 2);
+
+        let remote = RemoteSigner::connect(&address, Duration::from_secs(5)).unwrap();
+        assert_eq!(remote.pubkey().unwrap(), local.pubkey().unwrap());
+        let message = [42u8; 32];
+        assert_eq!(
+            remote.sign_message(message).unwrap(),
+            local.sign_message(message).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_remote_signer_wrong_key() {
+        let privkey = Generator::random_privkey();
+        let address = serve(PrivkeySigner::new(Generator::random_privkey()), 2);
+
+        let mut remote = RemoteSigner::connect(&address, Duration::from_secs(5)).unwrap();
+        // Pretend the signer has been swapped after connecting.
+        remote.pubkey = PrivkeySigner::new(privkey).pubkey().unwrap();
+        let err = remote.sign_message([42u8; 32]).unwrap_err();
+        assert!(err.to_string().contains("not signed by the signer pubkey"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remote_signer_in_runtime() {
+        let privkey = Generator::random_privkey();
+        let local = PrivkeySigner::new(privkey.clone());
+        let address = serve(PrivkeySigner::new(privkey), 2);
+
+        let remote = RemoteSigner::connect(&address, Duration::from_secs(5)).unwrap();
+        let message = [42u8; 32];
+        let signature = tokio::spawn(async move { remote.sign_message(message) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(signature, local.sign_message(message).unwrap());
+    }
+}
@@ -1,11 +1,11 @@
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
-use anyhow::{anyhow, ensure, Context, Result};
+use anyhow::{ensure, Context, Result};
 use ckb_crypto::secp::Privkey;
 use ckb_types::h256;
 use faster_hex::hex_decode;
 use gw_common::blake2b::{self, new_blake2b};
-use gw_config::WalletConfig;
+use gw_config::{SignerConfig, WalletConfig};
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
@@ -14,22 +14,41 @@ use gw_types::{
     prelude::*,
 };
 use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
 
-use crate::transaction_skeleton::{Signature, TransactionSkeleton};
+use crate::{
+    keystore::Keystore,
+    signer::{PrivkeySigner, RemoteSigner, Signer},
+    transaction_skeleton::{Signature, TransactionSkeleton},
+};
 
 pub const SIGHASH_TYPE_HASH: H256 =
     h256!("0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8").0;
 
 pub struct Wallet {
-    privkey: Privkey,
+    signer: Box<dyn Signer>,
     lock: Script,
 }
 
 impl TryFrom<Privkey> for Wallet {
-    type Error = ckb_crypto::secp::Error;
+    type Error = anyhow::Error;
 
     fn try_from(privkey: Privkey) -> Result<Self, Self::Error> {
-        let pk = privkey.pubkey()?.serialize();
+        Self::from_signer(Box::new(PrivkeySigner::new(privkey)))
+    }
+}
+
+impl Wallet {
+    pub fn new(privkey: Privkey, lock: Script) -> Self {
+        Wallet {
+            signer: Box::new(PrivkeySigner::new(privkey)),
+            lock,
+        }
+    }
+
+    /// Wallet with the secp256k1 sighash lock of the signer.
+    pub fn from_signer(signer: Box<dyn Signer>) -> Result<Self> {
+        let pk = signer.pubkey()?;
         let pk160 = &blake2b::hash(&pk)[..20];
         Ok(Self {
             lock: Script::new_builder()
@@ -37,31 +56,45 @@ impl TryFrom<Privkey> for Wallet {
                 .hash_type(ScriptHashType::Type.into())
                 .args(pk160.pack())
                 .build(),
-            privkey,
+            signer,
         })
     }
-}
-
-impl Wallet {
-    pub fn new(privkey: Privkey, lock: Script) -> Self {
-        Wallet { privkey, lock }
-    }
 
     pub fn from_privkey_path(p: &Path) -> Result<Self> {
         let privkey = {
-            let content = std::fs::read_to_string(p).context("read wallet privkey")?;
+            let content =
+                Zeroizing::new(std::fs::read_to_string(p).context("read wallet privkey")?);
             let content = content.trim_start_matches("0x").trim();
             ensure!(content.as_bytes().len() == 64, "invalid privkey length");
-            let mut decoded = [0u8; 32];
-            hex_decode(content.as_bytes(), &mut decoded)?;
-            Privkey::from_slice(&decoded)
+            let mut decoded = Zeroizing::new([0u8; 32]);
+            hex_decode(content.as_bytes(), &mut *decoded)?;
+            Privkey::from_slice(&*decoded)
         };
         let wallet = Self::try_from(privkey)?;
         Ok(wallet)
     }
 
     pub fn from_config(config: &WalletConfig) -> Result<Self> {
-        Self::from_privkey_path(&config.privkey_path)
+        match config.signer {
+            None => Self::from_privkey_path(&config.privkey_path),
+            Some(SignerConfig::Remote {
+                ref address,
+                timeout_ms,
+            }) => {
+                let signer = RemoteSigner::connect(address, Duration::from_millis(timeout_ms))?;
+                Self::from_signer(Box::new(signer))
+            }
+            Some(SignerConfig::Keystore {
+                ref path,
+                ref passphrase_env,
+            }) => {
+                let passphrase = std::env::var(passphrase_env)
+                    .map(Zeroizing::new)
+                    .with_context(|| format!("read keystore passphrase from {}", passphrase_env))?;
+                let privkey = Keystore::load(path)?.decrypt(&passphrase)?;
+                Self::try_from(privkey)
+            }
+        }
     }
 
     pub fn lock_script(&self) -> &Script {
@@ -73,8 +106,8 @@ impl Wallet {
         rollup_script_hash: &H256,
         eth_account_lock_code_hash: &H256,
     ) -> Result<Script> {
-        privkey_to_eth_account_script(
-            &self.privkey,
+        pubkey_to_eth_account_script(
+            &self.signer.pubkey()?,
             rollup_script_hash,
             eth_account_lock_code_hash,
         )
@@ -82,13 +115,7 @@ impl Wallet {
 
     // sign message
     pub fn sign_message(&self, msg: [u8; 32]) -> Result<[u8; 65]> {
-        let signature = self
-            .privkey
-            .sign_recoverable(&msg.into())
-            .map_err(|err| anyhow!("signing error: {}", err))?;
-        let mut inner = [0u8; 65];
-        inner.copy_from_slice(&signature.serialize());
-        Ok(inner)
+        self.signer.sign_message(msg)
     }
 
     pub fn sign_tx_skeleton(&self, tx_skeleton: TransactionSkeleton) -> Result<Transaction> {
@@ -153,7 +180,19 @@ pub fn privkey_to_eth_account_script(
     rollup_script_hash: &H256,
     eth_account_lock_code_hash: &H256,
 ) -> Result<Script> {
-    let pubkey = secp256k1::PublicKey::from_slice(&privkey.pubkey()?.serialize())?;
+    pubkey_to_eth_account_script(
+        &privkey.pubkey()?.serialize(),
+        rollup_script_hash,
+        eth_account_lock_code_hash,
+    )
+}
+
+pub fn pubkey_to_eth_account_script(
+    pubkey: &[u8],
+    rollup_script_hash: &H256,
+    eth_account_lock_code_hash: &H256,
+) -> Result<Script> {
+    let pubkey = secp256k1::PublicKey::from_slice(pubkey)?;
     let pubkey_hash = {
         let mut hasher = Keccak256::new();
         hasher.update(&pubkey.serialize_uncompressed()[1..]);
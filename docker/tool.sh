@@ -7,8 +7,8 @@ check_scripts_files_exists(){
 		
         "always-success"     "state-validator"
         
-        "meta-contract-generator"  "sudt-generator"  "eth-addr-reg-generator"
-		"meta-contract-validator"  "sudt-validator"  "eth-addr-reg-validator"
+        "meta-contract-generator"  "sudt-generator"  "eth-addr-reg-generator"  "ckb-addr-reg-generator"
+		"meta-contract-validator"  "sudt-validator"  "eth-addr-reg-validator"  "ckb-addr-reg-validator"
 	) 
     local path=`pwd`/test-result/scripts/godwoken-scripts
 	check_multiple_files_exists "$path" "${arr[@]}"
@@ -0,0 +1,77 @@
+/**
+ * `CKB Address Registry` layer2 contract
+ *
+ * This contract exposes the two-ways mappings between the secp256k1-blake160
+ * lock args (a.k.a. `ckb_address`) of a layer 2 EOA and its `gw_script_hash`.
+ *
+ * Unlike the `ETH Address Registry`, mappings are never set by transactions:
+ * they are created when a deposit creates the account, so only the query
+ * messages are supported.
+ */
+
+#include "gw_eth_addr_reg.h"
+
+/* MSG_TYPE */
+#define MSG_QUERY_GW_BY_CKB 0
+#define MSG_QUERY_CKB_BY_GW 1
+
+#define GW_CKB_ADDRESS_LEN 20
+
+int main() {
+  ckb_debug("====== CKB Address Registry ======");
+
+  /* initialize context */
+  gw_context_t ctx = {0};
+  int ret = gw_context_init(&ctx);
+  if (ret != 0) {
+    return ret;
+  };
+
+  /* verify and parse args, the args share the layout of `ETHAddrRegArgs` */
+  mol_seg_t args_seg;
+  args_seg.ptr = ctx.transaction_context.args;
+  args_seg.size = ctx.transaction_context.args_len;
+  if (MolReader_ETHAddrRegArgs_verify(&args_seg, false) != MOL_OK) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  mol_union_t msg = MolReader_ETHAddrRegArgs_unpack(&args_seg);
+
+  /* handle message */
+  if (msg.item_id == MSG_QUERY_GW_BY_CKB) {
+    mol_seg_t ckb_address_seg = MolReader_EthToGw_get_eth_address(&msg.seg);
+    uint8_t script_hash[GW_VALUE_BYTES] = {0};
+    /* addr */
+    gw_reg_addr_t addr;
+    memcpy(addr.addr, ckb_address_seg.ptr, GW_CKB_ADDRESS_LEN);
+    addr.addr_len = GW_CKB_ADDRESS_LEN;
+    addr.reg_id = ctx.transaction_context.to_id;
+    /* get script hash */
+    ret = ctx.sys_get_script_hash_by_registry_address(&ctx, &addr, script_hash);
+    if (ret != 0) {
+      return ret;
+    }
+    ret = ctx.sys_set_program_return_data(&ctx, script_hash, GW_VALUE_BYTES);
+    if (ret != 0) {
+      return ret;
+    }
+  } else if (msg.item_id == MSG_QUERY_CKB_BY_GW) {
+    mol_seg_t script_hash_seg = MolReader_GwToEth_get_gw_script_hash(&msg.seg);
+    gw_reg_addr_t addr;
+    ret = ctx.sys_get_registry_address_by_script_hash(
+        &ctx, script_hash_seg.ptr, ctx.transaction_context.to_id, &addr);
+    if (ret != 0) {
+      return ret;
+    }
+    if (addr.addr_len != GW_CKB_ADDRESS_LEN) {
+      return GW_FATAL_INVALID_DATA;
+    }
+    ret = ctx.sys_set_program_return_data(&ctx, addr.addr, GW_CKB_ADDRESS_LEN);
+    if (ret != 0) {
+      return ret;
+    }
+  } else {
+    return GW_FATAL_UNKNOWN_ARGS;
+  }
+
+  return gw_finalize(&ctx);
+}
@@ -90,7 +90,11 @@ pub fn main() -> Result<(), Error> {
     match target_type {
         ChallengeTargetType::TxExecution => {
             debug!("[challenge-lock] target: tx execution");
-            crate::verifications::tx_execution::verify_tx_execution(&rollup_config, &lock_args)?;
+            crate::verifications::tx_execution::verify_tx_execution(
+                &rollup_script_hash,
+                &rollup_config,
+                &lock_args,
+            )?;
         }
         ChallengeTargetType::TxSignature => {
             debug!("[challenge-lock] target: tx signature");
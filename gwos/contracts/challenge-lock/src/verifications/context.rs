@@ -1,6 +1,6 @@
 use core::result::Result;
 use gw_common::{
-    builtins::ETH_REGISTRY_ACCOUNT_ID, merkle_utils::calculate_state_checkpoint,
+    merkle_utils::calculate_state_checkpoint, registry::Registries,
     registry_address::RegistryAddress, state::State,
 };
 use gw_state::kv_state::KVState;
@@ -22,6 +22,7 @@ pub struct TxContextInput<'a> {
     pub kv_state: KVState<'a>,
     pub scripts: ScriptVec,
     pub raw_block: RawL2Block,
+    pub rollup_script_hash: &'a H256,
     pub rollup_config: &'a RollupConfig,
     pub target: ChallengeTarget,
     pub tx_proof: CKBMerkleProof,
@@ -33,6 +34,7 @@ pub struct TxContext {
     pub sender: Script,
     pub receiver: Script,
     pub sender_address: RegistryAddress,
+    pub registries: Registries,
 }
 
 pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
@@ -41,6 +43,7 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
         kv_state,
         scripts,
         raw_block,
+        rollup_script_hash,
         rollup_config,
         target,
         tx_proof,
@@ -168,8 +171,9 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
         return Err(Error::MerkleProof);
     }
 
+    let registries = Registries::resolve(&kv_state, rollup_script_hash, rollup_config)?;
     let sender_address = kv_state
-        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+        .find_registry_address_by_script_hash(&registries, &sender_script_hash)?
         .ok_or(Error::RegistryAddressNotFound)?;
 
     let tx_ctx = TxContext {
@@ -178,6 +182,7 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
         sender: sender_script,
         receiver: receiver_script,
         sender_address,
+        registries,
     };
     Ok(tx_ctx)
 }
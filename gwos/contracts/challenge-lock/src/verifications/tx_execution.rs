@@ -15,6 +15,7 @@ use gw_utils::{ckb_std::syscalls::load_witness, gw_types};
 
 /// Verify tx execution
 pub fn verify_tx_execution(
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
@@ -56,6 +57,7 @@ pub fn verify_tx_execution(
         kv_state,
         scripts,
         raw_block,
+        rollup_script_hash,
         rollup_config,
         target,
         tx_proof,
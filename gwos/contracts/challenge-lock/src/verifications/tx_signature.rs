@@ -2,6 +2,7 @@ use crate::verifications::context::{verify_tx_context, TxContext, TxContextInput
 use crate::verifications::eip712::{traits::EIP712Encode, types::EIP712Domain};
 use alloc::vec;
 use core::result::Result;
+use gw_common::registry::ckb_registry::calc_tx_signing_message;
 use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS, kv_state::KVState};
 use gw_types::{
     packed::{ChallengeLockArgs, RollupConfig},
@@ -27,7 +28,7 @@ use sha3::{Digest, Keccak256};
 
 /// Verify tx signature
 pub fn verify_tx_signature(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
@@ -76,6 +77,7 @@ pub fn verify_tx_signature(
         kv_state,
         scripts,
         raw_block,
+        rollup_script_hash,
         rollup_config,
         target,
         tx_proof,
@@ -87,8 +89,19 @@ pub fn verify_tx_signature(
         receiver,
         sender: _,
         sender_address,
+        registries,
     } = verify_tx_context(input)?;
 
+    if Some(sender_address.registry_id) == registries.ckb_registry_id() {
+        let message = calc_tx_signing_message(
+            rollup_script_hash,
+            &sender_script_hash,
+            &receiver_script_hash,
+            &raw_tx,
+        );
+        return check_l2_account_signature_cell(&sender_script_hash, SigningType::Raw, message);
+    }
+
     let (message, signing_type) = match try_assemble_polyjuice_args(&raw_tx, receiver) {
         Some(rlp_data) => {
             let mut hasher = Keccak256::new();
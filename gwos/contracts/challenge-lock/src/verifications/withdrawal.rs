@@ -1,7 +1,9 @@
 use crate::verifications::eip712::traits::EIP712Encode;
 use core::result::Result;
 use gw_common::{
-    builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State,
+    registry::{ckb_registry::calc_withdrawal_signing_message, Registries},
+    registry_address::RegistryAddress,
+    state::State,
 };
 use gw_state::kv_state::KVState;
 use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS};
@@ -37,9 +39,14 @@ struct WithdrawalContext {
     sender_script_hash: H256,
     withdrawal_address: RegistryAddress,
     owner_lock: Script,
+    registries: Registries,
 }
 
-fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalContext, Error> {
+fn verify_withdrawal_proof(
+    rollup_script_hash: &[u8; 32],
+    rollup_config: &RollupConfig,
+    lock_args: &ChallengeLockArgs,
+) -> Result<WithdrawalContext, Error> {
     let witness_args: Bytes = load_witness_args(0, Source::GroupInput)?
         .lock()
         .to_opt()
@@ -107,8 +114,12 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
         None,
     )?;
 
+    let registries = Registries::resolve(&kv_state, rollup_script_hash, rollup_config)?;
     let withdrawal_address = kv_state
-        .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+        .get_registry_address_by_script_hash(
+            raw_withdrawal.registry_id().unpack(),
+            &sender_script_hash,
+        )?
         .ok_or(Error::RegistryAddressNotFound)?;
 
     let context = WithdrawalContext {
@@ -116,6 +127,7 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
         withdrawal_address,
         sender_script_hash,
         owner_lock,
+        registries,
     };
 
     Ok(context)
@@ -123,7 +135,7 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
 
 /// Verify withdrawal signature
 pub fn verify_withdrawal(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
@@ -132,7 +144,8 @@ pub fn verify_withdrawal(
         sender_script_hash,
         withdrawal_address,
         owner_lock,
-    } = verify_withdrawal_proof(lock_args)?;
+        registries,
+    } = verify_withdrawal_proof(rollup_script_hash, rollup_config, lock_args)?;
     let raw_withdrawal = withdrawal.raw();
 
     // check rollup chain id
@@ -143,6 +156,15 @@ pub fn verify_withdrawal(
         return Err(Error::WrongSignature);
     }
 
+    if Some(withdrawal_address.registry_id) == registries.ckb_registry_id() {
+        let message = calc_withdrawal_signing_message(rollup_script_hash, &raw_withdrawal);
+        return check_l2_account_signature_cell(
+            &sender_script_hash,
+            gw_types::core::SigningType::Raw,
+            message,
+        );
+    }
+
     // calculate EIP-712 message
     let typed_message = crate::verifications::eip712::types::Withdrawal::from_raw(
         withdrawal.raw(),
@@ -0,0 +1,60 @@
+//! Secp256k1 blake160 implementation
+
+use gw_utils::{
+    ckb_std::debug,
+    error::Error,
+    gw_common::blake2b::new_blake2b,
+    gw_types::{bytes::Bytes, h256::H256},
+};
+use secp256k1_utils::recover_uncompressed_key;
+
+pub type PubkeyHash = [u8; 20];
+
+pub fn extract_blake160_lock_args(lock_args: Bytes) -> Result<(H256, PubkeyHash), Error> {
+    if lock_args.len() != 52 {
+        debug!("Invalid lock args len: {}", lock_args.len());
+        return Err(Error::InvalidArgs);
+    }
+    let rollup_script_hash = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&lock_args[..32]);
+        buf.into()
+    };
+    let pubkey_hash = {
+        let mut buf = [0u8; 20];
+        buf.copy_from_slice(&lock_args[32..]);
+        buf
+    };
+    Ok((rollup_script_hash, pubkey_hash))
+}
+
+#[derive(Default)]
+pub struct Secp256k1Blake160;
+
+impl Secp256k1Blake160 {
+    pub fn verify_alone(
+        &self,
+        pubkey_hash: PubkeyHash,
+        signature: [u8; 65],
+        message: H256,
+    ) -> Result<bool, Error> {
+        let pubkey = recover_uncompressed_key(message.into(), signature).map_err(|err| {
+            debug!("failed to recover secp256k1 pubkey, error number: {}", err);
+            Error::WrongSignature
+        })?;
+        // compress pubkey: 0x02 / 0x03 by the parity of y | x
+        let mut compressed_pubkey = [0u8; 33];
+        compressed_pubkey[0] = 0x02 | (pubkey[64] & 1);
+        compressed_pubkey[1..].copy_from_slice(&pubkey[1..33]);
+        let recovered_pubkey_hash = {
+            let mut hasher = new_blake2b();
+            hasher.update(&compressed_pubkey);
+            let mut buf = [0u8; 32];
+            hasher.finalize(&mut buf);
+            let mut pubkey_hash = [0u8; 20];
+            pubkey_hash.copy_from_slice(&buf[..20]);
+            pubkey_hash
+        };
+        Ok(recovered_pubkey_hash == pubkey_hash)
+    }
+}
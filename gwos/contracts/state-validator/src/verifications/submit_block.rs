@@ -8,6 +8,7 @@ use gw_state::ckb_smt::smt::{Pair, Tree};
 use gw_state::constants::GW_MAX_KV_PAIRS;
 use gw_utils::ckb_std::high_level::load_input_since;
 use gw_utils::ckb_std::since::{LockValue, Since};
+use gw_utils::gw_common::registry::{context::RegistryContext, Registries};
 use gw_utils::gw_common::registry_address::RegistryAddress;
 use gw_utils::gw_types::packed::{L2BlockReader, WithdrawalRequestReader};
 
@@ -248,9 +249,6 @@ fn check_layer2_deposit(
     kv_state: &mut KVState,
     deposit_cells: &[DepositRequestCell],
 ) -> Result<(), Error> {
-    let registry_ctx = gw_common::registry::context::RegistryContext::new(
-        config.allowed_eoa_type_hashes().into_iter().collect(),
-    );
     for request in deposit_cells {
         // check that account's script is a valid EOA script
         if request.account_script.hash_type() != ScriptHashType::Type.into() {
@@ -268,15 +266,21 @@ fn check_layer2_deposit(
             }
             None => {
                 // account isn't exist
+                let registries = Registries::resolve(&*kv_state, rollup_type_hash, config)?;
                 let _new_id = kv_state.create_account(request.account_script_hash)?;
                 let script = &request.account_script;
+                let registry_ctx = RegistryContext::new(
+                    registries,
+                    config.allowed_eoa_type_hashes().into_iter().collect(),
+                );
                 let addr = registry_ctx.extract_registry_address_from_deposit(
                     registry_id,
                     &script.code_hash(),
                     &script.args().raw_data(),
                 )?;
                 // mapping addr to script hash
-                kv_state.mapping_registry_address_to_script_hash(
+                kv_state.mapping_registry_address_to_script_hash_in(
+                    &registries,
                     addr.clone(),
                     request.account_script_hash,
                 )?;
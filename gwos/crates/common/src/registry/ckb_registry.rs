@@ -0,0 +1,80 @@
+use gw_types::{
+    core::AllowedEoaType,
+    h256::H256,
+    packed::{RawL2Transaction, RawWithdrawalRequest},
+};
+
+use crate::blake2b::new_blake2b;
+use crate::error::Error;
+use crate::vec::Vec;
+
+use super::Registry;
+
+/// rollup script hash (32 bytes) | blake160 of the pubkey (20 bytes)
+const EOA_SCRIPT_ARGS_LEN: usize = 52;
+const BLAKE160_LEN: usize = 20;
+
+/// Registry of CKB secp256k1-blake160 addresses, i.e. the pubkey hash in the
+/// args of the secp256k1-blake160-sighash-all lock. WebAuthn EOAs use the
+/// blake160 of their secp256r1 pubkeys.
+pub struct CkbRegistry;
+
+impl Registry for CkbRegistry {
+    fn supports_eoa_type(&self, eoa_type: AllowedEoaType) -> bool {
+        matches!(
+            eoa_type,
+            AllowedEoaType::Secp256k1Blake160 | AllowedEoaType::WebAuthn
+        )
+    }
+
+    fn address_len(&self) -> usize {
+        BLAKE160_LEN
+    }
+
+    fn extract_address_from_eoa(&self, script_args: &[u8]) -> Result<Vec<u8>, Error> {
+        extract_blake160_from_eoa(script_args)
+    }
+}
+
+/// Extract blake160 address from a secp256k1-blake160 or WebAuthn EOA script
+/// args
+pub fn extract_blake160_from_eoa(script_args: &[u8]) -> Result<Vec<u8>, Error> {
+    if script_args.len() != EOA_SCRIPT_ARGS_LEN {
+        return Err(Error::InvalidArgs);
+    }
+    Ok(script_args[32..].to_vec())
+}
+
+/// Signing message of a L2 transaction sent from a CKB registry EOA
+///
+/// blake2b(rollup_script_hash | sender_script_hash | receiver_script_hash | raw_tx_hash)
+pub fn calc_tx_signing_message(
+    rollup_script_hash: &H256,
+    sender_script_hash: &H256,
+    receiver_script_hash: &H256,
+    raw_tx: &RawL2Transaction,
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_script_hash);
+    hasher.update(sender_script_hash);
+    hasher.update(receiver_script_hash);
+    hasher.update(&raw_tx.hash());
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message
+}
+
+/// Signing message of a withdrawal from a CKB registry EOA
+///
+/// blake2b(rollup_script_hash | raw_withdrawal_hash)
+pub fn calc_withdrawal_signing_message(
+    rollup_script_hash: &H256,
+    raw_withdrawal: &RawWithdrawalRequest,
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_script_hash);
+    hasher.update(&raw_withdrawal.hash());
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message
+}
@@ -4,17 +4,17 @@ use gw_types::{
     prelude::*,
 };
 
-use crate::{
-    builtins::ETH_REGISTRY_ACCOUNT_ID, error::Error, registry_address::RegistryAddress, vec::Vec,
-};
+use crate::{error::Error, registry::Registries, registry_address::RegistryAddress, vec::Vec};
 
 pub struct RegistryContext {
+    registries: Registries,
     allowed_eoa_type_hashes: Vec<AllowedTypeHash>,
 }
 
 impl RegistryContext {
-    pub fn new(allowed_eoa_type_hashes: Vec<AllowedTypeHash>) -> Self {
+    pub fn new(registries: Registries, allowed_eoa_type_hashes: Vec<AllowedTypeHash>) -> Self {
         Self {
+            registries,
             allowed_eoa_type_hashes,
         }
     }
@@ -32,12 +32,10 @@ impl RegistryContext {
         code_hash: &Byte32,
         args: &[u8],
     ) -> Result<RegistryAddress, Error> {
-        // We only support ETH registry in current stage
-        if registry_id != ETH_REGISTRY_ACCOUNT_ID {
-            return Err(Error::InvalidArgs);
-        }
-        // Check EOA code hash
-        match self
+        let registry = self.registries.get(registry_id).ok_or(Error::InvalidArgs)?;
+        // Check EOA code hash, the EOA type must be allowed by the rollup config.
+        // `Secp256k1Blake160` and `WebAuthn` EOAs also require the CKB registry.
+        let eoa_type: AllowedEoaType = self
             .find_eoa_type_by_hash(code_hash)
             .map(|type_hash| {
                 let type_: u8 = type_hash.type_().into();
@@ -45,15 +43,11 @@ impl RegistryContext {
             })
             .transpose()
             .map_err(|_err| Error::UnknownEoaCodeHash)?
-        {
-            Some(AllowedEoaType::Eth) => {
-                // extract ETH EOA
-                let address =
-                    { crate::registry::eth_registry::extract_eth_address_from_eoa(args)? };
-                let addr = RegistryAddress::new(registry_id, address);
-                Ok(addr)
-            }
-            _ => Err(Error::UnknownEoaCodeHash),
+            .ok_or(Error::UnknownEoaCodeHash)?;
+        if !registry.supports_eoa_type(eoa_type) {
+            return Err(Error::UnknownEoaCodeHash);
         }
+        let address = registry.extract_address_from_eoa(args)?;
+        Ok(RegistryAddress::new(registry_id, address))
     }
 }
@@ -1,11 +1,33 @@
 #![allow(dead_code)]
 
+use gw_types::core::AllowedEoaType;
+
 use crate::error::Error;
 use crate::vec::Vec;
 
+use super::Registry;
+
 const EOA_SCRIPT_ARGS_LEN: usize = 52;
 /// 32 + 4 + 20
 const CONTRACT_ACCOUNT_SCRIPT_ARGS_LEN: usize = 56;
+const ETH_ADDRESS_LEN: usize = 20;
+
+/// Registry of ETH addresses
+pub struct EthRegistry;
+
+impl Registry for EthRegistry {
+    fn supports_eoa_type(&self, eoa_type: AllowedEoaType) -> bool {
+        eoa_type == AllowedEoaType::Eth
+    }
+
+    fn address_len(&self) -> usize {
+        ETH_ADDRESS_LEN
+    }
+
+    fn extract_address_from_eoa(&self, script_args: &[u8]) -> Result<Vec<u8>, Error> {
+        extract_eth_address_from_eoa(script_args)
+    }
+}
 
 /// Extract ETH address from an ETH EOA script args
 pub fn extract_eth_address_from_eoa(script_args: &[u8]) -> Result<Vec<u8>, Error> {
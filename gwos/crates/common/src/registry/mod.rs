@@ -1,2 +1,91 @@
+pub mod ckb_registry;
 pub mod context;
 pub mod eth_registry;
+
+use gw_types::{
+    core::{AllowedContractType, AllowedEoaType, ScriptHashType},
+    h256::H256,
+    packed::{RollupConfig, Script},
+    prelude::*,
+};
+
+use crate::{builtins::ETH_REGISTRY_ACCOUNT_ID, error::Error, state::State, vec::Vec};
+
+use self::{ckb_registry::CkbRegistry, eth_registry::EthRegistry};
+
+/// A registry maps the addresses of an EOA type to account script hashes.
+pub trait Registry {
+    /// Whether addresses can be extracted from the EOA locks of the type
+    fn supports_eoa_type(&self, eoa_type: AllowedEoaType) -> bool;
+    /// Length of the addresses
+    fn address_len(&self) -> usize;
+    /// Extract address from an EOA script args
+    fn extract_address_from_eoa(&self, script_args: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Registries of a rollup.
+///
+/// The ETH registry is builtin. The CKB registry is optional, it is enabled
+/// only if the rollup config allows a `CkbAddrReg` contract and the account of
+/// the contract has been created, see `Registries::resolve`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Registries {
+    ckb_registry_id: Option<u32>,
+}
+
+impl Registries {
+    /// The builtin ETH registry only
+    pub fn eth_only() -> Self {
+        Self::default()
+    }
+
+    /// Resolve the registries of the rollup from the state.
+    ///
+    /// The CKB registry account is the one whose script is
+    /// `Script { code_hash: CkbAddrReg type hash, hash_type: Type, args: rollup_script_hash }`.
+    pub fn resolve<S: State + ?Sized>(
+        state: &S,
+        rollup_script_hash: &H256,
+        rollup_config: &RollupConfig,
+    ) -> Result<Self, Error> {
+        let ckb_registry_type_hash = rollup_config
+            .allowed_contract_type_hashes()
+            .into_iter()
+            .find(|type_hash| {
+                let type_: u8 = type_hash.type_().into();
+                type_ == u8::from(AllowedContractType::CkbAddrReg)
+            });
+        let ckb_registry_id = match ckb_registry_type_hash {
+            Some(type_hash) => {
+                let script = Script::new_builder()
+                    .code_hash(type_hash.hash())
+                    .hash_type(ScriptHashType::Type.into())
+                    .args(rollup_script_hash.as_slice().pack())
+                    .build();
+                state.get_account_id_by_script_hash(&script.hash())?
+            }
+            None => None,
+        };
+        Ok(Self { ckb_registry_id })
+    }
+
+    /// Account id of the CKB registry
+    pub fn ckb_registry_id(&self) -> Option<u32> {
+        self.ckb_registry_id
+    }
+
+    pub fn get(&self, registry_id: u32) -> Option<&'static dyn Registry> {
+        if registry_id == ETH_REGISTRY_ACCOUNT_ID {
+            Some(&EthRegistry)
+        } else if Some(registry_id) == self.ckb_registry_id {
+            Some(&CkbRegistry)
+        } else {
+            None
+        }
+    }
+
+    /// Account ids of the registries, the ETH registry goes first
+    pub fn ids(&self) -> impl Iterator<Item = u32> {
+        core::iter::once(ETH_REGISTRY_ACCOUNT_ID).chain(self.ckb_registry_id)
+    }
+}
@@ -21,8 +21,8 @@ use gw_types::{
     U256,
 };
 
-use crate::builtins::ETH_REGISTRY_ACCOUNT_ID;
 use crate::error::Error;
+use crate::registry::Registries;
 use crate::registry_address::RegistryAddress;
 use crate::vec::Vec;
 use crate::{blake2b::new_blake2b, merkle_utils::calculate_state_checkpoint};
@@ -310,22 +310,47 @@ pub trait State {
         Ok(Some(RegistryAddress::from_slice(value.as_slice()).unwrap()))
     }
 
+    /// Find the registry address of the script hash in the registries.
+    fn find_registry_address_by_script_hash(
+        &self,
+        registries: &Registries,
+        script_hash: &H256,
+    ) -> Result<Option<RegistryAddress>, Error> {
+        for registry_id in registries.ids() {
+            let addr = self.get_registry_address_by_script_hash(registry_id, script_hash)?;
+            if addr.is_some() {
+                return Ok(addr);
+            }
+        }
+        Ok(None)
+    }
+
     /// This function create a bi-direction mapping between registry address & script_hash
+    ///
+    /// Only the ETH registry is supported, see `mapping_registry_address_to_script_hash_in`.
     fn mapping_registry_address_to_script_hash(
         &mut self,
         addr: RegistryAddress,
         script_hash: H256,
     ) -> Result<(), Error> {
-        // Only support addr len == 20 for now, we can revisit the condition in later version
-        if addr.address.len() != 20 {
+        self.mapping_registry_address_to_script_hash_in(&Registries::eth_only(), addr, script_hash)
+    }
+
+    /// Create a bi-direction mapping between registry address & script_hash in
+    /// the registries.
+    fn mapping_registry_address_to_script_hash_in(
+        &mut self,
+        registries: &Registries,
+        addr: RegistryAddress,
+        script_hash: H256,
+    ) -> Result<(), Error> {
+        let registry = registries.get(addr.registry_id).ok_or(Error::InvalidArgs)?;
+        if addr.address.len() != registry.address_len() {
             return Err(Error::InvalidArgs);
         }
         if script_hash.is_zero() {
             return Err(Error::InvalidArgs);
         }
-        if addr.registry_id != ETH_REGISTRY_ACCOUNT_ID {
-            return Err(Error::InvalidArgs);
-        }
         // Check duplication
         if self
             .get_registry_address_by_script_hash(addr.registry_id, &script_hash)?
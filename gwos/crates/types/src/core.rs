@@ -207,6 +207,10 @@ impl From<GlobalStateV0> for GlobalState {
 pub enum AllowedEoaType {
     Unknown,
     Eth,
+    Secp256k1Blake160,
+    /// Registered in the CKB registry, the type hash is a L1 lock verifying
+    /// WebAuthn assertions of secp256r1 passkeys
+    WebAuthn,
 }
 
 impl From<AllowedEoaType> for u8 {
@@ -223,6 +227,8 @@ impl TryFrom<u8> for AllowedEoaType {
         match value {
             0 => Ok(AllowedEoaType::Unknown),
             1 => Ok(AllowedEoaType::Eth),
+            2 => Ok(AllowedEoaType::Secp256k1Blake160),
+            3 => Ok(AllowedEoaType::WebAuthn),
             n => Err(n),
         }
     }
@@ -243,6 +249,7 @@ pub enum AllowedContractType {
     Sudt,
     Polyjuice,
     EthAddrReg,
+    CkbAddrReg,
 }
 
 impl From<AllowedContractType> for u8 {
@@ -262,6 +269,7 @@ impl TryFrom<u8> for AllowedContractType {
             2 => Ok(AllowedContractType::Sudt),
             3 => Ok(AllowedContractType::Polyjuice),
             4 => Ok(AllowedContractType::EthAddrReg),
+            5 => Ok(AllowedContractType::CkbAddrReg),
             n => Err(n),
         }
     }
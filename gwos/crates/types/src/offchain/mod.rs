@@ -8,6 +8,7 @@ mod pool;
 mod rpc;
 mod run_result;
 mod store;
+mod syscall_trace;
 
 pub use compatible_finalized_timepoint::CompatibleFinalizedTimepoint;
 pub use error_receipt::*;
@@ -19,3 +20,4 @@ pub use pool::*;
 pub use rpc::*;
 pub use run_result::*;
 pub use store::*;
+pub use syscall_trace::*;
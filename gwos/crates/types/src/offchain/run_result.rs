@@ -3,7 +3,7 @@ use crate::h256::H256;
 use crate::packed::{LogItem, Script};
 use std::collections::HashSet;
 
-use super::CycleMeter;
+use super::{CycleMeter, SyscallTraceStep};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct RecoverAccount {
@@ -33,4 +33,6 @@ pub struct RunResult {
     pub read_data_hashes: HashSet<H256>,
     pub write_data_hashes: HashSet<H256>,
     pub debug_log_buf: Vec<u8>,
+    /// Only recorded by `Generator::execute_transaction_with_syscall_trace`.
+    pub syscall_trace: Option<Vec<SyscallTraceStep>>,
 }
@@ -0,0 +1,52 @@
+use crate::bytes::Bytes;
+use crate::h256::H256;
+use crate::registry_address::RegistryAddress;
+use crate::U256;
+
+/// A syscall dispatched by the generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallTraceStep {
+    pub syscall: u64,
+    pub name: &'static str,
+    /// Execution cycles before the syscall.
+    pub cycles: u64,
+    /// Virtual cycles after charging the syscall.
+    pub virtual_cycles: u64,
+    /// Return code of the syscall, 0 is success.
+    pub return_code: i8,
+    pub data: Option<SyscallTraceData>,
+}
+
+/// Arguments and results of the syscalls that touch the state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyscallTraceData {
+    Load {
+        key: H256,
+        value: H256,
+    },
+    Store {
+        key: H256,
+        value: H256,
+    },
+    Create {
+        script_hash: H256,
+        /// None if the account isn't created.
+        account_id: Option<u32>,
+    },
+    PayFee {
+        payer: RegistryAddress,
+        sudt_id: u32,
+        amount: U256,
+    },
+    Log {
+        account_id: u32,
+        service_flag: u8,
+        data: Bytes,
+    },
+    Snapshot {
+        snapshot_id: u32,
+    },
+    Revert {
+        snapshot_id: u32,
+    },
+}
@@ -14,7 +14,7 @@ pub struct IndexerConfig {
     pub rollup_type_hash: H256,
     pub eth_account_lock_hash: H256,
     pub godwoken_rpc_url: String,
-    pub pg_url: String,
+    pub database_url: String,
     pub chain_id: u64,
     pub sentry_dsn: Option<String>,
     pub sentry_environment: Option<String>,
@@ -40,7 +40,7 @@ impl Display for IndexerConfig {
             self.eth_account_lock_hash
         )?;
         write!(f, "godwoken_rpc_url: {}, ", self.godwoken_rpc_url)?;
-        write!(f, "pg_url: {}", self.pg_url)?;
+        write!(f, "database_url: {}, ", self.database_url)?;
         write!(f, "chain_id: {}", self.chain_id)?;
         if let Some(t) = &self.sentry_dsn {
             write!(f, "sentry_dsn: {}, ", t)?;
@@ -57,7 +57,7 @@ impl Display for IndexerConfig {
 }
 
 // TODO: rename the configuration file from "indexer-config.toml" to ".env"
-// TODO: uppercase the environment variables name "pg_url", "godwoken_rpc_url", etc
+// TODO: uppercase the environment variables name "database_url", "godwoken_rpc_url", etc
 pub fn load_indexer_config<P: AsRef<Path>>(path: P) -> Result<IndexerConfig> {
     if path.as_ref().exists() {
         log::info!(
@@ -73,7 +73,10 @@ pub fn load_indexer_config<P: AsRef<Path>>(path: P) -> Result<IndexerConfig> {
     }
 
     // Load components configurations from environment variables
-    let pg_url = env::var("pg_url").expect("env var \"pg_url\" is required");
+    // `pg_url` is kept for backward compatibility.
+    let database_url = env::var("database_url")
+        .or_else(|_| env::var("pg_url"))
+        .expect("env var \"database_url\" or \"pg_url\" is required");
     let godwoken_rpc_url =
         env::var("godwoken_rpc_url").unwrap_or_else(|_| "http://127.0.0.1:8119".to_string());
     let sentry_dsn = env::var("sentry_dsn").ok();
@@ -124,7 +127,7 @@ pub fn load_indexer_config<P: AsRef<Path>>(path: P) -> Result<IndexerConfig> {
         rollup_type_hash,
         eth_account_lock_hash,
         godwoken_rpc_url,
-        pg_url,
+        database_url,
         chain_id,
         sentry_dsn,
         sentry_environment,
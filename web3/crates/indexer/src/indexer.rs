@@ -1,16 +1,15 @@
 use std::{
     collections::{HashMap, HashSet},
     iter::FromIterator,
+    sync::Arc,
 };
 
 use crate::{
     helper::{hex, parse_log, GwLog, PolyjuiceArgs, GW_LOG_POLYJUICE_SYSTEM},
-    insert_l2_block::{
-        insert_web3_block, insert_web3_txs_and_logs, update_web3_block, update_web3_txs_and_logs,
-    },
-    pool::POOL,
+    storage::Storage,
+    token::{decode_erc20_transfer, decode_sudt_transfer},
     types::{
-        Block as Web3Block, Log as Web3Log, Transaction as Web3Transaction,
+        Block as Web3Block, Log as Web3Log, Token, Transaction as Web3Transaction,
         TransactionWithLogs as Web3TransactionWithLogs,
     },
 };
@@ -30,7 +29,6 @@ use gw_web3_rpc_client::{
 };
 use itertools::Itertools;
 use rayon::prelude::*;
-use rust_decimal::{prelude::ToPrimitive, Decimal};
 use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
 
 const MILLIS_PER_SEC: u64 = 1_000;
@@ -43,6 +41,7 @@ pub struct Web3Indexer {
     allowed_eoa_hashes: HashSet<H256>,
     godwoken_rpc_client: GodwokenRpcClient,
     godwoken_async_client: GodwokenAsyncClient,
+    storage: Arc<dyn Storage>,
 }
 
 impl Web3Indexer {
@@ -52,6 +51,7 @@ impl Web3Indexer {
         rollup_type_hash: H256,
         eth_account_lock_hash: H256,
         gw_rpc_url: &str,
+        storage: Arc<dyn Storage>,
     ) -> Self {
         let mut allowed_eoa_hashes = HashSet::default();
         allowed_eoa_hashes.insert(eth_account_lock_hash);
@@ -65,6 +65,7 @@ impl Web3Indexer {
             allowed_eoa_hashes,
             godwoken_rpc_client,
             godwoken_async_client,
+            storage,
         }
     }
 
@@ -83,10 +84,10 @@ impl Web3Indexer {
 
     pub async fn store_l2_block(&self, l2_block: L2Block) -> Result<(usize, usize)> {
         let number: u64 = l2_block.raw().number().unpack();
-        let local_tip_number = self.tip_number().await?.unwrap_or(0);
+        let local_tip_number = self.storage.tip_number().await?.unwrap_or(0);
         let mut txs_len = 0;
         let mut logs_len = 0;
-        if number > local_tip_number || self.query_number(number).await?.is_none() {
+        if number > local_tip_number || self.storage.get_block_hash(number).await?.is_none() {
             // insert l2 block
             (txs_len, logs_len) = self.insert_or_update_l2block(l2_block, false).await?;
             log::debug!(
@@ -99,24 +100,6 @@ impl Web3Indexer {
         Ok((txs_len, logs_len))
     }
 
-    async fn query_number(&self, number: u64) -> Result<Option<u64>> {
-        let row: Option<(Decimal,)> = sqlx::query_as(&format!(
-            "SELECT number FROM blocks WHERE number={} LIMIT 1",
-            number
-        ))
-        .fetch_optional(&*POOL)
-        .await?;
-        Ok(row.and_then(|(n,)| n.to_u64()))
-    }
-
-    async fn tip_number(&self) -> Result<Option<u64>> {
-        let row: Option<(Decimal,)> =
-            sqlx::query_as("SELECT number FROM blocks ORDER BY number DESC LIMIT 1")
-                .fetch_optional(&*POOL)
-                .await?;
-        Ok(row.and_then(|(n,)| n.to_u64()))
-    }
-
     // NOTE: remember to update `tx_index`, `cumulative_gas_used`, `log.transaction_index`
     fn filter_single_transaction(
         &self,
@@ -292,8 +275,9 @@ impl Web3Indexer {
                 exit_code,
             );
 
-            let web3_logs = {
+            let (web3_logs, token_transfers) = {
                 let mut logs: Vec<Web3Log> = vec![];
+                let mut token_transfers = vec![];
                 // log_index is a log's index in block, not transaction, should update later.
                 let mut log_index = 0;
                 for log_item in log_item_vec {
@@ -317,20 +301,34 @@ impl Web3Indexer {
                                 log_index,
                                 topics,
                             );
+                            if let Some(transfer) = decode_erc20_transfer(&web3_log) {
+                                token_transfers.push(transfer);
+                            }
                             logs.push(web3_log);
                             log_index += 1;
                         }
-                        // TODO: Given the fact that Ethereum doesn't emit event for native ether transfer at system level, the SudtTransfer/SudtPayFee logs in polyjuice provide more info than we need here and could be ignored so far.
-                        GwLog::SudtTransfer { .. } => {}
+                        // Given the fact that Ethereum doesn't emit event for native ether transfer at system level, the SudtTransfer/SudtPayFee logs are not web3 logs.
+                        // SudtTransfer logs are indexed as token transfers, e.g. transfers through sUDT-ERC20 proxy contracts.
+                        sudt_log @ GwLog::SudtTransfer { .. } => {
+                            if let Some(transfer) = decode_sudt_transfer(
+                                &sudt_log,
+                                gw_tx_hash,
+                                block_number,
+                                block_hash,
+                            ) {
+                                token_transfers.push(transfer);
+                            }
+                        }
                         GwLog::SudtPayFee { .. } => {}
                     }
                 }
-                logs
+                (logs, token_transfers)
             };
 
             let web3_tx_with_logs = Web3TransactionWithLogs {
                 tx: web3_transaction,
                 logs: web3_logs,
+                token_transfers,
             };
             // tx_index += 1;
             return Ok(Some(web3_tx_with_logs));
@@ -395,9 +393,20 @@ impl Web3Indexer {
                         exit_code,
                     );
 
+                    let mut token_transfers = vec![];
+                    for log_item in tx_receipt.logs() {
+                        let log = parse_log(&log_item, &gw_tx_hash)?;
+                        if let Some(transfer) =
+                            decode_sudt_transfer(&log, gw_tx_hash, block_number, block_hash)
+                        {
+                            token_transfers.push(transfer);
+                        }
+                    }
+
                     let web3_tx_with_logs = Web3TransactionWithLogs {
                         tx: web3_transaction,
                         logs: vec![],
+                        token_transfers,
                     };
 
                     return Ok(Some(web3_tx_with_logs));
@@ -458,6 +467,56 @@ impl Web3Indexer {
         Ok(hashmap)
     }
 
+    // Only keep sUDT transfers of accounts with l2 sudt script
+    async fn retain_sudt_transfers(
+        &self,
+        txs: &mut [Web3TransactionWithLogs],
+        id_script_map: &HashMap<u32, Option<Script>>,
+    ) -> Result<()> {
+        let sudt_ids: HashSet<u32> = txs
+            .iter()
+            .flat_map(|tx| tx.token_transfers.iter())
+            .filter_map(|transfer| match transfer.token {
+                Token::Sudt(sudt_id) => Some(sudt_id),
+                Token::Erc20(_) => None,
+            })
+            .collect();
+        if sudt_ids.is_empty() {
+            return Ok(());
+        }
+
+        let missing_ids = sudt_ids
+            .iter()
+            .filter(|id| !id_script_map.contains_key(*id))
+            .copied()
+            .collect::<Vec<_>>();
+        let missing_scripts =
+            batch_account_id_to_script(&self.godwoken_async_client, missing_ids.clone()).await?;
+        let missing_script_map: HashMap<u32, Option<Script>> =
+            missing_ids.into_iter().zip(missing_scripts).collect();
+
+        let is_sudt: HashMap<u32, bool> = sudt_ids
+            .into_iter()
+            .map(|id| {
+                let script = id_script_map
+                    .get(&id)
+                    .or_else(|| missing_script_map.get(&id))
+                    .and_then(|script| script.as_ref());
+                let is_sudt = script.map_or(false, |script| {
+                    script.code_hash().as_slice() == self.l2_sudt_type_script_hash.0
+                });
+                (id, is_sudt)
+            })
+            .collect();
+        for tx in txs.iter_mut() {
+            tx.token_transfers.retain(|transfer| match transfer.token {
+                Token::Sudt(sudt_id) => is_sudt.get(&sudt_id).copied().unwrap_or(false),
+                Token::Erc20(_) => true,
+            });
+        }
+        Ok(())
+    }
+
     async fn insert_or_update_l2block(
         &self,
         l2_block: L2Block,
@@ -469,9 +528,6 @@ impl Web3Indexer {
         let l2_transactions = l2_block.transactions();
         let l2_transactions_vec: Vec<L2Transaction> = l2_transactions.into_iter().collect();
 
-        let mut logs_len: usize = 0;
-        let mut web3_txs_len: usize = 0;
-
         let id_script_hashmap = self.batch_from_script(&l2_transactions_vec).await?;
 
         let txs_slice = l2_transactions_vec
@@ -481,10 +537,7 @@ impl Web3Indexer {
             .map(|chunk| chunk.collect())
             .collect::<Vec<Vec<_>>>();
 
-        // begin db transaction
-        let pool = &*POOL;
-        let mut pg_tx = pool.begin().await?;
-
+        let mut web3_txs = Vec::new();
         let mut tx_index_cursor: u32 = 0;
         let mut log_index_cursor: u32 = 0;
 
@@ -516,6 +569,12 @@ impl Web3Indexer {
                             log
                         })
                         .collect();
+                    for transfer in tx.token_transfers.iter_mut() {
+                        transfer.transaction_index = transaction_index;
+                        if let Some(log_index) = transfer.log_index.as_mut() {
+                            *log_index += log_index_cursor;
+                        }
+                    }
                     cumulative_gas_used += tx.tx.gas_used;
                     tx.tx.cumulative_gas_used = cumulative_gas_used;
 
@@ -527,32 +586,20 @@ impl Web3Indexer {
                 .collect::<Vec<_>>();
 
             tx_index_cursor += txs_vec.len() as u32;
-
-            // insert to db or update
-            let (txs_part_len, logs_part_len) = if is_update {
-                update_web3_txs_and_logs(txs_vec, &mut pg_tx).await?
-            } else {
-                insert_web3_txs_and_logs(txs_vec, &mut pg_tx).await?
-            };
-
-            web3_txs_len += txs_part_len;
-            logs_len += logs_part_len;
+            web3_txs.extend(txs_vec);
         }
+        self.retain_sudt_transfers(&mut web3_txs, &id_script_hashmap)
+            .await?;
 
         // insert or update block
         let web3_block = self
             .build_web3_block(&l2_block, total_gas_limit, cumulative_gas_used)
             .await?;
         if is_update {
-            update_web3_block(web3_block, &mut pg_tx).await?;
+            self.storage.update_block(web3_block, web3_txs).await
         } else {
-            insert_web3_block(web3_block, &mut pg_tx).await?;
+            self.storage.insert_block(web3_block, web3_txs).await
         }
-
-        // commit
-        pg_tx.commit().await?;
-
-        Ok((web3_txs_len, logs_len))
     }
 
     fn get_transaction_receipt(
@@ -11,11 +11,10 @@ use sqlx::{
     },
     Row,
 };
-use sqlx::{Postgres, QueryBuilder};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
 use crate::{
     cpu_count::CPU_COUNT,
-    pool::POOL_FOR_UPDATE,
     types::{Block, Log, Transaction, TransactionWithLogs},
 };
 
@@ -306,7 +305,7 @@ pub async fn update_web3_block(
 
 pub async fn update_web3_txs_and_logs(
     web3_tx_with_logs_vec: Vec<TransactionWithLogs>,
-    _pg_tx: &mut sqlx::Transaction<'_, Postgres>,
+    pool: &PgPool,
 ) -> Result<(usize, usize)> {
     if web3_tx_with_logs_vec.is_empty() {
         return Ok((0, 0));
@@ -369,7 +368,7 @@ pub async fn update_web3_txs_and_logs(
                             .bind(tx.chain_id)
                             .bind(tx.block_number)
                             .bind(tx.transaction_index)
-                            .execute(&*POOL_FOR_UPDATE)
+                            .execute(pool)
         })
     )
     .await
@@ -420,7 +419,7 @@ pub async fn update_web3_txs_and_logs(
                 .par_iter_mut()
                 .map(|query_builder| {
                     let query = query_builder.build();
-                    smol::block_on(query.execute(&*POOL_FOR_UPDATE)).map_err(|err| anyhow!(err))
+                    smol::block_on(query.execute(pool)).map_err(|err| anyhow!(err))
                 })
                 .collect::<Vec<_>>()
                 .into_iter()
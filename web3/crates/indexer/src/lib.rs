@@ -3,8 +3,9 @@ pub mod cpu_count;
 pub mod helper;
 pub mod indexer;
 pub mod insert_l2_block;
-pub mod pool;
 pub mod runner;
+pub mod storage;
+pub mod token;
 pub mod types;
 
 pub use indexer::Web3Indexer;
@@ -1,4 +1,4 @@
-use gw_web3_indexer::{config::load_indexer_config, runner::Runner};
+use gw_web3_indexer::{config::load_indexer_config, runner::Runner, storage};
 
 use anyhow::Result;
 use sentry_log::LogFilter;
@@ -20,14 +20,18 @@ fn main() -> Result<()> {
         None => sentry::init(()),
     };
 
-    let mut runner = Runner::new(indexer_config)?;
+    let storage = smol::block_on(storage::connect(&indexer_config.database_url))?;
+    let mut runner = Runner::new(indexer_config, storage)?;
 
     let command_name = std::env::args().nth(1);
 
     // `cargo run` -> run sync mode
     // `cargo run update <optional start number> <optional end number>` -> run update mode
+    // `cargo run reindex-tokens` -> rebuild ERC-20 token transfers and balances
     if let Some(name) = command_name {
-        if name == "update" {
+        if name == "reindex-tokens" {
+            smol::block_on(runner.run_reindex_tokens())?;
+        } else if name == "update" {
             let start_block_number = std::env::args()
                 .nth(2)
                 .map(|num| num.parse::<u64>().unwrap());
@@ -2,31 +2,34 @@ use ckb_types::prelude::Entity;
 use gw_web3_rpc_client::{
     convertion::to_l2_block, error::RpcClientError, godwoken_rpc_client::GodwokenRpcClient,
 };
-use rust_decimal::{prelude::ToPrimitive, Decimal};
+use std::sync::Arc;
 
-use crate::{config::IndexerConfig, helper::hex, pool::POOL, Web3Indexer};
+use crate::{config::IndexerConfig, helper::hex, storage::Storage, Web3Indexer};
 use anyhow::{anyhow, Result};
 
 pub struct Runner {
     indexer: Web3Indexer,
     local_tip: Option<u64>,
     godwoken_rpc_client: GodwokenRpcClient,
+    storage: Arc<dyn Storage>,
 }
 
 impl Runner {
-    pub fn new(config: IndexerConfig) -> Result<Runner> {
+    pub fn new(config: IndexerConfig, storage: Arc<dyn Storage>) -> Result<Runner> {
         let indexer = Web3Indexer::new(
             config.l2_sudt_type_script_hash,
             config.polyjuice_type_script_hash,
             config.rollup_type_hash,
             config.eth_account_lock_hash,
             config.godwoken_rpc_url.as_str(),
+            Arc::clone(&storage),
         );
         let godwoken_rpc_client = GodwokenRpcClient::new(config.godwoken_rpc_url.as_str());
         let runner = Runner {
             indexer,
             local_tip: None,
             godwoken_rpc_client,
+            storage,
         };
         Ok(runner)
     }
@@ -70,47 +73,15 @@ impl Runner {
     }
 
     async fn get_db_tip_number(&self) -> Result<Option<u64>> {
-        let row: Option<(Decimal,)> =
-            sqlx::query_as("select number from blocks order by number desc limit 1;")
-                .fetch_optional(&*POOL)
-                .await?;
-
-        let num = row.and_then(|(n,)| n.to_u64());
-        Ok(num)
+        self.storage.tip_number().await
     }
 
-    async fn get_db_block_hash(&self, block_number: u64) -> Result<Option<ckb_types::H256>> {
-        let row: Option<(Vec<u8>,)> =
-            sqlx::query_as("select hash from blocks where number = $1 limit 1;")
-                .bind(Decimal::from(block_number))
-                .fetch_optional(&*POOL)
-                .await?;
-
-        if let Some((block_hash_vec,)) = row {
-            let block_hash = ckb_types::H256::from_slice(block_hash_vec.as_ref())?;
-            return Ok(Some(block_hash));
-        }
-        Ok(None)
+    async fn get_db_block_hash(&self, block_number: u64) -> Result<Option<gw_types::h256::H256>> {
+        self.storage.get_block_hash(block_number).await
     }
 
     async fn delete_block(&self, block_number: u64) -> Result<()> {
-        let number = Decimal::from(block_number);
-        let pool = &*POOL;
-        let mut tx = pool.begin().await?;
-        sqlx::query("delete from logs where block_number = $1;")
-            .bind(number)
-            .execute(&mut tx)
-            .await?;
-        sqlx::query("delete from transactions where block_number = $1;")
-            .bind(number)
-            .execute(&mut tx)
-            .await?;
-        sqlx::query("delete from blocks where number = $1;")
-            .bind(number)
-            .execute(&mut tx)
-            .await?;
-        tx.commit().await?;
-        Ok(())
+        self.storage.delete_block(block_number).await
     }
 
     async fn update(&mut self, current_block_number: u64) -> Result<bool> {
@@ -130,7 +101,7 @@ impl Runner {
             if let Some(prev_block_hash) = db_prev_block_hash {
                 // if match, insert a new block
                 // if not match, sleep and try again
-                if l2_block_parent_hash.as_slice() == prev_block_hash.as_bytes() {
+                if l2_block_parent_hash.as_slice() == prev_block_hash.as_slice() {
                     let (txs_len, logs_len) = self.indexer.update_l2_block(l2_block).await?;
 
                     let duration = start.elapsed();
@@ -145,7 +116,7 @@ impl Runner {
                     return Ok(true);
                 } else {
                     // Sleep and try again, wait for indexer to deal with revert
-                    log::info!("block {}'s parent_block_hash: {} not match prev block's hash {}, sleep and try again", current_block_number, hex(l2_block_parent_hash.as_slice())?, hex(prev_block_hash.as_bytes())?);
+                    log::info!("block {}'s parent_block_hash: {} not match prev block's hash {}, sleep and try again", current_block_number, hex(l2_block_parent_hash.as_slice())?, hex(&prev_block_hash)?);
                     let sleep_time = std::time::Duration::from_secs(3);
                     smol::Timer::after(sleep_time).await;
                 }
@@ -190,7 +161,7 @@ impl Runner {
                 if let Some(prev_block_hash) = db_prev_block_hash {
                     // if match, insert a new block
                     // if not match, delete prev block
-                    if l2_block_parent_hash.as_slice() == prev_block_hash.as_bytes() {
+                    if l2_block_parent_hash.as_slice() == prev_block_hash.as_slice() {
                         let (txs_len, logs_len) = self.indexer.store_l2_block(l2_block).await?;
 
                         let duration = start.elapsed();
@@ -252,6 +223,19 @@ impl Runner {
         }
     }
 
+    /// Rebuild ERC-20 token transfers and balances from the indexed logs.
+    pub async fn run_reindex_tokens(&self) -> Result<()> {
+        let start = std::time::Instant::now();
+        let (transfers_len, balances_len) = self.storage.reindex_erc20_transfers().await?;
+        log::info!(
+            "Reindex {} ERC-20 transfers, {} token balances, duration: {:?}",
+            transfers_len,
+            balances_len,
+            start.elapsed(),
+        );
+        Ok(())
+    }
+
     pub async fn run_update(
         &mut self,
         start_block_number: Option<u64>,
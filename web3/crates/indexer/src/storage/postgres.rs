@@ -0,0 +1,330 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use gw_types::h256::H256;
+use itertools::Itertools;
+use num_bigint::{BigInt, Sign};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    types::BigDecimal,
+    ConnectOptions, PgPool, Postgres, QueryBuilder,
+};
+
+use super::{
+    decode_erc20_transfers, revert_changes, take_token_transfers, to_address, token_columns,
+    token_from_columns, Storage,
+};
+use crate::{
+    insert_l2_block::{
+        insert_web3_block, insert_web3_txs_and_logs, update_web3_block, update_web3_txs_and_logs,
+    },
+    token::{balance_changes, u256_to_big_int, BalanceChanges, ERC20_TRANSFER_TOPIC},
+    types::{Block, Log, TokenTransfer, TransactionWithLogs},
+};
+
+const TXS_BATCH_SIZE: usize = 100;
+// 10 binds per transfer, postgres supports at most 65535 binds in a query
+const INSERT_TOKEN_TRANSFERS_BATCH_SIZE: usize = 5000;
+const REINDEX_LOGS_BATCH_SIZE: i64 = 5000;
+
+pub struct PgStorage {
+    pool: PgPool,
+    // Adapt slow query duration to 30s, and enlarge max connections
+    pool_for_update: PgPool,
+}
+
+impl PgStorage {
+    pub fn connect_lazy(url: &str) -> Result<Self> {
+        let pool = {
+            let mut opts: PgConnectOptions = url.parse()?;
+            opts.log_statements(log::LevelFilter::Debug)
+                .log_slow_statements(log::LevelFilter::Warn, Duration::from_secs(5));
+            PgPoolOptions::new()
+                .max_connections(5)
+                .connect_lazy_with(opts)
+        };
+        let pool_for_update = {
+            let mut opts: PgConnectOptions = url.parse()?;
+            opts.log_statements(log::LevelFilter::Debug)
+                .log_slow_statements(log::LevelFilter::Warn, Duration::from_secs(30));
+            PgPoolOptions::new()
+                .max_connections(20)
+                .connect_lazy_with(opts)
+        };
+        Ok(Self {
+            pool,
+            pool_for_update,
+        })
+    }
+}
+
+fn into_batches(txs: Vec<TransactionWithLogs>) -> Vec<Vec<TransactionWithLogs>> {
+    txs.into_iter()
+        .chunks(TXS_BATCH_SIZE)
+        .into_iter()
+        .map(|chunk| chunk.collect())
+        .collect()
+}
+
+fn big_int_to_big_decimal(value: BigInt) -> BigDecimal {
+    BigDecimal::new(value, 0)
+}
+
+fn big_decimal_to_big_int(value: &BigDecimal) -> BigInt {
+    value.with_scale(0).into_bigint_and_exponent().0
+}
+
+async fn insert_token_transfers(
+    transfers: &[TokenTransfer],
+    pg_tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<()> {
+    for chunk in transfers.chunks(INSERT_TOKEN_TRANSFERS_BATCH_SIZE) {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO token_transfers
+            (transaction_hash, transaction_index, block_number, block_hash, log_index, token_address, sudt_id, from_address, to_address, amount)"
+        );
+        query_builder.push_values(chunk, |mut b, transfer| {
+            let (token_address, sudt_id) = token_columns(transfer.token);
+            b.push_bind(transfer.transaction_hash.to_vec())
+                .push_bind(transfer.transaction_index as i32)
+                .push_bind(Decimal::from(transfer.block_number))
+                .push_bind(transfer.block_hash.to_vec())
+                .push_bind(transfer.log_index.map(|index| index as i32))
+                .push_bind(token_address.map(|address| address.to_vec()))
+                .push_bind(sudt_id.map(|id| id as i64))
+                .push_bind(transfer.from_address.to_vec())
+                .push_bind(transfer.to_address.to_vec())
+                .push_bind(big_int_to_big_decimal(u256_to_big_int(&transfer.amount)));
+        });
+        query_builder.build().execute(&mut *pg_tx).await?;
+    }
+    Ok(())
+}
+
+/// Delete token transfers of the block, returns the balance changes to revert them.
+async fn delete_token_transfers(
+    number: Decimal,
+    pg_tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<BalanceChanges> {
+    let rows: Vec<(Option<Vec<u8>>, Option<i64>, Vec<u8>, Vec<u8>, BigDecimal)> = sqlx::query_as(
+        "DELETE FROM token_transfers WHERE block_number = $1 RETURNING token_address, sudt_id, from_address, to_address, amount"
+    )
+        .bind(number)
+        .fetch_all(&mut *pg_tx)
+        .await?;
+    let transfers = rows
+        .into_iter()
+        .map(|(token_address, sudt_id, from, to, amount)| {
+            let token = token_from_columns(token_address, sudt_id)?;
+            Ok((
+                token,
+                to_address(&from)?,
+                to_address(&to)?,
+                big_decimal_to_big_int(&amount),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(revert_changes(transfers))
+}
+
+async fn apply_balance_changes(
+    changes: BalanceChanges,
+    pg_tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<()> {
+    for ((token_address, address), change) in changes {
+        let row: Option<(i64, BigDecimal)> = sqlx::query_as(
+            "SELECT id, balance FROM token_balances WHERE token_address = $1 AND address = $2",
+        )
+        .bind(&token_address[..])
+        .bind(&address[..])
+        .fetch_optional(&mut *pg_tx)
+        .await?;
+        match row {
+            Some((id, balance)) => {
+                let balance = big_decimal_to_big_int(&balance) + change;
+                if balance.sign() == Sign::NoSign {
+                    sqlx::query("DELETE FROM token_balances WHERE id = $1")
+                        .bind(id)
+                        .execute(&mut *pg_tx)
+                        .await?;
+                } else {
+                    sqlx::query("UPDATE token_balances SET balance = $1 WHERE id = $2")
+                        .bind(big_int_to_big_decimal(balance))
+                        .bind(id)
+                        .execute(&mut *pg_tx)
+                        .await?;
+                }
+            }
+            None => {
+                sqlx::query(
+                    "INSERT INTO token_balances (token_address, address, balance) VALUES ($1, $2, $3)",
+                )
+                .bind(&token_address[..])
+                .bind(&address[..])
+                .bind(big_int_to_big_decimal(change))
+                .execute(&mut *pg_tx)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+type LogRow = (
+    i64,
+    Vec<u8>,
+    i32,
+    Decimal,
+    Vec<u8>,
+    Vec<u8>,
+    Option<Vec<u8>>,
+    i32,
+    Vec<Vec<u8>>,
+);
+
+fn log_from_row(row: LogRow) -> Result<Log> {
+    let (_id, tx_hash, tx_index, block_number, block_hash, address, data, log_index, topics) = row;
+    let topics = topics
+        .iter()
+        .map(|topic| topic.as_slice().try_into())
+        .collect::<Result<Vec<H256>, _>>()?;
+    Ok(Log::new(
+        tx_hash.as_slice().try_into()?,
+        tx_index as u32,
+        block_number
+            .to_u64()
+            .ok_or_else(|| anyhow!("invalid block number {}", block_number))?,
+        block_hash.as_slice().try_into()?,
+        to_address(&address)?,
+        data.unwrap_or_default(),
+        log_index as u32,
+        topics,
+    ))
+}
+
+#[async_trait]
+impl Storage for PgStorage {
+    async fn tip_number(&self) -> Result<Option<u64>> {
+        let row: Option<(Decimal,)> =
+            sqlx::query_as("SELECT number FROM blocks ORDER BY number DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.and_then(|(n,)| n.to_u64()))
+    }
+
+    async fn get_block_hash(&self, number: u64) -> Result<Option<H256>> {
+        let row: Option<(Vec<u8>,)> =
+            sqlx::query_as("SELECT hash FROM blocks WHERE number = $1 LIMIT 1")
+                .bind(Decimal::from(number))
+                .fetch_optional(&self.pool)
+                .await?;
+        let hash: Option<H256> = row.map(|(hash,)| hash.as_slice().try_into()).transpose()?;
+        Ok(hash)
+    }
+
+    async fn insert_block(
+        &self,
+        block: Block,
+        mut txs: Vec<TransactionWithLogs>,
+    ) -> Result<(usize, usize)> {
+        let token_transfers = take_token_transfers(&mut txs);
+        let mut pg_tx = self.pool.begin().await?;
+        let (mut txs_len, mut logs_len) = (0, 0);
+        for batch in into_batches(txs) {
+            let (txs_part_len, logs_part_len) = insert_web3_txs_and_logs(batch, &mut pg_tx).await?;
+            txs_len += txs_part_len;
+            logs_len += logs_part_len;
+        }
+        insert_token_transfers(&token_transfers, &mut pg_tx).await?;
+        apply_balance_changes(balance_changes(&token_transfers), &mut pg_tx).await?;
+        insert_web3_block(block, &mut pg_tx).await?;
+        pg_tx.commit().await?;
+        Ok((txs_len, logs_len))
+    }
+
+    async fn update_block(
+        &self,
+        block: Block,
+        mut txs: Vec<TransactionWithLogs>,
+    ) -> Result<(usize, usize)> {
+        let token_transfers = take_token_transfers(&mut txs);
+        let mut pg_tx = self.pool.begin().await?;
+        let (mut txs_len, mut logs_len) = (0, 0);
+        for batch in into_batches(txs) {
+            let (txs_part_len, logs_part_len) =
+                update_web3_txs_and_logs(batch, &self.pool_for_update).await?;
+            txs_len += txs_part_len;
+            logs_len += logs_part_len;
+        }
+        let number = Decimal::from(block.number);
+        let reverted = delete_token_transfers(number, &mut pg_tx).await?;
+        apply_balance_changes(reverted, &mut pg_tx).await?;
+        insert_token_transfers(&token_transfers, &mut pg_tx).await?;
+        apply_balance_changes(balance_changes(&token_transfers), &mut pg_tx).await?;
+        update_web3_block(block, &mut pg_tx).await?;
+        pg_tx.commit().await?;
+        Ok((txs_len, logs_len))
+    }
+
+    async fn delete_block(&self, number: u64) -> Result<()> {
+        let number = Decimal::from(number);
+        let mut tx = self.pool.begin().await?;
+        let reverted = delete_token_transfers(number, &mut tx).await?;
+        apply_balance_changes(reverted, &mut tx).await?;
+        sqlx::query("delete from logs where block_number = $1;")
+            .bind(number)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("delete from transactions where block_number = $1;")
+            .bind(number)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("delete from blocks where number = $1;")
+            .bind(number)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn reindex_erc20_transfers(&self) -> Result<(usize, usize)> {
+        let mut pg_tx = self.pool_for_update.begin().await?;
+        sqlx::query("DELETE FROM token_transfers WHERE token_address IS NOT NULL")
+            .execute(&mut pg_tx)
+            .await?;
+        sqlx::query("DELETE FROM token_balances")
+            .execute(&mut pg_tx)
+            .await?;
+        let mut changes = BalanceChanges::new();
+        let mut transfers_len = 0;
+        let mut last_id = 0;
+        loop {
+            let rows: Vec<LogRow> = sqlx::query_as(
+                "SELECT id, transaction_hash, transaction_index, block_number, block_hash, address, data, log_index, topics FROM logs WHERE id > $1 AND topics[1] = $2 ORDER BY id LIMIT $3"
+            )
+                .bind(last_id)
+                .bind(&ERC20_TRANSFER_TOPIC[..])
+                .bind(REINDEX_LOGS_BATCH_SIZE)
+                .fetch_all(&mut pg_tx)
+                .await?;
+            last_id = match rows.last() {
+                Some(row) => row.0,
+                None => break,
+            };
+            let logs = rows
+                .into_iter()
+                .map(log_from_row)
+                .collect::<Result<Vec<_>>>()?;
+            let transfers = decode_erc20_transfers(&logs, &mut changes);
+            insert_token_transfers(&transfers, &mut pg_tx).await?;
+            transfers_len += transfers.len();
+        }
+        changes.retain(|_, change| change.sign() != Sign::NoSign);
+        let balances_len = changes.len();
+        apply_balance_changes(changes, &mut pg_tx).await?;
+        pg_tx.commit().await?;
+        Ok((transfers_len, balances_len))
+    }
+}
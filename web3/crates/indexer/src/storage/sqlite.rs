@@ -0,0 +1,686 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use gw_types::h256::H256;
+use num_bigint::{BigInt, Sign};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Sqlite, SqlitePool,
+};
+
+use super::{
+    decode_erc20_transfers, revert_changes, take_token_transfers, to_address, token_columns,
+    token_from_columns, Storage,
+};
+use crate::{
+    token::{balance_changes, BalanceChanges, ERC20_TRANSFER_TOPIC},
+    types::{Block, Log, TokenTransfer, Transaction, TransactionWithLogs},
+};
+
+const REINDEX_LOGS_BATCH_SIZE: i64 = 1000;
+
+// The same tables as the postgres schema of web3 api server. u64 numbers are
+// INTEGER, u128 / u256 numbers are decimal TEXT, and topics of a log are
+// concatenated into one BLOB. Token balances may be negative, they are signed
+// decimal TEXT.
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS blocks (
+        number INTEGER PRIMARY KEY NOT NULL,
+        hash BLOB NOT NULL UNIQUE,
+        parent_hash BLOB NOT NULL,
+        gas_limit TEXT NOT NULL,
+        gas_used TEXT NOT NULL,
+        miner BLOB NOT NULL,
+        size INTEGER NOT NULL,
+        timestamp TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS transactions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        hash BLOB NOT NULL UNIQUE,
+        eth_tx_hash BLOB NOT NULL UNIQUE,
+        block_number INTEGER NOT NULL,
+        block_hash BLOB NOT NULL,
+        transaction_index INTEGER NOT NULL,
+        from_address BLOB NOT NULL,
+        to_address BLOB,
+        value TEXT NOT NULL,
+        nonce INTEGER NOT NULL,
+        gas_limit TEXT,
+        gas_price TEXT,
+        input BLOB,
+        v INTEGER NOT NULL,
+        r BLOB NOT NULL,
+        s BLOB NOT NULL,
+        cumulative_gas_used TEXT,
+        gas_used TEXT,
+        contract_address BLOB,
+        exit_code INTEGER NOT NULL,
+        chain_id INTEGER,
+        UNIQUE (block_hash, transaction_index),
+        UNIQUE (block_number, transaction_index)
+    )",
+    "CREATE INDEX IF NOT EXISTS transactions_from_address_index ON transactions (from_address)",
+    "CREATE INDEX IF NOT EXISTS transactions_to_address_index ON transactions (to_address)",
+    "CREATE INDEX IF NOT EXISTS transactions_contract_address_index ON transactions (contract_address)",
+    "CREATE TABLE IF NOT EXISTS logs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        transaction_id INTEGER NOT NULL,
+        transaction_hash BLOB NOT NULL,
+        transaction_index INTEGER NOT NULL,
+        block_number INTEGER NOT NULL,
+        block_hash BLOB NOT NULL,
+        address BLOB NOT NULL,
+        data BLOB,
+        log_index INTEGER NOT NULL,
+        topics BLOB NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS logs_transaction_id_index ON logs (transaction_id)",
+    "CREATE INDEX IF NOT EXISTS logs_transaction_hash_index ON logs (transaction_hash)",
+    "CREATE INDEX IF NOT EXISTS logs_block_number_index ON logs (block_number)",
+    "CREATE INDEX IF NOT EXISTS logs_block_hash_index ON logs (block_hash)",
+    "CREATE INDEX IF NOT EXISTS logs_address_index ON logs (address)",
+    "CREATE TABLE IF NOT EXISTS token_transfers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        transaction_hash BLOB NOT NULL,
+        transaction_index INTEGER NOT NULL,
+        block_number INTEGER NOT NULL,
+        block_hash BLOB NOT NULL,
+        log_index INTEGER,
+        token_address BLOB,
+        sudt_id INTEGER,
+        from_address BLOB NOT NULL,
+        to_address BLOB NOT NULL,
+        amount TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS token_transfers_transaction_hash_index ON token_transfers (transaction_hash)",
+    "CREATE INDEX IF NOT EXISTS token_transfers_block_number_index ON token_transfers (block_number)",
+    "CREATE INDEX IF NOT EXISTS token_transfers_token_address_index ON token_transfers (token_address)",
+    "CREATE INDEX IF NOT EXISTS token_transfers_sudt_id_index ON token_transfers (sudt_id)",
+    "CREATE INDEX IF NOT EXISTS token_transfers_from_address_index ON token_transfers (from_address)",
+    "CREATE INDEX IF NOT EXISTS token_transfers_to_address_index ON token_transfers (to_address)",
+    "CREATE TABLE IF NOT EXISTS token_balances (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        token_address BLOB NOT NULL,
+        address BLOB NOT NULL,
+        balance TEXT NOT NULL,
+        UNIQUE (token_address, address)
+    )",
+    "CREATE INDEX IF NOT EXISTS token_balances_address_index ON token_balances (address)",
+];
+
+pub struct SqliteStorage {
+    pool: SqlitePool,
+}
+
+impl SqliteStorage {
+    /// Connect to the db, e.g. `sqlite://indexer.db` or `sqlite::memory:`, and
+    /// create the tables if not exist.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let opts = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        // Writes are serialized by sqlite anyway, and each connection of an
+        // in-memory db has its own db.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(opts)
+            .await?;
+        for stmt in SCHEMA {
+            sqlx::query(stmt).execute(&pool).await?;
+        }
+        Ok(Self { pool })
+    }
+}
+
+fn concat_topics(topics: &[H256]) -> Vec<u8> {
+    topics.concat()
+}
+
+async fn insert_tx(tx: &Transaction, db_tx: &mut sqlx::Transaction<'_, Sqlite>) -> Result<i64> {
+    let id = sqlx::query(
+        "INSERT INTO transactions (hash, eth_tx_hash, block_number, block_hash, transaction_index, from_address, to_address, value, nonce, gas_limit, gas_price, input, v, r, s, cumulative_gas_used, gas_used, contract_address, exit_code, chain_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+        .bind(&tx.gw_tx_hash[..])
+        .bind(&tx.compute_eth_tx_hash()[..])
+        .bind(tx.block_number as i64)
+        .bind(&tx.block_hash[..])
+        .bind(tx.transaction_index as i64)
+        .bind(&tx.from_address[..])
+        .bind(tx.to_address.as_ref().map(|addr| &addr[..]))
+        .bind(tx.value.to_string())
+        .bind(tx.nonce as i64)
+        .bind(tx.gas_limit.to_string())
+        .bind(tx.gas_price.to_string())
+        .bind(&tx.data)
+        .bind(tx.v as i64)
+        .bind(&tx.r[..])
+        .bind(&tx.s[..])
+        .bind(tx.cumulative_gas_used.to_string())
+        .bind(tx.gas_used.to_string())
+        .bind(tx.contract_address.as_ref().map(|addr| &addr[..]))
+        .bind(tx.exit_code as i64)
+        .bind(tx.chain_id.map(|id| id as i64))
+        .execute(&mut *db_tx)
+        .await?
+        .last_insert_rowid();
+    Ok(id)
+}
+
+async fn insert_log(
+    log: &Log,
+    transaction_id: i64,
+    db_tx: &mut sqlx::Transaction<'_, Sqlite>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO logs (transaction_id, transaction_hash, transaction_index, block_number, block_hash, address, data, log_index, topics) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+        .bind(transaction_id)
+        .bind(&log.transaction_hash[..])
+        .bind(log.transaction_index as i64)
+        .bind(log.block_number as i64)
+        .bind(&log.block_hash[..])
+        .bind(&log.address[..])
+        .bind(&log.data)
+        .bind(log.log_index as i64)
+        .bind(concat_topics(&log.topics))
+        .execute(&mut *db_tx)
+        .await?;
+    Ok(())
+}
+
+async fn insert_token_transfers(
+    transfers: &[TokenTransfer],
+    db_tx: &mut sqlx::Transaction<'_, Sqlite>,
+) -> Result<()> {
+    for transfer in transfers {
+        let (token_address, sudt_id) = token_columns(transfer.token);
+        sqlx::query(
+            "INSERT INTO token_transfers (transaction_hash, transaction_index, block_number, block_hash, log_index, token_address, sudt_id, from_address, to_address, amount) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(&transfer.transaction_hash[..])
+            .bind(transfer.transaction_index as i64)
+            .bind(transfer.block_number as i64)
+            .bind(&transfer.block_hash[..])
+            .bind(transfer.log_index.map(|index| index as i64))
+            .bind(token_address.as_ref().map(|address| &address[..]))
+            .bind(sudt_id.map(|id| id as i64))
+            .bind(&transfer.from_address[..])
+            .bind(&transfer.to_address[..])
+            .bind(transfer.amount.to_string())
+            .execute(&mut *db_tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Delete token transfers of the block, returns the balance changes to revert them.
+async fn delete_token_transfers(
+    number: i64,
+    db_tx: &mut sqlx::Transaction<'_, Sqlite>,
+) -> Result<BalanceChanges> {
+    let rows: Vec<(Option<Vec<u8>>, Option<i64>, Vec<u8>, Vec<u8>, String)> = sqlx::query_as(
+        "SELECT token_address, sudt_id, from_address, to_address, amount FROM token_transfers WHERE block_number = ?"
+    )
+        .bind(number)
+        .fetch_all(&mut *db_tx)
+        .await?;
+    sqlx::query("DELETE FROM token_transfers WHERE block_number = ?")
+        .bind(number)
+        .execute(&mut *db_tx)
+        .await?;
+    let transfers = rows
+        .into_iter()
+        .map(|(token_address, sudt_id, from, to, amount)| {
+            let token = token_from_columns(token_address, sudt_id)?;
+            Ok((
+                token,
+                to_address(&from)?,
+                to_address(&to)?,
+                BigInt::from_str(&amount)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(revert_changes(transfers))
+}
+
+async fn apply_balance_changes(
+    changes: BalanceChanges,
+    db_tx: &mut sqlx::Transaction<'_, Sqlite>,
+) -> Result<()> {
+    for ((token_address, address), change) in changes {
+        let row: Option<(i64, String)> = sqlx::query_as(
+            "SELECT id, balance FROM token_balances WHERE token_address = ? AND address = ?",
+        )
+        .bind(&token_address[..])
+        .bind(&address[..])
+        .fetch_optional(&mut *db_tx)
+        .await?;
+        match row {
+            Some((id, balance)) => {
+                let balance = BigInt::from_str(&balance)? + change;
+                if balance.sign() == Sign::NoSign {
+                    sqlx::query("DELETE FROM token_balances WHERE id = ?")
+                        .bind(id)
+                        .execute(&mut *db_tx)
+                        .await?;
+                } else {
+                    sqlx::query("UPDATE token_balances SET balance = ? WHERE id = ?")
+                        .bind(balance.to_string())
+                        .bind(id)
+                        .execute(&mut *db_tx)
+                        .await?;
+                }
+            }
+            None => {
+                sqlx::query(
+                    "INSERT INTO token_balances (token_address, address, balance) VALUES (?, ?, ?)",
+                )
+                .bind(&token_address[..])
+                .bind(&address[..])
+                .bind(change.to_string())
+                .execute(&mut *db_tx)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+type LogRow = (
+    i64,
+    Vec<u8>,
+    i64,
+    i64,
+    Vec<u8>,
+    Vec<u8>,
+    Option<Vec<u8>>,
+    i64,
+    Vec<u8>,
+);
+
+fn log_from_row(row: LogRow) -> Result<Log> {
+    let (_id, tx_hash, tx_index, block_number, block_hash, address, data, log_index, topics) = row;
+    let topics = topics
+        .chunks(32)
+        .map(|topic| topic.try_into())
+        .collect::<Result<Vec<H256>, _>>()?;
+    Ok(Log::new(
+        tx_hash.as_slice().try_into()?,
+        tx_index as u32,
+        block_number as u64,
+        block_hash.as_slice().try_into()?,
+        to_address(&address)?,
+        data.unwrap_or_default(),
+        log_index as u32,
+        topics,
+    ))
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn tip_number(&self) -> Result<Option<u64>> {
+        let row: Option<(i64,)> =
+            sqlx::query_as("SELECT number FROM blocks ORDER BY number DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.map(|(n,)| n as u64))
+    }
+
+    async fn get_block_hash(&self, number: u64) -> Result<Option<H256>> {
+        let row: Option<(Vec<u8>,)> =
+            sqlx::query_as("SELECT hash FROM blocks WHERE number = ? LIMIT 1")
+                .bind(number as i64)
+                .fetch_optional(&self.pool)
+                .await?;
+        let hash: Option<H256> = row.map(|(hash,)| hash.as_slice().try_into()).transpose()?;
+        Ok(hash)
+    }
+
+    async fn insert_block(
+        &self,
+        block: Block,
+        mut txs: Vec<TransactionWithLogs>,
+    ) -> Result<(usize, usize)> {
+        let token_transfers = take_token_transfers(&mut txs);
+        let mut db_tx = self.pool.begin().await?;
+        let txs_len = txs.len();
+        let mut logs_len = 0;
+        for TransactionWithLogs { tx, logs, .. } in txs {
+            let transaction_id = insert_tx(&tx, &mut db_tx).await?;
+            for log in logs {
+                insert_log(&log, transaction_id, &mut db_tx).await?;
+                logs_len += 1;
+            }
+        }
+        insert_token_transfers(&token_transfers, &mut db_tx).await?;
+        apply_balance_changes(balance_changes(&token_transfers), &mut db_tx).await?;
+        sqlx::query(
+            "INSERT INTO blocks (number, hash, parent_hash, gas_limit, gas_used, timestamp, miner, size) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(block.number as i64)
+            .bind(&block.hash[..])
+            .bind(&block.parent_hash[..])
+            .bind(block.gas_limit.to_string())
+            .bind(block.gas_used.to_string())
+            .bind(block.timestamp)
+            .bind(&block.miner[..])
+            .bind(block.size as i64)
+            .execute(&mut db_tx)
+            .await?;
+        db_tx.commit().await?;
+        Ok((txs_len, logs_len))
+    }
+
+    async fn update_block(
+        &self,
+        block: Block,
+        mut txs: Vec<TransactionWithLogs>,
+    ) -> Result<(usize, usize)> {
+        let token_transfers = take_token_transfers(&mut txs);
+        let mut db_tx = self.pool.begin().await?;
+        let txs_len = txs.len();
+        let mut logs_len = 0;
+        for TransactionWithLogs { tx, logs, .. } in txs {
+            sqlx::query(
+                "UPDATE transactions SET hash = ?, eth_tx_hash = ?, from_address = ?, to_address = ?, value = ?, nonce = ?, gas_limit = ?, gas_price = ?, input = ?, v = ?, r = ?, s = ?, cumulative_gas_used = ?, gas_used = ?, contract_address = ?, exit_code = ?, chain_id = ? WHERE block_number = ? AND transaction_index = ?"
+            )
+                .bind(&tx.gw_tx_hash[..])
+                .bind(&tx.compute_eth_tx_hash()[..])
+                .bind(&tx.from_address[..])
+                .bind(tx.to_address.as_ref().map(|addr| &addr[..]))
+                .bind(tx.value.to_string())
+                .bind(tx.nonce as i64)
+                .bind(tx.gas_limit.to_string())
+                .bind(tx.gas_price.to_string())
+                .bind(&tx.data)
+                .bind(tx.v as i64)
+                .bind(&tx.r[..])
+                .bind(&tx.s[..])
+                .bind(tx.cumulative_gas_used.to_string())
+                .bind(tx.gas_used.to_string())
+                .bind(tx.contract_address.as_ref().map(|addr| &addr[..]))
+                .bind(tx.exit_code as i64)
+                .bind(tx.chain_id.map(|id| id as i64))
+                .bind(tx.block_number as i64)
+                .bind(tx.transaction_index as i64)
+                .execute(&mut db_tx)
+                .await?;
+            for log in logs {
+                sqlx::query(
+                    "UPDATE logs SET transaction_hash = ?, address = ?, data = ?, topics = ? WHERE block_number = ? AND log_index = ?"
+                )
+                    .bind(&log.transaction_hash[..])
+                    .bind(&log.address[..])
+                    .bind(&log.data)
+                    .bind(concat_topics(&log.topics))
+                    .bind(log.block_number as i64)
+                    .bind(log.log_index as i64)
+                    .execute(&mut db_tx)
+                    .await?;
+                logs_len += 1;
+            }
+        }
+        let reverted = delete_token_transfers(block.number as i64, &mut db_tx).await?;
+        apply_balance_changes(reverted, &mut db_tx).await?;
+        insert_token_transfers(&token_transfers, &mut db_tx).await?;
+        apply_balance_changes(balance_changes(&token_transfers), &mut db_tx).await?;
+        sqlx::query(
+            "UPDATE blocks SET hash = ?, parent_hash = ?, gas_limit = ?, gas_used = ?, timestamp = ?, miner = ?, size = ? WHERE number = ?"
+        )
+            .bind(&block.hash[..])
+            .bind(&block.parent_hash[..])
+            .bind(block.gas_limit.to_string())
+            .bind(block.gas_used.to_string())
+            .bind(block.timestamp)
+            .bind(&block.miner[..])
+            .bind(block.size as i64)
+            .bind(block.number as i64)
+            .execute(&mut db_tx)
+            .await?;
+        db_tx.commit().await?;
+        Ok((txs_len, logs_len))
+    }
+
+    async fn delete_block(&self, number: u64) -> Result<()> {
+        let number = number as i64;
+        let mut db_tx = self.pool.begin().await?;
+        let reverted = delete_token_transfers(number, &mut db_tx).await?;
+        apply_balance_changes(reverted, &mut db_tx).await?;
+        sqlx::query("DELETE FROM logs WHERE block_number = ?")
+            .bind(number)
+            .execute(&mut db_tx)
+            .await?;
+        sqlx::query("DELETE FROM transactions WHERE block_number = ?")
+            .bind(number)
+            .execute(&mut db_tx)
+            .await?;
+        sqlx::query("DELETE FROM blocks WHERE number = ?")
+            .bind(number)
+            .execute(&mut db_tx)
+            .await?;
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    async fn reindex_erc20_transfers(&self) -> Result<(usize, usize)> {
+        let mut db_tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM token_transfers WHERE token_address IS NOT NULL")
+            .execute(&mut db_tx)
+            .await?;
+        sqlx::query("DELETE FROM token_balances")
+            .execute(&mut db_tx)
+            .await?;
+        let mut changes = BalanceChanges::new();
+        let mut transfers_len = 0;
+        let mut last_id = 0;
+        loop {
+            let rows: Vec<LogRow> = sqlx::query_as(
+                "SELECT id, transaction_hash, transaction_index, block_number, block_hash, address, data, log_index, topics FROM logs WHERE id > ? AND substr(topics, 1, 32) = ? ORDER BY id LIMIT ?"
+            )
+                .bind(last_id)
+                .bind(&ERC20_TRANSFER_TOPIC[..])
+                .bind(REINDEX_LOGS_BATCH_SIZE)
+                .fetch_all(&mut db_tx)
+                .await?;
+            last_id = match rows.last() {
+                Some(row) => row.0,
+                None => break,
+            };
+            let logs = rows
+                .into_iter()
+                .map(log_from_row)
+                .collect::<Result<Vec<_>>>()?;
+            let transfers = decode_erc20_transfers(&logs, &mut changes);
+            insert_token_transfers(&transfers, &mut db_tx).await?;
+            transfers_len += transfers.len();
+        }
+        changes.retain(|_, change| change.sign() != Sign::NoSign);
+        let balances_len = changes.len();
+        apply_balance_changes(changes, &mut db_tx).await?;
+        db_tx.commit().await?;
+        Ok((transfers_len, balances_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_types::U256;
+    use sqlx::types::chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{token::decode_erc20_transfer, types::Token};
+
+    const TOKEN: [u8; 20] = [3u8; 20];
+    const FROM: [u8; 20] = [2u8; 20];
+    const TO: [u8; 20] = [4u8; 20];
+
+    fn address_topic(address: [u8; 20]) -> H256 {
+        let mut topic = [0u8; 32];
+        topic[12..].copy_from_slice(&address);
+        topic
+    }
+
+    fn block(number: u64, hash: H256) -> Block {
+        Block {
+            number,
+            hash,
+            parent_hash: [number as u8; 32],
+            gas_limit: 12_500_000,
+            gas_used: 21_000,
+            miner: [1u8; 20],
+            size: 100,
+            timestamp: Utc.timestamp(1_600_000_000 + number as i64, 0),
+        }
+    }
+
+    fn tx_with_logs(number: u64, block_hash: H256, index: u32) -> TransactionWithLogs {
+        let gw_tx_hash = [index as u8 + 1; 32];
+        let tx = Transaction::new(
+            gw_tx_hash,
+            Some(71400),
+            number,
+            block_hash,
+            index,
+            [2u8; 20],
+            Some([3u8; 20]),
+            U256::from(1000u64),
+            index,
+            21_000,
+            1,
+            vec![],
+            [4u8; 32],
+            [5u8; 32],
+            0,
+            21_000,
+            21_000,
+            None,
+            0,
+        );
+        let mut amount = [0u8; 32];
+        U256::from(100u64).to_big_endian(&mut amount);
+        let log = Log::new(
+            gw_tx_hash,
+            index,
+            number,
+            block_hash,
+            TOKEN,
+            amount.to_vec(),
+            index,
+            vec![ERC20_TRANSFER_TOPIC, address_topic(FROM), address_topic(TO)],
+        );
+        let sudt_transfer = TokenTransfer {
+            transaction_hash: gw_tx_hash,
+            transaction_index: index,
+            block_number: number,
+            block_hash,
+            log_index: None,
+            token: Token::Sudt(u32::MAX),
+            from_address: FROM,
+            to_address: TO,
+            amount: U256::from(10u64),
+        };
+        TransactionWithLogs {
+            tx,
+            token_transfers: vec![decode_erc20_transfer(&log).unwrap(), sudt_transfer],
+            logs: vec![log],
+        }
+    }
+
+    async fn balances(storage: &SqliteStorage) -> Vec<(Vec<u8>, Vec<u8>, String)> {
+        sqlx::query_as(
+            "SELECT token_address, address, balance FROM token_balances ORDER BY address",
+        )
+        .fetch_all(&storage.pool)
+        .await
+        .unwrap()
+    }
+
+    async fn sudt_ids(storage: &SqliteStorage) -> Vec<(i64,)> {
+        sqlx::query_as("SELECT sudt_id FROM token_transfers WHERE sudt_id IS NOT NULL")
+            .fetch_all(&storage.pool)
+            .await
+            .unwrap()
+    }
+
+    fn expected_balances() -> Vec<(Vec<u8>, Vec<u8>, String)> {
+        // sUDT transfers don't change token balances
+        vec![
+            (TOKEN.to_vec(), FROM.to_vec(), "-200".to_string()),
+            (TOKEN.to_vec(), TO.to_vec(), "200".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_sqlite_storage() {
+        smol::block_on(async {
+            let storage = SqliteStorage::connect("sqlite::memory:").await.unwrap();
+            assert_eq!(storage.tip_number().await.unwrap(), None);
+
+            let hash = [9u8; 32];
+            let txs = (0..2).map(|i| tx_with_logs(0, hash, i)).collect();
+            let lens = storage.insert_block(block(0, hash), txs).await.unwrap();
+            assert_eq!(lens, (2, 2));
+            assert_eq!(storage.tip_number().await.unwrap(), Some(0));
+            assert_eq!(storage.get_block_hash(0).await.unwrap(), Some(hash));
+            assert_eq!(balances(&storage).await, expected_balances());
+            assert_eq!(sudt_ids(&storage).await, vec![(u32::MAX as i64,); 2]);
+
+            let new_hash = [10u8; 32];
+            let txs = (0..2).map(|i| tx_with_logs(0, new_hash, i)).collect();
+            let lens = storage.update_block(block(0, new_hash), txs).await.unwrap();
+            assert_eq!(lens, (2, 2));
+            assert_eq!(storage.get_block_hash(0).await.unwrap(), Some(new_hash));
+            // Token transfers are replaced, not applied twice
+            assert_eq!(balances(&storage).await, expected_balances());
+
+            let topics: (Vec<u8>,) =
+                sqlx::query_as("SELECT topics FROM logs WHERE block_number = 0 AND log_index = 1")
+                    .fetch_one(&storage.pool)
+                    .await
+                    .unwrap();
+            assert_eq!(
+                topics.0,
+                [ERC20_TRANSFER_TOPIC, address_topic(FROM), address_topic(TO)].concat()
+            );
+
+            storage.delete_block(0).await.unwrap();
+            assert_eq!(storage.tip_number().await.unwrap(), None);
+            let txs: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transactions")
+                .fetch_one(&storage.pool)
+                .await
+                .unwrap();
+            assert_eq!(txs.0, 0);
+            let transfers: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM token_transfers")
+                .fetch_one(&storage.pool)
+                .await
+                .unwrap();
+            assert_eq!(transfers.0, 0);
+            assert!(balances(&storage).await.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_reindex_erc20_transfers() {
+        smol::block_on(async {
+            let storage = SqliteStorage::connect("sqlite::memory:").await.unwrap();
+            let hash = [9u8; 32];
+            let txs = (0..2).map(|i| tx_with_logs(0, hash, i)).collect();
+            storage.insert_block(block(0, hash), txs).await.unwrap();
+
+            // Indexed before token transfers were
+            sqlx::query("DELETE FROM token_transfers WHERE token_address IS NOT NULL")
+                .execute(&storage.pool)
+                .await
+                .unwrap();
+            sqlx::query("DELETE FROM token_balances")
+                .execute(&storage.pool)
+                .await
+                .unwrap();
+
+            for _ in 0..2 {
+                assert_eq!(storage.reindex_erc20_transfers().await.unwrap(), (2, 2));
+                assert_eq!(balances(&storage).await, expected_balances());
+                assert_eq!(sudt_ids(&storage).await.len(), 2);
+            }
+        });
+    }
+}
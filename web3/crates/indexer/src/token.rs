@@ -0,0 +1,131 @@
+//! Decode token transfers and compute holder balance changes.
+
+use std::collections::HashMap;
+
+use gw_types::{h256::H256, U256};
+use num_bigint::{BigInt, Sign};
+
+use crate::{
+    helper::GwLog,
+    types::{Log, Token, TokenTransfer},
+};
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const ERC20_TRANSFER_TOPIC: [u8; 32] = [
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
+
+/// Balances of the zero address are not tracked, it's the counterparty of
+/// mint and burn.
+const ZERO_ADDRESS: [u8; 20] = [0u8; 20];
+
+/// ERC-20 balance changes by `(token_address, address)`. sUDT balances also
+/// change by deposits, withdrawals and fees, which are not transfers, so they
+/// are not tracked, see `gw_get_balance`.
+pub type BalanceChanges = HashMap<([u8; 20], [u8; 20]), BigInt>;
+
+/// Decode an ERC-20 `Transfer(address indexed, address indexed, uint256)` log.
+/// ERC-721 `Transfer` has the same signature but an indexed token id, so it is
+/// excluded by the topics count.
+pub fn decode_erc20_transfer(log: &Log) -> Option<TokenTransfer> {
+    if log.topics.len() != 3 || log.topics[0] != ERC20_TRANSFER_TOPIC || log.data.len() != 32 {
+        return None;
+    }
+    let mut from_address = [0u8; 20];
+    from_address.copy_from_slice(&log.topics[1][12..]);
+    let mut to_address = [0u8; 20];
+    to_address.copy_from_slice(&log.topics[2][12..]);
+    Some(TokenTransfer {
+        transaction_hash: log.transaction_hash,
+        transaction_index: log.transaction_index,
+        block_number: log.block_number,
+        block_hash: log.block_hash,
+        log_index: Some(log.log_index),
+        token: Token::Erc20(log.address),
+        from_address,
+        to_address,
+        amount: U256::from_big_endian(&log.data),
+    })
+}
+
+/// Decode a sUDT transfer log between eth addresses. The caller must check the
+/// `sudt_id` is a sUDT account, see `l2_sudt_type_script_hash`.
+pub fn decode_sudt_transfer(
+    log: &GwLog,
+    transaction_hash: H256,
+    block_number: u64,
+    block_hash: H256,
+) -> Option<TokenTransfer> {
+    let (sudt_id, from_address, to_address, amount) = match log {
+        GwLog::SudtTransfer {
+            sudt_id,
+            from_address,
+            to_address,
+            amount,
+        } => (*sudt_id, from_address, to_address, *amount),
+        _ => return None,
+    };
+    if from_address.address.len() != 20 || to_address.address.len() != 20 {
+        return None;
+    }
+    let mut from = [0u8; 20];
+    from.copy_from_slice(&from_address.address);
+    let mut to = [0u8; 20];
+    to.copy_from_slice(&to_address.address);
+    Some(TokenTransfer {
+        transaction_hash,
+        transaction_index: 0, // should update later
+        block_number,
+        block_hash,
+        log_index: None,
+        token: Token::Sudt(sudt_id),
+        from_address: from,
+        to_address: to,
+        amount,
+    })
+}
+
+pub fn u256_to_big_int(value: &U256) -> BigInt {
+    let mut buf = [0u8; 32];
+    value.to_big_endian(&mut buf);
+    BigInt::from_bytes_be(Sign::Plus, &buf)
+}
+
+/// Add a transfer to the balance changes, swap `from` and `to` to revert it.
+pub fn add_transfer(
+    changes: &mut BalanceChanges,
+    token_address: [u8; 20],
+    from: [u8; 20],
+    to: [u8; 20],
+    amount: &BigInt,
+) {
+    if from == to {
+        return;
+    }
+    if from != ZERO_ADDRESS {
+        *changes.entry((token_address, from)).or_default() -= amount;
+    }
+    if to != ZERO_ADDRESS {
+        *changes.entry((token_address, to)).or_default() += amount;
+    }
+}
+
+pub fn balance_changes<'a>(
+    transfers: impl IntoIterator<Item = &'a TokenTransfer>,
+) -> BalanceChanges {
+    let mut changes = BalanceChanges::new();
+    for transfer in transfers {
+        if let Token::Erc20(token_address) = transfer.token {
+            add_transfer(
+                &mut changes,
+                token_address,
+                transfer.from_address,
+                transfer.to_address,
+                &u256_to_big_int(&transfer.amount),
+            );
+        }
+    }
+    changes.retain(|_, change| change.sign() != Sign::NoSign);
+    changes
+}
@@ -176,8 +176,30 @@ impl Log {
     }
 }
 
+/// Token of a transfer, an ERC-20 contract or a sUDT account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Token {
+    Erc20(Address),
+    Sudt(u32),
+}
+
+#[derive(Debug)]
+pub struct TokenTransfer {
+    pub transaction_hash: H256,
+    pub transaction_index: u32,
+    pub block_number: u64,
+    pub block_hash: H256,
+    /// Index of the ERC-20 `Transfer` log in block, sUDT transfers are not web3 logs.
+    pub log_index: Option<u32>,
+    pub token: Token,
+    pub from_address: Address,
+    pub to_address: Address,
+    pub amount: U256,
+}
+
 #[derive(Debug)]
 pub struct TransactionWithLogs {
     pub tx: Transaction,
     pub logs: Vec<Log>,
+    pub token_transfers: Vec<TokenTransfer>,
 }
@@ -0,0 +1,37 @@
+import { Knex } from "knex";
+
+// Decoded by web3 indexer from ERC-20 `Transfer` logs and sUDT transfer logs.
+// A token is either an ERC-20 contract `token_address` or a sUDT `sudt_id`.
+export async function up(knex: Knex): Promise<void> {
+  await knex.schema
+    .createTable("token_transfers", function (table: Knex.TableBuilder) {
+      table.bigIncrements("id");
+      table.binary("transaction_hash").notNullable().index();
+      table.integer("transaction_index").notNullable();
+      table.decimal("block_number", null, 0).notNullable().index();
+      table.binary("block_hash").notNullable();
+      // null for sUDT transfers, they are not web3 logs
+      table.integer("log_index");
+      table.binary("token_address").index();
+      // u32 account id
+      table.bigInteger("sudt_id").index();
+      table.binary("from_address").notNullable().index();
+      table.binary("to_address").notNullable().index();
+      table.decimal("amount", 80, 0).notNullable();
+    })
+    // Only ERC-20 balances, sUDT balances also change by deposits, withdrawals
+    // and fees, query them by `gw_get_balance`.
+    .createTable("token_balances", function (table: Knex.TableBuilder) {
+      table.bigIncrements("id");
+      table.binary("token_address").notNullable();
+      table.binary("address").notNullable().index();
+      // signed, balances are incomplete for blocks indexed before this
+      // migration until `gw-web3-indexer reindex-tokens`
+      table.decimal("balance", 80, 0).notNullable();
+      table.unique(["token_address", "address"]);
+    });
+}
+
+export async function down(knex: Knex): Promise<void> {
+  await knex.schema.dropTable("token_balances").dropTable("token_transfers");
+}